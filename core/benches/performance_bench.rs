@@ -58,7 +58,26 @@ fn benchmark_frame_execution(c: &mut Criterion) {
                 nes
             },
             |mut nes| {
-                nes.run_frame();
+                nes.run_frame_unchecked();
+            },
+            criterion::BatchSize::SmallInput,
+        );
+    });
+}
+
+fn benchmark_frame_execution_no_audio(c: &mut Criterion) {
+    c.bench_function("frame_execution_no_audio", |b| {
+        b.iter_batched(
+            || {
+                let rom_data = create_test_rom();
+                let cartridge = Cartridge::from_ines(&rom_data[..]).unwrap();
+                let mut nes = Nes::new();
+                nes.load_cartridge(cartridge);
+                nes.set_audio_enabled(false);
+                nes
+            },
+            |mut nes| {
+                nes.run_frame_unchecked();
             },
             criterion::BatchSize::SmallInput,
         );
@@ -77,7 +96,7 @@ fn benchmark_cpu_step(c: &mut Criterion) {
             },
             |mut nes| {
                 for _ in 0..1000 {
-                    nes.step();
+                    nes.step_unchecked();
                 }
             },
             criterion::BatchSize::SmallInput,
@@ -100,7 +119,7 @@ fn benchmark_ppu_rendering(c: &mut Criterion) {
             |mut nes| {
                 // Run for approximately one scanline worth of cycles
                 for _ in 0..114 {
-                    nes.step();
+                    nes.step_unchecked();
                 }
             },
             criterion::BatchSize::SmallInput,
@@ -108,6 +127,24 @@ fn benchmark_ppu_rendering(c: &mut Criterion) {
     });
 }
 
+fn benchmark_sprite_heavy_frame(c: &mut Criterion) {
+    let rom_data = ccnes_core::test_rom::create_sprite_test_rom();
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    for _ in 0..3 {
+        nes.run_frame_unchecked();
+    }
+    // All 64 sprites on screen, in eight rows of eight
+    for i in 0..64 {
+        let sprite = &mut nes.bus.ppu.oam[i * 4..i * 4 + 4];
+        sprite.copy_from_slice(&[(i / 8 * 29) as u8, 0x01, (i as u8) & 0x43, (i % 8 * 31) as u8]);
+    }
+    
+    c.bench_function("frame_execution_sprite_heavy", |b| {
+        b.iter(|| nes.run_frame_unchecked());
+    });
+}
+
 fn benchmark_memory_access(c: &mut Criterion) {
     c.bench_function("memory_read_write", |b| {
         b.iter_batched(
@@ -138,8 +175,10 @@ fn benchmark_memory_access(c: &mut Criterion) {
 criterion_group!(
     benches,
     benchmark_frame_execution,
+    benchmark_frame_execution_no_audio,
     benchmark_cpu_step,
     benchmark_ppu_rendering,
+    benchmark_sprite_heavy_frame,
     benchmark_memory_access
 );
 criterion_main!(benches);
\ No newline at end of file
@@ -1,11 +1,14 @@
 /// Audio buffer management for NES APU
 /// Handles ring buffer, synchronization, and timing
 
+#[cfg(feature = "std")]
 use std::sync::{Arc, Mutex};
-use std::collections::VecDeque;
+use alloc::collections::VecDeque;
+use alloc::vec;
+use serde::{Serialize, Deserialize};
 
 /// Ring buffer for audio samples
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioRingBuffer {
     buffer: VecDeque<f32>,
     capacity: usize,
@@ -71,10 +74,12 @@ impl AudioRingBuffer {
 }
 
 /// Thread-safe audio buffer for cross-thread communication
+#[cfg(feature = "std")]
 pub struct ThreadSafeAudioBuffer {
     buffer: Arc<Mutex<AudioRingBuffer>>,
 }
 
+#[cfg(feature = "std")]
 impl ThreadSafeAudioBuffer {
     pub fn new(capacity: usize) -> Self {
         Self {
@@ -130,7 +135,7 @@ pub struct BufferStats {
 }
 
 /// Dynamic buffer size adjustment based on timing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdaptiveBuffer {
     buffer: AudioRingBuffer,
     target_latency_ms: f32,
@@ -141,7 +146,7 @@ pub struct AdaptiveBuffer {
     // Statistics for adaptation
     underrun_count: u32,
     overrun_count: u32,
-    last_adjustment: std::time::Instant,
+    samples_since_adjustment: usize,
 }
 
 impl AdaptiveBuffer {
@@ -158,11 +163,12 @@ impl AdaptiveBuffer {
             max_size,
             underrun_count: 0,
             overrun_count: 0,
-            last_adjustment: std::time::Instant::now(),
+            samples_since_adjustment: 0,
         }
     }
     
     pub fn write(&mut self, samples: &[f32]) -> usize {
+        self.samples_since_adjustment += samples.len();
         if self.buffer.is_full() {
             self.overrun_count += 1;
             self.check_resize();
@@ -187,8 +193,9 @@ impl AdaptiveBuffer {
     }
     
     fn check_resize(&mut self) {
-        // Only adjust every second to avoid thrashing
-        if self.last_adjustment.elapsed().as_secs() < 1 {
+        // Only adjust every second of audio to avoid thrashing. Counting
+        // samples rather than wall time keeps this usable without std.
+        if self.samples_since_adjustment < self.sample_rate as usize {
             return;
         }
         
@@ -216,7 +223,7 @@ impl AdaptiveBuffer {
             self.buffer = new_buffer;
             self.underrun_count = 0;
             self.overrun_count = 0;
-            self.last_adjustment = std::time::Instant::now();
+            self.samples_since_adjustment = 0;
         }
     }
     
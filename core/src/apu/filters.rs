@@ -1,9 +1,12 @@
 /// Audio filters for NES APU
 /// Implements low-pass and high-pass filters for accurate NES audio reproduction
 
+use alloc::{vec, vec::Vec};
+use serde::{Serialize, Deserialize};
+
 /// First-order low-pass filter
 /// Used to simulate the analog characteristics of the NES audio output
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LowPassFilter {
     cutoff_freq: f32,
     sample_rate: f32,
@@ -13,7 +16,7 @@ pub struct LowPassFilter {
 
 impl LowPassFilter {
     pub fn new(cutoff_freq: f32, sample_rate: f32) -> Self {
-        let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff_freq);
+        let rc = 1.0 / (2.0 * core::f32::consts::PI * cutoff_freq);
         let dt = 1.0 / sample_rate;
         let alpha = dt / (rc + dt);
         
@@ -37,7 +40,7 @@ impl LowPassFilter {
     
     pub fn set_cutoff(&mut self, cutoff_freq: f32) {
         self.cutoff_freq = cutoff_freq;
-        let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff_freq);
+        let rc = 1.0 / (2.0 * core::f32::consts::PI * cutoff_freq);
         let dt = 1.0 / self.sample_rate;
         self.alpha = dt / (rc + dt);
     }
@@ -45,7 +48,7 @@ impl LowPassFilter {
 
 /// First-order high-pass filter
 /// Used to remove DC offset from the audio signal
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HighPassFilter {
     cutoff_freq: f32,
     sample_rate: f32,
@@ -56,7 +59,7 @@ pub struct HighPassFilter {
 
 impl HighPassFilter {
     pub fn new(cutoff_freq: f32, sample_rate: f32) -> Self {
-        let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff_freq);
+        let rc = 1.0 / (2.0 * core::f32::consts::PI * cutoff_freq);
         let dt = 1.0 / sample_rate;
         let alpha = rc / (rc + dt);
         
@@ -83,7 +86,7 @@ impl HighPassFilter {
     
     pub fn set_cutoff(&mut self, cutoff_freq: f32) {
         self.cutoff_freq = cutoff_freq;
-        let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff_freq);
+        let rc = 1.0 / (2.0 * core::f32::consts::PI * cutoff_freq);
         let dt = 1.0 / self.sample_rate;
         self.alpha = rc / (rc + dt);
     }
@@ -91,7 +94,7 @@ impl HighPassFilter {
 
 /// Combined filter chain for NES audio
 /// Applies both high-pass and low-pass filtering in sequence
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NesAudioFilter {
     high_pass1: HighPassFilter,
     high_pass2: HighPassFilter,
@@ -119,9 +122,9 @@ impl NesAudioFilter {
         
         // Soft clipping to prevent harsh distortion
         if output > 1.0 {
-            1.0 - (1.0 - output).abs().powf(0.7)
+            1.0 - libm::powf((1.0 - output).abs(), 0.7)
         } else if output < -1.0 {
-            -1.0 + (1.0 + output).abs().powf(0.7)
+            -1.0 + libm::powf((1.0 + output).abs(), 0.7)
         } else {
             output
         }
@@ -134,6 +137,46 @@ impl NesAudioFilter {
     }
 }
 
+/// Moving average over one output sample's worth of input. Run at the
+/// CPU rate ahead of the resampler so level changes faster than the output
+/// rate, like DMC PCM written through $4011, are averaged instead of
+/// aliased by the interpolating resamplers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoxFilter {
+    history: Vec<f32>,
+    position: usize,
+    sum: f32,
+}
+
+impl BoxFilter {
+    pub fn new(source_rate: f32, target_rate: f32) -> Self {
+        let width = (libm::roundf(source_rate / target_rate) as usize).max(1);
+        Self {
+            history: vec![0.0; width],
+            position: 0,
+            sum: 0.0,
+        }
+    }
+    
+    pub fn process(&mut self, input: f32) -> f32 {
+        self.sum += input - self.history[self.position];
+        self.history[self.position] = input;
+        self.position += 1;
+        if self.position == self.history.len() {
+            // Re-add once per lap so rounding errors can't build up
+            self.position = 0;
+            self.sum = self.history.iter().sum();
+        }
+        self.sum / self.history.len() as f32
+    }
+    
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.position = 0;
+        self.sum = 0.0;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,4 +217,22 @@ mod tests {
         let clipped_neg = filter.process(-2.0);
         assert!(clipped_neg >= -1.0);
     }
+    
+    #[test]
+    fn test_box_filter() {
+        let mut filter = BoxFilter::new(40.0, 10.0);
+        
+        // A step takes the full width to come through
+        assert_eq!(filter.process(1.0), 0.25);
+        filter.process(1.0);
+        filter.process(1.0);
+        assert_eq!(filter.process(1.0), 1.0);
+        
+        // Alternating samples average out
+        for _ in 0..8 {
+            filter.process(0.0);
+            filter.process(1.0);
+        }
+        assert_eq!(filter.process(0.0), 0.5);
+    }
 }
\ No newline at end of file
@@ -1,12 +1,20 @@
 mod filters;
 mod resampler;
 mod buffer;
+mod stereo;
+mod scope;
 
-use filters::NesAudioFilter;
+use filters::{BoxFilter, NesAudioFilter};
 use resampler::Resampler;
 use buffer::AdaptiveBuffer;
+use stereo::StereoDelay;
+use scope::ChannelScopes;
+use alloc::vec::Vec;
+use serde::{Serialize, Deserialize};
+use crate::Region;
 
 pub use resampler::ResamplerQuality;
+pub use scope::WAVEFORM_LENGTH;
 
 const LENGTH_TABLE: [u8; 32] = [
     10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
@@ -21,7 +29,44 @@ const DMC_RATE_TABLE: [u16; 16] = [
     428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54,
 ];
 
-#[derive(Debug, Clone)]
+/// Frame counter and IRQ state, for debuggers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApuDebugInfo {
+    /// 5-step sequence selected by $4017 bit 7
+    pub five_step_mode: bool,
+    /// $4017 bit 6
+    pub frame_irq_inhibit: bool,
+    pub frame_irq: bool,
+    pub dmc_irq: bool,
+    /// CPU cycles into the current frame sequence
+    pub frame_cycle: u32,
+}
+
+/// The five sound generators, in `channel_waveforms` order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApuChannel {
+    Pulse1,
+    Pulse2,
+    Triangle,
+    Noise,
+    Dmc,
+}
+
+/// How the channel outputs are combined into one sample
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MixingMode {
+    /// The hardware's DAC curves: loud channels compress each other
+    #[default]
+    NonLinear,
+    /// A fixed weight per channel, summed. Each channel's level no longer
+    /// depends on the others, which keeps exported stems clean. Overall a
+    /// little quieter: every channel at full volume peaks about 15% lower,
+    /// a lone full-volume pulse about 25% lower and a lone triangle about
+    /// half as loud.
+    Linear,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Apu {
     // Pulse channels
     pulse1: PulseChannel,
@@ -44,19 +89,43 @@ pub struct Apu {
     
     cycles: u32,
     frame_cycles: u32,
+    // CPU cycles until a $4017 write restarts the sequence, 0 if none pending
+    frame_reset_delay: u8,
     
     // Audio output
     sample_rate: u32,
-    sample_counter: f32,
     samples: Vec<f32>,
+    samples_generated: u64,
     
     // Audio processing
     filter: NesAudioFilter,
+    box_filter: BoxFilter,
     resampler: Resampler,
+    /// Scratch space for the resampler's output
+    #[serde(skip)]
+    resampled: Vec<f32>,
     output_buffer: AdaptiveBuffer,
+    stereo_delay: StereoDelay,
+    // Off skips mixing, resampling and filtering; a frontend setting
+    #[serde(skip, default = "audio_enabled_default")]
+    audio_enabled: bool,
+    #[serde(skip)]
+    mixing: MixingMode,
+    // Visualization only, so not saved
+    #[serde(skip)]
+    scopes: ChannelScopes,
+}
+
+fn audio_enabled_default() -> bool {
+    true
 }
 
-#[derive(Debug, Clone, Default)]
+/// Rate the APU is stepped at. Only NTSC timing is emulated, see `Nes::region`.
+fn cpu_rate() -> f32 {
+    Region::Ntsc.cpu_clock() as f32
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct PulseChannel {
     enabled: bool,
     duty: u8,
@@ -65,6 +134,8 @@ struct PulseChannel {
     timer_period: u16,
     volume: u8,
     constant_volume: bool,
+    // Envelope loop, doubling as the length counter halt flag
+    envelope_loop: bool,
     envelope: u8,
     envelope_start: bool,
     envelope_period: u8,
@@ -75,9 +146,11 @@ struct PulseChannel {
     sweep_shift: u8,
     sweep_reload: bool,
     duty_position: u8,
+    #[serde(skip)]
+    length_write: LengthWrite,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct TriangleChannel {
     enabled: bool,
     length_counter: u8,
@@ -88,9 +161,11 @@ struct TriangleChannel {
     linear_counter_reload_flag: bool,
     control_flag: bool,
     sequence_position: u8,
+    #[serde(skip)]
+    length_write: LengthWrite,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct NoiseChannel {
     enabled: bool,
     length_counter: u8,
@@ -98,15 +173,42 @@ struct NoiseChannel {
     timer_period: u16,
     volume: u8,
     constant_volume: bool,
+    // Envelope loop, doubling as the length counter halt flag
+    envelope_loop: bool,
     envelope: u8,
     envelope_start: bool,
     envelope_period: u8,
     envelope_value: u8,
     mode: bool,
     shift_register: u16,
+    #[serde(skip)]
+    length_write: LengthWrite,
+}
+
+/// Length counter and halt flag as they were before a register write in the
+/// current cycle. A half-frame clock in the same cycle sees these, which
+/// resolves the write/clock race like hardware: reloading a non-zero counter
+/// loses to the clock, reloading a zero counter wins, and a halt change
+/// only applies after the clock. Cleared at the end of every cycle.
+#[derive(Debug, Clone, Copy, Default)]
+struct LengthWrite {
+    counter_before: Option<u8>,
+    halt_before: Option<bool>,
 }
 
-#[derive(Debug, Clone)]
+impl LengthWrite {
+    fn reload(&mut self, counter: &mut u8, value: u8) {
+        self.counter_before.get_or_insert(*counter);
+        *counter = LENGTH_TABLE[(value >> 3) as usize];
+    }
+    
+    fn set_halt(&mut self, halt: &mut bool, value: bool) {
+        self.halt_before.get_or_insert(*halt);
+        *halt = value;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct DmcChannel {
     enabled: bool,
     
@@ -168,7 +270,7 @@ impl Apu {
         let mut noise = NoiseChannel::default();
         noise.shift_register = 1;
         
-        let cpu_rate = 1789773.0; // NTSC CPU frequency
+        let cpu_rate = cpu_rate();
         
         Self {
             pulse1: PulseChannel::default(),
@@ -182,15 +284,23 @@ impl Apu {
             frame_irq_inhibit: false,
             cycles: 0,
             frame_cycles: 0,
+            frame_reset_delay: 0,
             sample_rate,
-            sample_counter: 0.0,
             samples: Vec::new(),
+            samples_generated: 0,
             filter: NesAudioFilter::new(sample_rate as f32),
+            box_filter: BoxFilter::new(cpu_rate, sample_rate as f32),
             resampler: Resampler::new(ResamplerQuality::Medium, cpu_rate, sample_rate as f32),
+            resampled: Vec::new(),
             output_buffer: AdaptiveBuffer::new(sample_rate as f32, 20.0), // 20ms latency target
+            stereo_delay: StereoDelay::new(sample_rate, 0.0),
+            audio_enabled: true,
+            mixing: MixingMode::NonLinear,
+            scopes: ChannelScopes::new(),
         }
     }
     
+    /// Only $4015 is readable; the bus answers the rest with open bus
     pub fn read_register(&self, addr: u16) -> u8 {
         match addr {
             0x4015 => {
@@ -255,32 +365,53 @@ impl Apu {
                 
                 if !self.pulse1.enabled {
                     self.pulse1.length_counter = 0;
+                    self.pulse1.length_write.counter_before = None;
                 }
                 if !self.pulse2.enabled {
                     self.pulse2.length_counter = 0;
+                    self.pulse2.length_write.counter_before = None;
                 }
                 if !self.triangle.enabled {
                     self.triangle.length_counter = 0;
+                    self.triangle.length_write.counter_before = None;
                 }
                 if !self.noise.enabled {
                     self.noise.length_counter = 0;
+                    self.noise.length_write.counter_before = None;
                 }
                 
-                if self.dmc.enabled && self.dmc.bytes_remaining == 0 {
-                    // Restart DMC
+                // Any write acknowledges the DMC interrupt, whatever bit 4 says
+                self.dmc.interrupt = false;
+                
+                if !self.dmc.enabled {
+                    // Stop fetching; the byte already in the buffer still plays out
+                    self.dmc.bytes_remaining = 0;
+                } else if self.dmc.bytes_remaining == 0 {
+                    // Restart only a finished sample, never one still playing
                     self.dmc.current_address = self.dmc.sample_address;
                     self.dmc.bytes_remaining = self.dmc.sample_length;
-                } else if !self.dmc.enabled {
-                    self.dmc.bytes_remaining = 0;
                 }
-                
-                // Clear DMC interrupt
-                self.dmc.interrupt = false;
             }
             0x4017 => {
-                // Frame counter
+                // Frame counter: bit 7 selects the 5-step sequence, bit 6
+                // inhibits the frame IRQ and acknowledges a pending one
                 self.frame_mode = (value & 0x80) != 0;
-                self.frame_irq = (value & 0x40) == 0;
+                self.frame_irq_inhibit = (value & 0x40) != 0;
+                if self.frame_irq_inhibit {
+                    self.frame_irq = false;
+                }
+                
+                // The sequence restarts 3 CPU cycles later when written on
+                // an APU cycle (the even cycles that clock the pulse
+                // timers), 4 when written between two
+                self.frame_reset_delay = if self.cycles % 2 == 0 { 3 } else { 4 };
+                
+                // The 5-step mode clocks the envelopes, length counters and
+                // sweeps right away
+                if self.frame_mode {
+                    self.clock_quarter_frame();
+                    self.clock_half_frame();
+                }
             }
             _ => {}
         }
@@ -291,6 +422,7 @@ impl Apu {
             0 => {
                 channel.duty = (value >> 6) & 0x3;
                 channel.envelope_period = value & 0xF;
+                channel.length_write.set_halt(&mut channel.envelope_loop, (value & 0x20) != 0);
                 channel.constant_volume = (value & 0x10) != 0;
                 channel.volume = value & 0xF;
             }
@@ -308,7 +440,7 @@ impl Apu {
             3 => {
                 channel.timer_period = (channel.timer_period & 0x00FF) | ((value as u16 & 0x7) << 8);
                 channel.timer = channel.timer_period;
-                channel.length_counter = LENGTH_TABLE[(value >> 3) as usize];
+                channel.length_write.reload(&mut channel.length_counter, value);
                 channel.envelope_start = true;
                 channel.duty_position = 0;
             }
@@ -319,7 +451,7 @@ impl Apu {
     fn write_triangle(&mut self, reg: u16, value: u8) {
         match reg {
             0 => {
-                self.triangle.control_flag = (value & 0x80) != 0;
+                self.triangle.length_write.set_halt(&mut self.triangle.control_flag, (value & 0x80) != 0);
                 self.triangle.linear_counter_reload = value & 0x7F;
             }
             2 => {
@@ -328,7 +460,7 @@ impl Apu {
             3 => {
                 self.triangle.timer_period = (self.triangle.timer_period & 0x00FF) | ((value as u16 & 0x7) << 8);
                 self.triangle.timer = self.triangle.timer_period;
-                self.triangle.length_counter = LENGTH_TABLE[(value >> 3) as usize];
+                self.triangle.length_write.reload(&mut self.triangle.length_counter, value);
                 self.triangle.linear_counter_reload_flag = true;
             }
             _ => {}
@@ -339,6 +471,7 @@ impl Apu {
         match reg {
             0 => {
                 self.noise.envelope_period = value & 0xF;
+                self.noise.length_write.set_halt(&mut self.noise.envelope_loop, (value & 0x20) != 0);
                 self.noise.constant_volume = (value & 0x10) != 0;
                 self.noise.volume = value & 0xF;
             }
@@ -347,7 +480,7 @@ impl Apu {
                 self.noise.timer_period = NOISE_PERIOD_TABLE[(value & 0xF) as usize];
             }
             3 => {
-                self.noise.length_counter = LENGTH_TABLE[(value >> 3) as usize];
+                self.noise.length_write.reload(&mut self.noise.length_counter, value);
                 self.noise.envelope_start = true;
             }
             _ => {}
@@ -390,50 +523,52 @@ impl Apu {
         self.clock_timers();
         
         // Frame counter
+        if self.frame_reset_delay > 0 {
+            self.frame_reset_delay -= 1;
+            if self.frame_reset_delay == 0 {
+                self.frame_cycles = 0;
+            }
+        }
         self.frame_cycles += 1;
         
-        if !self.frame_mode {
-            // 4-step sequence (60 Hz)
-            match self.frame_cycles {
-                7457 => self.clock_quarter_frame(),
-                14913 => {
-                    self.clock_quarter_frame();
-                    self.clock_half_frame();
-                }
-                22371 => self.clock_quarter_frame(),
-                29829 => {
-                    self.clock_quarter_frame();
-                    self.clock_half_frame();
-                    if !self.frame_irq_inhibit {
-                        self.frame_irq = true;
-                    }
-                    self.frame_cycles = 0;
-                }
-                _ => {}
+        // The sequencer counts APU cycles and steps halfway through one, so
+        // every step lands on an odd CPU cycle: APU cycle 3728.5 is 7457
+        match (self.frame_mode, self.frame_cycles) {
+            (_, 7457) | (_, 22371) => self.clock_quarter_frame(),
+            (_, 14913) => {
+                self.clock_quarter_frame();
+                self.clock_half_frame();
             }
-        } else {
-            // 5-step sequence (48 Hz)
-            match self.frame_cycles {
-                7457 => self.clock_quarter_frame(),
-                14913 => {
-                    self.clock_quarter_frame();
-                    self.clock_half_frame();
-                }
-                22371 => self.clock_quarter_frame(),
-                29829 => {
-                    // Do nothing
-                }
-                37281 => {
-                    self.clock_quarter_frame();
-                    self.clock_half_frame();
-                    self.frame_cycles = 0;
-                }
-                _ => {}
+            // 4-step sequence (60 Hz): the IRQ flag is raised on each of
+            // the last three cycles, so acknowledging it early doesn't stick
+            (false, 29828) => self.raise_frame_irq(),
+            (false, 29829) => {
+                self.clock_quarter_frame();
+                self.clock_half_frame();
+                self.raise_frame_irq();
             }
+            (false, 29830) => {
+                self.raise_frame_irq();
+                self.frame_cycles = 0;
+            }
+            // 5-step sequence (48 Hz): the fourth step does nothing
+            (true, 37281) => {
+                self.clock_quarter_frame();
+                self.clock_half_frame();
+            }
+            (true, 37282) => self.frame_cycles = 0,
+            _ => {}
         }
         
-        // Generate sample
-        self.generate_sample();
+        // Writes from before this cycle no longer race the frame counter
+        self.pulse1.length_write = LengthWrite::default();
+        self.pulse2.length_write = LengthWrite::default();
+        self.triangle.length_write = LengthWrite::default();
+        self.noise.length_write = LengthWrite::default();
+        
+        if self.audio_enabled {
+            self.generate_sample();
+        }
     }
     
     fn clock_timers(&mut self) {
@@ -556,6 +691,12 @@ impl Apu {
         }
     }
     
+    fn raise_frame_irq(&mut self) {
+        if !self.frame_irq_inhibit {
+            self.frame_irq = true;
+        }
+    }
+    
     fn clock_quarter_frame(&mut self) {
         // Clock envelopes
         Self::clock_envelope_pulse(&mut self.pulse1);
@@ -575,11 +716,11 @@ impl Apu {
     }
     
     fn clock_half_frame(&mut self) {
-        // Clock length counters
-        Self::clock_length_counter(&mut self.pulse1.length_counter);
-        Self::clock_length_counter(&mut self.pulse2.length_counter);
-        Self::clock_length_counter(&mut self.triangle.length_counter);
-        Self::clock_length_counter(&mut self.noise.length_counter);
+        // Clock length counters unless halted
+        Self::clock_length_counter(&mut self.pulse1.length_counter, self.pulse1.envelope_loop, self.pulse1.length_write);
+        Self::clock_length_counter(&mut self.pulse2.length_counter, self.pulse2.envelope_loop, self.pulse2.length_write);
+        Self::clock_length_counter(&mut self.triangle.length_counter, self.triangle.control_flag, self.triangle.length_write);
+        Self::clock_length_counter(&mut self.noise.length_counter, self.noise.envelope_loop, self.noise.length_write);
         
         // Clock sweep units  
         let mut pulse1 = self.pulse1.clone();
@@ -591,6 +732,7 @@ impl Apu {
     }
     
     fn clock_envelope_pulse(channel: &mut PulseChannel) {
+        // Runs in constant-volume mode too, only the output ignores it
         if channel.envelope_start {
             channel.envelope_value = 15;
             channel.envelope = channel.envelope_period;
@@ -600,7 +742,7 @@ impl Apu {
         } else {
             if channel.envelope_value > 0 {
                 channel.envelope_value -= 1;
-            } else if channel.envelope_period > 0 {
+            } else if channel.envelope_loop {
                 channel.envelope_value = 15;
             }
             channel.envelope = channel.envelope_period;
@@ -608,6 +750,7 @@ impl Apu {
     }
     
     fn clock_envelope_noise(channel: &mut NoiseChannel) {
+        // Runs in constant-volume mode too, only the output ignores it
         if channel.envelope_start {
             channel.envelope_value = 15;
             channel.envelope = channel.envelope_period;
@@ -617,15 +760,25 @@ impl Apu {
         } else {
             if channel.envelope_value > 0 {
                 channel.envelope_value -= 1;
-            } else if channel.envelope_period > 0 {
+            } else if channel.envelope_loop {
                 channel.envelope_value = 15;
             }
             channel.envelope = channel.envelope_period;
         }
     }
     
-    fn clock_length_counter(counter: &mut u8) {
-        if *counter > 0 {
+    fn clock_length_counter(counter: &mut u8, halt: bool, write: LengthWrite) {
+        let halt = write.halt_before.unwrap_or(halt);
+        if let Some(before) = write.counter_before {
+            // The reload only survives if the clock would have left the old
+            // value alone; otherwise the clock wins and the reload is dropped
+            if !halt && before > 0 {
+                *counter = before - 1;
+            }
+            return;
+        }
+        
+        if !halt && *counter > 0 {
             *counter -= 1;
         }
     }
@@ -660,50 +813,49 @@ impl Apu {
         }
     }
     
+    /// Mix the channels every CPU cycle and let the resampler convert
+    /// to the output rate; it is the only rate conversion in the chain
     fn generate_sample(&mut self) {
-        // Sample rate conversion
-        let cpu_rate = 1789773.0; // NTSC CPU frequency
-        let sample_period = cpu_rate / self.sample_rate as f32;
+        let pulse1 = self.get_pulse_output(&self.pulse1);
+        let pulse2 = self.get_pulse_output(&self.pulse2);
+        let triangle = self.get_triangle_output();
+        let noise = self.get_noise_output();
+        let dmc = self.dmc.output_level as f32;
         
-        self.sample_counter += 1.0;
-        if self.sample_counter >= sample_period {
-            self.sample_counter -= sample_period;
-            
-            // Mix channels
-            let pulse1 = self.get_pulse_output(&self.pulse1);
-            let pulse2 = self.get_pulse_output(&self.pulse2);
-            let triangle = self.get_triangle_output();
-            let noise = self.get_noise_output();
-            let dmc = self.dmc.output_level as f32;
-            
-            // Improved non-linear mixing with better approximation
-            let pulse_out = if pulse1 + pulse2 > 0.0 {
-                95.88 / ((8128.0 / (pulse1 + pulse2)) + 100.0)
-            } else {
-                0.0
-            };
-            
-            let tnd_out = if triangle + noise + dmc > 0.0 {
-                159.79 / ((1.0 / ((triangle / 8227.0) + (noise / 12241.0) + (dmc / 22638.0))) + 100.0)
-            } else {
-                0.0
-            };
-            
-            // Mix and normalize
-            let mixed = pulse_out + tnd_out;
-            
-            // Apply filtering
-            let filtered = self.filter.process(mixed);
-            
-            // Resample to output rate
-            let mut resampled = Vec::new();
-            self.resampler.process(filtered, &mut resampled);
-            
-            // Add to output buffer
-            for sample in resampled {
-                self.samples.push(sample);
-                self.output_buffer.write(&[sample]);
+        let mixed = match self.mixing {
+            MixingMode::NonLinear => {
+                let pulse_out = if pulse1 + pulse2 > 0.0 {
+                    95.88 / ((8128.0 / (pulse1 + pulse2)) + 100.0)
+                } else {
+                    0.0
+                };
+                
+                let tnd_out = if triangle + noise + dmc > 0.0 {
+                    159.79 / ((1.0 / ((triangle / 8227.0) + (noise / 12241.0) + (dmc / 22638.0))) + 100.0)
+                } else {
+                    0.0
+                };
+                
+                pulse_out + tnd_out
+            }
+            // The usual linear approximation of the curves above
+            MixingMode::Linear => {
+                0.00752 * (pulse1 + pulse2) + 0.00851 * triangle + 0.00494 * noise + 0.00335 * dmc
             }
+        };
+        
+        // Band limit, then resample to output rate
+        let smoothed = self.box_filter.process(mixed);
+        self.resampler.process(smoothed, &mut self.resampled);
+        
+        // Filter at the output rate and add to output buffer
+        self.samples_generated += self.resampled.len() as u64;
+        let levels = [pulse1 / 15.0, pulse2 / 15.0, triangle / 15.0, noise / 15.0, dmc / 127.0];
+        for sample in self.resampled.drain(..) {
+            self.scopes.push(levels);
+            let sample = self.filter.process(sample);
+            self.samples.push(sample);
+            self.output_buffer.write(&[sample]);
         }
     }
     
@@ -772,8 +924,63 @@ impl Apu {
         volume as f32
     }
     
+    /// The last `WAVEFORM_LENGTH` levels of pulse 1, pulse 2, triangle,
+    /// noise and DMC, oldest first, for drawing oscilloscopes. One entry
+    /// per output sample, taken before mixing and scaled to 0.0-1.0 of the
+    /// channel's DAC range. Not part of save states, and frozen while
+    /// audio is disabled.
+    pub fn channel_waveforms(&self) -> [&[f32]; 5] {
+        self.scopes.waveforms()
+    }
+    
+    /// Pitch in Hz of a pulse or the triangle channel, for note displays.
+    /// `None` while the channel is silenced by $4015, its length or linear
+    /// counter or an out of range period, and always for noise and DMC,
+    /// which have no pitch to speak of.
+    pub fn channel_frequency(&self, channel: ApuChannel, region: Region) -> Option<f32> {
+        let cpu_clock = region.cpu_clock() as f32;
+        match channel {
+            ApuChannel::Pulse1 | ApuChannel::Pulse2 => {
+                let pulse = if channel == ApuChannel::Pulse1 { &self.pulse1 } else { &self.pulse2 };
+                let audible = pulse.enabled && pulse.length_counter > 0 && (8..=0x7FF).contains(&pulse.timer_period);
+                audible.then(|| cpu_clock / (16.0 * (pulse.timer_period as f32 + 1.0)))
+            }
+            ApuChannel::Triangle => {
+                let triangle = &self.triangle;
+                let audible = triangle.enabled
+                    && triangle.length_counter > 0
+                    && triangle.linear_counter > 0
+                    && triangle.timer_period >= 2;
+                audible.then(|| cpu_clock / (32.0 * (triangle.timer_period as f32 + 1.0)))
+            }
+            ApuChannel::Noise | ApuChannel::Dmc => None,
+        }
+    }
+    
     pub fn get_samples(&mut self) -> Vec<f32> {
-        std::mem::take(&mut self.samples)
+        core::mem::take(&mut self.samples)
+    }
+    
+    /// Output samples produced since power on, drained or not
+    pub fn samples_generated(&self) -> u64 {
+        self.samples_generated
+    }
+    
+    /// Drain the same samples as `get_samples`, as interleaved L/R pairs
+    pub fn get_stereo_samples(&mut self) -> Vec<f32> {
+        let mono = core::mem::take(&mut self.samples);
+        let mut stereo = Vec::with_capacity(mono.len() * 2);
+        for sample in mono {
+            let (left, right) = self.stereo_delay.process(sample);
+            stereo.push(left);
+            stereo.push(right);
+        }
+        stereo
+    }
+    
+    /// Delay the right channel of `get_stereo_samples` for a wider image, 0 disables
+    pub fn set_stereo_delay_ms(&mut self, delay_ms: f32) {
+        self.stereo_delay = StereoDelay::new(self.sample_rate, delay_ms);
     }
     
     /// Get samples from the output buffer with proper timing
@@ -786,16 +993,138 @@ impl Apu {
         self.output_buffer.stats()
     }
     
-    /// Set audio quality
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    
+    /// Resample to a new output rate, e.g. the 48kHz many browsers and
+    /// sound cards run at. The resampler quality, output latency and stereo
+    /// delay carry over; samples not yet drained are dropped.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        let cpu_rate = cpu_rate();
+        let latency_ms = self.output_buffer.stats().target_latency_ms;
+        let delay_ms = self.stereo_delay.delay_samples() as f32 * 1000.0 / self.sample_rate as f32;
+        
+        self.sample_rate = sample_rate;
+        self.filter = NesAudioFilter::new(sample_rate as f32);
+        self.box_filter = BoxFilter::new(cpu_rate, sample_rate as f32);
+        self.resampler = Resampler::new(self.resampler.quality(), cpu_rate, sample_rate as f32);
+        self.output_buffer = AdaptiveBuffer::new(sample_rate as f32, latency_ms);
+        self.stereo_delay = StereoDelay::new(sample_rate, delay_ms);
+        self.samples.clear();
+    }
+    
+    /// Change the resampler quality. Safe to call while audio is
+    /// playing: the new resampler picks up the stream where the old one
+    /// left it, so switching doesn't click.
     pub fn set_quality(&mut self, quality: ResamplerQuality) {
-        let cpu_rate = 1789773.0;
-        self.resampler = Resampler::new(quality, cpu_rate, self.sample_rate as f32);
+        self.resampler.set_quality(quality, cpu_rate(), self.sample_rate as f32);
+    }
+    
+    pub fn quality(&self) -> ResamplerQuality {
+        self.resampler.quality()
+    }
+    
+    /// Choose how channels are mixed; NonLinear by default. See
+    /// `MixingMode` for how the levels differ.
+    pub fn set_mixing(&mut self, mixing: MixingMode) {
+        self.mixing = mixing;
+    }
+    
+    pub fn mixing(&self) -> MixingMode {
+        self.mixing
+    }
+    
+    /// Resize the output buffer for a new latency target
+    pub fn set_output_latency(&mut self, latency_ms: f32) {
+        self.output_buffer = AdaptiveBuffer::new(self.sample_rate as f32, latency_ms);
+    }
+    
+    pub fn debug_info(&self) -> ApuDebugInfo {
+        ApuDebugInfo {
+            five_step_mode: self.frame_mode,
+            frame_irq_inhibit: self.frame_irq_inhibit,
+            frame_irq: self.frame_irq,
+            dmc_irq: self.dmc.interrupt,
+            frame_cycle: self.frame_cycles,
+        }
+    }
+    
+    /// Reset button: silences every channel as a $4015 write of 0 would and
+    /// restarts the frame sequence with the frame IRQ cleared. The other
+    /// channel registers and the $4017 mode are kept.
+    pub fn reset(&mut self) {
+        self.write_register(0x4015, 0);
+        self.frame_irq = false;
+        self.frame_cycles = 0;
+        self.frame_reset_delay = 0;
+    }
+    
+    /// Power-on state: every register as if written with 0 and the frame
+    /// counter in 4-step mode. Output settings like the sample rate and
+    /// resampler quality are kept.
+    pub fn power_on(&mut self) {
+        let fresh = Self::with_sample_rate(self.sample_rate);
+        self.pulse1 = fresh.pulse1;
+        self.pulse2 = fresh.pulse2;
+        self.triangle = fresh.triangle;
+        self.noise = fresh.noise;
+        self.dmc = fresh.dmc;
+        self.frame_counter = fresh.frame_counter;
+        self.frame_mode = fresh.frame_mode;
+        self.frame_irq = fresh.frame_irq;
+        self.frame_irq_inhibit = fresh.frame_irq_inhibit;
+        self.cycles = 0;
+        self.frame_cycles = 0;
+        self.frame_reset_delay = 0;
+        self.reset_audio();
+    }
+    
+    /// Adopt the output settings of `current`, the APU this one is
+    /// replacing: sample rate, resampler quality, latency, stereo delay,
+    /// mixing and whether audio is on. Settings that already match leave
+    /// the filters and resampler mid-stream, so a restored state replays
+    /// the same samples.
+    pub fn keep_output_settings(&mut self, current: &Apu) {
+        if self.sample_rate != current.sample_rate {
+            self.set_sample_rate(current.sample_rate);
+        }
+        if self.quality() != current.quality() {
+            self.set_quality(current.quality());
+        }
+        let latency_ms = current.output_buffer.stats().target_latency_ms;
+        if self.output_buffer.stats().target_latency_ms != latency_ms {
+            self.set_output_latency(latency_ms);
+        }
+        if self.stereo_delay.delay_samples() != current.stereo_delay.delay_samples() {
+            self.stereo_delay = current.stereo_delay.clone();
+        }
+        self.mixing = current.mixing;
+        self.set_audio_enabled(current.audio_enabled);
+    }
+    
+    /// With audio off the channels, length counters and IRQs keep running,
+    /// so $4015 and game timing are unaffected, but no samples are made.
+    /// Saves the mixing, resampling and filtering cost when running
+    /// headless. On by default.
+    pub fn set_audio_enabled(&mut self, enabled: bool) {
+        if enabled && !self.audio_enabled {
+            self.reset_audio();
+        }
+        self.audio_enabled = enabled;
+    }
+    
+    pub fn audio_enabled(&self) -> bool {
+        self.audio_enabled
     }
     
     /// Reset audio processing
     pub fn reset_audio(&mut self) {
         self.filter.reset();
+        self.box_filter.reset();
         self.resampler.reset();
+        self.stereo_delay.reset();
+        self.scopes.clear();
         self.samples.clear();
     }
 }
\ No newline at end of file
@@ -1,11 +1,13 @@
 /// Audio resampler for NES APU
 /// Converts from NES native sample rate to target output sample rate
 
-use std::collections::VecDeque;
+use alloc::collections::VecDeque;
+use alloc::{vec, vec::Vec};
+use serde::{Serialize, Deserialize};
 
 /// Linear interpolation resampler
 /// Simple but effective for most use cases
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LinearResampler {
     source_rate: f32,
     target_rate: f32,
@@ -41,11 +43,21 @@ impl LinearResampler {
         self.phase = 0.0;
         self.prev_sample = 0.0;
     }
+    
+    pub fn last_input(&self) -> f32 {
+        self.prev_sample
+    }
+    
+    /// Pick up a stream another resampler was processing
+    pub fn prime(&mut self, phase: f32, last_input: f32) {
+        self.phase = phase;
+        self.prev_sample = last_input;
+    }
 }
 
 /// Hermite interpolation resampler
 /// Higher quality than linear, good balance of quality and performance
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HermiteResampler {
     source_rate: f32,
     target_rate: f32,
@@ -101,11 +113,135 @@ impl HermiteResampler {
         self.phase = 0.0;
         self.history = [0.0; 4];
     }
+    
+    pub fn last_input(&self) -> f32 {
+        self.history[3]
+    }
+    
+    /// Pick up a stream another resampler was processing
+    pub fn prime(&mut self, phase: f32, last_input: f32) {
+        self.phase = phase;
+        self.history = [last_input; 4];
+    }
+}
+
+/// Windowed-sinc FIR resampler
+/// Low-pass filters below the output Nyquist frequency before decimating,
+/// so tones the output rate can't represent are removed instead of aliased
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SincResampler {
+    ratio: f32,
+    phase: f32,
+    history: VecDeque<f32>,
+    /// Kernel half width in input samples
+    half_width: f32,
+    /// One side of the kernel, `TABLE_STEPS` entries per zero crossing
+    table: Vec<f32>,
+}
+
+impl SincResampler {
+    /// Zero crossings on each side of the kernel at unity ratio
+    const ZERO_CROSSINGS: usize = 8;
+    const TABLE_STEPS: usize = 64;
+    /// Passband edge as a fraction of the output Nyquist frequency
+    const CUTOFF: f32 = 0.9;
+    
+    pub fn new(source_rate: f32, target_rate: f32) -> Self {
+        let ratio = source_rate / target_rate;
+        
+        // When downsampling the kernel is stretched so it cuts off below the
+        // output Nyquist frequency rather than the input one
+        let half_width = Self::ZERO_CROSSINGS as f32 * ratio.max(1.0);
+        let taps = 2 * libm::ceilf(half_width) as usize + 2;
+        
+        let size = Self::ZERO_CROSSINGS * Self::TABLE_STEPS;
+        let table = (0..=size)
+            .map(|i| {
+                let u = i as f32 / size as f32;
+                Self::sinc(Self::CUTOFF * Self::ZERO_CROSSINGS as f32 * u) * Self::blackman(u)
+            })
+            .collect();
+        
+        Self {
+            ratio,
+            phase: 0.0,
+            history: VecDeque::from(vec![0.0; taps]),
+            half_width,
+            table,
+        }
+    }
+    
+    pub fn process(&mut self, input: f32, output: &mut Vec<f32>) {
+        self.history.pop_front();
+        self.history.push_back(input);
+        
+        // Output lands between the two samples just before the lookahead half
+        let center = (self.history.len() / 2 - 1) as f32;
+        while self.phase < 1.0 {
+            output.push(self.convolve(center + self.phase));
+            self.phase += self.ratio;
+        }
+        
+        self.phase -= 1.0;
+    }
+    
+    fn convolve(&self, position: f32) -> f32 {
+        let mut sum = 0.0;
+        let mut weight_sum = 0.0;
+        for (i, &sample) in self.history.iter().enumerate() {
+            let weight = self.kernel((i as f32 - position).abs() / self.half_width);
+            sum += sample * weight;
+            weight_sum += weight;
+        }
+        // Normalizing keeps the DC gain at exactly 1 for any phase
+        if weight_sum != 0.0 { sum / weight_sum } else { 0.0 }
+    }
+    
+    /// Kernel at `u` half widths from the center, linearly interpolated
+    fn kernel(&self, u: f32) -> f32 {
+        if u >= 1.0 {
+            return 0.0;
+        }
+        let position = u * (self.table.len() - 1) as f32;
+        let index = position as usize;
+        let frac = position - index as f32;
+        self.table[index] + (self.table[index + 1] - self.table[index]) * frac
+    }
+    
+    fn sinc(x: f32) -> f32 {
+        if x == 0.0 {
+            1.0
+        } else {
+            let px = core::f32::consts::PI * x;
+            libm::sinf(px) / px
+        }
+    }
+    
+    fn blackman(u: f32) -> f32 {
+        let pu = core::f32::consts::PI * u;
+        0.42 + 0.5 * libm::cosf(pu) + 0.08 * libm::cosf(2.0 * pu)
+    }
+    
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+        self.history.iter_mut().for_each(|sample| *sample = 0.0);
+    }
+    
+    pub fn last_input(&self) -> f32 {
+        self.history.back().copied().unwrap_or(0.0)
+    }
+    
+    /// Pick up a stream another resampler was processing
+    pub fn prime(&mut self, phase: f32, last_input: f32) {
+        self.phase = phase;
+        self.history.iter_mut().for_each(|sample| *sample = last_input);
+    }
 }
 
 /// Blep (Band-Limited Step) resampler
-/// Highest quality, reduces aliasing artifacts
-#[derive(Debug, Clone)]
+/// Experimental: the step correction is a rough approximation and can
+/// sound worse than Hermite. Use `ResamplerQuality::High` for quality.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlepResampler {
     source_rate: f32,
     target_rate: f32,
@@ -195,21 +331,34 @@ impl BlepResampler {
         self.blep_buffer.clear();
         self.prev_sample = 0.0;
     }
+    
+    pub fn last_input(&self) -> f32 {
+        self.prev_sample
+    }
+    
+    /// Pick up a stream another resampler was processing
+    pub fn prime(&mut self, phase: f32, last_input: f32) {
+        self.phase = phase;
+        self.blep_buffer.clear();
+        self.prev_sample = last_input;
+    }
 }
 
 /// Resampler quality settings
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResamplerQuality {
     Low,      // Linear interpolation
     Medium,   // Hermite interpolation
-    High,     // BLEP resampling
+    High,     // Windowed-sinc FIR
+    ExperimentalBlep, // BLEP resampling, see `BlepResampler`
 }
 
 /// Unified resampler interface
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Resampler {
     Linear(LinearResampler),
     Hermite(HermiteResampler),
+    Sinc(SincResampler),
     Blep(BlepResampler),
 }
 
@@ -218,7 +367,8 @@ impl Resampler {
         match quality {
             ResamplerQuality::Low => Resampler::Linear(LinearResampler::new(source_rate, target_rate)),
             ResamplerQuality::Medium => Resampler::Hermite(HermiteResampler::new(source_rate, target_rate)),
-            ResamplerQuality::High => Resampler::Blep(BlepResampler::new(source_rate, target_rate)),
+            ResamplerQuality::High => Resampler::Sinc(SincResampler::new(source_rate, target_rate)),
+            ResamplerQuality::ExperimentalBlep => Resampler::Blep(BlepResampler::new(source_rate, target_rate)),
         }
     }
     
@@ -226,6 +376,7 @@ impl Resampler {
         match self {
             Resampler::Linear(r) => r.process(input, output),
             Resampler::Hermite(r) => r.process(input, output),
+            Resampler::Sinc(r) => r.process(input, output),
             Resampler::Blep(r) => r.process(input, output),
         }
     }
@@ -234,9 +385,40 @@ impl Resampler {
         match self {
             Resampler::Linear(r) => r.reset(),
             Resampler::Hermite(r) => r.reset(),
+            Resampler::Sinc(r) => r.reset(),
             Resampler::Blep(r) => r.reset(),
         }
     }
+    
+    /// Switch quality mid-stream. The new resampler continues at the old
+    /// one's phase with its history filled with the last input sample, so
+    /// the output carries on from its current level rather than restarting
+    /// from silence, which is heard as a click.
+    pub fn set_quality(&mut self, quality: ResamplerQuality, source_rate: f32, target_rate: f32) {
+        let (phase, last_input) = match self {
+            Resampler::Linear(r) => (r.phase, r.last_input()),
+            Resampler::Hermite(r) => (r.phase, r.last_input()),
+            Resampler::Sinc(r) => (r.phase, r.last_input()),
+            Resampler::Blep(r) => (r.phase, r.last_input()),
+        };
+        
+        *self = Resampler::new(quality, source_rate, target_rate);
+        match self {
+            Resampler::Linear(r) => r.prime(phase, last_input),
+            Resampler::Hermite(r) => r.prime(phase, last_input),
+            Resampler::Sinc(r) => r.prime(phase, last_input),
+            Resampler::Blep(r) => r.prime(phase, last_input),
+        }
+    }
+    
+    pub fn quality(&self) -> ResamplerQuality {
+        match self {
+            Resampler::Linear(_) => ResamplerQuality::Low,
+            Resampler::Hermite(_) => ResamplerQuality::Medium,
+            Resampler::Sinc(_) => ResamplerQuality::High,
+            Resampler::Blep(_) => ResamplerQuality::ExperimentalBlep,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -266,10 +448,62 @@ mod tests {
         assert!(!output.is_empty());
     }
     
+    #[test]
+    fn test_quality_switch_keeps_level() {
+        // A rebuilt resampler would restart from 0 and jump by the full level
+        let mut resampler = Resampler::new(ResamplerQuality::Medium, 176400.0, 44100.0);
+        let mut output = Vec::new();
+        for _ in 0..100 {
+            resampler.process(0.5, &mut output);
+        }
+        
+        for quality in [ResamplerQuality::High, ResamplerQuality::Low, ResamplerQuality::ExperimentalBlep, ResamplerQuality::Medium] {
+            resampler.set_quality(quality, 176400.0, 44100.0);
+            assert_eq!(resampler.quality(), quality);
+            output.clear();
+            resampler.process(0.5, &mut output);
+            resampler.process(0.5, &mut output);
+            assert!(output.iter().all(|s| (s - 0.5).abs() < 1e-4), "{:?} output {:?}", quality, output);
+        }
+    }
+    
     #[test]
     fn test_resampler_quality_selection() {
         let _low = Resampler::new(ResamplerQuality::Low, 48000.0, 44100.0);
         let _med = Resampler::new(ResamplerQuality::Medium, 48000.0, 44100.0);
         let _high = Resampler::new(ResamplerQuality::High, 48000.0, 44100.0);
+        let _blep = Resampler::new(ResamplerQuality::ExperimentalBlep, 48000.0, 44100.0);
+    }
+    
+    // RMS of a sine at `freq` Hz after resampling 176.4kHz -> 44.1kHz,
+    // skipping the start-up transient
+    fn resampled_rms(quality: ResamplerQuality, freq: f32) -> f32 {
+        let source_rate = 176400.0;
+        let mut resampler = Resampler::new(quality, source_rate, 44100.0);
+        let mut output = Vec::new();
+        for i in 0..17640 {
+            let t = i as f32 / source_rate;
+            resampler.process(libm::sinf(2.0 * core::f32::consts::PI * freq * t), &mut output);
+        }
+        
+        let tail = &output[400..];
+        libm::sqrtf(tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32)
+    }
+    
+    #[test]
+    fn test_sinc_resampler_passband() {
+        // A full scale sine has an RMS of 1/sqrt(2)
+        let rms = resampled_rms(ResamplerQuality::High, 1000.0);
+        assert!((rms - 0.707).abs() < 0.02, "1kHz RMS {}", rms);
+    }
+    
+    #[test]
+    fn test_sinc_resampler_rejects_aliases() {
+        // 30kHz is above the 22.05kHz output Nyquist and would fold down
+        // to 14.1kHz; interpolating resamplers let most of it through
+        let high = resampled_rms(ResamplerQuality::High, 30000.0);
+        let medium = resampled_rms(ResamplerQuality::Medium, 30000.0);
+        assert!(high < 0.01, "Alias RMS {}", high);
+        assert!(high * 10.0 < medium, "Sinc {} vs Hermite {}", high, medium);
     }
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+use alloc::boxed::Box;
+
+/// Samples `Apu::channel_waveforms` keeps per channel
+pub const WAVEFORM_LENGTH: usize = 256;
+
+/// Recent levels of the five channels for oscilloscope views. Each ring is
+/// written twice, half a buffer apart, so the latest `WAVEFORM_LENGTH`
+/// samples are always one contiguous slice.
+#[derive(Debug, Clone)]
+pub struct ChannelScopes {
+    rings: Box<[[f32; WAVEFORM_LENGTH * 2]; 5]>,
+    // Oldest sample, and where the next one goes
+    position: usize,
+}
+
+impl ChannelScopes {
+    pub fn new() -> Self {
+        Self {
+            rings: Box::new([[0.0; WAVEFORM_LENGTH * 2]; 5]),
+            position: 0,
+        }
+    }
+    
+    pub fn push(&mut self, levels: [f32; 5]) {
+        for (ring, level) in self.rings.iter_mut().zip(levels) {
+            ring[self.position] = level;
+            ring[self.position + WAVEFORM_LENGTH] = level;
+        }
+        self.position = (self.position + 1) % WAVEFORM_LENGTH;
+    }
+    
+    /// Oldest first
+    pub fn waveforms(&self) -> [&[f32]; 5] {
+        self.rings.each_ref().map(|ring| &ring[self.position..self.position + WAVEFORM_LENGTH])
+    }
+    
+    pub fn clear(&mut self) {
+        for ring in self.rings.iter_mut() {
+            ring.fill(0.0);
+        }
+        self.position = 0;
+    }
+}
+
+impl Default for ChannelScopes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
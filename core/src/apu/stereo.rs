@@ -0,0 +1,72 @@
+use alloc::{vec, vec::Vec};
+use serde::{Serialize, Deserialize};
+
+/// Haas-effect widener: the left channel carries the mono mix as-is and the
+/// right channel a copy delayed by a few milliseconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StereoDelay {
+    delay_line: Vec<f32>,
+    position: usize,
+}
+
+impl StereoDelay {
+    pub fn new(sample_rate: u32, delay_ms: f32) -> Self {
+        let delay_samples = (sample_rate as f32 * delay_ms.max(0.0) / 1000.0) as usize;
+        
+        Self {
+            delay_line: vec![0.0; delay_samples],
+            position: 0,
+        }
+    }
+    
+    /// Returns (left, right)
+    pub fn process(&mut self, input: f32) -> (f32, f32) {
+        if self.delay_line.is_empty() {
+            return (input, input);
+        }
+        
+        let delayed = self.delay_line[self.position];
+        self.delay_line[self.position] = input;
+        self.position = (self.position + 1) % self.delay_line.len();
+        (input, delayed)
+    }
+    
+    pub fn delay_samples(&self) -> usize {
+        self.delay_line.len()
+    }
+    
+    pub fn reset(&mut self) {
+        self.delay_line.fill(0.0);
+        self.position = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_zero_delay_is_mono() {
+        let mut delay = StereoDelay::new(44100, 0.0);
+        assert_eq!(delay.process(0.5), (0.5, 0.5));
+        assert_eq!(delay.process(-0.25), (-0.25, -0.25));
+    }
+    
+    #[test]
+    fn test_impulse_delay() {
+        // 10ms at 44.1kHz = 441 samples
+        let mut delay = StereoDelay::new(44100, 10.0);
+        
+        let mut right_peak = None;
+        for i in 0..1000 {
+            let input = if i == 0 { 1.0 } else { 0.0 };
+            let (left, right) = delay.process(input);
+            assert_eq!(left, input);
+            if right == 1.0 {
+                right_peak = Some(i);
+            }
+        }
+        
+        assert_eq!(right_peak, Some(441));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::Cartridge;
+
+/// Keeps a cartridge's battery-backed PRG RAM in a `.sav` file.
+/// Writes are debounced: the file is rewritten once the game has left PRG RAM
+/// alone for `flush_delay` frames, and whenever `flush` is called (on exit).
+pub struct BatterySave {
+    path: PathBuf,
+    flush_delay: u32,
+    unsaved: bool,
+    quiet_frames: u32,
+}
+
+impl BatterySave {
+    /// About three seconds at 60 FPS
+    pub const DEFAULT_FLUSH_DELAY: u32 = 180;
+    
+    pub fn new<P: AsRef<Path>>(path: P, flush_delay: u32) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            flush_delay,
+            unsaved: false,
+            quiet_frames: 0,
+        }
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    /// Copy an existing save file into PRG RAM. Returns false if the
+    /// cartridge has no battery or there is no file yet.
+    pub fn load(&self, cartridge: &mut Cartridge) -> io::Result<bool> {
+        if !cartridge.has_battery() || cartridge.prg_ram_size() == 0 {
+            return Ok(false);
+        }
+        match fs::read(&self.path) {
+            Ok(data) => {
+                cartridge.load_battery_ram(&data);
+                cartridge.clear_battery_ram_dirty();
+                Ok(true)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Call once per frame. Returns true if the save file was written.
+    pub fn end_frame(&mut self, cartridge: &mut Cartridge) -> io::Result<bool> {
+        if cartridge.battery_ram_dirty() {
+            cartridge.clear_battery_ram_dirty();
+            self.unsaved = true;
+            self.quiet_frames = 0;
+            return Ok(false);
+        }
+        if !self.unsaved {
+            return Ok(false);
+        }
+        
+        self.quiet_frames += 1;
+        if self.quiet_frames < self.flush_delay {
+            return Ok(false);
+        }
+        self.flush(cartridge)
+    }
+    
+    /// Write PRG RAM now if it changed since the last write
+    pub fn flush(&mut self, cartridge: &mut Cartridge) -> io::Result<bool> {
+        if !self.unsaved && !cartridge.battery_ram_dirty() {
+            return Ok(false);
+        }
+        let Some(ram) = cartridge.battery_ram() else {
+            return Ok(false);
+        };
+        
+        write_atomic(&self.path, ram)?;
+        cartridge.clear_battery_ram_dirty();
+        self.unsaved = false;
+        self.quiet_frames = 0;
+        Ok(true)
+    }
+}
+
+/// Write through a temporary file and rename it over `path`, so a crash
+/// mid-write leaves the previous save intact
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    
+    let file = fs::File::create(&temp)?;
+    io::Write::write_all(&mut &file, data)?;
+    file.sync_all()?;
+    fs::rename(&temp, path)
+}
\ No newline at end of file
@@ -1,20 +1,111 @@
-use crate::cpu::{Cpu, CpuBus};
+use crate::cpu::{ClockedBus, Cpu, CpuBus, StackWrap};
 use crate::ppu::Ppu;
 use crate::apu::Apu;
 use crate::cartridge::Cartridge;
+use crate::expansion::ExpansionDevice;
+use crate::coverage::{Access, CoverageMap};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use serde::{Serialize, Deserialize};
+use serde_big_array::BigArray;
 
+/// Controller shift registers and OAM DMA progress, for save states
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusIoState {
+    pub controller_shift: [u8; 2],
+    pub controller_strobe: bool,
+    pub oam_dma_page: Option<u8>,
+    pub oam_dma_cycle: u16,
+}
+
+/// What kind of suspicious access an `AccessWarning` reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessWarningKind {
+    /// Write to cartridge space that nothing on the board latches, e.g. ROM
+    ReadOnlyWrite,
+    /// Read of an address nothing drives, which returns open bus
+    UnmappedRead,
+    UnmappedWrite,
+    /// A mapper register the emulator doesn't implement yet
+    UnimplementedRegister,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessWarning {
+    pub kind: AccessWarningKind,
+    pub addr: u16,
+    /// `None` for reads
+    pub value: Option<u8>,
+    /// Address of the instruction that made the access
+    pub pc: u16,
+}
+
+pub type AccessWarningHook = Box<dyn FnMut(AccessWarning)>;
+
+/// Clones start without the hook, so a copy of the machine (rewind,
+/// run-ahead) doesn't report accesses the original already did
+#[derive(Default)]
+struct AccessWarnings(Option<AccessWarningHook>);
+
+impl Clone for AccessWarnings {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+/// The stack pointer wrapped, usually a push/pull imbalance in the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackWarning {
+    pub wrap: StackWrap,
+    /// Address of the instruction that pushed or pulled; for an interrupt,
+    /// the last instruction before it
+    pub pc: u16,
+}
+
+pub type StackWarningHook = Box<dyn FnMut(StackWarning)>;
+
+/// Not cloned, like `AccessWarnings`
+#[derive(Default)]
+struct StackWarnings(Option<StackWarningHook>);
+
+impl Clone for StackWarnings {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+/// Serializes everything but the cartridge (saved through `MapperState`),
+/// the expansion device, the coverage map and the warning hooks
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Bus {
+    #[serde(with = "BigArray")]
     ram: [u8; 0x800],      // 2KB internal RAM
     pub ppu: Ppu,
     pub apu: Apu,
+    #[serde(skip)]
     pub cartridge: Option<Cartridge>,
     controller1: u8,
     controller2: u8,
     controller1_state: u8,
     controller2_state: u8,
     controller_strobe: bool,
+    controllers_connected: [bool; 2],
+    microphone: bool,
     oam_dma_page: Option<u8>,
     oam_dma_cycle: u16,
+    // Last value on the CPU data bus, what reads of undriven registers see
+    open_bus: u8,
+    #[serde(skip)]
+    expansion: Option<Box<dyn ExpansionDevice>>,
+    #[serde(skip)]
+    coverage: Option<CoverageMap>,
+    #[serde(skip)]
+    access_warnings: AccessWarnings,
+    #[serde(skip)]
+    stack_warnings: StackWarnings,
+    /// Address of the last opcode fetch, reported in access warnings
+    #[serde(skip)]
+    instruction_pc: u16,
 }
 
 impl Bus {
@@ -29,15 +120,49 @@ impl Bus {
             controller1_state: 0,
             controller2_state: 0,
             controller_strobe: false,
+            controllers_connected: [true; 2],
+            microphone: false,
             oam_dma_page: None,
             oam_dma_cycle: 0,
+            open_bus: 0,
+            expansion: None,
+            coverage: None,
+            access_warnings: AccessWarnings::default(),
+            stack_warnings: StackWarnings::default(),
+            instruction_pc: 0,
         }
     }
     
     pub fn load_cartridge(&mut self, cartridge: Cartridge) {
+        self.ppu.set_mirroring(cartridge.mirroring());
         self.cartridge = Some(cartridge);
     }
     
+    /// Reset button: only the PPU and APU see the reset line
+    pub fn reset(&mut self) {
+        self.ppu.reset();
+        self.apu.reset();
+        self.oam_dma_page = None;
+        self.oam_dma_cycle = 0;
+    }
+    
+    /// Power-on state for RAM, the PPU, the APU and the controller ports.
+    /// RAM comes up zeroed, as does PRG RAM without a battery. Mapper
+    /// registers and battery-backed RAM are kept.
+    pub fn power_on(&mut self) {
+        self.ram = [0; 0x800];
+        self.ppu.power_on();
+        self.apu.power_on();
+        self.controller1 = 0;
+        self.controller2 = 0;
+        self.controller_strobe = false;
+        self.oam_dma_page = None;
+        self.oam_dma_cycle = 0;
+        if let Some(cartridge) = &mut self.cartridge {
+            cartridge.clear_volatile_ram();
+        }
+    }
+    
     pub fn set_controller1(&mut self, state: u8) {
         self.controller1_state = state;
     }
@@ -46,7 +171,87 @@ impl Bus {
         self.controller2_state = state;
     }
     
-    pub fn tick(&mut self, cpu: &mut Cpu) {
+    /// Unplug (or replug) the controller in `port` (0 or 1). Both start connected.
+    pub fn set_controller_connected(&mut self, port: usize, connected: bool) {
+        if let Some(slot) = self.controllers_connected.get_mut(port) {
+            *slot = connected;
+        }
+    }
+    
+    pub fn controller_connected(&self, port: usize) -> bool {
+        self.controllers_connected.get(port).copied().unwrap_or(false)
+    }
+    
+    pub fn set_microphone(&mut self, active: bool) {
+        self.microphone = active;
+    }
+    
+    // The mic sits on the second controller but is read through $4016
+    fn microphone_bit(&self) -> u8 {
+        if self.microphone && self.controllers_connected[1] { 0x04 } else { 0 }
+    }
+    
+    pub fn set_expansion_device(&mut self, device: Option<Box<dyn ExpansionDevice>>) {
+        self.expansion = device;
+    }
+    
+    pub fn expansion_device_mut(&mut self) -> Option<&mut (dyn ExpansionDevice + 'static)> {
+        self.expansion.as_deref_mut()
+    }
+    
+    /// Start recording a `CoverageMap`, or stop and drop it. Everything that
+    /// reads through the bus counts, including OAM DMA and debugger peeks.
+    pub fn set_coverage_enabled(&mut self, enabled: bool) {
+        if enabled != self.coverage.is_some() {
+            self.coverage = enabled.then(CoverageMap::new);
+        }
+    }
+    
+    pub fn coverage(&self) -> Option<&CoverageMap> {
+        self.coverage.as_ref()
+    }
+    
+    pub fn coverage_mut(&mut self) -> Option<&mut CoverageMap> {
+        self.coverage.as_mut()
+    }
+    
+    /// Called on reads of unmapped addresses, writes nothing takes and
+    /// unimplemented mapper registers. Debugger peeks count too.
+    pub fn set_access_warning_hook(&mut self, hook: Option<AccessWarningHook>) {
+        self.access_warnings = AccessWarnings(hook);
+    }
+    
+    /// Called when a push or pull wraps the stack pointer
+    pub fn set_stack_warning_hook(&mut self, hook: Option<StackWarningHook>) {
+        self.stack_warnings = StackWarnings(hook);
+    }
+    
+    #[cold]
+    fn warn_access(&mut self, addr: u16, value: Option<u8>) {
+        let kind = match &self.cartridge {
+            Some(cartridge) if addr >= 0x4020 => cartridge.access_warning(addr, value.is_some()),
+            _ if value.is_some() => Some(AccessWarningKind::UnmappedWrite),
+            _ => Some(AccessWarningKind::UnmappedRead),
+        };
+        if let (Some(kind), Some(hook)) = (kind, &mut self.access_warnings.0) {
+            hook(AccessWarning { kind, addr, value, pc: self.instruction_pc });
+        }
+    }
+    
+    #[inline]
+    pub(crate) fn record_access(&mut self, addr: u16, access: Access) {
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record(addr, access);
+        }
+    }
+    
+    /// Advance one CPU cycle. Returns true if the PPU finished a frame.
+    pub fn tick(&mut self, cpu: &mut Cpu) -> bool {
+        // A $2002 read during the last instruction raced VBlank
+        if self.ppu.take_nmi_cancelled() {
+            cpu.cancel_nmi();
+        }
+        
         // Handle OAM DMA if active
         if let Some(page) = self.oam_dma_page {
             // DMA takes 513 or 514 cycles
@@ -69,11 +274,12 @@ impl Bus {
         
         // PPU runs 3 times per CPU cycle
         let mut nmi = false;
+        let mut frame_complete = false;
         for _ in 0..3 {
             if let Some(ref cartridge) = self.cartridge {
-                if self.ppu.step(cartridge) {
-                    nmi = true;
-                }
+                let result = self.ppu.step(cartridge);
+                nmi |= result.nmi;
+                frame_complete |= result.frame_complete;
             }
         }
         
@@ -83,6 +289,19 @@ impl Bus {
         
         // APU runs once per CPU cycle
         self.apu.step();
+        
+        frame_complete
+    }
+    
+    /// What a CPU read of `addr` would return, without its side effects.
+    /// RAM and cartridge space read normally; registers that react to
+    /// being read give the open bus value instead.
+    pub fn peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x1FFF => self.ram[(addr & 0x7FF) as usize],
+            0x4020..=0xFFFF => self.cartridge.as_ref().map_or(self.open_bus, |cart| cart.read_prg(addr)),
+            _ => self.open_bus,
+        }
     }
     
     // Getters for save state support
@@ -90,6 +309,16 @@ impl Bus {
         &self.ram
     }
     
+    /// Every nametable byte: the console's 2KB, then the cartridge's 2KB
+    /// on four-screen boards
+    pub fn dump_vram(&self) -> Vec<u8> {
+        let mut vram = self.ppu.dump_vram().to_vec();
+        if let Some(cartridge) = &self.cartridge {
+            vram.extend_from_slice(cartridge.nametable_ram());
+        }
+        vram
+    }
+    
     pub fn get_controller1_state(&self) -> u8 {
         self.controller1_state
     }
@@ -98,70 +327,158 @@ impl Bus {
         self.controller2_state
     }
     
+    pub fn get_io_state(&self) -> BusIoState {
+        BusIoState {
+            controller_shift: [self.controller1, self.controller2],
+            controller_strobe: self.controller_strobe,
+            oam_dma_page: self.oam_dma_page,
+            oam_dma_cycle: self.oam_dma_cycle,
+        }
+    }
+    
     // Setters for save state support
     pub fn set_ram(&mut self, ram: &[u8]) {
         self.ram.copy_from_slice(ram);
     }
     
+    /// Inverse of `dump_vram`. Returns false and leaves VRAM alone if
+    /// `vram` isn't the length `dump_vram` gives.
+    pub fn load_vram(&mut self, vram: &[u8]) -> bool {
+        let board_len = self.cartridge.as_ref().map_or(0, |c| c.nametable_ram().len());
+        let Some((console, board)) = vram.split_first_chunk::<0x800>() else {
+            return false;
+        };
+        if board.len() != board_len {
+            return false;
+        }
+        self.ppu.load_vram(console);
+        if let Some(cartridge) = &mut self.cartridge {
+            cartridge.nametable_ram_mut().copy_from_slice(board);
+        }
+        true
+    }
+    
     pub fn set_controller_states(&mut self, controller1: u8, controller2: u8) {
         self.controller1_state = controller1;
         self.controller2_state = controller2;
     }
+    
+    /// Take over the state of a deserialized bus, keeping the cartridge,
+    /// expansion device and the frontend's video and audio settings
+    pub fn restore(&mut self, mut saved: Bus) {
+        saved.cartridge = self.cartridge.take();
+        saved.expansion = self.expansion.take();
+        saved.coverage = self.coverage.take();
+        saved.access_warnings = core::mem::take(&mut self.access_warnings);
+        saved.stack_warnings = core::mem::take(&mut self.stack_warnings);
+        saved.ppu.set_color_table(self.ppu.color_table());
+        saved.ppu.set_verify_fast_path(self.ppu.verify_fast_path());
+        saved.ppu.set_output_filter(self.ppu.take_output_filter());
+        saved.ppu.set_frame_output(self.ppu.frame_output());
+        saved.ppu.set_accuracy(self.ppu.accuracy());
+        saved.ppu.set_sprite_limit(self.ppu.sprite_limit());
+        saved.ppu.set_layer_override(self.ppu.layer_override());
+        saved.ppu.set_greyscale_palette_reads(self.ppu.greyscale_palette_reads());
+        saved.apu.keep_output_settings(&self.apu);
+        *self = saved;
+    }
+    
+    pub fn set_io_state(&mut self, state: &BusIoState) {
+        [self.controller1, self.controller2] = state.controller_shift;
+        self.controller_strobe = state.controller_strobe;
+        self.oam_dma_page = state.oam_dma_page;
+        self.oam_dma_cycle = state.oam_dma_cycle;
+    }
+    
+    fn read_expansion(&mut self, addr: u16) -> Option<u8> {
+        self.expansion.as_mut().and_then(|device| device.read(addr))
+    }
+    
+    fn write_expansion(&mut self, addr: u16, value: u8) -> bool {
+        self.expansion.as_mut().is_some_and(|device| device.write(addr, value))
+    }
+}
+
+impl ClockedBus for Bus {
+    fn tick(&mut self, cpu: &mut Cpu) -> bool {
+        Bus::tick(self, cpu)
+    }
 }
 
 impl CpuBus for Bus {
     #[inline]
     fn read(&mut self, addr: u16) -> u8 {
-        match addr {
+        self.record_access(addr, Access::READ);
+        let value = match addr {
             0x0000..=0x1FFF => {
                 // RAM and mirrors
                 self.ram[(addr & 0x7FF) as usize]
             }
             0x2000..=0x3FFF => {
                 // PPU registers and mirrors
-                self.ppu.read_register((addr & 0x7) as u8)
+                self.ppu.read_register_with_cartridge((addr & 0x7) as u8, self.cartridge.as_ref())
             }
-            0x4000..=0x4015 => {
-                // APU registers
+            0x4000..=0x4014 => {
+                // Write-only APU registers leave the bus floating
+                self.open_bus
+            }
+            0x4015 => {
+                // APU status
                 self.apu.read_register(addr)
             }
+            // Only the low five bits are driven, the top three are open bus
+            0x4016 if !self.controllers_connected[0] => {
+                // Empty port: the data line idles low, only expansion bits show up
+                self.open_bus & 0xE0 | self.microphone_bit() | self.read_expansion(addr).unwrap_or(0)
+            }
+            0x4017 if !self.controllers_connected[1] => {
+                self.open_bus & 0xE0 | self.read_expansion(addr).unwrap_or(0)
+            }
             0x4016 => {
                 // Controller 1
-                if self.controller_strobe {
-                    self.controller1 = self.controller1_state;
-                }
-                let bit = self.controller1 & 0x01;
-                self.controller1 >>= 1;
-                self.controller1 |= 0x80;
-                bit
+                let bit = read_shift_register(&mut self.controller1, self.controller1_state, self.controller_strobe);
+                self.open_bus & 0xE0 | bit | self.microphone_bit() | self.read_expansion(addr).unwrap_or(0)
             }
             0x4017 => {
                 // Controller 2
-                if self.controller_strobe {
-                    self.controller2 = self.controller2_state;
-                }
-                let bit = self.controller2 & 0x01;
-                self.controller2 >>= 1;
-                self.controller2 |= 0x80;
-                bit
+                let bit = read_shift_register(&mut self.controller2, self.controller2_state, self.controller_strobe);
+                self.open_bus & 0xE0 | bit | self.read_expansion(addr).unwrap_or(0)
             }
             0x4018..=0x401F => {
                 // APU and I/O functionality that is normally disabled
-                0
+                let value = self.read_expansion(addr);
+                if value.is_none() && self.access_warnings.0.is_some() {
+                    self.warn_access(addr, None);
+                }
+                value.unwrap_or(0)
             }
             0x4020..=0xFFFF => {
                 // Cartridge space
-                if let Some(ref cart) = self.cartridge {
-                    cart.read_prg(addr)
+                if let Some(value) = self.read_expansion(addr) {
+                    value
                 } else {
-                    0
+                    if self.access_warnings.0.is_some() {
+                        self.warn_access(addr, None);
+                    }
+                    if let Some(ref cart) = self.cartridge {
+                        cart.read_prg(addr)
+                    } else {
+                        0
+                    }
                 }
             }
+        };
+        // $4015 is read inside the CPU, so the external bus keeps its value
+        if addr != 0x4015 {
+            self.open_bus = value;
         }
+        value
     }
     
     #[inline]
     fn write(&mut self, addr: u16, value: u8) {
+        self.record_access(addr, Access::WRITTEN);
+        self.open_bus = value;
         match addr {
             0x0000..=0x1FFF => {
                 // RAM and mirrors
@@ -169,7 +486,7 @@ impl CpuBus for Bus {
             }
             0x2000..=0x3FFF => {
                 // PPU registers and mirrors
-                self.ppu.write_register((addr & 0x7) as u8, value);
+                self.ppu.write_register_with_cartridge((addr & 0x7) as u8, value, self.cartridge.as_mut());
             }
             0x4000..=0x4013 => {
                 // APU registers
@@ -185,26 +502,64 @@ impl CpuBus for Bus {
                 self.apu.write_register(addr, value);
             }
             0x4016 => {
-                // Controller strobe
-                self.controller_strobe = value & 0x01 != 0;
-                if self.controller_strobe {
+                // Controller strobe. The shift registers follow the buttons
+                // while it is high and keep the state from when it drops.
+                if self.controller_strobe || value & 0x01 != 0 {
                     self.controller1 = self.controller1_state;
                     self.controller2 = self.controller2_state;
                 }
+                self.controller_strobe = value & 0x01 != 0;
+                self.write_expansion(addr, value);
             }
             0x4017 => {
                 // APU Frame Counter
                 self.apu.write_register(addr, value);
+                self.write_expansion(addr, value);
             }
             0x4018..=0x401F => {
                 // APU and I/O functionality that is normally disabled
+                if !self.write_expansion(addr, value) && self.access_warnings.0.is_some() {
+                    self.warn_access(addr, Some(value));
+                }
             }
             0x4020..=0xFFFF => {
                 // Cartridge space
+                if self.write_expansion(addr, value) {
+                    return;
+                }
+                if self.access_warnings.0.is_some() {
+                    self.warn_access(addr, Some(value));
+                }
                 if let Some(ref mut cart) = self.cartridge {
                     cart.write_prg(addr, value);
                 }
             }
         }
     }
+    
+    #[inline]
+    fn fetch_opcode(&mut self, addr: u16) -> u8 {
+        self.record_access(addr, Access::EXECUTED);
+        self.instruction_pc = addr;
+        self.read(addr)
+    }
+    
+    #[inline]
+    fn stack_wrapped(&mut self, wrap: StackWrap) {
+        if let Some(hook) = &mut self.stack_warnings.0 {
+            hook(StackWarning { wrap, pc: self.instruction_pc });
+        }
+    }
+}
+
+/// One $4016/$4017 read. With strobe high the register is held at reload,
+/// so every read returns button A as it is right now.
+fn read_shift_register(shift: &mut u8, buttons: u8, strobe: bool) -> u8 {
+    if strobe {
+        *shift = buttons;
+        return buttons & 0x01;
+    }
+    let bit = *shift & 0x01;
+    *shift = (*shift >> 1) | 0x80;
+    bit
 }
\ No newline at end of file
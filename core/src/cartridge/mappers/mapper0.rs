@@ -1,6 +1,8 @@
-use crate::cartridge::{Mapper, MapperState};
+use crate::bus::AccessWarningKind;
+use crate::cartridge::{unmapped_access, Mapper, MapperState};
+use alloc::boxed::Box;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Mapper0;
 
 impl Mapper0 {
@@ -12,15 +14,10 @@ impl Mapper0 {
 impl Mapper for Mapper0 {
     fn read_prg(&self, addr: u16, prg_rom: &[u8]) -> u8 {
         match addr {
-            0x8000..=0xFFFF => {
-                let index = if prg_rom.len() == 16384 {
-                    // 16KB ROM - mirror it
-                    (addr & 0x3FFF) as usize
-                } else {
-                    // 32KB ROM
-                    (addr & 0x7FFF) as usize
-                };
-                prg_rom[index]
+            0x8000..=0xFFFF if !prg_rom.is_empty() => {
+                // 32KB maps linearly; smaller ROMs (NROM-128) repeat
+                // through the window, so the vectors come from the mirror
+                prg_rom[(addr & 0x7FFF) as usize % prg_rom.len()]
             }
             _ => 0,
         }
@@ -43,6 +40,18 @@ impl Mapper for Mapper0 {
         // CHR RAM would be handled differently
     }
     
+    /// No registers, so every write to $8000-$FFFF hits ROM
+    fn access_warning(&self, addr: u16, write: bool) -> Option<AccessWarningKind> {
+        if write && addr >= 0x8000 {
+            return Some(AccessWarningKind::ReadOnlyWrite);
+        }
+        unmapped_access(addr, write, false)
+    }
+    
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
+    }
+    
     fn get_state(&self) -> MapperState {
         MapperState::Mapper0
     }
@@ -1,6 +1,7 @@
-use crate::cartridge::{Mapper, MapperState};
+use crate::cartridge::{Mapper, MapperState, Mirroring};
+use alloc::{boxed::Box, vec, vec::Vec};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Mapper1 {
     shift_register: u8,
     shift_count: u8,
@@ -8,10 +9,14 @@ pub struct Mapper1 {
     chr_bank0: u8,
     chr_bank1: u8,
     prg_bank: u8,
+    prg_rom_size: usize,
+    prg_ram: Vec<u8>,
+    prg_ram_dirty: bool,
 }
 
 impl Mapper1 {
-    pub fn new() -> Self {
+    /// `prg_ram_size` is 8KB on most boards, 16KB on SOROM and 32KB on SXROM
+    pub fn new(prg_rom_size: usize, prg_ram_size: usize) -> Self {
         Self {
             shift_register: 0x10,
             shift_count: 0,
@@ -19,13 +24,32 @@ impl Mapper1 {
             chr_bank0: 0,
             chr_bank1: 0,
             prg_bank: 0,
+            prg_rom_size,
+            prg_ram: vec![0; prg_ram_size],
+            prg_ram_dirty: false,
         }
     }
+    
+    /// Where $6000-$7FFF lands in PRG RAM. SOROM and SXROM pick the 8KB
+    /// bank with CHR bank 0 bits that have no CHR ROM lines to drive.
+    fn prg_ram_index(&self, addr: u16) -> Option<usize> {
+        let bank = match self.prg_ram.len() / 0x2000 {
+            0 => return None,
+            2 => (self.chr_bank0 as usize >> 3) & 0x01,
+            4 => (self.chr_bank0 as usize >> 2) & 0x03,
+            _ => 0,
+        };
+        Some((bank * 0x2000 + (addr - 0x6000) as usize) % self.prg_ram.len())
+    }
 }
 
 impl Mapper for Mapper1 {
     fn read_prg(&self, addr: u16, prg_rom: &[u8]) -> u8 {
         match addr {
+            0x6000..=0x7FFF => {
+                // PRG RAM
+                self.prg_ram_index(addr).map_or(0, |index| self.prg_ram[index])
+            }
             0x8000..=0xBFFF => {
                 let bank = if self.control & 0x08 != 0 {
                     // 16KB mode - use prg_bank
@@ -56,7 +80,40 @@ impl Mapper for Mapper1 {
         }
     }
     
+    fn prg_bank_map(&self) -> [usize; 4] {
+        let (low, high) = if self.control & 0x08 != 0 {
+            let high = if self.control & 0x04 != 0 {
+                (self.prg_rom_size / 0x4000).saturating_sub(1)
+            } else {
+                self.prg_bank as usize
+            };
+            (self.prg_bank as usize, high)
+        } else {
+            (0, 1)
+        };
+        [low * 0x4000, low * 0x4000 + 0x2000, high * 0x4000, high * 0x4000 + 0x2000]
+    }
+    
+    fn chr_bank_map(&self) -> [usize; 8] {
+        let (low, high) = if self.control & 0x10 != 0 {
+            (self.chr_bank0 as usize, self.chr_bank1 as usize)
+        } else {
+            ((self.chr_bank0 & 0xFE) as usize, (self.chr_bank0 | 1) as usize)
+        };
+        core::array::from_fn(|i| {
+            let bank = if i < 4 { low } else { high };
+            bank * 0x1000 + (i % 4) * 0x400
+        })
+    }
+    
     fn write_prg(&mut self, addr: u16, value: u8) {
+        if (0x6000..0x8000).contains(&addr) {
+            if let Some(index) = self.prg_ram_index(addr) {
+                self.prg_ram[index] = value;
+                self.prg_ram_dirty = true;
+            }
+            return;
+        }
         if addr < 0x8000 {
             return;
         }
@@ -117,18 +174,54 @@ impl Mapper for Mapper1 {
             _ => return 0,
         };
         
+        // SNROM and friends use the upper bank bits for other purposes,
+        // so only as many as the CHR size needs count
         let offset = (addr & 0x0FFF) as usize;
-        chr_rom[bank * 0x1000 + offset]
+        chr_rom[(bank * 0x1000 + offset) % chr_rom.len()]
     }
     
     fn write_chr(&mut self, _addr: u16, _value: u8) {
         // CHR writes would go to CHR RAM if present
     }
     
+    fn mirroring(&self) -> Option<Mirroring> {
+        Some(match self.control & 0x03 {
+            0 => Mirroring::SingleScreenLow,
+            1 => Mirroring::SingleScreenHigh,
+            2 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal,
+        })
+    }
+    
+    fn prg_ram(&self) -> Option<&[u8]> {
+        (!self.prg_ram.is_empty()).then_some(&self.prg_ram[..])
+    }
+    
+    fn prg_ram_mut(&mut self) -> Option<&mut [u8]> {
+        (!self.prg_ram.is_empty()).then_some(&mut self.prg_ram[..])
+    }
+    
+    fn prg_ram_size(&self) -> usize {
+        self.prg_ram.len()
+    }
+    
+    fn prg_ram_dirty(&self) -> bool {
+        self.prg_ram_dirty
+    }
+    
+    fn clear_prg_ram_dirty(&mut self) {
+        self.prg_ram_dirty = false;
+    }
+    
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
+    }
+    
     fn get_state(&self) -> MapperState {
         MapperState::Mapper1 {
             shift_register: self.shift_register,
             shift_count: self.shift_count,
+            mirroring: self.control & 0x03,
             prg_bank_mode: (self.control >> 2) & 0x03,
             chr_bank_mode: (self.control >> 4) & 0x01,
             prg_bank: self.prg_bank,
@@ -141,6 +234,7 @@ impl Mapper for Mapper1 {
         if let MapperState::Mapper1 {
             shift_register,
             shift_count,
+            mirroring,
             prg_bank_mode,
             chr_bank_mode,
             prg_bank,
@@ -149,7 +243,7 @@ impl Mapper for Mapper1 {
         } = state {
             self.shift_register = *shift_register;
             self.shift_count = *shift_count;
-            self.control = (*prg_bank_mode << 2) | (*chr_bank_mode << 4);
+            self.control = (*prg_bank_mode << 2) | (*chr_bank_mode << 4) | (*mirroring & 0x03);
             self.prg_bank = *prg_bank;
             self.chr_bank0 = *chr_bank0;
             self.chr_bank1 = *chr_bank1;
@@ -1,4 +1,5 @@
 use crate::cartridge::{Mapper, MapperState};
+use alloc::boxed::Box;
 
 // Mapper 11: Color Dreams
 // Used by some unlicensed games
@@ -6,7 +7,7 @@ use crate::cartridge::{Mapper, MapperState};
 // - 32KB PRG ROM bank switching
 // - 8KB CHR ROM bank switching
 // - Very simple mapper with single register
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Mapper11 {
     prg_bank: usize,
     chr_bank: usize,
@@ -78,11 +79,29 @@ impl Mapper for Mapper11 {
         // CHR ROM is not writable in mapper 11
     }
     
+    fn prg_bank_map(&self) -> [usize; 4] {
+        core::array::from_fn(|i| self.prg_bank * 0x8000 + i * 0x2000)
+    }
+    
+    fn chr_bank_map(&self) -> [usize; 8] {
+        core::array::from_fn(|i| self.chr_bank * 0x2000 + i * 0x400)
+    }
+    
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
+    }
+    
     fn get_state(&self) -> MapperState {
-        MapperState::Other
+        MapperState::Mapper11 {
+            prg_bank: self.prg_bank,
+            chr_bank: self.chr_bank,
+        }
     }
     
-    fn set_state(&mut self, _state: &MapperState) {
-        // Mapper 11 state restoration not implemented yet
+    fn set_state(&mut self, state: &MapperState) {
+        if let MapperState::Mapper11 { prg_bank, chr_bank } = state {
+            self.prg_bank = *prg_bank;
+            self.chr_bank = *chr_bank;
+        }
     }
 }
\ No newline at end of file
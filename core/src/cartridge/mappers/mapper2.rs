@@ -1,18 +1,25 @@
 use crate::cartridge::{Mapper, MapperState};
+use alloc::boxed::Box;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Mapper2 {
     prg_bank: u8,
-    prg_banks: u8,
+    prg_banks: usize,
+    bus_conflicts: bool,
 }
 
 impl Mapper2 {
-    pub fn new(prg_size: usize) -> Self {
+    pub fn new(prg_size: usize, bus_conflicts: bool) -> Self {
         Self {
             prg_bank: 0,
-            prg_banks: (prg_size / 0x4000) as u8,
+            prg_banks: (prg_size / 0x4000).max(1),
+            bus_conflicts,
         }
     }
+    
+    fn last_bank(&self) -> usize {
+        self.prg_banks - 1
+    }
 }
 
 impl Mapper for Mapper2 {
@@ -21,24 +28,35 @@ impl Mapper for Mapper2 {
             0x8000..=0xBFFF => {
                 // Switchable 16KB bank
                 let offset = (addr & 0x3FFF) as usize;
-                prg_rom[self.prg_bank as usize * 0x4000 + offset]
+                prg_rom.get(self.prg_bank as usize * 0x4000 + offset).copied().unwrap_or(0)
             }
             0xC000..=0xFFFF => {
                 // Fixed last 16KB bank
                 let offset = (addr & 0x3FFF) as usize;
-                let last_bank = self.prg_banks - 1;
-                prg_rom[last_bank as usize * 0x4000 + offset]
+                prg_rom.get(self.last_bank() * 0x4000 + offset).copied().unwrap_or(0)
             }
             _ => 0,
         }
     }
     
+    fn prg_bank_map(&self) -> [usize; 4] {
+        let bank = self.prg_bank as usize * 0x4000;
+        let last = self.last_bank() * 0x4000;
+        [bank, bank + 0x2000, last, last + 0x2000]
+    }
+    
     fn write_prg(&mut self, addr: u16, value: u8) {
         if addr >= 0x8000 {
-            self.prg_bank = value & 0x0F;
+            // Only as many bank bits as the ROM needs: 3 for 128KB UNROM,
+            // 4 for 256KB UOROM
+            self.prg_bank = (value as usize % self.prg_banks) as u8;
         }
     }
     
+    fn bus_conflicts(&self) -> bool {
+        self.bus_conflicts
+    }
+    
     fn read_chr(&self, addr: u16, chr_rom: &[u8]) -> u8 {
         if addr < 0x2000 && !chr_rom.is_empty() {
             chr_rom[addr as usize]
@@ -51,6 +69,10 @@ impl Mapper for Mapper2 {
         // CHR RAM if present
     }
     
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
+    }
+    
     fn get_state(&self) -> MapperState {
         MapperState::Mapper2 {
             prg_bank: self.prg_bank,
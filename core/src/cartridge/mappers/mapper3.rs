@@ -1,14 +1,19 @@
 use crate::cartridge::{Mapper, MapperState};
+use alloc::boxed::Box;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Mapper3 {
     chr_bank: u8,
+    chr_banks: usize,
+    bus_conflicts: bool,
 }
 
 impl Mapper3 {
-    pub fn new() -> Self {
+    pub fn new(chr_size: usize, bus_conflicts: bool) -> Self {
         Self {
             chr_bank: 0,
+            chr_banks: (chr_size / 0x2000).max(1),
+            bus_conflicts,
         }
     }
 }
@@ -32,27 +37,35 @@ impl Mapper for Mapper3 {
     
     fn write_prg(&mut self, addr: u16, value: u8) {
         if addr >= 0x8000 {
-            self.chr_bank = value & 0x03;
+            // Bank numbers past the end of CHR ROM wrap around
+            self.chr_bank = (value as usize % self.chr_banks) as u8;
         }
     }
     
+    fn bus_conflicts(&self) -> bool {
+        self.bus_conflicts
+    }
+    
     fn read_chr(&self, addr: u16, chr_rom: &[u8]) -> u8 {
-        if addr < 0x2000 && !chr_rom.is_empty() {
-            let offset = addr as usize + (self.chr_bank as usize * 0x2000);
-            if offset < chr_rom.len() {
-                chr_rom[offset]
-            } else {
-                0
-            }
+        if addr < 0x2000 {
+            chr_rom.get(self.chr_bank as usize * 0x2000 + addr as usize).copied().unwrap_or(0)
         } else {
             0
         }
     }
     
+    fn chr_bank_map(&self) -> [usize; 8] {
+        core::array::from_fn(|i| self.chr_bank as usize * 0x2000 + i * 0x400)
+    }
+    
     fn write_chr(&mut self, _addr: u16, _value: u8) {
         // No CHR RAM on mapper 3
     }
     
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
+    }
+    
     fn get_state(&self) -> MapperState {
         MapperState::Mapper3 {
             chr_bank: self.chr_bank,
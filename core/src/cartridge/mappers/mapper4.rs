@@ -1,8 +1,9 @@
-use crate::cartridge::{Mapper, MapperState};
+use crate::cartridge::{Mapper, MapperState, Mirroring};
+use alloc::{boxed::Box, vec, vec::Vec};
 
 // Mapper 4: MMC3 (Memory Management Controller 3)
 // Used by many popular games like Super Mario Bros. 3, Mega Man 3-6, etc.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Mapper4 {
     // Bank registers
     bank_select: u8,
@@ -30,13 +31,13 @@ pub struct Mapper4 {
     
     // RAM
     prg_ram: Vec<u8>,
-    chr_ram: Vec<u8>,
+    prg_ram_dirty: bool,
     prg_rom_size: usize,
     chr_rom_size: usize,
 }
 
 impl Mapper4 {
-    pub fn new(prg_rom_size: usize, chr_rom_size: usize) -> Self {
+    pub fn new(prg_rom_size: usize, chr_rom_size: usize, prg_ram_size: usize) -> Self {
         let prg_banks = [
             0,
             0x2000,
@@ -59,8 +60,8 @@ impl Mapper4 {
             irq_pending: false,
             last_a12: false,
             a12_filter: 0,
-            prg_ram: vec![0; 0x2000], // 8KB PRG RAM
-            chr_ram: if chr_rom_size == 0 { vec![0; 0x2000] } else { vec![] },
+            prg_ram: vec![0; prg_ram_size],
+            prg_ram_dirty: false,
             prg_rom_size,
             chr_rom_size,
         }
@@ -112,14 +113,13 @@ impl Mapper4 {
             *bank = (*bank).min(self.prg_rom_size.saturating_sub(0x2000));
         }
         
-        if self.chr_rom_size > 0 {
-            for bank in &mut self.chr_banks {
-                *bank = (*bank).min(self.chr_rom_size.saturating_sub(0x400));
-            }
+        for bank in &mut self.chr_banks {
+            *bank = (*bank).min(self.chr_rom_size.saturating_sub(0x400));
         }
     }
     
-    fn clock_scanline(&mut self, addr: u16) {
+    /// Feed a PPU bus address; filtered A12 rising edges clock the scanline counter
+    pub fn clock_scanline(&mut self, addr: u16) {
         // Detect PPU A12 rising edge (used for scanline counting)
         let a12 = (addr & 0x1000) != 0;
         
@@ -153,8 +153,9 @@ impl Mapper for Mapper4 {
     fn read_prg(&self, addr: u16, prg_rom: &[u8]) -> u8 {
         match addr {
             0x6000..=0x7FFF => {
-                // PRG RAM
-                self.prg_ram[(addr - 0x6000) as usize]
+                // PRG RAM, mirrored when smaller than 8KB
+                let index = ((addr - 0x6000) as usize).checked_rem(self.prg_ram.len());
+                index.map_or(0, |index| self.prg_ram[index])
             }
             0x8000..=0x9FFF => {
                 let offset = (addr - 0x8000) as usize;
@@ -180,7 +181,10 @@ impl Mapper for Mapper4 {
         match addr {
             0x6000..=0x7FFF => {
                 // PRG RAM
-                self.prg_ram[(addr - 0x6000) as usize] = value;
+                if let Some(index) = ((addr - 0x6000) as usize).checked_rem(self.prg_ram.len()) {
+                    self.prg_ram[index] = value;
+                    self.prg_ram_dirty = true;
+                }
             }
             0x8000..=0x9FFF => {
                 if addr & 1 == 0 {
@@ -228,23 +232,21 @@ impl Mapper for Mapper4 {
     fn read_chr(&self, addr: u16, chr_rom: &[u8]) -> u8 {
         // Note: Can't clock scanline on reads in this trait design
         
-        if chr_rom.is_empty() {
-            // CHR RAM
-            self.chr_ram.get(addr as usize).copied().unwrap_or(0)
-        } else {
-            let bank = (addr / 0x400) as usize;
-            let offset = (addr % 0x400) as usize;
-            chr_rom.get(self.chr_banks[bank] + offset).copied().unwrap_or(0)
-        }
+        let bank = (addr / 0x400) as usize;
+        let offset = (addr % 0x400) as usize;
+        chr_rom.get(self.chr_banks[bank] + offset).copied().unwrap_or(0)
     }
     
-    fn write_chr(&mut self, addr: u16, value: u8) {
-        // Note: Can't clock scanline on writes in this trait design
-        
-        if self.chr_rom_size == 0 && addr < 0x2000 {
-            // CHR RAM
-            self.chr_ram[addr as usize] = value;
-        }
+    fn write_chr(&mut self, _addr: u16, _value: u8) {
+        // CHR RAM is written by the cartridge through `chr_bank_map`
+    }
+    
+    fn mirroring(&self) -> Option<Mirroring> {
+        Some(if self.mirroring_mode == 0 { Mirroring::Vertical } else { Mirroring::Horizontal })
+    }
+    
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
     }
     
     fn get_state(&self) -> MapperState {
@@ -256,6 +258,11 @@ impl Mapper for Mapper4 {
             irq_counter: self.irq_counter,
             irq_reload: self.irq_latch,
             irq_enable: self.irq_enabled,
+            irq_reload_pending: self.irq_reload,
+            irq_pending: self.irq_pending,
+            last_a12: self.last_a12,
+            a12_filter: self.a12_filter,
+            mirroring_mode: self.mirroring_mode,
         }
     }
     
@@ -268,13 +275,55 @@ impl Mapper for Mapper4 {
             irq_counter,
             irq_reload,
             irq_enable,
+            irq_reload_pending,
+            irq_pending,
+            last_a12,
+            a12_filter,
+            mirroring_mode,
         } = state {
             self.bank_select = *bank_select;
             self.bank_data.copy_from_slice(&bank_registers[0..8]);
             self.irq_counter = *irq_counter;
             self.irq_latch = *irq_reload;
             self.irq_enabled = *irq_enable;
+            self.irq_reload = *irq_reload_pending;
+            self.irq_pending = *irq_pending;
+            self.last_a12 = *last_a12;
+            self.a12_filter = *a12_filter;
+            self.mirroring_mode = *mirroring_mode;
             self.update_banks();
         }
     }
+    
+    fn prg_bank_map(&self) -> [usize; 4] {
+        self.prg_banks
+    }
+    
+    fn chr_bank_map(&self) -> [usize; 8] {
+        self.chr_banks
+    }
+    
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+    
+    fn prg_ram(&self) -> Option<&[u8]> {
+        (!self.prg_ram.is_empty()).then_some(&self.prg_ram[..])
+    }
+    
+    fn prg_ram_mut(&mut self) -> Option<&mut [u8]> {
+        (!self.prg_ram.is_empty()).then_some(&mut self.prg_ram[..])
+    }
+    
+    fn prg_ram_size(&self) -> usize {
+        self.prg_ram.len()
+    }
+    
+    fn prg_ram_dirty(&self) -> bool {
+        self.prg_ram_dirty
+    }
+    
+    fn clear_prg_ram_dirty(&mut self) {
+        self.prg_ram_dirty = false;
+    }
 }
\ No newline at end of file
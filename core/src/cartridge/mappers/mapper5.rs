@@ -1,9 +1,11 @@
-use crate::cartridge::{Mapper, MapperState};
+use crate::bus::AccessWarningKind;
+use crate::cartridge::{unmapped_access, ExtendedAttribute, Mapper, MapperState, Mirroring};
+use alloc::{boxed::Box, vec, vec::Vec};
 
 // Mapper 5: MMC5 (Memory Management Controller 5)
 // One of the most complex mappers, used by games like Castlevania III
 // This is a simplified implementation of core features
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Mapper5 {
     // ROM sizes
     prg_rom_size: usize,
@@ -16,10 +18,13 @@ pub struct Mapper5 {
     // CHR banks (1KB each)  
     chr_banks: [usize; 12],
     chr_mode: u8,
+    // Upper CHR bank bits ($5130), used by extended attributes
+    chr_upper: u8,
     
     // RAM banks
     prg_ram_banks: [usize; 2],
     prg_ram: Vec<u8>,
+    prg_ram_dirty: bool,
     
     // Fill mode tile and attribute
     fill_tile: u8,
@@ -41,7 +46,8 @@ pub struct Mapper5 {
 }
 
 impl Mapper5 {
-    pub fn new(prg_rom_size: usize, chr_rom_size: usize) -> Self {
+    /// `prg_ram_size` is 8KB on EKROM, 16KB on ETROM and 32KB on EWROM
+    pub fn new(prg_rom_size: usize, chr_rom_size: usize, prg_ram_size: usize) -> Self {
         Self {
             prg_rom_size,
             chr_rom_size,
@@ -49,8 +55,10 @@ impl Mapper5 {
             prg_mode: 3, // Default to mode 3
             chr_banks: [0; 12],
             chr_mode: 0,
+            chr_upper: 0,
             prg_ram_banks: [0; 2],
-            prg_ram: vec![0; 0x10000], // 64KB PRG RAM
+            prg_ram: vec![0; prg_ram_size],
+            prg_ram_dirty: false,
             fill_tile: 0,
             fill_attr: 0,
             mirroring_mode: 0,
@@ -64,6 +72,19 @@ impl Mapper5 {
         }
     }
     
+    /// $5105 source for the nametable at `addr`: 0/1 CIRAM page, 2 ExRAM,
+    /// 3 fill mode
+    fn nametable_source(&self, addr: u16) -> u8 {
+        let table = (addr >> 10) & 0x03;
+        (self.mirroring_mode >> (table * 2)) & 0x03
+    }
+    
+    /// Where an offset into an 8KB RAM bank lands, with bank numbers past
+    /// the fitted RAM wrapping around it
+    fn prg_ram_index(&self, bank: usize, offset: usize) -> Option<usize> {
+        (bank * 0x2000 + offset).checked_rem(self.prg_ram.len())
+    }
+    
     fn get_prg_bank(&self, addr: u16) -> (usize, usize) {
         let bank_index = match self.prg_mode {
             0 => {
@@ -126,9 +147,8 @@ impl Mapper for Mapper5 {
             }
             0x6000..=0x7FFF => {
                 // PRG RAM bank 0
-                let bank_offset = self.prg_ram_banks[0] * 0x2000;
                 let offset = (addr - 0x6000) as usize;
-                self.prg_ram.get(bank_offset + offset).copied().unwrap_or(0)
+                self.prg_ram_index(self.prg_ram_banks[0], offset).map_or(0, |index| self.prg_ram[index])
             }
             0x8000..=0xFFFF => {
                 let (bank, offset) = self.get_prg_bank(addr);
@@ -138,8 +158,7 @@ impl Mapper for Mapper5 {
                 if bank >= 0x80 {
                     // RAM bank
                     let ram_bank = (bank - 0x80) & 0x07;
-                    let ram_offset = ram_bank * 0x2000 + offset;
-                    self.prg_ram.get(ram_offset).copied().unwrap_or(0)
+                    self.prg_ram_index(ram_bank, offset).map_or(0, |index| self.prg_ram[index])
                 } else {
                     // ROM bank
                     prg_rom.get(bank_offset + offset).copied().unwrap_or(0)
@@ -173,7 +192,7 @@ impl Mapper for Mapper5 {
                 self.exram_mode = value & 0x03;
             }
             0x5105 => {
-                // Nametable mapping (simplified)
+                // Nametable mapping, two bits per nametable
                 self.mirroring_mode = value;
             }
             0x5106 => {
@@ -198,6 +217,10 @@ impl Mapper for Mapper5 {
                 let bank_index = (addr - 0x5120) as usize;
                 self.chr_banks[bank_index] = value as usize | ((value as usize) << 8);
             }
+            0x5130 => {
+                // Upper CHR bank bits
+                self.chr_upper = value & 0x03;
+            }
             0x5200 => {
                 // Vertical split mode (not implemented)
             }
@@ -220,10 +243,10 @@ impl Mapper for Mapper5 {
             }
             0x6000..=0x7FFF => {
                 // PRG RAM
-                let bank_offset = self.prg_ram_banks[0] * 0x2000;
                 let offset = (addr - 0x6000) as usize;
-                if let Some(byte) = self.prg_ram.get_mut(bank_offset + offset) {
-                    *byte = value;
+                if let Some(index) = self.prg_ram_index(self.prg_ram_banks[0], offset) {
+                    self.prg_ram[index] = value;
+                    self.prg_ram_dirty = true;
                 }
             }
             _ => {}
@@ -268,12 +291,182 @@ impl Mapper for Mapper5 {
         // CHR ROM is not writable in mapper 5
     }
     
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
+    }
+    
     fn get_state(&self) -> MapperState {
-        // Mapper 5 is complex - return Other for now
-        MapperState::Other
+        // PRG RAM is saved separately, see `prg_ram`
+        MapperState::Mapper5 {
+            prg_mode: self.prg_mode,
+            chr_mode: self.chr_mode,
+            prg_banks: self.prg_banks,
+            chr_banks: self.chr_banks,
+            prg_ram_bank: self.prg_ram_banks[0],
+            chr_upper: self.chr_upper,
+            exram_mode: self.exram_mode,
+            exram: self.exram.clone(),
+            mirroring_mode: self.mirroring_mode,
+            fill_tile: self.fill_tile,
+            fill_attr: self.fill_attr,
+            irq_enabled: self.irq_enabled,
+            irq_pending: self.irq_pending,
+            irq_scanline: self.irq_scanline,
+            irq_compare: self.irq_compare,
+            in_frame: self.in_frame,
+        }
+    }
+    
+    fn set_state(&mut self, state: &MapperState) {
+        if let MapperState::Mapper5 {
+            prg_mode,
+            chr_mode,
+            prg_banks,
+            chr_banks,
+            prg_ram_bank,
+            chr_upper,
+            exram_mode,
+            exram,
+            mirroring_mode,
+            fill_tile,
+            fill_attr,
+            irq_enabled,
+            irq_pending,
+            irq_scanline,
+            irq_compare,
+            in_frame,
+        } = state {
+            self.prg_mode = *prg_mode;
+            self.chr_mode = *chr_mode;
+            self.prg_banks = *prg_banks;
+            self.chr_banks = *chr_banks;
+            self.prg_ram_banks[0] = *prg_ram_bank;
+            self.chr_upper = *chr_upper;
+            self.exram_mode = *exram_mode;
+            if exram.len() == self.exram.len() {
+                self.exram.copy_from_slice(exram);
+            }
+            self.mirroring_mode = *mirroring_mode;
+            self.fill_tile = *fill_tile;
+            self.fill_attr = *fill_attr;
+            self.irq_enabled = *irq_enabled;
+            self.irq_pending = *irq_pending;
+            self.irq_scanline = *irq_scanline;
+            self.irq_compare = *irq_compare;
+            self.in_frame = *in_frame;
+        }
+    }
+    
+    /// Windows switched to PRG RAM report the offset the ROM bank number
+    /// would select
+    fn prg_bank_map(&self) -> [usize; 4] {
+        core::array::from_fn(|i| {
+            let (bank, offset) = self.get_prg_bank(0x8000 + i as u16 * 0x2000);
+            bank * 0x2000 + offset
+        })
+    }
+    
+    fn chr_bank_map(&self) -> [usize; 8] {
+        let (shift, mask) = match self.chr_mode {
+            0 => (13, 0x1FFF),
+            1 => (12, 0x0FFF),
+            2 => (11, 0x07FF),
+            _ => (10, 0x03FF),
+        };
+        core::array::from_fn(|i| {
+            let addr = i * 0x400;
+            self.chr_banks[addr >> shift] * 0x400 + (addr & mask)
+        })
+    }
+    
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+    
+    /// ExRAM mode 1: each nametable entry has an ExRAM byte at the same
+    /// offset, bits 0-5 picking a 4KB CHR bank and bits 6-7 the palette
+    fn extended_attribute(&self, nametable_addr: u16) -> Option<ExtendedAttribute> {
+        if self.exram_mode != 1 {
+            return None;
+        }
+        let value = self.exram[(nametable_addr & 0x3FF) as usize];
+        let bank = ((self.chr_upper as usize) << 6) | (value & 0x3F) as usize;
+        Some(ExtendedAttribute {
+            palette: value >> 6,
+            chr_offset: bank * 0x1000,
+        })
+    }
+    
+    /// ExRAM and fill-mode nametables; CIRAM pages are left to the PPU, which
+    /// asks `ciram_page` which one
+    fn read_nametable(&self, addr: u16) -> Option<u8> {
+        let offset = (addr & 0x3FF) as usize;
+        match self.nametable_source(addr) {
+            2 if self.exram_mode < 2 => Some(self.exram[offset]),
+            2 => Some(0),
+            3 if offset < 0x3C0 => Some(self.fill_tile),
+            3 => Some(self.fill_attr * 0x55),
+            _ => None,
+        }
+    }
+    
+    fn write_nametable(&mut self, addr: u16, value: u8) -> bool {
+        match self.nametable_source(addr) {
+            2 => {
+                if self.exram_mode < 2 {
+                    self.exram[(addr & 0x3FF) as usize] = value;
+                }
+                true
+            }
+            3 => true,
+            _ => false,
+        }
+    }
+    
+    /// $5105 values that match a standard layout, for anything asking the
+    /// cartridge for its mirroring; the PPU goes by `ciram_page`
+    fn mirroring(&self) -> Option<Mirroring> {
+        match self.mirroring_mode {
+            0x44 => Some(Mirroring::Vertical),
+            0x50 => Some(Mirroring::Horizontal),
+            0x00 => Some(Mirroring::SingleScreenLow),
+            0x55 => Some(Mirroring::SingleScreenHigh),
+            _ => None,
+        }
+    }
+    
+    fn ciram_page(&self, addr: u16) -> Option<u16> {
+        Some(self.nametable_source(addr) as u16 & 0x01)
+    }
+    
+    /// Audio, PRG RAM protect, split screen, the multiplier and IRQ status
+    /// are stubs; the other write-only registers read as open bus
+    fn access_warning(&self, addr: u16, write: bool) -> Option<AccessWarningKind> {
+        match (addr, write) {
+            (0x5000..=0x5015 | 0x5102 | 0x5103 | 0x5200..=0x5202 | 0x5205 | 0x5206, _)
+            | (0x5204, false) => Some(AccessWarningKind::UnimplementedRegister),
+            (0x5100..=0x5130 | 0x5203 | 0x5204, true) | (0x5C00..=0x5FFF, _) => None,
+            _ => unmapped_access(addr, write, true),
+        }
+    }
+    
+    fn prg_ram(&self) -> Option<&[u8]> {
+        (!self.prg_ram.is_empty()).then_some(&self.prg_ram[..])
+    }
+    
+    fn prg_ram_mut(&mut self) -> Option<&mut [u8]> {
+        (!self.prg_ram.is_empty()).then_some(&mut self.prg_ram[..])
+    }
+    
+    fn prg_ram_size(&self) -> usize {
+        self.prg_ram.len()
+    }
+    
+    fn prg_ram_dirty(&self) -> bool {
+        self.prg_ram_dirty
     }
     
-    fn set_state(&mut self, _state: &MapperState) {
-        // Mapper 5 state restoration not implemented yet
+    fn clear_prg_ram_dirty(&mut self) {
+        self.prg_ram_dirty = false;
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
-use crate::cartridge::{Mapper, MapperState};
+use crate::cartridge::{Mapper, MapperState, Mirroring};
+use alloc::boxed::Box;
 
 // Mapper 7: AxROM
 // Used by games like Battletoads, Wizards & Warriors, etc.
@@ -6,21 +7,22 @@ use crate::cartridge::{Mapper, MapperState};
 // - 32KB PRG ROM bank switching
 // - Single screen mirroring
 // - No CHR ROM (uses CHR RAM)
-#[derive(Debug)]
+// - Bus conflicts on AMROM only
+#[derive(Debug, Clone)]
 pub struct Mapper7 {
-    chr_ram: Vec<u8>,
     prg_bank: usize,
     mirroring_mode: u8,
-    prg_rom_size: usize,
+    prg_banks: usize,
+    bus_conflicts: bool,
 }
 
 impl Mapper7 {
-    pub fn new(prg_rom_size: usize) -> Self {
+    pub fn new(prg_rom_size: usize, bus_conflicts: bool) -> Self {
         Self {
-            chr_ram: vec![0; 0x2000], // 8KB CHR RAM
             prg_bank: 0,
             mirroring_mode: 0,
-            prg_rom_size,
+            prg_banks: (prg_rom_size / 0x8000).max(1),
+            bus_conflicts,
         }
     }
 }
@@ -39,39 +41,37 @@ impl Mapper for Mapper7 {
     }
     
     fn write_prg(&mut self, addr: u16, value: u8) {
-        match addr {
-            0x8000..=0xFFFF => {
-                // Bus conflicts: The written data must match the data on the bus
-                let current_data = self.read_prg(addr, &[]);
-                let actual_value = if current_data != 0 {
-                    // Bus conflict occurred - data is ANDed together
-                    value & current_data
-                } else {
-                    value
-                };
-                
-                // Bank select (bits 0-2) and mirroring (bit 4)
-                self.prg_bank = (actual_value & 0x07) as usize;
-                self.mirroring_mode = (actual_value >> 4) & 1;
-                
-                // Ensure bank doesn't exceed ROM size
-                let max_bank = (self.prg_rom_size / 0x8000).saturating_sub(1);
-                self.prg_bank = self.prg_bank.min(max_bank);
-            }
-            _ => {}
+        if addr >= 0x8000 {
+            // Bank select in bits 0-2 (bit 3 on oversized 512KB dumps),
+            // wrapped to the ROM size; single-screen page in bit 4
+            self.prg_bank = (value as usize & 0x0F) % self.prg_banks;
+            self.mirroring_mode = (value >> 4) & 1;
         }
     }
     
-    fn read_chr(&self, addr: u16, _chr_rom: &[u8]) -> u8 {
-        // Always uses CHR RAM
-        self.chr_ram.get(addr as usize & 0x1FFF).copied().unwrap_or(0)
+    fn bus_conflicts(&self) -> bool {
+        self.bus_conflicts
     }
     
-    fn write_chr(&mut self, addr: u16, value: u8) {
-        // CHR RAM
-        if addr < 0x2000 {
-            self.chr_ram[addr as usize] = value;
-        }
+    fn read_chr(&self, addr: u16, chr_rom: &[u8]) -> u8 {
+        // Unbanked 8KB of CHR RAM
+        chr_rom.get(addr as usize & 0x1FFF).copied().unwrap_or(0)
+    }
+    
+    fn write_chr(&mut self, _addr: u16, _value: u8) {
+        // CHR RAM is written by the cartridge
+    }
+    
+    fn prg_bank_map(&self) -> [usize; 4] {
+        core::array::from_fn(|i| self.prg_bank * 0x8000 + i * 0x2000)
+    }
+    
+    fn mirroring(&self) -> Option<Mirroring> {
+        Some(if self.mirroring_mode == 0 { Mirroring::SingleScreenLow } else { Mirroring::SingleScreenHigh })
+    }
+    
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
     }
     
     fn get_state(&self) -> MapperState {
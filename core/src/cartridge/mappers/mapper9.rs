@@ -1,4 +1,5 @@
-use crate::cartridge::{Mapper, MapperState};
+use crate::cartridge::{ChrLatch, Mapper, MapperState, Mirroring};
+use alloc::{boxed::Box, vec, vec::Vec};
 
 // Mapper 9: MMC2 (Memory Management Controller 2)
 // Used by Mike Tyson's Punch-Out!!
@@ -6,7 +7,7 @@ use crate::cartridge::{Mapper, MapperState};
 // - 8KB PRG ROM bank switching
 // - 4KB CHR ROM bank switching with automatic latch
 // - Special CHR bank switching triggered by reading tiles $FD/$FE
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Mapper9 {
     // ROM sizes
     prg_rom_size: usize,
@@ -23,13 +24,14 @@ pub struct Mapper9 {
     
     // PRG RAM
     prg_ram: Vec<u8>,
+    prg_ram_dirty: bool,
     
     // Mirroring
     mirroring_mode: u8,
 }
 
 impl Mapper9 {
-    pub fn new(prg_rom_size: usize, chr_rom_size: usize) -> Self {
+    pub fn new(prg_rom_size: usize, chr_rom_size: usize, prg_ram_size: usize) -> Self {
         Self {
             prg_rom_size,
             chr_rom_size,
@@ -38,11 +40,18 @@ impl Mapper9 {
             chr_bank_1: [0, 0],
             latch_0: 0,
             latch_1: 0,
-            prg_ram: vec![0; 0x2000], // 8KB PRG RAM
+            prg_ram: vec![0; prg_ram_size],
+            prg_ram_dirty: false,
             mirroring_mode: 0,
         }
     }
     
+    fn chr_banks_for(&self, latch_0: usize, latch_1: usize) -> [usize; 8] {
+        let low = self.chr_bank_0[latch_0] * 0x1000;
+        let high = self.chr_bank_1[latch_1] * 0x1000;
+        core::array::from_fn(|i| if i < 4 { low + i * 0x400 } else { high + (i - 4) * 0x400 })
+    }
+    
     fn update_chr_latch(&mut self, addr: u16) {
         // Check for special tiles that trigger latch
         match addr {
@@ -71,8 +80,9 @@ impl Mapper for Mapper9 {
     fn read_prg(&self, addr: u16, prg_rom: &[u8]) -> u8 {
         match addr {
             0x6000..=0x7FFF => {
-                // PRG RAM
-                self.prg_ram[(addr - 0x6000) as usize]
+                // PRG RAM, mirrored when smaller than 8KB
+                let index = ((addr - 0x6000) as usize).checked_rem(self.prg_ram.len());
+                index.map_or(0, |index| self.prg_ram[index])
             }
             0x8000..=0x9FFF => {
                 // First 8KB PRG ROM bank (fixed to first bank)
@@ -99,7 +109,10 @@ impl Mapper for Mapper9 {
         match addr {
             0x6000..=0x7FFF => {
                 // PRG RAM
-                self.prg_ram[(addr - 0x6000) as usize] = value;
+                if let Some(index) = ((addr - 0x6000) as usize).checked_rem(self.prg_ram.len()) {
+                    self.prg_ram[index] = value;
+                    self.prg_ram_dirty = true;
+                }
             }
             0xA000..=0xAFFF => {
                 // PRG ROM bank select
@@ -158,11 +171,67 @@ impl Mapper for Mapper9 {
         // CHR ROM is not writable in mapper 9
     }
     
+    fn prg_bank_map(&self) -> [usize; 4] {
+        let last = self.prg_rom_size.saturating_sub(0x4000);
+        [0, self.prg_bank * 0x2000, last, last + 0x2000]
+    }
+    
+    fn chr_bank_map(&self) -> [usize; 8] {
+        self.chr_banks_for(self.latch_0, self.latch_1)
+    }
+    
+    fn chr_bank_map_with_latches(&self, latches: [ChrLatch; 2]) -> [usize; 8] {
+        let [latch_0, latch_1] = latches.map(|latch| (latch == ChrLatch::Fe) as usize);
+        self.chr_banks_for(latch_0, latch_1)
+    }
+    
+    fn mirroring(&self) -> Option<Mirroring> {
+        Some(if self.mirroring_mode == 0 { Mirroring::Vertical } else { Mirroring::Horizontal })
+    }
+    
+    fn clone_box(&self) -> Box<dyn Mapper> {
+        Box::new(self.clone())
+    }
+    
     fn get_state(&self) -> MapperState {
-        MapperState::Other
+        MapperState::Mapper9 {
+            prg_bank: self.prg_bank,
+            chr_bank_0: self.chr_bank_0,
+            chr_bank_1: self.chr_bank_1,
+            latch_0: self.latch_0,
+            latch_1: self.latch_1,
+            mirroring_mode: self.mirroring_mode,
+        }
+    }
+    
+    fn set_state(&mut self, state: &MapperState) {
+        if let MapperState::Mapper9 { prg_bank, chr_bank_0, chr_bank_1, latch_0, latch_1, mirroring_mode } = state {
+            self.prg_bank = *prg_bank;
+            self.chr_bank_0 = *chr_bank_0;
+            self.chr_bank_1 = *chr_bank_1;
+            self.latch_0 = *latch_0;
+            self.latch_1 = *latch_1;
+            self.mirroring_mode = *mirroring_mode;
+        }
+    }
+    
+    fn prg_ram(&self) -> Option<&[u8]> {
+        (!self.prg_ram.is_empty()).then_some(&self.prg_ram[..])
+    }
+    
+    fn prg_ram_mut(&mut self) -> Option<&mut [u8]> {
+        (!self.prg_ram.is_empty()).then_some(&mut self.prg_ram[..])
+    }
+    
+    fn prg_ram_size(&self) -> usize {
+        self.prg_ram.len()
+    }
+    
+    fn prg_ram_dirty(&self) -> bool {
+        self.prg_ram_dirty
     }
     
-    fn set_state(&mut self, _state: &MapperState) {
-        // Mapper 9 state restoration not implemented yet
+    fn clear_prg_ram_dirty(&mut self) {
+        self.prg_ram_dirty = false;
     }
 }
\ No newline at end of file
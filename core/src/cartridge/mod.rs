@@ -1,8 +1,14 @@
-use std::io::Read;
+use alloc::boxed::Box;
+use alloc::{string::String, vec, vec::Vec};
 use thiserror::Error;
 use serde::{Serialize, Deserialize};
+use crate::Region;
+use crate::bus::AccessWarningKind;
 
 pub mod mappers;
+mod unif;
+
+pub use unif::board_mapper;
 
 #[derive(Debug, Error)]
 pub enum CartridgeError {
@@ -10,19 +16,76 @@ pub enum CartridgeError {
     InvalidHeader,
     #[error("Unsupported mapper: {0}")]
     UnsupportedMapper(u8),
+    #[error("Unsupported board: {0}")]
+    UnsupportedBoard(String),
+    #[error("ROM data is truncated")]
+    Truncated,
+    #[error("Unrecognized ROM format")]
+    UnknownFormat,
+    #[error("{} images aren't supported", .0.name())]
+    UnsupportedFormat(RomFormat),
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
 
-#[derive(Debug)]
+/// Container formats told apart by `RomFormat::detect`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RomFormat {
+    INes,
+    Nes2,
+    /// Famicom Disk System image, with or without the fwNES header
+    Fds,
+    Unif,
+}
+
+impl RomFormat {
+    /// Sniff the format from the first bytes of an image
+    pub fn detect(data: &[u8]) -> Option<RomFormat> {
+        if data.starts_with(b"NES\x1A") {
+            // NES 2.0 marks byte 7 bits 2-3 with 10
+            if data.len() >= 8 && data[7] & 0x0C == 0x08 {
+                Some(RomFormat::Nes2)
+            } else {
+                Some(RomFormat::INes)
+            }
+        } else if data.starts_with(b"FDS\x1A") || data.starts_with(b"\x01*NINTENDO-HVC*") {
+            // A headerless disk starts straight with its disk info block
+            Some(RomFormat::Fds)
+        } else if data.starts_with(b"UNIF") {
+            Some(RomFormat::Unif)
+        } else {
+            None
+        }
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            RomFormat::INes => "iNES",
+            RomFormat::Nes2 => "NES 2.0",
+            RomFormat::Fds => "FDS",
+            RomFormat::Unif => "UNIF",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Cartridge {
     prg_rom: Vec<u8>,
-    chr_rom: Vec<u8>,
+    /// CHR ROM, or 8KB of CHR RAM when the header has no CHR ROM
+    chr: Vec<u8>,
+    chr_ram: bool,
     mapper: Box<dyn Mapper>,
+    mapper_number: u8,
     mirroring: Mirroring,
+    /// The 2KB behind $2800-$2FFF on four-screen boards, empty otherwise
+    four_screen_ram: Vec<u8>,
+    playchoice: bool,
+    region_hint: Option<Region>,
+    battery: bool,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Mirroring {
     Horizontal,
     Vertical,
@@ -31,12 +94,46 @@ pub enum Mirroring {
     SingleScreenHigh,
 }
 
+/// What the header says about a cartridge, for logs and ROM info screens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderInfo {
+    pub mapper_number: u8,
+    /// See `Cartridge::mapper_name`
+    pub mapper_name: &'static str,
+    pub prg_rom_size: usize,
+    /// 0 for boards with CHR RAM
+    pub chr_rom_size: usize,
+    pub prg_ram_size: usize,
+    pub battery: bool,
+    pub four_screen: bool,
+    pub region: Option<Region>,
+}
+
+/// Background tile attributes a mapper supplies in place of the
+/// attribute table and CHR banking (MMC5 extended attributes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtendedAttribute {
+    /// Replaces the two palette bits from the attribute table
+    pub palette: u8,
+    /// Offset into CHR ROM of the 4KB bank holding the tile's pattern
+    pub chr_offset: usize,
+}
+
+/// Which bank an MMC2/MMC4 CHR latch selects: the one written for tile
+/// $FD or for tile $FE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChrLatch {
+    Fd,
+    Fe,
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum MapperState {
     Mapper0,
     Mapper1 {
         shift_register: u8,
         shift_count: u8,
+        mirroring: u8,
         prg_bank_mode: u8,
         chr_bank_mode: u8,
         prg_bank: u8,
@@ -57,27 +154,222 @@ pub enum MapperState {
         irq_counter: u8,
         irq_reload: u8,
         irq_enable: bool,
+        irq_reload_pending: bool,
+        irq_pending: bool,
+        last_a12: bool,
+        a12_filter: u8,
+        mirroring_mode: u8,
+    },
+    Mapper5 {
+        prg_mode: u8,
+        chr_mode: u8,
+        prg_banks: [usize; 4],
+        chr_banks: [usize; 12],
+        prg_ram_bank: usize,
+        chr_upper: u8,
+        exram_mode: u8,
+        // 1KB, nametable or extended attribute data in modes 0 and 1
+        exram: Vec<u8>,
+        mirroring_mode: u8,
+        fill_tile: u8,
+        fill_attr: u8,
+        irq_enabled: bool,
+        irq_pending: bool,
+        irq_scanline: u8,
+        irq_compare: u8,
+        in_frame: bool,
     },
     Mapper7 {
         prg_bank: usize,
         mirroring_mode: u8,
     },
+    Mapper9 {
+        prg_bank: usize,
+        chr_bank_0: [usize; 2],
+        chr_bank_1: [usize; 2],
+        latch_0: usize,
+        latch_1: usize,
+        mirroring_mode: u8,
+    },
+    Mapper11 {
+        prg_bank: usize,
+        chr_bank: usize,
+    },
+    Mapper66 {
+        prg_bank: usize,
+        chr_bank: usize,
+    },
     Other,
 }
 
-pub trait Mapper: std::fmt::Debug {
+pub trait Mapper: core::fmt::Debug {
     fn read_prg(&self, addr: u16, prg_rom: &[u8]) -> u8;
     fn write_prg(&mut self, addr: u16, value: u8);
     fn read_chr(&self, addr: u16, chr_rom: &[u8]) -> u8;
     fn write_chr(&mut self, addr: u16, value: u8);
     fn get_state(&self) -> MapperState;
     fn set_state(&mut self, state: &MapperState);
+    fn clone_box(&self) -> Box<dyn Mapper>;
+    
+    /// Level of the mapper's IRQ output
+    fn irq_pending(&self) -> bool {
+        false
+    }
+    
+    /// Whether ROM drives the data bus during register writes at
+    /// $8000-$FFFF, so the mapper sees the written value ANDed with the
+    /// ROM byte at that address
+    fn bus_conflicts(&self) -> bool {
+        false
+    }
+    
+    /// Nametable layout selected by the mapper's registers. `None` for
+    /// boards where it is wired by the header.
+    fn mirroring(&self) -> Option<Mirroring> {
+        None
+    }
+    
+    /// PRG RAM at $6000-$7FFF, if the board has any
+    fn prg_ram(&self) -> Option<&[u8]> {
+        None
+    }
+    
+    fn prg_ram_mut(&mut self) -> Option<&mut [u8]> {
+        None
+    }
+    
+    /// All of the board's PRG RAM in bytes, including banks not currently
+    /// mapped at $6000-$7FFF
+    fn prg_ram_size(&self) -> usize {
+        0
+    }
+    
+    /// Set by writes to PRG RAM until `clear_prg_ram_dirty`
+    fn prg_ram_dirty(&self) -> bool {
+        false
+    }
+    
+    fn clear_prg_ram_dirty(&mut self) {}
+    
+    /// Consulted on every background tile fetch with the tile's nametable
+    /// address ($2000-$2FBF). `Some` overrides its palette and pattern bank.
+    fn extended_attribute(&self, _nametable_addr: u16) -> Option<ExtendedAttribute> {
+        None
+    }
+    
+    /// Nametable memory on the board, e.g. MMC5 ExRAM or fill mode. `addr`
+    /// is $2000-$2FFF; `None` leaves it to console VRAM and the mirroring.
+    fn read_nametable(&self, _addr: u16) -> Option<u8> {
+        None
+    }
+    
+    /// Returns true if the mapper took the write, false for console VRAM
+    fn write_nametable(&mut self, _addr: u16, _value: u8) -> bool {
+        false
+    }
+    
+    /// Console VRAM page (0 or 1) for the nametable at `addr` on boards
+    /// that pick one per nametable. `None` lays them out by the mirroring.
+    fn ciram_page(&self, _addr: u16) -> Option<u16> {
+        None
+    }
+    
+    /// Classify a CPU access to $4020-$FFFF that the board ignores or only
+    /// stubs out. Only asked while an access warning hook is set.
+    fn access_warning(&self, addr: u16, write: bool) -> Option<AccessWarningKind> {
+        unmapped_access(addr, write, self.prg_ram().is_some())
+    }
+    
+    /// Offset into PRG ROM of the bank mapped at each 8KB window of
+    /// $8000-$FFFF. The default is the unbanked 32KB layout.
+    fn prg_bank_map(&self) -> [usize; 4] {
+        core::array::from_fn(|i| i * 0x2000)
+    }
+    
+    /// Offset into CHR ROM/RAM of the bank mapped at each 1KB window of
+    /// $0000-$1FFF. The default is the unbanked 8KB layout.
+    fn chr_bank_map(&self) -> [usize; 8] {
+        core::array::from_fn(|i| i * 0x400)
+    }
+    
+    /// `chr_bank_map` as it would be with the MMC2/MMC4 latches for the
+    /// $0000 and $1000 tables set to `latches`. Boards without latches
+    /// return their current map.
+    fn chr_bank_map_with_latches(&self, _latches: [ChrLatch; 2]) -> [usize; 8] {
+        self.chr_bank_map()
+    }
+}
+
+impl Clone for Box<dyn Mapper> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Builds a mapper from the PRG and CHR ROM sizes, the NES 2.0 submapper
+/// and the PRG RAM size, `None` when the file doesn't give one
+type MapperConstructor = fn(usize, usize, u8, Option<usize>) -> Box<dyn Mapper>;
+
+/// Every mapper `from_ines` can load: iNES number, board name, constructor
+const MAPPERS: [(u8, &str, MapperConstructor); 10] = [
+    (0, "NROM", |_, _, _, _| Box::new(mappers::Mapper0::new())),
+    // SNROM's 8KB unless the file says SOROM (16KB) or SXROM (32KB)
+    (1, "MMC1", |prg_size, _, _, prg_ram| Box::new(mappers::Mapper1::new(prg_size, prg_ram.unwrap_or(0x2000)))),
+    // Submapper 2 is UOROM with bus conflicts, 1 and 0 have none
+    (2, "UxROM", |prg_size, _, submapper, _| Box::new(mappers::Mapper2::new(prg_size, submapper == 2))),
+    // Submapper 2 is CNROM with bus conflicts, 1 and 0 have none
+    (3, "CNROM", |_, chr_size, submapper, _| Box::new(mappers::Mapper3::new(chr_size, submapper == 2))),
+    (4, "MMC3", |prg_size, chr_size, _, prg_ram| Box::new(mappers::Mapper4::new(prg_size, chr_size, prg_ram.unwrap_or(0x2000)))),
+    // The chip addresses 64KB, but no board was made with more than 32KB
+    (5, "MMC5", |prg_size, chr_size, _, prg_ram| Box::new(mappers::Mapper5::new(prg_size, chr_size, prg_ram.unwrap_or(0x8000)))),
+    // Submapper 2 is AMROM with bus conflicts, ANROM/AOROM have none
+    (7, "AxROM", |prg_size, _, submapper, _| Box::new(mappers::Mapper7::new(prg_size, submapper == 2))),
+    (9, "MMC2", |prg_size, chr_size, _, prg_ram| Box::new(mappers::Mapper9::new(prg_size, chr_size, prg_ram.unwrap_or(0x2000)))),
+    (11, "Color Dreams", |prg_size, chr_size, _, _| Box::new(mappers::Mapper11::new(prg_size, chr_size))),
+    (66, "GxROM", |prg_size, chr_size, _, _| Box::new(mappers::Mapper66::new(prg_size, chr_size))),
+];
+
+static SUPPORTED_MAPPERS: [(u8, &str); MAPPERS.len()] = {
+    let mut supported = [(0, ""); MAPPERS.len()];
+    let mut i = 0;
+    while i < MAPPERS.len() {
+        supported[i] = (MAPPERS[i].0, MAPPERS[i].1);
+        i += 1;
+    }
+    supported
+};
+
+/// Mapper numbers `Cartridge::from_ines` accepts, with their board names,
+/// in ascending order. Anything else fails with `UnsupportedMapper`.
+pub fn supported_mappers() -> &'static [(u8, &'static str)] {
+    &SUPPORTED_MAPPERS
 }
 
 impl Cartridge {
-    pub fn from_ines<R: Read>(mut reader: R) -> Result<Self, CartridgeError> {
-        let mut header = [0u8; 16];
-        reader.read_exact(&mut header)?;
+    /// Load an image in any format `RomFormat::detect` knows. Fails with
+    /// `UnknownFormat` for anything else and `UnsupportedFormat` for the
+    /// formats that can't be loaded yet.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, CartridgeError> {
+        match RomFormat::detect(data) {
+            Some(RomFormat::INes | RomFormat::Nes2) => Self::from_ines_bytes(data),
+            Some(RomFormat::Unif) => Self::from_unif_bytes(data),
+            Some(format) => Err(CartridgeError::UnsupportedFormat(format)),
+            None => Err(CartridgeError::UnknownFormat),
+        }
+    }
+    
+    #[cfg(feature = "std")]
+    pub fn from_ines<R: std::io::Read>(mut reader: R) -> Result<Self, CartridgeError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::from_ines_bytes(&data)
+    }
+    
+    pub fn from_ines_bytes(data: &[u8]) -> Result<Self, CartridgeError> {
+        if data.len() < 16 {
+            return Err(CartridgeError::Truncated);
+        }
+        let header = &data[0..16];
         
         // Check "NES\x1A" magic
         if &header[0..4] != b"NES\x1A" {
@@ -87,7 +379,47 @@ impl Cartridge {
         let prg_size = header[4] as usize * 16384;  // 16KB units
         let chr_size = header[5] as usize * 8192;   // 8KB units
         
-        let mapper_num = (header[6] >> 4) | (header[7] & 0xF0);
+        // Bytes 12-15 are zero in a clean iNES 1.0 header. Old dumping tools
+        // wrote their name ("DiskDude!") from byte 7 on, so ignore bytes 7-10
+        // when that padding isn't empty.
+        let nes2 = header[7] & 0x0C == 0x08;
+        let garbage = !nes2 && header[12..16].iter().any(|&b| b != 0);
+        let flags7 = if garbage { 0 } else { header[7] };
+        
+        // TV system: NES 2.0 byte 12, or iNES byte 9 bit 0 and the unofficial
+        // byte 10. iNES uses 0 for NTSC, which is also what unset bytes read
+        // as, so only a PAL flag counts there.
+        let region_hint = if nes2 {
+            match header[12] & 0x03 {
+                0 => Some(Region::Ntsc),
+                // Dendy clones run at 50Hz like PAL
+                1 | 3 => Some(Region::Pal),
+                _ => None,
+            }
+        } else if !garbage && (header[9] & 0x01 != 0 || header[10] & 0x03 == 2) {
+            Some(Region::Pal)
+        } else {
+            None
+        };
+        
+        let mapper_num = (header[6] >> 4) | (flags7 & 0xF0);
+        let submapper = if nes2 { header[8] >> 4 } else { 0 };
+        
+        // NES 2.0 byte 10 gives volatile (low nibble) and battery backed
+        // (high nibble) PRG RAM as shift counts, 64 << n bytes or 0 for
+        // none. iNES 1.0 byte 8 counts 8KB units, with 0 left to the board.
+        let prg_ram_size = if nes2 {
+            let size = |shift: u8| if shift == 0 { 0 } else { 64 << shift };
+            Some(size(header[10] & 0x0F) + size(header[10] >> 4))
+        } else if !garbage && header[8] != 0 {
+            Some(header[8] as usize * 0x2000)
+        } else {
+            None
+        };
+        
+        // PlayChoice-10 dumps append an 8KB INST-ROM and the PROM after CHR.
+        // That hardware isn't emulated and the data is simply left unread.
+        let playchoice = flags7 & 0x02 != 0;
         
         let mirroring = if header[6] & 0x08 != 0 {
             Mirroring::FourScreen
@@ -98,39 +430,61 @@ impl Cartridge {
         };
         
         // Skip trainer if present
-        if header[6] & 0x04 != 0 {
-            let mut trainer = [0u8; 512];
-            reader.read_exact(&mut trainer)?;
+        let prg_start = if header[6] & 0x04 != 0 { 16 + 512 } else { 16 };
+        let chr_start = prg_start + prg_size;
+        if data.len() < chr_start + chr_size {
+            return Err(CartridgeError::Truncated);
         }
         
-        // Read PRG ROM
-        let mut prg_rom = vec![0u8; prg_size];
-        reader.read_exact(&mut prg_rom)?;
+        let mut cartridge = Self::with_mapper(
+            data[prg_start..chr_start].to_vec(),
+            data[chr_start..chr_start + chr_size].to_vec(),
+            mapper_num,
+            submapper,
+            prg_ram_size,
+            mirroring,
+        )?;
+        cartridge.playchoice = playchoice;
+        cartridge.region_hint = region_hint;
+        cartridge.battery = header[6] & 0x02 != 0;
+        Ok(cartridge)
+    }
+    
+    /// Build the board for a mapper number from the ROM data, shared by the
+    /// file format loaders. An empty `chr` means the board has CHR RAM,
+    /// and a `None` PRG RAM size the mapper's usual amount.
+    fn with_mapper(
+        prg_rom: Vec<u8>,
+        chr: Vec<u8>,
+        mapper_number: u8,
+        submapper: u8,
+        prg_ram_size: Option<usize>,
+        mirroring: Mirroring,
+    ) -> Result<Self, CartridgeError> {
+        // Without CHR ROM the board has 8KB of CHR RAM instead. It lives here
+        // rather than in the mapper so every board banks it the same way.
+        let chr_ram = chr.is_empty();
+        let chr = if chr_ram { vec![0; 0x2000] } else { chr };
         
-        // Read CHR ROM
-        let mut chr_rom = vec![0u8; chr_size];
-        reader.read_exact(&mut chr_rom)?;
-        
-        // Create mapper
-        let mapper: Box<dyn Mapper> = match mapper_num {
-            0 => Box::new(mappers::Mapper0::new()),
-            1 => Box::new(mappers::Mapper1::new()),
-            2 => Box::new(mappers::Mapper2::new(prg_size)),
-            3 => Box::new(mappers::Mapper3::new()),
-            4 => Box::new(mappers::Mapper4::new(prg_size, chr_size)),
-            5 => Box::new(mappers::Mapper5::new(prg_size, chr_size)),
-            7 => Box::new(mappers::Mapper7::new(prg_size)),
-            9 => Box::new(mappers::Mapper9::new(prg_size, chr_size)),
-            11 => Box::new(mappers::Mapper11::new(prg_size, chr_size)),
-            66 => Box::new(mappers::Mapper66::new(prg_size, chr_size)),
-            _ => return Err(CartridgeError::UnsupportedMapper(mapper_num)),
-        };
+        let create = MAPPERS
+            .iter()
+            .find(|(number, _, _)| *number == mapper_number)
+            .map(|(_, _, create)| create)
+            .ok_or(CartridgeError::UnsupportedMapper(mapper_number))?;
+        let mapper = create(prg_rom.len(), chr.len(), submapper, prg_ram_size);
+        let four_screen_ram = if mirroring == Mirroring::FourScreen { vec![0; 0x800] } else { Vec::new() };
         
         Ok(Cartridge {
             prg_rom,
-            chr_rom,
+            chr,
+            chr_ram,
             mapper,
+            mapper_number,
             mirroring,
+            four_screen_ram,
+            playchoice: false,
+            region_hint: None,
+            battery: false,
         })
     }
     
@@ -139,32 +493,233 @@ impl Cartridge {
     }
     
     pub fn write_prg(&mut self, addr: u16, value: u8) {
+        let value = if addr >= 0x8000 && self.mapper.bus_conflicts() {
+            value & self.mapper.read_prg(addr, &self.prg_rom)
+        } else {
+            value
+        };
         self.mapper.write_prg(addr, value);
+        self.sync_mirroring();
+    }
+    
+    /// Take the mirroring from the mapper's registers, if it has any.
+    /// Four-screen boards have their own VRAM and ignore them.
+    fn sync_mirroring(&mut self) {
+        if let Some(mirroring) = self.mapper.mirroring() {
+            if self.mirroring != Mirroring::FourScreen {
+                self.mirroring = mirroring;
+            }
+        }
     }
     
     pub fn read_chr(&self, addr: u16) -> u8 {
-        self.mapper.read_chr(addr, &self.chr_rom)
+        self.mapper.read_chr(addr, &self.chr)
     }
     
+    /// CHR RAM writes land at the offset the mapper's banks select; CHR ROM
+    /// ignores them
     pub fn write_chr(&mut self, addr: u16, value: u8) {
+        if self.chr_ram && addr < 0x2000 {
+            let offset = self.chr_bank_map()[addr as usize / 0x400] + addr as usize % 0x400;
+            self.chr[offset] = value;
+        }
         self.mapper.write_chr(addr, value);
     }
     
+    /// The 4KB pattern table at $0000 (`table` 0) or $1000 (1) as the PPU
+    /// would fetch it with the current banks, for tile viewers. CHR RAM
+    /// shows what the game has uploaded so far. On MMC2/MMC4 `latches`
+    /// picks the $FD/$FE latch state per table to show instead of the
+    /// current one; other boards ignore it.
+    pub fn pattern_table(&self, table: usize, latches: Option<[ChrLatch; 2]>) -> [u8; 0x1000] {
+        let banks = match latches {
+            Some(latches) => self.mapper.chr_bank_map_with_latches(latches),
+            None => self.mapper.chr_bank_map(),
+        };
+        let banks = wrap_offsets(banks, self.chr.len());
+        core::array::from_fn(|i| {
+            let addr = (table & 1) * 0x1000 + i;
+            self.chr[(banks[addr / 0x400] + addr % 0x400) % self.chr.len()]
+        })
+    }
+    
+    /// See `Mapper::extended_attribute`
+    pub fn extended_attribute(&self, nametable_addr: u16) -> Option<ExtendedAttribute> {
+        self.mapper.extended_attribute(nametable_addr)
+    }
+    
+    /// See `Mapper::access_warning`
+    pub fn access_warning(&self, addr: u16, write: bool) -> Option<AccessWarningKind> {
+        self.mapper.access_warning(addr, write)
+    }
+    
+    /// See `Mapper::read_nametable`. Four-screen boards answer for
+    /// $2800-$2FFF from their own RAM.
+    pub fn read_nametable(&self, addr: u16) -> Option<u8> {
+        self.mapper
+            .read_nametable(addr)
+            .or_else(|| self.four_screen_index(addr).map(|index| self.four_screen_ram[index]))
+    }
+    
+    /// See `Mapper::write_nametable`
+    pub fn write_nametable(&mut self, addr: u16, value: u8) -> bool {
+        if self.mapper.write_nametable(addr, value) {
+            return true;
+        }
+        match self.four_screen_index(addr) {
+            Some(index) => {
+                self.four_screen_ram[index] = value;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// See `Mapper::ciram_page`. Four-screen boards wire $2000 and $2400
+    /// to the two console pages.
+    pub fn ciram_page(&self, addr: u16) -> Option<u16> {
+        self.mapper.ciram_page(addr).or_else(|| {
+            (self.mirroring == Mirroring::FourScreen).then_some((addr >> 10) & 0x01)
+        })
+    }
+    
+    fn four_screen_index(&self, addr: u16) -> Option<usize> {
+        let offset = (addr & 0x0FFF) as usize;
+        (!self.four_screen_ram.is_empty() && offset >= 0x800).then(|| offset - 0x800)
+    }
+    
+    /// Nametable RAM on the board rather than in the console, empty unless
+    /// it has four-screen VRAM
+    pub fn nametable_ram(&self) -> &[u8] {
+        &self.four_screen_ram
+    }
+    
+    pub fn nametable_ram_mut(&mut self) -> &mut [u8] {
+        &mut self.four_screen_ram
+    }
+    
+    /// CHR ROM byte at `offset`, bypassing the mapper's banking. Offsets
+    /// past the end wrap like an undersized ROM would.
+    pub fn read_chr_rom(&self, offset: usize) -> u8 {
+        self.chr[offset % self.chr.len()]
+    }
+    
+    /// Current nametable layout: the header's until the mapper changes it
     pub fn mirroring(&self) -> Mirroring {
         self.mirroring
     }
     
+    pub fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+    
     pub fn get_mapper_number(&self) -> u8 {
-        // This should be stored when creating the cartridge
-        // For now, we'll determine from the mapper type
-        match self.mapper.get_state() {
-            MapperState::Mapper0 => 0,
-            MapperState::Mapper1 { .. } => 1,
-            MapperState::Mapper2 { .. } => 2,
-            MapperState::Mapper3 { .. } => 3,
-            MapperState::Mapper4 { .. } => 4,
-            MapperState::Mapper7 { .. } => 7,
-            MapperState::Other => 255,
+        self.mapper_number
+    }
+    
+    /// Common board name for the mapper, e.g. "MMC3"
+    pub fn mapper_name(&self) -> &'static str {
+        supported_mappers()
+            .iter()
+            .find(|(number, _)| *number == self.mapper_number)
+            .map_or("Unknown", |(_, name)| name)
+    }
+    
+    pub fn prg_rom_size(&self) -> usize {
+        self.prg_rom.len()
+    }
+    
+    /// 0 for boards with CHR RAM
+    pub fn chr_rom_size(&self) -> usize {
+        if self.chr_ram { 0 } else { self.chr.len() }
+    }
+    
+    pub fn has_chr_ram(&self) -> bool {
+        self.chr_ram
+    }
+    
+    /// The 8KB of CHR RAM, `None` for CHR ROM boards
+    pub fn chr_ram(&self) -> Option<&[u8]> {
+        self.chr_ram.then_some(&self.chr[..])
+    }
+    
+    pub fn chr_ram_mut(&mut self) -> Option<&mut [u8]> {
+        self.chr_ram.then_some(&mut self.chr[..])
+    }
+    
+    /// The header marks a PlayChoice-10 dump; the game runs as a normal cart
+    pub fn is_playchoice(&self) -> bool {
+        self.playchoice
+    }
+    
+    /// The header says PRG RAM is battery backed, i.e. the game saves
+    pub fn has_battery(&self) -> bool {
+        self.battery
+    }
+    
+    /// PRG RAM contents worth persisting: `None` unless the cartridge has a
+    /// battery and the mapper has PRG RAM
+    pub fn battery_ram(&self) -> Option<&[u8]> {
+        self.prg_ram().filter(|_| self.battery)
+    }
+    
+    /// Every bank of PRG RAM, battery backed or not
+    pub fn prg_ram(&self) -> Option<&[u8]> {
+        self.mapper.prg_ram()
+    }
+    
+    pub fn prg_ram_mut(&mut self) -> Option<&mut [u8]> {
+        self.mapper.prg_ram_mut()
+    }
+    
+    /// As the NES 2.0 header or UNIF board says, else the usual amount for
+    /// the mapper. 0 for boards without PRG RAM.
+    pub fn prg_ram_size(&self) -> usize {
+        self.mapper.prg_ram_size()
+    }
+    
+    /// Restore PRG RAM from a save file. Extra or missing bytes are ignored.
+    pub fn load_battery_ram(&mut self, data: &[u8]) {
+        if let Some(ram) = self.mapper.prg_ram_mut() {
+            let len = ram.len().min(data.len());
+            ram[..len].copy_from_slice(&data[..len]);
+        }
+    }
+    
+    /// PRG RAM was written since the last `clear_battery_ram_dirty`
+    pub fn battery_ram_dirty(&self) -> bool {
+        self.battery && self.mapper.prg_ram_dirty()
+    }
+    
+    pub fn clear_battery_ram_dirty(&mut self) {
+        self.mapper.clear_prg_ram_dirty();
+    }
+    
+    /// PRG RAM without a battery loses its contents when the power goes
+    pub fn clear_volatile_ram(&mut self) {
+        if !self.battery {
+            if let Some(ram) = self.mapper.prg_ram_mut() {
+                ram.fill(0);
+            }
+        }
+    }
+    
+    /// TV system from the header, if it names one. Most iNES 1.0 dumps
+    /// don't, see `Region::detect` for the fallbacks.
+    pub fn region_hint(&self) -> Option<Region> {
+        self.region_hint
+    }
+    
+    pub fn header_info(&self) -> HeaderInfo {
+        HeaderInfo {
+            mapper_number: self.mapper_number,
+            mapper_name: self.mapper_name(),
+            prg_rom_size: self.prg_rom_size(),
+            chr_rom_size: self.chr_rom_size(),
+            prg_ram_size: self.prg_ram_size(),
+            battery: self.battery,
+            four_screen: !self.four_screen_ram.is_empty(),
+            region: self.region_hint,
         }
     }
     
@@ -174,5 +729,45 @@ impl Cartridge {
     
     pub fn set_mapper_state(&mut self, state: &MapperState) {
         self.mapper.set_state(state);
+        self.sync_mirroring();
+    }
+    
+    pub fn irq_pending(&self) -> bool {
+        self.mapper.irq_pending()
+    }
+    
+    /// `Mapper::prg_bank_map`, wrapped to the ROM size the way small ROMs
+    /// mirror (NROM-128 shows the same bank twice)
+    pub fn prg_bank_map(&self) -> [usize; 4] {
+        wrap_offsets(self.mapper.prg_bank_map(), self.prg_rom.len())
+    }
+    
+    /// `Mapper::chr_bank_map`, wrapped to the CHR ROM or RAM size
+    pub fn chr_bank_map(&self) -> [usize; 8] {
+        wrap_offsets(self.mapper.chr_bank_map(), self.chr.len())
+    }
+}
+
+/// `Mapper::access_warning` for a board with registers at $8000-$FFFF and
+/// nothing below $6000
+pub(crate) fn unmapped_access(addr: u16, write: bool, has_prg_ram: bool) -> Option<AccessWarningKind> {
+    let unmapped = match addr {
+        0x4020..=0x5FFF => true,
+        0x6000..=0x7FFF => !has_prg_ram,
+        _ => false,
+    };
+    if !unmapped {
+        None
+    } else if write {
+        Some(AccessWarningKind::UnmappedWrite)
+    } else {
+        Some(AccessWarningKind::UnmappedRead)
+    }
+}
+
+fn wrap_offsets<const N: usize>(offsets: [usize; N], size: usize) -> [usize; N] {
+    if size == 0 {
+        return offsets;
     }
+    offsets.map(|offset| offset % size)
 }
\ No newline at end of file
@@ -0,0 +1,179 @@
+//! UNIF images: a 32 byte header followed by chunks, each a 4 byte ID, a
+//! little-endian length and the data. The board is named by the MAPR chunk
+//! rather than numbered.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use crate::Region;
+use super::{Cartridge, CartridgeError, Mirroring};
+
+const HEADER_SIZE: usize = 32;
+
+/// Board names, without the "NES-"/"HVC-" style prefix, with the iNES
+/// mapper and submapper that emulate them
+const BOARDS: &[(&str, u8, u8)] = &[
+    ("NROM", 0, 0),
+    ("NROM-128", 0, 0),
+    ("NROM-256", 0, 0),
+    ("RROM", 0, 0),
+    ("SAROM", 1, 0),
+    ("SBROM", 1, 0),
+    ("SCROM", 1, 0),
+    ("SEROM", 1, 0),
+    ("SGROM", 1, 0),
+    ("SKROM", 1, 0),
+    ("SLROM", 1, 0),
+    ("SL1ROM", 1, 0),
+    ("SNROM", 1, 0),
+    ("SOROM", 1, 0),
+    ("SXROM", 1, 0),
+    ("MMC1", 1, 0),
+    ("UNROM", 2, 0),
+    ("UOROM", 2, 0),
+    ("CNROM", 3, 0),
+    ("TBROM", 4, 0),
+    ("TEROM", 4, 0),
+    ("TFROM", 4, 0),
+    ("TGROM", 4, 0),
+    ("TKROM", 4, 0),
+    ("TLROM", 4, 0),
+    ("TSROM", 4, 0),
+    ("MMC3", 4, 0),
+    ("EKROM", 5, 0),
+    ("ELROM", 5, 0),
+    ("ETROM", 5, 0),
+    ("EWROM", 5, 0),
+    ("ANROM", 7, 0),
+    ("AN1ROM", 7, 0),
+    ("AOROM", 7, 0),
+    ("AMROM", 7, 2),
+    ("PNROM", 9, 0),
+    ("GNROM", 66, 0),
+    ("MHROM", 66, 0),
+];
+
+/// Boards whose PRG RAM differs from what their mapper usually has
+const BOARD_PRG_RAM: &[(&str, usize)] = &[
+    ("SOROM", 0x4000),
+    ("SXROM", 0x8000),
+    ("TLROM", 0),
+    ("EKROM", 0x2000),
+    ("ELROM", 0),
+    ("ETROM", 0x4000),
+    ("EWROM", 0x8000),
+];
+
+/// The board name without its "NES-"/"HVC-" style prefix
+fn board_name(board: &str) -> &str {
+    board
+        .split_once('-')
+        .filter(|(prefix, _)| matches!(*prefix, "NES" | "HVC" | "UNL" | "BTL" | "BMC"))
+        .map_or(board, |(_, name)| name)
+}
+
+/// The mapper and submapper for a MAPR board name, if it's one we emulate
+pub fn board_mapper(board: &str) -> Option<(u8, u8)> {
+    let name = board_name(board);
+    BOARDS
+        .iter()
+        .find(|(board, _, _)| *board == name)
+        .map(|&(_, mapper, submapper)| (mapper, submapper))
+}
+
+/// PRG RAM for boards that don't have the mapper's usual amount
+fn board_prg_ram(board: &str) -> Option<usize> {
+    let name = board_name(board);
+    BOARD_PRG_RAM
+        .iter()
+        .find(|(board, _)| *board == name)
+        .map(|&(_, size)| size)
+}
+
+impl Cartridge {
+    /// Load a UNIF image. PRG0-PRGF and CHR0-CHRF are joined in order,
+    /// MIRR, BATR and TVCI are honoured and the other chunks are ignored.
+    pub fn from_unif_bytes(data: &[u8]) -> Result<Self, CartridgeError> {
+        if data.len() < HEADER_SIZE {
+            return Err(CartridgeError::Truncated);
+        }
+        if &data[0..4] != b"UNIF" {
+            return Err(CartridgeError::InvalidHeader);
+        }
+        
+        let mut board = None;
+        let mut prg = [None; 16];
+        let mut chr = [None; 16];
+        let mut mirroring = None;
+        let mut battery = false;
+        let mut region_hint = None;
+        
+        let mut rest = &data[HEADER_SIZE..];
+        while !rest.is_empty() {
+            if rest.len() < 8 {
+                return Err(CartridgeError::Truncated);
+            }
+            let id = &rest[0..4];
+            let length = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+            if rest.len() - 8 < length {
+                return Err(CartridgeError::Truncated);
+            }
+            let (body, next) = rest[8..].split_at(length);
+            rest = next;
+            
+            match id {
+                b"MAPR" => {
+                    // Null terminated, though not every dumper bothered
+                    let name = body.split(|&b| b == 0).next().unwrap_or(&[]);
+                    board = Some(String::from_utf8_lossy(name).trim().to_string());
+                }
+                [b'P', b'R', b'G', n] | [b'C', b'H', b'R', n] => {
+                    let index = (*n as char).to_digit(16).ok_or(CartridgeError::InvalidHeader)? as usize;
+                    let banks = if id[0] == b'P' { &mut prg } else { &mut chr };
+                    banks[index] = Some(body);
+                }
+                b"MIRR" => {
+                    mirroring = match body.first() {
+                        Some(0) => Some(Mirroring::Horizontal),
+                        Some(1) => Some(Mirroring::Vertical),
+                        Some(2) => Some(Mirroring::SingleScreenLow),
+                        Some(3) => Some(Mirroring::SingleScreenHigh),
+                        Some(4) => Some(Mirroring::FourScreen),
+                        // 5 is mapper controlled, which the mapper does anyway
+                        _ => None,
+                    };
+                }
+                b"BATR" => battery = true,
+                b"TVCI" => {
+                    region_hint = match body.first() {
+                        Some(0) => Some(Region::Ntsc),
+                        Some(1) => Some(Region::Pal),
+                        _ => None,
+                    };
+                }
+                _ => {}
+            }
+        }
+        
+        let board = board.ok_or(CartridgeError::InvalidHeader)?;
+        let Some((mapper, submapper)) = board_mapper(&board) else {
+            return Err(CartridgeError::UnsupportedBoard(board));
+        };
+        let prg_rom: Vec<u8> = prg.iter().flatten().flat_map(|bank| bank.iter().copied()).collect();
+        let chr_rom: Vec<u8> = chr.iter().flatten().flat_map(|bank| bank.iter().copied()).collect();
+        if prg_rom.is_empty() {
+            return Err(CartridgeError::Truncated);
+        }
+        
+        let mut cartridge = Self::with_mapper(
+            prg_rom,
+            chr_rom,
+            mapper,
+            submapper,
+            board_prg_ram(&board),
+            mirroring.unwrap_or(Mirroring::Horizontal),
+        )?;
+        cartridge.battery = battery;
+        cartridge.region_hint = region_hint;
+        Ok(cartridge)
+    }
+}
@@ -17,16 +17,29 @@ bitflags! {
 #[derive(Debug, Clone)]
 pub struct Controller {
     buttons: ControllerButton,
+    microphone: bool,
+    disallow_opposites: bool,
+    // The more recently pressed of Up/Down and of Left/Right
+    newest: ControllerButton,
 }
 
+const OPPOSITES: [ControllerButton; 2] = [
+    ControllerButton::UP.union(ControllerButton::DOWN),
+    ControllerButton::LEFT.union(ControllerButton::RIGHT),
+];
+
 impl Controller {
     pub fn new() -> Self {
         Self {
             buttons: ControllerButton::empty(),
+            microphone: false,
+            disallow_opposites: false,
+            newest: ControllerButton::empty(),
         }
     }
     
     pub fn set_button(&mut self, button: ControllerButton, pressed: bool) {
+        self.note_presses(if pressed { button - self.buttons } else { ControllerButton::empty() });
         if pressed {
             self.buttons.insert(button);
         } else {
@@ -35,19 +48,65 @@ impl Controller {
     }
     
     pub fn set_buttons(&mut self, buttons: ControllerButton) {
+        self.note_presses(buttons - self.buttons);
         self.buttons = buttons;
     }
     
     pub fn get_state(&self) -> u8 {
-        self.buttons.bits()
+        self.resolved().bits()
     }
     
     pub fn is_pressed(&self, button: ControllerButton) -> bool {
-        self.buttons.contains(button)
+        self.resolved().contains(button)
+    }
+    
+    /// A real pad can't press Left and Right, or Up and Down, together and
+    /// some games glitch when a keyboard does. When disallowed, only the more
+    /// recently pressed of the two counts until it's released. Off by default.
+    pub fn set_disallow_opposites(&mut self, disallow: bool) {
+        self.disallow_opposites = disallow;
+    }
+    
+    pub fn disallow_opposites(&self) -> bool {
+        self.disallow_opposites
+    }
+    
+    fn note_presses(&mut self, pressed: ControllerButton) {
+        for pair in OPPOSITES {
+            let new = pressed & pair;
+            // Both at once says nothing about which came last
+            if new != pair && !new.is_empty() {
+                self.newest = (self.newest - pair) | new;
+            }
+        }
+    }
+    
+    fn resolved(&self) -> ControllerButton {
+        let mut buttons = self.buttons;
+        if self.disallow_opposites {
+            for pair in OPPOSITES {
+                if buttons.contains(pair) {
+                    buttons.remove(pair - self.newest);
+                }
+            }
+        }
+        buttons
+    }
+    
+    /// Famicom only: the second controller has a microphone in place of
+    /// Start/Select. See `Nes::set_microphone`.
+    pub fn set_microphone(&mut self, active: bool) {
+        self.microphone = active;
+    }
+    
+    pub fn microphone(&self) -> bool {
+        self.microphone
     }
     
     pub fn clear(&mut self) {
         self.buttons = ControllerButton::empty();
+        self.microphone = false;
+        self.newest = ControllerButton::empty();
     }
 }
 
@@ -101,4 +160,33 @@ mod tests {
         assert!(!controller.is_pressed(ControllerButton::DOWN));
         assert_eq!(controller.get_state(), 0x11);
     }
+    
+    #[test]
+    fn test_controller_disallow_opposites() {
+        let mut controller = Controller::new();
+        controller.set_button(ControllerButton::LEFT, true);
+        controller.set_button(ControllerButton::RIGHT, true);
+        
+        // Raw input by default
+        assert!(controller.is_pressed(ControllerButton::LEFT));
+        assert!(controller.is_pressed(ControllerButton::RIGHT));
+        
+        // Otherwise the later press wins, and the earlier one is back once it's let go
+        controller.set_disallow_opposites(true);
+        assert!(!controller.is_pressed(ControllerButton::LEFT));
+        assert!(controller.is_pressed(ControllerButton::RIGHT));
+        assert_eq!(controller.get_state(), ControllerButton::RIGHT.bits());
+        
+        // Re-reporting a held button doesn't count as a new press
+        controller.set_button(ControllerButton::LEFT, true);
+        assert!(controller.is_pressed(ControllerButton::RIGHT));
+        controller.set_buttons(ControllerButton::LEFT | ControllerButton::RIGHT | ControllerButton::UP);
+        assert_eq!(controller.get_state(), (ControllerButton::RIGHT | ControllerButton::UP).bits());
+        
+        controller.set_button(ControllerButton::RIGHT, false);
+        assert_eq!(controller.get_state(), (ControllerButton::LEFT | ControllerButton::UP).bits());
+        
+        controller.set_button(ControllerButton::DOWN, true);
+        assert_eq!(controller.get_state(), (ControllerButton::LEFT | ControllerButton::DOWN).bits());
+    }
 }
\ No newline at end of file
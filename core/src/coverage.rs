@@ -0,0 +1,67 @@
+use alloc::{vec, vec::Vec};
+use bitflags::bitflags;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Access: u8 {
+        /// An instruction started here (opcode byte)
+        const EXECUTED = 0b001;
+        /// Any CPU read, including opcode and operand fetches
+        const READ     = 0b010;
+        const WRITTEN  = 0b100;
+    }
+}
+
+/// Which CPU addresses were executed, read or written since coverage was
+/// enabled. Recorded per CPU address, so a bank-switched window collects
+/// the accesses of every bank mapped there.
+#[derive(Debug, Clone)]
+pub struct CoverageMap {
+    flags: Vec<Access>,
+}
+
+impl CoverageMap {
+    pub fn new() -> Self {
+        Self {
+            flags: vec![Access::empty(); 0x10000],
+        }
+    }
+    
+    #[inline]
+    pub fn record(&mut self, addr: u16, access: Access) {
+        self.flags[addr as usize] |= access;
+    }
+    
+    pub fn get(&self, addr: u16) -> Access {
+        self.flags[addr as usize]
+    }
+    
+    pub fn clear(&mut self) {
+        self.flags.fill(Access::empty());
+    }
+    
+    /// Addresses with at least one of the `access` bits, in ascending order
+    pub fn addresses(&self, access: Access) -> impl Iterator<Item = u16> + '_ {
+        self.flags
+            .iter()
+            .enumerate()
+            .filter(move |(_, flags)| flags.intersects(access))
+            .map(|(addr, _)| addr as u16)
+    }
+    
+    /// Number of addresses with at least one of the `access` bits
+    pub fn count(&self, access: Access) -> usize {
+        self.addresses(access).count()
+    }
+    
+    /// One `Access` byte per address from $0000 to $FFFF, for exporting
+    pub fn as_bytes(&self) -> Vec<u8> {
+        self.flags.iter().map(|flags| flags.bits()).collect()
+    }
+}
+
+impl Default for CoverageMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
\ No newline at end of file
@@ -1,6 +1,6 @@
 use crate::cpu::{Cpu, CpuBus};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AddressingMode {
     Implicit,
     Accumulator,
@@ -17,6 +17,20 @@ pub enum AddressingMode {
     Relative,
 }
 
+impl AddressingMode {
+    /// Bytes following the opcode
+    pub const fn operand_bytes(self) -> u8 {
+        match self {
+            AddressingMode::Implicit | AddressingMode::Accumulator => 0,
+            AddressingMode::Absolute
+            | AddressingMode::AbsoluteX
+            | AddressingMode::AbsoluteY
+            | AddressingMode::Indirect => 2,
+            _ => 1,
+        }
+    }
+}
+
 impl Cpu {
     pub fn get_operand_address(&mut self, mode: AddressingMode, bus: &mut impl CpuBus) -> (u16, bool) {
         let mut page_crossed = false;
@@ -1,6 +1,9 @@
 use crate::cpu::{Cpu, CpuBus, StatusFlags};
 use super::addressing::AddressingMode;
 
+/// Stands in for the chip-dependent bits XAA and LXA OR into A
+const UNSTABLE_MAGIC: u8 = 0xFF;
+
 impl Cpu {
     // Load operations
     pub fn lda(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
@@ -32,7 +35,8 @@ impl Cpu {
     
     // Store operations
     pub fn sta(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
-        let (addr, _) = self.get_operand_address(mode, bus);
+        let (addr, page_crossed) = self.get_operand_address(mode, bus);
+        self.indexed_store_read(bus, addr, page_crossed, mode);
         self.write_byte(bus, addr, self.a, mode);
     }
     
@@ -141,17 +145,7 @@ impl Cpu {
     pub fn adc(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
         let (addr, page_crossed) = self.get_operand_address(mode, bus);
         let value = self.read_byte(bus, addr, mode);
-        let carry = if self.status.contains(StatusFlags::CARRY) { 1 } else { 0 };
-        
-        let sum = self.a as u16 + value as u16 + carry;
-        let result = sum as u8;
-        
-        self.status.set(StatusFlags::CARRY, sum > 0xFF);
-        self.status.set(StatusFlags::OVERFLOW, 
-            (self.a ^ result) & (value ^ result) & 0x80 != 0);
-        
-        self.a = result;
-        self.set_zn_flags(self.a);
+        self.add_with_carry(value);
         
         if page_crossed {
             self.add_cycle();
@@ -161,17 +155,8 @@ impl Cpu {
     pub fn sbc(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
         let (addr, page_crossed) = self.get_operand_address(mode, bus);
         let value = self.read_byte(bus, addr, mode);
-        let borrow = if self.status.contains(StatusFlags::CARRY) { 0 } else { 1 };
-        
-        let diff = self.a as i16 - value as i16 - borrow;
-        let result = diff as u8;
-        
-        self.status.set(StatusFlags::CARRY, diff >= 0);
-        self.status.set(StatusFlags::OVERFLOW,
-            (self.a ^ result) & (self.a ^ value) & 0x80 != 0);
-        
-        self.a = result;
-        self.set_zn_flags(self.a);
+        // A - M - borrow is A + !M + carry
+        self.add_with_carry(!value);
         
         if page_crossed {
             self.add_cycle();
@@ -331,6 +316,8 @@ impl Cpu {
             // Add another cycle if page boundary crossed
             if (old_pc & 0xFF00) != (self.pc & 0xFF00) {
                 self.add_cycle();
+            } else {
+                self.set_early_poll();
             }
         }
     }
@@ -412,7 +399,186 @@ impl Cpu {
         self.status.insert(StatusFlags::INTERRUPT);
     }
     
+    // Unofficial read-modify-write operations: the memory half, then the
+    // accumulator half with the new value. No page crossing penalty.
+    pub fn slo(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let value = self.read_byte(bus, addr, mode);
+        self.status.set(StatusFlags::CARRY, value & 0x80 != 0);
+        let result = value << 1;
+        self.write_byte(bus, addr, result, mode);
+        self.a |= result;
+        self.set_zn_flags(self.a);
+    }
+    
+    pub fn rla(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let value = self.read_byte(bus, addr, mode);
+        let old_carry = self.status.contains(StatusFlags::CARRY) as u8;
+        self.status.set(StatusFlags::CARRY, value & 0x80 != 0);
+        let result = (value << 1) | old_carry;
+        self.write_byte(bus, addr, result, mode);
+        self.a &= result;
+        self.set_zn_flags(self.a);
+    }
+    
+    pub fn sre(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let value = self.read_byte(bus, addr, mode);
+        self.status.set(StatusFlags::CARRY, value & 0x01 != 0);
+        let result = value >> 1;
+        self.write_byte(bus, addr, result, mode);
+        self.a ^= result;
+        self.set_zn_flags(self.a);
+    }
+    
+    pub fn rra(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let value = self.read_byte(bus, addr, mode);
+        let old_carry = if self.status.contains(StatusFlags::CARRY) { 0x80 } else { 0 };
+        self.status.set(StatusFlags::CARRY, value & 0x01 != 0);
+        let result = (value >> 1) | old_carry;
+        self.write_byte(bus, addr, result, mode);
+        self.add_with_carry(result);
+    }
+    
+    pub fn dcp(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let result = self.read_byte(bus, addr, mode).wrapping_sub(1);
+        self.write_byte(bus, addr, result, mode);
+        self.compare(self.a, result);
+    }
+    
+    pub fn isc(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let result = self.read_byte(bus, addr, mode).wrapping_add(1);
+        self.write_byte(bus, addr, result, mode);
+        self.add_with_carry(!result);
+    }
+    
+    // Unofficial loads and stores
+    pub fn lax(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, page_crossed) = self.get_operand_address(mode, bus);
+        self.a = self.read_byte(bus, addr, mode);
+        self.x = self.a;
+        self.set_zn_flags(self.a);
+        if page_crossed {
+            self.add_cycle();
+        }
+    }
+    
+    pub fn sax(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        self.write_byte(bus, addr, self.a & self.x, mode);
+    }
+    
+    pub fn las(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, page_crossed) = self.get_operand_address(mode, bus);
+        let value = self.read_byte(bus, addr, mode) & self.sp;
+        self.a = value;
+        self.x = value;
+        self.sp = value;
+        self.set_zn_flags(value);
+        if page_crossed {
+            self.add_cycle();
+        }
+    }
+    
+    // Unofficial immediate operations
+    pub fn anc(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        self.a &= self.read_byte(bus, addr, mode);
+        self.set_zn_flags(self.a);
+        self.status.set(StatusFlags::CARRY, self.a & 0x80 != 0);
+    }
+    
+    pub fn alr(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let value = self.a & self.read_byte(bus, addr, mode);
+        self.status.set(StatusFlags::CARRY, value & 0x01 != 0);
+        self.a = value >> 1;
+        self.set_zn_flags(self.a);
+    }
+    
+    /// AND then ROR A, with C from bit 6 of the result and V from bit 6 XOR bit 5
+    pub fn arr(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let value = self.a & self.read_byte(bus, addr, mode);
+        let old_carry = if self.status.contains(StatusFlags::CARRY) { 0x80 } else { 0 };
+        self.a = (value >> 1) | old_carry;
+        self.set_zn_flags(self.a);
+        self.status.set(StatusFlags::CARRY, self.a & 0x40 != 0);
+        self.status.set(StatusFlags::OVERFLOW, ((self.a >> 6) ^ (self.a >> 5)) & 0x01 != 0);
+    }
+    
+    /// X = (A AND X) - operand, setting flags like CMP
+    pub fn axs(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        let value = self.read_byte(bus, addr, mode);
+        let and = self.a & self.x;
+        self.compare(and, value);
+        self.x = and.wrapping_sub(value);
+    }
+    
+    /// Unstable on real chips; modelled with a magic constant of $FF, so
+    /// A = X AND operand
+    pub fn xaa(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        self.a = (self.a | UNSTABLE_MAGIC) & self.x & self.read_byte(bus, addr, mode);
+        self.set_zn_flags(self.a);
+    }
+    
+    /// Unstable like XAA; with the same constant it loads A and X
+    pub fn lxa(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        let (addr, _) = self.get_operand_address(mode, bus);
+        self.a = (self.a | UNSTABLE_MAGIC) & self.read_byte(bus, addr, mode);
+        self.x = self.a;
+        self.set_zn_flags(self.a);
+    }
+    
+    // Unofficial stores of a register ANDed with the target's high byte + 1
+    pub fn ahx(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        self.store_and_high(bus, mode, self.a & self.x);
+    }
+    
+    pub fn shx(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        self.store_and_high(bus, mode, self.x);
+    }
+    
+    pub fn shy(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        self.store_and_high(bus, mode, self.y);
+    }
+    
+    pub fn tas(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        self.sp = self.a & self.x;
+        self.store_and_high(bus, mode, self.sp);
+    }
+    
+    /// On a page crossing the stored value also replaces the high byte of
+    /// the address
+    fn store_and_high(&mut self, bus: &mut impl CpuBus, mode: AddressingMode, value: u8) {
+        let (addr, page_crossed) = self.get_operand_address(mode, bus);
+        let base_high = ((addr >> 8) as u8).wrapping_sub(page_crossed as u8);
+        let value = value & base_high.wrapping_add(1);
+        let addr = if page_crossed { ((value as u16) << 8) | (addr & 0xFF) } else { addr };
+        self.write_byte(bus, addr, value, mode);
+    }
+    
     // Helper functions
+    fn add_with_carry(&mut self, value: u8) {
+        let carry = if self.status.contains(StatusFlags::CARRY) { 1 } else { 0 };
+        
+        let sum = self.a as u16 + value as u16 + carry;
+        let result = sum as u8;
+        
+        self.status.set(StatusFlags::CARRY, sum > 0xFF);
+        self.status.set(StatusFlags::OVERFLOW, 
+            (self.a ^ result) & (value ^ result) & 0x80 != 0);
+        
+        self.a = result;
+        self.set_zn_flags(self.a);
+    }
+    
     fn set_zn_flags(&mut self, value: u8) {
         self.status.set(StatusFlags::ZERO, value == 0);
         self.status.set(StatusFlags::NEGATIVE, value & 0x80 != 0);
@@ -437,13 +603,22 @@ impl Cpu {
         bus.write(addr, value);
     }
     
+    /// Indexed stores can't skip the high-byte fix-up the way reads do, so
+    /// they always spend that cycle (counted in the opcode table) reading
+    /// from the not yet corrected address
+    fn indexed_store_read(&self, bus: &mut impl CpuBus, addr: u16, page_crossed: bool, mode: AddressingMode) {
+        if matches!(mode, AddressingMode::AbsoluteX | AddressingMode::AbsoluteY | AddressingMode::IndirectY) {
+            bus.read(addr.wrapping_sub((page_crossed as u16) << 8));
+        }
+    }
+    
     fn pop_word(&mut self, bus: &mut impl CpuBus) -> u16 {
         let lo = self.pop(bus) as u16;
         let hi = self.pop(bus) as u16;
         (hi << 8) | lo
     }
     
-    fn add_cycle(&mut self) {
+    pub(super) fn add_cycle(&mut self) {
         self.cycles += 1;
     }
 }
\ No newline at end of file
@@ -1,14 +1,16 @@
 use bitflags::bitflags;
+use serde::{Serialize, Deserialize};
 
 pub mod instructions;
 pub mod addressing;
 pub mod opcodes;
 pub mod optimized;
 
-use opcodes::{OPCODE_TABLE, Instruction};
+use opcodes::{OPCODE_TABLE, Instruction, Opcode};
+use addressing::AddressingMode;
 
 bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
     pub struct StatusFlags: u8 {
         const CARRY     = 0b00000001;  // C
         const ZERO      = 0b00000010;  // Z
@@ -21,7 +23,7 @@ bitflags! {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cpu {
     pub a: u8,      // Accumulator
     pub x: u8,      // X index register
@@ -35,7 +37,24 @@ pub struct Cpu {
     
     // Interrupt flags
     nmi_pending: bool,
+    // NMI that arrived during the last cycle of an instruction
+    nmi_delayed: bool,
     irq_pending: bool,
+    // IRQ that arrived after the instruction's interrupt poll
+    irq_delayed: bool,
+    // Set by a taken branch that stays on its page: it polls before its
+    // second cycle rather than its third, so interrupts raised in either
+    // of its last two cycles wait an instruction
+    #[serde(skip)]
+    early_poll: bool,
+    // Set by KIL; only reset gets the CPU going again
+    jammed: bool,
+    
+    // Interrupts taken since power on; diagnostics, not saved
+    #[serde(skip)]
+    nmi_count: u64,
+    #[serde(skip)]
+    irq_count: u64,
 }
 
 impl Cpu {
@@ -50,7 +69,13 @@ impl Cpu {
             cycles: 0,
             stall_cycles: 0,
             nmi_pending: false,
+            nmi_delayed: false,
             irq_pending: false,
+            irq_delayed: false,
+            early_poll: false,
+            jammed: false,
+            nmi_count: 0,
+            irq_count: 0,
         }
     }
     
@@ -68,27 +93,85 @@ impl Cpu {
         self.cycles = 0;
         self.stall_cycles = 0;
         self.nmi_pending = false;
+        self.nmi_delayed = false;
         self.irq_pending = false;
+        self.irq_delayed = false;
+        self.jammed = false;
     }
     
     pub fn step(&mut self, bus: &mut impl CpuBus) -> u32 {
         if self.stall_cycles > 0 {
             self.stall_cycles -= 1;
+            if self.nmi_delayed {
+                self.nmi_delayed = false;
+                self.nmi_pending = true;
+            }
+            if self.irq_delayed {
+                self.irq_delayed = false;
+                self.irq_pending = true;
+            }
+            return 1;
+        }
+        
+        // A jammed CPU ignores interrupts; the rest of the machine keeps running
+        if self.jammed {
             return 1;
         }
         
+        // Interrupt entry cycles count towards this step
+        let start_cycles = self.cycles;
+        let late_nmi = core::mem::take(&mut self.nmi_delayed);
+        let late_irq = core::mem::take(&mut self.irq_delayed);
+        self.early_poll = false;
+        
         // Handle interrupts
         self.handle_interrupts(bus);
         
-        let opcode = bus.read(self.pc);
+        let opcode = bus.fetch_opcode(self.pc);
         self.pc = self.pc.wrapping_add(1);
         
-        let start_cycles = self.cycles;
         self.execute_instruction(opcode, bus);
         
+        // A late interrupt missed the poll before this instruction, so it is taken after it
+        if late_nmi {
+            self.nmi_pending = true;
+        }
+        if late_irq {
+            self.irq_pending = true;
+        }
+        
         self.cycles - start_cycles
     }
     
+    /// Run one instruction, then clock `bus` once per cycle it took.
+    /// Returns the cycle count and whether any tick returned true.
+    pub fn step_clocked(&mut self, bus: &mut impl ClockedBus) -> (u32, bool) {
+        let cycles = self.step(bus);
+        (cycles, self.tick_bus(bus, cycles))
+    }
+    
+    /// Clock `bus` for `cycles` CPU cycles of the instruction that just ran.
+    /// An interrupt raised on the last of them, or the last two of a taken
+    /// branch that stays on its page, is deferred by one instruction.
+    pub fn tick_bus(&mut self, bus: &mut impl ClockedBus, cycles: u32) -> bool {
+        let unpolled = if self.early_poll { 2 } else { 1 };
+        let mut result = false;
+        for cycle in 0..cycles {
+            let (nmi_was_pending, irq_was_pending) = (self.nmi_pending, self.irq_pending);
+            result |= bus.tick(self);
+            if cycle + unpolled >= cycles {
+                if !nmi_was_pending && self.nmi_pending {
+                    self.delay_nmi();
+                }
+                if !irq_was_pending && self.irq_pending {
+                    self.irq_pending = false;
+                    self.irq_delayed = true;
+                }
+            }
+        }
+        result
+    }
+    
     #[inline(always)]
     fn execute_instruction(&mut self, opcode_byte: u8, bus: &mut impl CpuBus) {
         // Use optimized helpers for common operations
@@ -134,10 +217,7 @@ impl Cpu {
             _ => {}
         }
         
-        let opcode = match OPCODE_TABLE[opcode_byte as usize] {
-            Some(op) => op,
-            None => panic!("Invalid opcode: 0x{:02X}", opcode_byte),
-        };
+        let opcode = OPCODE_TABLE[opcode_byte as usize];
         
         self.cycles += opcode.cycles as u32;
         
@@ -224,10 +304,45 @@ impl Cpu {
             
             // System Functions
             Instruction::BRK => self.brk(bus),
-            Instruction::NOP => self.nop(),
+            Instruction::NOP => self.nop(bus, opcode.mode),
+            
+            // Unofficial opcodes
+            Instruction::SLO => self.slo(bus, opcode.mode),
+            Instruction::RLA => self.rla(bus, opcode.mode),
+            Instruction::SRE => self.sre(bus, opcode.mode),
+            Instruction::RRA => self.rra(bus, opcode.mode),
+            Instruction::DCP => self.dcp(bus, opcode.mode),
+            Instruction::ISC => self.isc(bus, opcode.mode),
+            Instruction::LAX => self.lax(bus, opcode.mode),
+            Instruction::SAX => self.sax(bus, opcode.mode),
+            Instruction::LAS => self.las(bus, opcode.mode),
+            Instruction::ANC => self.anc(bus, opcode.mode),
+            Instruction::ALR => self.alr(bus, opcode.mode),
+            Instruction::ARR => self.arr(bus, opcode.mode),
+            Instruction::AXS => self.axs(bus, opcode.mode),
+            Instruction::XAA => self.xaa(bus, opcode.mode),
+            Instruction::LXA => self.lxa(bus, opcode.mode),
+            Instruction::AHX => self.ahx(bus, opcode.mode),
+            Instruction::SHX => self.shx(bus, opcode.mode),
+            Instruction::SHY => self.shy(bus, opcode.mode),
+            Instruction::TAS => self.tas(bus, opcode.mode),
+            Instruction::KIL => self.kil(),
         }
     }
     
+    /// The table entry for an opcode byte, None for the KIL opcodes
+    /// that jam the CPU
+    pub fn decode(opcode: u8) -> Option<Opcode> {
+        let opcode = OPCODE_TABLE[opcode as usize];
+        (opcode.instruction != Instruction::KIL).then_some(opcode)
+    }
+    
+    /// Bytes taken by the instruction starting with `opcode`, operands
+    /// included. BRK counts as 1 although it skips a padding byte on return.
+    pub fn instruction_length(opcode: u8) -> u8 {
+        1 + OPCODE_TABLE[opcode as usize].mode.operand_bytes()
+    }
+    
     pub fn brk(&mut self, bus: &mut impl CpuBus) {
         self.pc = self.pc.wrapping_add(1);
         self.push_word(self.pc, bus);
@@ -236,12 +351,37 @@ impl Cpu {
         self.pc = self.read_word(0xFFFE, bus);
     }
     
-    pub fn nop(&mut self) {
-        // No operation
+    /// The unofficial variants with an operand still read it
+    pub fn nop(&mut self, bus: &mut impl CpuBus, mode: AddressingMode) {
+        if matches!(mode, AddressingMode::Implicit) {
+            return;
+        }
+        let (addr, page_crossed) = self.get_operand_address(mode, bus);
+        bus.read(addr);
+        if page_crossed {
+            self.add_cycle();
+        }
+    }
+    
+    /// Stop fetching instructions until reset. PC stays on the KIL opcode.
+    pub fn kil(&mut self) {
+        self.pc = self.pc.wrapping_sub(1);
+        self.jammed = true;
+    }
+    
+    pub fn is_jammed(&self) -> bool {
+        self.jammed
+    }
+    
+    pub(crate) fn set_jammed(&mut self, jammed: bool) {
+        self.jammed = jammed;
     }
     
     pub fn push(&mut self, value: u8, bus: &mut impl CpuBus) {
         bus.write(0x0100 | self.sp as u16, value);
+        if self.sp == 0x00 {
+            bus.stack_wrapped(StackWrap::Overflow);
+        }
         self.sp = self.sp.wrapping_sub(1);
     }
     
@@ -251,6 +391,9 @@ impl Cpu {
     }
     
     pub fn pop(&mut self, bus: &mut impl CpuBus) -> u8 {
+        if self.sp == 0xFF {
+            bus.stack_wrapped(StackWrap::Underflow);
+        }
         self.sp = self.sp.wrapping_add(1);
         bus.read(0x0100 | self.sp as u16)
     }
@@ -265,6 +408,27 @@ impl Cpu {
         self.nmi_pending = true;
     }
     
+    /// The NMI was raised during the final cycle of the instruction that just ran.
+    /// Interrupts are polled before that cycle, so one more instruction executes first.
+    pub fn delay_nmi(&mut self) {
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            self.nmi_delayed = true;
+        }
+    }
+    
+    /// Taken branches that stay on their page poll for interrupts a cycle
+    /// early, see `tick_bus`
+    pub(super) fn set_early_poll(&mut self) {
+        self.early_poll = true;
+    }
+    
+    /// Drop an NMI that hasn't been serviced yet
+    pub fn cancel_nmi(&mut self) {
+        self.nmi_pending = false;
+        self.nmi_delayed = false;
+    }
+    
     pub fn trigger_irq(&mut self) {
         if !self.status.contains(StatusFlags::INTERRUPT) {
             self.irq_pending = true;
@@ -279,6 +443,7 @@ impl Cpu {
             self.status.insert(StatusFlags::INTERRUPT);
             self.pc = self.read_word(0xFFFA, bus);
             self.cycles += 7;
+            self.nmi_count += 1;
         } else if self.irq_pending && !self.status.contains(StatusFlags::INTERRUPT) {
             self.irq_pending = false;
             self.push_word(self.pc, bus);
@@ -286,6 +451,7 @@ impl Cpu {
             self.status.insert(StatusFlags::INTERRUPT);
             self.pc = self.read_word(0xFFFE, bus);
             self.cycles += 7;
+            self.irq_count += 1;
         }
     }
     
@@ -301,6 +467,23 @@ impl Cpu {
         self.irq_pending
     }
     
+    /// NMIs the CPU has entered its handler for, since it was created
+    pub fn nmi_count(&self) -> u64 {
+        self.nmi_count
+    }
+    
+    /// IRQs the CPU has entered its handler for; BRK isn't counted
+    pub fn irq_count(&self) -> u64 {
+        self.irq_count
+    }
+    
+    /// An NMI or unmasked IRQ is waiting to be taken
+    pub fn has_pending_interrupt(&self) -> bool {
+        self.nmi_pending
+            || self.nmi_delayed
+            || ((self.irq_pending || self.irq_delayed) && !self.status.contains(StatusFlags::INTERRUPT))
+    }
+    
     pub fn set_nmi_pending(&mut self, pending: bool) {
         self.nmi_pending = pending;
     }
@@ -310,7 +493,37 @@ impl Cpu {
     }
 }
 
+/// Which way a push or pull took the stack pointer around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackWrap {
+    /// A push with SP at $00
+    Overflow,
+    /// A pull with SP at $FF
+    Underflow,
+}
+
 pub trait CpuBus {
     fn read(&mut self, addr: u16) -> u8;
     fn write(&mut self, addr: u16, value: u8);
+    
+    /// Told when a push or pull wraps SP. The wrap itself is what the
+    /// hardware does; buses that report it to a debugger override this.
+    #[inline]
+    fn stack_wrapped(&mut self, _wrap: StackWrap) {}
+    
+    /// Read the opcode of the instruction about to run. Buses that trace
+    /// execution override this; it must behave like `read`.
+    #[inline]
+    fn fetch_opcode(&mut self, addr: u16) -> u8 {
+        self.read(addr)
+    }
+}
+
+/// A `CpuBus` that also runs the rest of the machine, one CPU cycle per
+/// `tick`, and raises interrupts on the CPU. `Bus` drives the real PPU and
+/// APU; tests can script NMIs and register reads instead.
+pub trait ClockedBus: CpuBus {
+    /// Advance one CPU cycle. The meaning of the return value is up to the
+    /// bus; `Bus` reports a finished frame.
+    fn tick(&mut self, cpu: &mut Cpu) -> bool;
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use super::addressing::AddressingMode;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Opcode {
     pub instruction: Instruction,
     pub mode: AddressingMode,
@@ -47,235 +47,390 @@ pub enum Instruction {
     
     // System Functions
     BRK, NOP,
+    
+    // Unofficial read-modify-write combinations
+    SLO, RLA, SRE, RRA, DCP, ISC,
+    
+    // Unofficial loads and stores
+    LAX, SAX, LAS,
+    
+    // Unofficial immediate operations
+    ANC, ALR, ARR, AXS, XAA, LXA,
+    
+    // Unofficial stores of a register ANDed with the address high byte + 1
+    AHX, SHX, SHY, TAS,
+    
+    // Halts the CPU until reset
+    KIL,
 }
 
-pub const OPCODE_TABLE: [Option<Opcode>; 256] = {
-    let mut table = [None; 256];
+/// Every byte decodes to something: the official set plus the unofficial
+/// opcodes of the NMOS 6502, with KIL for the twelve that jam the CPU
+pub const OPCODE_TABLE: [Opcode; 256] = {
+    let mut table = [Opcode { instruction: Instruction::KIL, mode: AddressingMode::Implicit, cycles: 2 }; 256];
     
     // BRK
-    table[0x00] = Some(Opcode { instruction: Instruction::BRK, mode: AddressingMode::Implicit, cycles: 7 });
+    table[0x00] = Opcode { instruction: Instruction::BRK, mode: AddressingMode::Implicit, cycles: 7 };
     
     // ORA
-    table[0x01] = Some(Opcode { instruction: Instruction::ORA, mode: AddressingMode::IndirectX, cycles: 6 });
-    table[0x05] = Some(Opcode { instruction: Instruction::ORA, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0x09] = Some(Opcode { instruction: Instruction::ORA, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0x0D] = Some(Opcode { instruction: Instruction::ORA, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0x11] = Some(Opcode { instruction: Instruction::ORA, mode: AddressingMode::IndirectY, cycles: 5 });
-    table[0x15] = Some(Opcode { instruction: Instruction::ORA, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0x19] = Some(Opcode { instruction: Instruction::ORA, mode: AddressingMode::AbsoluteY, cycles: 4 });
-    table[0x1D] = Some(Opcode { instruction: Instruction::ORA, mode: AddressingMode::AbsoluteX, cycles: 4 });
+    table[0x01] = Opcode { instruction: Instruction::ORA, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0x05] = Opcode { instruction: Instruction::ORA, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x09] = Opcode { instruction: Instruction::ORA, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0x0D] = Opcode { instruction: Instruction::ORA, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x11] = Opcode { instruction: Instruction::ORA, mode: AddressingMode::IndirectY, cycles: 5 };
+    table[0x15] = Opcode { instruction: Instruction::ORA, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x19] = Opcode { instruction: Instruction::ORA, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    table[0x1D] = Opcode { instruction: Instruction::ORA, mode: AddressingMode::AbsoluteX, cycles: 4 };
     
     // ASL
-    table[0x06] = Some(Opcode { instruction: Instruction::ASL, mode: AddressingMode::ZeroPage, cycles: 5 });
-    table[0x0A] = Some(Opcode { instruction: Instruction::ASL, mode: AddressingMode::Accumulator, cycles: 2 });
-    table[0x0E] = Some(Opcode { instruction: Instruction::ASL, mode: AddressingMode::Absolute, cycles: 6 });
-    table[0x16] = Some(Opcode { instruction: Instruction::ASL, mode: AddressingMode::ZeroPageX, cycles: 6 });
-    table[0x1E] = Some(Opcode { instruction: Instruction::ASL, mode: AddressingMode::AbsoluteX, cycles: 7 });
+    table[0x06] = Opcode { instruction: Instruction::ASL, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0x0A] = Opcode { instruction: Instruction::ASL, mode: AddressingMode::Accumulator, cycles: 2 };
+    table[0x0E] = Opcode { instruction: Instruction::ASL, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0x16] = Opcode { instruction: Instruction::ASL, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0x1E] = Opcode { instruction: Instruction::ASL, mode: AddressingMode::AbsoluteX, cycles: 7 };
     
     // PHP, CLC
-    table[0x08] = Some(Opcode { instruction: Instruction::PHP, mode: AddressingMode::Implicit, cycles: 3 });
-    table[0x18] = Some(Opcode { instruction: Instruction::CLC, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0x08] = Opcode { instruction: Instruction::PHP, mode: AddressingMode::Implicit, cycles: 3 };
+    table[0x18] = Opcode { instruction: Instruction::CLC, mode: AddressingMode::Implicit, cycles: 2 };
     
     // JSR
-    table[0x20] = Some(Opcode { instruction: Instruction::JSR, mode: AddressingMode::Absolute, cycles: 6 });
+    table[0x20] = Opcode { instruction: Instruction::JSR, mode: AddressingMode::Absolute, cycles: 6 };
     
     // AND
-    table[0x21] = Some(Opcode { instruction: Instruction::AND, mode: AddressingMode::IndirectX, cycles: 6 });
-    table[0x25] = Some(Opcode { instruction: Instruction::AND, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0x29] = Some(Opcode { instruction: Instruction::AND, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0x2D] = Some(Opcode { instruction: Instruction::AND, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0x31] = Some(Opcode { instruction: Instruction::AND, mode: AddressingMode::IndirectY, cycles: 5 });
-    table[0x35] = Some(Opcode { instruction: Instruction::AND, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0x39] = Some(Opcode { instruction: Instruction::AND, mode: AddressingMode::AbsoluteY, cycles: 4 });
-    table[0x3D] = Some(Opcode { instruction: Instruction::AND, mode: AddressingMode::AbsoluteX, cycles: 4 });
+    table[0x21] = Opcode { instruction: Instruction::AND, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0x25] = Opcode { instruction: Instruction::AND, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x29] = Opcode { instruction: Instruction::AND, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0x2D] = Opcode { instruction: Instruction::AND, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x31] = Opcode { instruction: Instruction::AND, mode: AddressingMode::IndirectY, cycles: 5 };
+    table[0x35] = Opcode { instruction: Instruction::AND, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x39] = Opcode { instruction: Instruction::AND, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    table[0x3D] = Opcode { instruction: Instruction::AND, mode: AddressingMode::AbsoluteX, cycles: 4 };
     
     // BIT
-    table[0x24] = Some(Opcode { instruction: Instruction::BIT, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0x2C] = Some(Opcode { instruction: Instruction::BIT, mode: AddressingMode::Absolute, cycles: 4 });
+    table[0x24] = Opcode { instruction: Instruction::BIT, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x2C] = Opcode { instruction: Instruction::BIT, mode: AddressingMode::Absolute, cycles: 4 };
     
     // ROL
-    table[0x26] = Some(Opcode { instruction: Instruction::ROL, mode: AddressingMode::ZeroPage, cycles: 5 });
-    table[0x2A] = Some(Opcode { instruction: Instruction::ROL, mode: AddressingMode::Accumulator, cycles: 2 });
-    table[0x2E] = Some(Opcode { instruction: Instruction::ROL, mode: AddressingMode::Absolute, cycles: 6 });
-    table[0x36] = Some(Opcode { instruction: Instruction::ROL, mode: AddressingMode::ZeroPageX, cycles: 6 });
-    table[0x3E] = Some(Opcode { instruction: Instruction::ROL, mode: AddressingMode::AbsoluteX, cycles: 7 });
+    table[0x26] = Opcode { instruction: Instruction::ROL, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0x2A] = Opcode { instruction: Instruction::ROL, mode: AddressingMode::Accumulator, cycles: 2 };
+    table[0x2E] = Opcode { instruction: Instruction::ROL, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0x36] = Opcode { instruction: Instruction::ROL, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0x3E] = Opcode { instruction: Instruction::ROL, mode: AddressingMode::AbsoluteX, cycles: 7 };
     
     // PLP, SEC
-    table[0x28] = Some(Opcode { instruction: Instruction::PLP, mode: AddressingMode::Implicit, cycles: 4 });
-    table[0x38] = Some(Opcode { instruction: Instruction::SEC, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0x28] = Opcode { instruction: Instruction::PLP, mode: AddressingMode::Implicit, cycles: 4 };
+    table[0x38] = Opcode { instruction: Instruction::SEC, mode: AddressingMode::Implicit, cycles: 2 };
     
     // Branch instructions
-    table[0x10] = Some(Opcode { instruction: Instruction::BPL, mode: AddressingMode::Relative, cycles: 2 });
-    table[0x30] = Some(Opcode { instruction: Instruction::BMI, mode: AddressingMode::Relative, cycles: 2 });
-    table[0x50] = Some(Opcode { instruction: Instruction::BVC, mode: AddressingMode::Relative, cycles: 2 });
-    table[0x70] = Some(Opcode { instruction: Instruction::BVS, mode: AddressingMode::Relative, cycles: 2 });
-    table[0x90] = Some(Opcode { instruction: Instruction::BCC, mode: AddressingMode::Relative, cycles: 2 });
-    table[0xB0] = Some(Opcode { instruction: Instruction::BCS, mode: AddressingMode::Relative, cycles: 2 });
-    table[0xD0] = Some(Opcode { instruction: Instruction::BNE, mode: AddressingMode::Relative, cycles: 2 });
-    table[0xF0] = Some(Opcode { instruction: Instruction::BEQ, mode: AddressingMode::Relative, cycles: 2 });
+    table[0x10] = Opcode { instruction: Instruction::BPL, mode: AddressingMode::Relative, cycles: 2 };
+    table[0x30] = Opcode { instruction: Instruction::BMI, mode: AddressingMode::Relative, cycles: 2 };
+    table[0x50] = Opcode { instruction: Instruction::BVC, mode: AddressingMode::Relative, cycles: 2 };
+    table[0x70] = Opcode { instruction: Instruction::BVS, mode: AddressingMode::Relative, cycles: 2 };
+    table[0x90] = Opcode { instruction: Instruction::BCC, mode: AddressingMode::Relative, cycles: 2 };
+    table[0xB0] = Opcode { instruction: Instruction::BCS, mode: AddressingMode::Relative, cycles: 2 };
+    table[0xD0] = Opcode { instruction: Instruction::BNE, mode: AddressingMode::Relative, cycles: 2 };
+    table[0xF0] = Opcode { instruction: Instruction::BEQ, mode: AddressingMode::Relative, cycles: 2 };
     
     // RTI
-    table[0x40] = Some(Opcode { instruction: Instruction::RTI, mode: AddressingMode::Implicit, cycles: 6 });
+    table[0x40] = Opcode { instruction: Instruction::RTI, mode: AddressingMode::Implicit, cycles: 6 };
     
     // EOR
-    table[0x41] = Some(Opcode { instruction: Instruction::EOR, mode: AddressingMode::IndirectX, cycles: 6 });
-    table[0x45] = Some(Opcode { instruction: Instruction::EOR, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0x49] = Some(Opcode { instruction: Instruction::EOR, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0x4D] = Some(Opcode { instruction: Instruction::EOR, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0x51] = Some(Opcode { instruction: Instruction::EOR, mode: AddressingMode::IndirectY, cycles: 5 });
-    table[0x55] = Some(Opcode { instruction: Instruction::EOR, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0x59] = Some(Opcode { instruction: Instruction::EOR, mode: AddressingMode::AbsoluteY, cycles: 4 });
-    table[0x5D] = Some(Opcode { instruction: Instruction::EOR, mode: AddressingMode::AbsoluteX, cycles: 4 });
+    table[0x41] = Opcode { instruction: Instruction::EOR, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0x45] = Opcode { instruction: Instruction::EOR, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x49] = Opcode { instruction: Instruction::EOR, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0x4D] = Opcode { instruction: Instruction::EOR, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x51] = Opcode { instruction: Instruction::EOR, mode: AddressingMode::IndirectY, cycles: 5 };
+    table[0x55] = Opcode { instruction: Instruction::EOR, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x59] = Opcode { instruction: Instruction::EOR, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    table[0x5D] = Opcode { instruction: Instruction::EOR, mode: AddressingMode::AbsoluteX, cycles: 4 };
     
     // LSR
-    table[0x46] = Some(Opcode { instruction: Instruction::LSR, mode: AddressingMode::ZeroPage, cycles: 5 });
-    table[0x4A] = Some(Opcode { instruction: Instruction::LSR, mode: AddressingMode::Accumulator, cycles: 2 });
-    table[0x4E] = Some(Opcode { instruction: Instruction::LSR, mode: AddressingMode::Absolute, cycles: 6 });
-    table[0x56] = Some(Opcode { instruction: Instruction::LSR, mode: AddressingMode::ZeroPageX, cycles: 6 });
-    table[0x5E] = Some(Opcode { instruction: Instruction::LSR, mode: AddressingMode::AbsoluteX, cycles: 7 });
+    table[0x46] = Opcode { instruction: Instruction::LSR, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0x4A] = Opcode { instruction: Instruction::LSR, mode: AddressingMode::Accumulator, cycles: 2 };
+    table[0x4E] = Opcode { instruction: Instruction::LSR, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0x56] = Opcode { instruction: Instruction::LSR, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0x5E] = Opcode { instruction: Instruction::LSR, mode: AddressingMode::AbsoluteX, cycles: 7 };
     
     // PHA, CLI
-    table[0x48] = Some(Opcode { instruction: Instruction::PHA, mode: AddressingMode::Implicit, cycles: 3 });
-    table[0x58] = Some(Opcode { instruction: Instruction::CLI, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0x48] = Opcode { instruction: Instruction::PHA, mode: AddressingMode::Implicit, cycles: 3 };
+    table[0x58] = Opcode { instruction: Instruction::CLI, mode: AddressingMode::Implicit, cycles: 2 };
     
     // JMP
-    table[0x4C] = Some(Opcode { instruction: Instruction::JMP, mode: AddressingMode::Absolute, cycles: 3 });
-    table[0x6C] = Some(Opcode { instruction: Instruction::JMP, mode: AddressingMode::Indirect, cycles: 5 });
+    table[0x4C] = Opcode { instruction: Instruction::JMP, mode: AddressingMode::Absolute, cycles: 3 };
+    table[0x6C] = Opcode { instruction: Instruction::JMP, mode: AddressingMode::Indirect, cycles: 5 };
     
     // RTS
-    table[0x60] = Some(Opcode { instruction: Instruction::RTS, mode: AddressingMode::Implicit, cycles: 6 });
+    table[0x60] = Opcode { instruction: Instruction::RTS, mode: AddressingMode::Implicit, cycles: 6 };
     
     // ADC
-    table[0x61] = Some(Opcode { instruction: Instruction::ADC, mode: AddressingMode::IndirectX, cycles: 6 });
-    table[0x65] = Some(Opcode { instruction: Instruction::ADC, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0x69] = Some(Opcode { instruction: Instruction::ADC, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0x6D] = Some(Opcode { instruction: Instruction::ADC, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0x71] = Some(Opcode { instruction: Instruction::ADC, mode: AddressingMode::IndirectY, cycles: 5 });
-    table[0x75] = Some(Opcode { instruction: Instruction::ADC, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0x79] = Some(Opcode { instruction: Instruction::ADC, mode: AddressingMode::AbsoluteY, cycles: 4 });
-    table[0x7D] = Some(Opcode { instruction: Instruction::ADC, mode: AddressingMode::AbsoluteX, cycles: 4 });
+    table[0x61] = Opcode { instruction: Instruction::ADC, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0x65] = Opcode { instruction: Instruction::ADC, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x69] = Opcode { instruction: Instruction::ADC, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0x6D] = Opcode { instruction: Instruction::ADC, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x71] = Opcode { instruction: Instruction::ADC, mode: AddressingMode::IndirectY, cycles: 5 };
+    table[0x75] = Opcode { instruction: Instruction::ADC, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x79] = Opcode { instruction: Instruction::ADC, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    table[0x7D] = Opcode { instruction: Instruction::ADC, mode: AddressingMode::AbsoluteX, cycles: 4 };
     
     // ROR
-    table[0x66] = Some(Opcode { instruction: Instruction::ROR, mode: AddressingMode::ZeroPage, cycles: 5 });
-    table[0x6A] = Some(Opcode { instruction: Instruction::ROR, mode: AddressingMode::Accumulator, cycles: 2 });
-    table[0x6E] = Some(Opcode { instruction: Instruction::ROR, mode: AddressingMode::Absolute, cycles: 6 });
-    table[0x76] = Some(Opcode { instruction: Instruction::ROR, mode: AddressingMode::ZeroPageX, cycles: 6 });
-    table[0x7E] = Some(Opcode { instruction: Instruction::ROR, mode: AddressingMode::AbsoluteX, cycles: 7 });
+    table[0x66] = Opcode { instruction: Instruction::ROR, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0x6A] = Opcode { instruction: Instruction::ROR, mode: AddressingMode::Accumulator, cycles: 2 };
+    table[0x6E] = Opcode { instruction: Instruction::ROR, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0x76] = Opcode { instruction: Instruction::ROR, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0x7E] = Opcode { instruction: Instruction::ROR, mode: AddressingMode::AbsoluteX, cycles: 7 };
     
     // PLA, SEI
-    table[0x68] = Some(Opcode { instruction: Instruction::PLA, mode: AddressingMode::Implicit, cycles: 4 });
-    table[0x78] = Some(Opcode { instruction: Instruction::SEI, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0x68] = Opcode { instruction: Instruction::PLA, mode: AddressingMode::Implicit, cycles: 4 };
+    table[0x78] = Opcode { instruction: Instruction::SEI, mode: AddressingMode::Implicit, cycles: 2 };
     
     // STA
-    table[0x81] = Some(Opcode { instruction: Instruction::STA, mode: AddressingMode::IndirectX, cycles: 6 });
-    table[0x85] = Some(Opcode { instruction: Instruction::STA, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0x8D] = Some(Opcode { instruction: Instruction::STA, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0x91] = Some(Opcode { instruction: Instruction::STA, mode: AddressingMode::IndirectY, cycles: 6 });
-    table[0x95] = Some(Opcode { instruction: Instruction::STA, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0x99] = Some(Opcode { instruction: Instruction::STA, mode: AddressingMode::AbsoluteY, cycles: 5 });
-    table[0x9D] = Some(Opcode { instruction: Instruction::STA, mode: AddressingMode::AbsoluteX, cycles: 5 });
+    table[0x81] = Opcode { instruction: Instruction::STA, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0x85] = Opcode { instruction: Instruction::STA, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x8D] = Opcode { instruction: Instruction::STA, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x91] = Opcode { instruction: Instruction::STA, mode: AddressingMode::IndirectY, cycles: 6 };
+    table[0x95] = Opcode { instruction: Instruction::STA, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x99] = Opcode { instruction: Instruction::STA, mode: AddressingMode::AbsoluteY, cycles: 5 };
+    table[0x9D] = Opcode { instruction: Instruction::STA, mode: AddressingMode::AbsoluteX, cycles: 5 };
     
     // STX
-    table[0x86] = Some(Opcode { instruction: Instruction::STX, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0x8E] = Some(Opcode { instruction: Instruction::STX, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0x96] = Some(Opcode { instruction: Instruction::STX, mode: AddressingMode::ZeroPageY, cycles: 4 });
+    table[0x86] = Opcode { instruction: Instruction::STX, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x8E] = Opcode { instruction: Instruction::STX, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x96] = Opcode { instruction: Instruction::STX, mode: AddressingMode::ZeroPageY, cycles: 4 };
     
     // STY
-    table[0x84] = Some(Opcode { instruction: Instruction::STY, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0x8C] = Some(Opcode { instruction: Instruction::STY, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0x94] = Some(Opcode { instruction: Instruction::STY, mode: AddressingMode::ZeroPageX, cycles: 4 });
+    table[0x84] = Opcode { instruction: Instruction::STY, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x8C] = Opcode { instruction: Instruction::STY, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x94] = Opcode { instruction: Instruction::STY, mode: AddressingMode::ZeroPageX, cycles: 4 };
     
     // DEY, TXA, TYA
-    table[0x88] = Some(Opcode { instruction: Instruction::DEY, mode: AddressingMode::Implicit, cycles: 2 });
-    table[0x8A] = Some(Opcode { instruction: Instruction::TXA, mode: AddressingMode::Implicit, cycles: 2 });
-    table[0x98] = Some(Opcode { instruction: Instruction::TYA, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0x88] = Opcode { instruction: Instruction::DEY, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0x8A] = Opcode { instruction: Instruction::TXA, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0x98] = Opcode { instruction: Instruction::TYA, mode: AddressingMode::Implicit, cycles: 2 };
     
     // TXS
-    table[0x9A] = Some(Opcode { instruction: Instruction::TXS, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0x9A] = Opcode { instruction: Instruction::TXS, mode: AddressingMode::Implicit, cycles: 2 };
     
     // LDY
-    table[0xA0] = Some(Opcode { instruction: Instruction::LDY, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0xA4] = Some(Opcode { instruction: Instruction::LDY, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0xAC] = Some(Opcode { instruction: Instruction::LDY, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0xB4] = Some(Opcode { instruction: Instruction::LDY, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0xBC] = Some(Opcode { instruction: Instruction::LDY, mode: AddressingMode::AbsoluteX, cycles: 4 });
+    table[0xA0] = Opcode { instruction: Instruction::LDY, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xA4] = Opcode { instruction: Instruction::LDY, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0xAC] = Opcode { instruction: Instruction::LDY, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0xB4] = Opcode { instruction: Instruction::LDY, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0xBC] = Opcode { instruction: Instruction::LDY, mode: AddressingMode::AbsoluteX, cycles: 4 };
     
     // LDA
-    table[0xA1] = Some(Opcode { instruction: Instruction::LDA, mode: AddressingMode::IndirectX, cycles: 6 });
-    table[0xA5] = Some(Opcode { instruction: Instruction::LDA, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0xA9] = Some(Opcode { instruction: Instruction::LDA, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0xAD] = Some(Opcode { instruction: Instruction::LDA, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0xB1] = Some(Opcode { instruction: Instruction::LDA, mode: AddressingMode::IndirectY, cycles: 5 });
-    table[0xB5] = Some(Opcode { instruction: Instruction::LDA, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0xB9] = Some(Opcode { instruction: Instruction::LDA, mode: AddressingMode::AbsoluteY, cycles: 4 });
-    table[0xBD] = Some(Opcode { instruction: Instruction::LDA, mode: AddressingMode::AbsoluteX, cycles: 4 });
+    table[0xA1] = Opcode { instruction: Instruction::LDA, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0xA5] = Opcode { instruction: Instruction::LDA, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0xA9] = Opcode { instruction: Instruction::LDA, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xAD] = Opcode { instruction: Instruction::LDA, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0xB1] = Opcode { instruction: Instruction::LDA, mode: AddressingMode::IndirectY, cycles: 5 };
+    table[0xB5] = Opcode { instruction: Instruction::LDA, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0xB9] = Opcode { instruction: Instruction::LDA, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    table[0xBD] = Opcode { instruction: Instruction::LDA, mode: AddressingMode::AbsoluteX, cycles: 4 };
     
     // LDX
-    table[0xA2] = Some(Opcode { instruction: Instruction::LDX, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0xA6] = Some(Opcode { instruction: Instruction::LDX, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0xAE] = Some(Opcode { instruction: Instruction::LDX, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0xB6] = Some(Opcode { instruction: Instruction::LDX, mode: AddressingMode::ZeroPageY, cycles: 4 });
-    table[0xBE] = Some(Opcode { instruction: Instruction::LDX, mode: AddressingMode::AbsoluteY, cycles: 4 });
+    table[0xA2] = Opcode { instruction: Instruction::LDX, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xA6] = Opcode { instruction: Instruction::LDX, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0xAE] = Opcode { instruction: Instruction::LDX, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0xB6] = Opcode { instruction: Instruction::LDX, mode: AddressingMode::ZeroPageY, cycles: 4 };
+    table[0xBE] = Opcode { instruction: Instruction::LDX, mode: AddressingMode::AbsoluteY, cycles: 4 };
     
     // TAY, TAX, TSX
-    table[0xA8] = Some(Opcode { instruction: Instruction::TAY, mode: AddressingMode::Implicit, cycles: 2 });
-    table[0xAA] = Some(Opcode { instruction: Instruction::TAX, mode: AddressingMode::Implicit, cycles: 2 });
-    table[0xBA] = Some(Opcode { instruction: Instruction::TSX, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0xA8] = Opcode { instruction: Instruction::TAY, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0xAA] = Opcode { instruction: Instruction::TAX, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0xBA] = Opcode { instruction: Instruction::TSX, mode: AddressingMode::Implicit, cycles: 2 };
     
     // CLD, CLV
-    table[0xB8] = Some(Opcode { instruction: Instruction::CLV, mode: AddressingMode::Implicit, cycles: 2 });
-    table[0xD8] = Some(Opcode { instruction: Instruction::CLD, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0xB8] = Opcode { instruction: Instruction::CLV, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0xD8] = Opcode { instruction: Instruction::CLD, mode: AddressingMode::Implicit, cycles: 2 };
     
     // CPY
-    table[0xC0] = Some(Opcode { instruction: Instruction::CPY, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0xC4] = Some(Opcode { instruction: Instruction::CPY, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0xCC] = Some(Opcode { instruction: Instruction::CPY, mode: AddressingMode::Absolute, cycles: 4 });
+    table[0xC0] = Opcode { instruction: Instruction::CPY, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xC4] = Opcode { instruction: Instruction::CPY, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0xCC] = Opcode { instruction: Instruction::CPY, mode: AddressingMode::Absolute, cycles: 4 };
     
     // CMP
-    table[0xC1] = Some(Opcode { instruction: Instruction::CMP, mode: AddressingMode::IndirectX, cycles: 6 });
-    table[0xC5] = Some(Opcode { instruction: Instruction::CMP, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0xC9] = Some(Opcode { instruction: Instruction::CMP, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0xCD] = Some(Opcode { instruction: Instruction::CMP, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0xD1] = Some(Opcode { instruction: Instruction::CMP, mode: AddressingMode::IndirectY, cycles: 5 });
-    table[0xD5] = Some(Opcode { instruction: Instruction::CMP, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0xD9] = Some(Opcode { instruction: Instruction::CMP, mode: AddressingMode::AbsoluteY, cycles: 4 });
-    table[0xDD] = Some(Opcode { instruction: Instruction::CMP, mode: AddressingMode::AbsoluteX, cycles: 4 });
+    table[0xC1] = Opcode { instruction: Instruction::CMP, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0xC5] = Opcode { instruction: Instruction::CMP, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0xC9] = Opcode { instruction: Instruction::CMP, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xCD] = Opcode { instruction: Instruction::CMP, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0xD1] = Opcode { instruction: Instruction::CMP, mode: AddressingMode::IndirectY, cycles: 5 };
+    table[0xD5] = Opcode { instruction: Instruction::CMP, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0xD9] = Opcode { instruction: Instruction::CMP, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    table[0xDD] = Opcode { instruction: Instruction::CMP, mode: AddressingMode::AbsoluteX, cycles: 4 };
     
     // DEC
-    table[0xC6] = Some(Opcode { instruction: Instruction::DEC, mode: AddressingMode::ZeroPage, cycles: 5 });
-    table[0xCE] = Some(Opcode { instruction: Instruction::DEC, mode: AddressingMode::Absolute, cycles: 6 });
-    table[0xD6] = Some(Opcode { instruction: Instruction::DEC, mode: AddressingMode::ZeroPageX, cycles: 6 });
-    table[0xDE] = Some(Opcode { instruction: Instruction::DEC, mode: AddressingMode::AbsoluteX, cycles: 7 });
+    table[0xC6] = Opcode { instruction: Instruction::DEC, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0xCE] = Opcode { instruction: Instruction::DEC, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0xD6] = Opcode { instruction: Instruction::DEC, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0xDE] = Opcode { instruction: Instruction::DEC, mode: AddressingMode::AbsoluteX, cycles: 7 };
     
     // INY, DEX, INX
-    table[0xC8] = Some(Opcode { instruction: Instruction::INY, mode: AddressingMode::Implicit, cycles: 2 });
-    table[0xCA] = Some(Opcode { instruction: Instruction::DEX, mode: AddressingMode::Implicit, cycles: 2 });
-    table[0xE8] = Some(Opcode { instruction: Instruction::INX, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0xC8] = Opcode { instruction: Instruction::INY, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0xCA] = Opcode { instruction: Instruction::DEX, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0xE8] = Opcode { instruction: Instruction::INX, mode: AddressingMode::Implicit, cycles: 2 };
     
     // SBC
-    table[0xE1] = Some(Opcode { instruction: Instruction::SBC, mode: AddressingMode::IndirectX, cycles: 6 });
-    table[0xE5] = Some(Opcode { instruction: Instruction::SBC, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0xE9] = Some(Opcode { instruction: Instruction::SBC, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0xED] = Some(Opcode { instruction: Instruction::SBC, mode: AddressingMode::Absolute, cycles: 4 });
-    table[0xF1] = Some(Opcode { instruction: Instruction::SBC, mode: AddressingMode::IndirectY, cycles: 5 });
-    table[0xF5] = Some(Opcode { instruction: Instruction::SBC, mode: AddressingMode::ZeroPageX, cycles: 4 });
-    table[0xF9] = Some(Opcode { instruction: Instruction::SBC, mode: AddressingMode::AbsoluteY, cycles: 4 });
-    table[0xFD] = Some(Opcode { instruction: Instruction::SBC, mode: AddressingMode::AbsoluteX, cycles: 4 });
+    table[0xE1] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0xE5] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0xE9] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xED] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0xF1] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::IndirectY, cycles: 5 };
+    table[0xF5] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0xF9] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    table[0xFD] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::AbsoluteX, cycles: 4 };
     
     // CPX
-    table[0xE0] = Some(Opcode { instruction: Instruction::CPX, mode: AddressingMode::Immediate, cycles: 2 });
-    table[0xE4] = Some(Opcode { instruction: Instruction::CPX, mode: AddressingMode::ZeroPage, cycles: 3 });
-    table[0xEC] = Some(Opcode { instruction: Instruction::CPX, mode: AddressingMode::Absolute, cycles: 4 });
+    table[0xE0] = Opcode { instruction: Instruction::CPX, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xE4] = Opcode { instruction: Instruction::CPX, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0xEC] = Opcode { instruction: Instruction::CPX, mode: AddressingMode::Absolute, cycles: 4 };
     
     // INC
-    table[0xE6] = Some(Opcode { instruction: Instruction::INC, mode: AddressingMode::ZeroPage, cycles: 5 });
-    table[0xEE] = Some(Opcode { instruction: Instruction::INC, mode: AddressingMode::Absolute, cycles: 6 });
-    table[0xF6] = Some(Opcode { instruction: Instruction::INC, mode: AddressingMode::ZeroPageX, cycles: 6 });
-    table[0xFE] = Some(Opcode { instruction: Instruction::INC, mode: AddressingMode::AbsoluteX, cycles: 7 });
+    table[0xE6] = Opcode { instruction: Instruction::INC, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0xEE] = Opcode { instruction: Instruction::INC, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0xF6] = Opcode { instruction: Instruction::INC, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0xFE] = Opcode { instruction: Instruction::INC, mode: AddressingMode::AbsoluteX, cycles: 7 };
     
     // NOP, SED
-    table[0xEA] = Some(Opcode { instruction: Instruction::NOP, mode: AddressingMode::Implicit, cycles: 2 });
-    table[0xF8] = Some(Opcode { instruction: Instruction::SED, mode: AddressingMode::Implicit, cycles: 2 });
+    table[0xEA] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0xF8] = Opcode { instruction: Instruction::SED, mode: AddressingMode::Implicit, cycles: 2 };
+    
+    // NOP (unofficial, reading and discarding their operand)
+    table[0x1A] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0x3A] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0x5A] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0x7A] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0xDA] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0xFA] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Implicit, cycles: 2 };
+    table[0x80] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0x82] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0x89] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xC2] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0xE2] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0x04] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x44] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x64] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x14] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x34] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x54] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x74] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0xD4] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0xF4] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::ZeroPageX, cycles: 4 };
+    table[0x0C] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x1C] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::AbsoluteX, cycles: 4 };
+    table[0x3C] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::AbsoluteX, cycles: 4 };
+    table[0x5C] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::AbsoluteX, cycles: 4 };
+    table[0x7C] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::AbsoluteX, cycles: 4 };
+    table[0xDC] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::AbsoluteX, cycles: 4 };
+    table[0xFC] = Opcode { instruction: Instruction::NOP, mode: AddressingMode::AbsoluteX, cycles: 4 };
+    
+    // SLO
+    table[0x03] = Opcode { instruction: Instruction::SLO, mode: AddressingMode::IndirectX, cycles: 8 };
+    table[0x07] = Opcode { instruction: Instruction::SLO, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0x0F] = Opcode { instruction: Instruction::SLO, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0x13] = Opcode { instruction: Instruction::SLO, mode: AddressingMode::IndirectY, cycles: 8 };
+    table[0x17] = Opcode { instruction: Instruction::SLO, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0x1B] = Opcode { instruction: Instruction::SLO, mode: AddressingMode::AbsoluteY, cycles: 7 };
+    table[0x1F] = Opcode { instruction: Instruction::SLO, mode: AddressingMode::AbsoluteX, cycles: 7 };
+    
+    // RLA
+    table[0x23] = Opcode { instruction: Instruction::RLA, mode: AddressingMode::IndirectX, cycles: 8 };
+    table[0x27] = Opcode { instruction: Instruction::RLA, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0x2F] = Opcode { instruction: Instruction::RLA, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0x33] = Opcode { instruction: Instruction::RLA, mode: AddressingMode::IndirectY, cycles: 8 };
+    table[0x37] = Opcode { instruction: Instruction::RLA, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0x3B] = Opcode { instruction: Instruction::RLA, mode: AddressingMode::AbsoluteY, cycles: 7 };
+    table[0x3F] = Opcode { instruction: Instruction::RLA, mode: AddressingMode::AbsoluteX, cycles: 7 };
+    
+    // SRE
+    table[0x43] = Opcode { instruction: Instruction::SRE, mode: AddressingMode::IndirectX, cycles: 8 };
+    table[0x47] = Opcode { instruction: Instruction::SRE, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0x4F] = Opcode { instruction: Instruction::SRE, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0x53] = Opcode { instruction: Instruction::SRE, mode: AddressingMode::IndirectY, cycles: 8 };
+    table[0x57] = Opcode { instruction: Instruction::SRE, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0x5B] = Opcode { instruction: Instruction::SRE, mode: AddressingMode::AbsoluteY, cycles: 7 };
+    table[0x5F] = Opcode { instruction: Instruction::SRE, mode: AddressingMode::AbsoluteX, cycles: 7 };
+    
+    // RRA
+    table[0x63] = Opcode { instruction: Instruction::RRA, mode: AddressingMode::IndirectX, cycles: 8 };
+    table[0x67] = Opcode { instruction: Instruction::RRA, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0x6F] = Opcode { instruction: Instruction::RRA, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0x73] = Opcode { instruction: Instruction::RRA, mode: AddressingMode::IndirectY, cycles: 8 };
+    table[0x77] = Opcode { instruction: Instruction::RRA, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0x7B] = Opcode { instruction: Instruction::RRA, mode: AddressingMode::AbsoluteY, cycles: 7 };
+    table[0x7F] = Opcode { instruction: Instruction::RRA, mode: AddressingMode::AbsoluteX, cycles: 7 };
+    
+    // DCP
+    table[0xC3] = Opcode { instruction: Instruction::DCP, mode: AddressingMode::IndirectX, cycles: 8 };
+    table[0xC7] = Opcode { instruction: Instruction::DCP, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0xCF] = Opcode { instruction: Instruction::DCP, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0xD3] = Opcode { instruction: Instruction::DCP, mode: AddressingMode::IndirectY, cycles: 8 };
+    table[0xD7] = Opcode { instruction: Instruction::DCP, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0xDB] = Opcode { instruction: Instruction::DCP, mode: AddressingMode::AbsoluteY, cycles: 7 };
+    table[0xDF] = Opcode { instruction: Instruction::DCP, mode: AddressingMode::AbsoluteX, cycles: 7 };
+    
+    // ISC
+    table[0xE3] = Opcode { instruction: Instruction::ISC, mode: AddressingMode::IndirectX, cycles: 8 };
+    table[0xE7] = Opcode { instruction: Instruction::ISC, mode: AddressingMode::ZeroPage, cycles: 5 };
+    table[0xEF] = Opcode { instruction: Instruction::ISC, mode: AddressingMode::Absolute, cycles: 6 };
+    table[0xF3] = Opcode { instruction: Instruction::ISC, mode: AddressingMode::IndirectY, cycles: 8 };
+    table[0xF7] = Opcode { instruction: Instruction::ISC, mode: AddressingMode::ZeroPageX, cycles: 6 };
+    table[0xFB] = Opcode { instruction: Instruction::ISC, mode: AddressingMode::AbsoluteY, cycles: 7 };
+    table[0xFF] = Opcode { instruction: Instruction::ISC, mode: AddressingMode::AbsoluteX, cycles: 7 };
+    
+    // LAX
+    table[0xA3] = Opcode { instruction: Instruction::LAX, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0xA7] = Opcode { instruction: Instruction::LAX, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0xAF] = Opcode { instruction: Instruction::LAX, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0xB3] = Opcode { instruction: Instruction::LAX, mode: AddressingMode::IndirectY, cycles: 5 };
+    table[0xB7] = Opcode { instruction: Instruction::LAX, mode: AddressingMode::ZeroPageY, cycles: 4 };
+    table[0xBF] = Opcode { instruction: Instruction::LAX, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    
+    // SAX
+    table[0x83] = Opcode { instruction: Instruction::SAX, mode: AddressingMode::IndirectX, cycles: 6 };
+    table[0x87] = Opcode { instruction: Instruction::SAX, mode: AddressingMode::ZeroPage, cycles: 3 };
+    table[0x8F] = Opcode { instruction: Instruction::SAX, mode: AddressingMode::Absolute, cycles: 4 };
+    table[0x97] = Opcode { instruction: Instruction::SAX, mode: AddressingMode::ZeroPageY, cycles: 4 };
+    
+    // LAS
+    table[0xBB] = Opcode { instruction: Instruction::LAS, mode: AddressingMode::AbsoluteY, cycles: 4 };
+    
+    // ANC
+    table[0x0B] = Opcode { instruction: Instruction::ANC, mode: AddressingMode::Immediate, cycles: 2 };
+    table[0x2B] = Opcode { instruction: Instruction::ANC, mode: AddressingMode::Immediate, cycles: 2 };
+    
+    // ALR
+    table[0x4B] = Opcode { instruction: Instruction::ALR, mode: AddressingMode::Immediate, cycles: 2 };
+    
+    // ARR
+    table[0x6B] = Opcode { instruction: Instruction::ARR, mode: AddressingMode::Immediate, cycles: 2 };
+    
+    // AXS
+    table[0xCB] = Opcode { instruction: Instruction::AXS, mode: AddressingMode::Immediate, cycles: 2 };
+    
+    // XAA
+    table[0x8B] = Opcode { instruction: Instruction::XAA, mode: AddressingMode::Immediate, cycles: 2 };
+    
+    // LXA
+    table[0xAB] = Opcode { instruction: Instruction::LXA, mode: AddressingMode::Immediate, cycles: 2 };
+    
+    // SBC (unofficial duplicate of $E9)
+    table[0xEB] = Opcode { instruction: Instruction::SBC, mode: AddressingMode::Immediate, cycles: 2 };
+    
+    // AHX
+    table[0x93] = Opcode { instruction: Instruction::AHX, mode: AddressingMode::IndirectY, cycles: 6 };
+    table[0x9F] = Opcode { instruction: Instruction::AHX, mode: AddressingMode::AbsoluteY, cycles: 5 };
+    
+    // SHX
+    table[0x9E] = Opcode { instruction: Instruction::SHX, mode: AddressingMode::AbsoluteY, cycles: 5 };
+    
+    // SHY
+    table[0x9C] = Opcode { instruction: Instruction::SHY, mode: AddressingMode::AbsoluteX, cycles: 5 };
+    
+    // TAS
+    table[0x9B] = Opcode { instruction: Instruction::TAS, mode: AddressingMode::AbsoluteY, cycles: 5 };
+    
+    // KIL: $02, $12, $22, $32, $42, $52, $62, $72, $92, $B2, $D2 and $F2
+    // keep the default entry
     
     table
 };
\ No newline at end of file
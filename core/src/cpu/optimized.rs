@@ -91,4 +91,3 @@ pub mod flags {
         cpu.status.set(StatusFlags::OVERFLOW, value);
     }
 }
-
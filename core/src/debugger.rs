@@ -1,5 +1,7 @@
 use crate::{Cpu, Bus, Nes};
 use crate::cpu::CpuBus;
+use crate::cpu::addressing::AddressingMode;
+use crate::cpu::opcodes::OPCODE_TABLE;
 use std::collections::{HashSet, HashMap};
 use std::fmt;
 
@@ -221,156 +223,120 @@ impl<'a> fmt::Display for DebugInfo<'a> {
     }
 }
 
-// Disassembler
-pub fn disassemble(bus: &mut Bus, address: u16, count: usize) -> Vec<String> {
-    let mut result = Vec::new();
-    let mut addr = address;
+/// Table-driven disassembler. With a label map, operand addresses that have
+/// a symbol are printed by name (`JSR ResetHandler`), the rest stay hex.
+#[derive(Debug, Clone, Default)]
+pub struct Disassembler {
+    labels: HashMap<u16, String>,
+}
+
+impl Disassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_labels(labels: HashMap<u16, String>) -> Self {
+        Self { labels }
+    }
+    
+    pub fn labels(&self) -> &HashMap<u16, String> {
+        &self.labels
+    }
     
-    for _ in 0..count {
-        let opcode = bus.read(addr);
-        let (instruction, length) = decode_instruction(bus, addr);
+    /// `count` instructions from `address`, one line each:
+    /// address, raw bytes, then the instruction
+    pub fn disassemble(&self, bus: &Bus, address: u16, count: usize) -> Vec<String> {
+        let mut result = Vec::new();
+        let mut addr = address;
         
-        let mut bytes = format!("{:02X}", opcode);
-        for i in 1..length {
-            bytes.push_str(&format!(" {:02X}", bus.read(addr + i)));
+        for _ in 0..count {
+            let (instruction, length) = self.decode_instruction(bus, addr);
+            
+            let mut bytes = format!("{:02X}", bus.peek(addr));
+            for i in 1..length {
+                bytes.push_str(&format!(" {:02X}", bus.peek(addr.wrapping_add(i))));
+            }
+            
+            result.push(format!("{:04X}: {:9} {}", addr, bytes, instruction));
+            addr = addr.wrapping_add(length);
         }
         
-        result.push(format!("{:04X}: {:9} {}", addr, bytes, instruction));
-        addr += length;
+        result
     }
     
-    result
-}
-
-fn decode_instruction(bus: &mut Bus, addr: u16) -> (String, u16) {
-    let opcode = bus.read(addr);
-    
-    // This is a simplified disassembler - in a real implementation,
-    // you would decode all instructions properly
-    match opcode {
-        0x00 => ("BRK".to_string(), 1),
-        0x01 => {
-            let operand = bus.read(addr + 1);
-            (format!("ORA (${:02X},X)", operand), 2)
-        }
-        0x05 => {
-            let operand = bus.read(addr + 1);
-            (format!("ORA ${:02X}", operand), 2)
-        }
-        0x06 => {
-            let operand = bus.read(addr + 1);
-            (format!("ASL ${:02X}", operand), 2)
-        }
-        0x08 => ("PHP".to_string(), 1),
-        0x09 => {
-            let operand = bus.read(addr + 1);
-            (format!("ORA #${:02X}", operand), 2)
-        }
-        0x0A => ("ASL A".to_string(), 1),
-        0x0D => {
-            let lo = bus.read(addr + 1);
-            let hi = bus.read(addr + 2);
-            let operand = (hi as u16) << 8 | lo as u16;
-            (format!("ORA ${:04X}", operand), 3)
-        }
-        0x10 => {
-            let offset = bus.read(addr + 1) as i8;
-            let target = (addr as i32 + 2 + offset as i32) as u16;
-            (format!("BPL ${:04X}", target), 2)
-        }
-        0x18 => ("CLC".to_string(), 1),
-        0x20 => {
-            let lo = bus.read(addr + 1);
-            let hi = bus.read(addr + 2);
-            let operand = (hi as u16) << 8 | lo as u16;
-            (format!("JSR ${:04X}", operand), 3)
-        }
-        0x28 => ("PLP".to_string(), 1),
-        0x29 => {
-            let operand = bus.read(addr + 1);
-            (format!("AND #${:02X}", operand), 2)
-        }
-        0x2C => {
-            let lo = bus.read(addr + 1);
-            let hi = bus.read(addr + 2);
-            let operand = (hi as u16) << 8 | lo as u16;
-            (format!("BIT ${:04X}", operand), 3)
-        }
-        0x30 => {
-            let offset = bus.read(addr + 1) as i8;
-            let target = (addr as i32 + 2 + offset as i32) as u16;
-            (format!("BMI ${:04X}", target), 2)
-        }
-        0x38 => ("SEC".to_string(), 1),
-        0x40 => ("RTI".to_string(), 1),
-        0x48 => ("PHA".to_string(), 1),
-        0x4C => {
-            let lo = bus.read(addr + 1);
-            let hi = bus.read(addr + 2);
-            let operand = (hi as u16) << 8 | lo as u16;
-            (format!("JMP ${:04X}", operand), 3)
-        }
-        0x60 => ("RTS".to_string(), 1),
-        0x68 => ("PLA".to_string(), 1),
-        0x6C => {
-            let lo = bus.read(addr + 1);
-            let hi = bus.read(addr + 2);
-            let operand = (hi as u16) << 8 | lo as u16;
-            (format!("JMP (${:04X})", operand), 3)
-        }
-        0x78 => ("SEI".to_string(), 1),
-        0x88 => ("DEY".to_string(), 1),
-        0x8D => {
-            let lo = bus.read(addr + 1);
-            let hi = bus.read(addr + 2);
-            let operand = (hi as u16) << 8 | lo as u16;
-            (format!("STA ${:04X}", operand), 3)
-        }
-        0x9A => ("TXS".to_string(), 1),
-        0xA0 => {
-            let operand = bus.read(addr + 1);
-            (format!("LDY #${:02X}", operand), 2)
-        }
-        0xA2 => {
-            let operand = bus.read(addr + 1);
-            (format!("LDX #${:02X}", operand), 2)
-        }
-        0xA9 => {
-            let operand = bus.read(addr + 1);
-            (format!("LDA #${:02X}", operand), 2)
-        }
-        0xAD => {
-            let lo = bus.read(addr + 1);
-            let hi = bus.read(addr + 2);
-            let operand = (hi as u16) << 8 | lo as u16;
-            (format!("LDA ${:04X}", operand), 3)
-        }
-        0xBD => {
-            let lo = bus.read(addr + 1);
-            let hi = bus.read(addr + 2);
-            let operand = (hi as u16) << 8 | lo as u16;
-            (format!("LDA ${:04X},X", operand), 3)
+    /// Peeks only at the bytes the instruction is made of
+    fn decode_instruction(&self, bus: &Bus, addr: u16) -> (String, u16) {
+        let opcode_byte = bus.peek(addr);
+        let opcode = OPCODE_TABLE[opcode_byte as usize];
+        let length = Cpu::instruction_length(opcode_byte) as u16;
+        let byte = if length > 1 { bus.peek(addr.wrapping_add(1)) } else { 0 };
+        let word = || (bus.peek(addr.wrapping_add(2)) as u16) << 8 | byte as u16;
+        
+        let operand = match opcode.mode {
+            AddressingMode::Implicit => String::new(),
+            AddressingMode::Accumulator => "A".to_string(),
+            AddressingMode::Immediate => format!("#${:02X}", byte),
+            AddressingMode::ZeroPage => self.zero_page(byte),
+            AddressingMode::ZeroPageX => format!("{},X", self.zero_page(byte)),
+            AddressingMode::ZeroPageY => format!("{},Y", self.zero_page(byte)),
+            AddressingMode::Absolute => self.absolute(word()),
+            AddressingMode::AbsoluteX => format!("{},X", self.absolute(word())),
+            AddressingMode::AbsoluteY => format!("{},Y", self.absolute(word())),
+            AddressingMode::Indirect => format!("({})", self.absolute(word())),
+            AddressingMode::IndirectX => format!("({},X)", self.zero_page(byte)),
+            AddressingMode::IndirectY => format!("({}),Y", self.zero_page(byte)),
+            AddressingMode::Relative => {
+                let target = addr.wrapping_add(2).wrapping_add(byte as i8 as u16);
+                self.absolute(target)
+            }
+        };
+        
+        let mnemonic = format!("{:?}", opcode.instruction);
+        if operand.is_empty() {
+            (mnemonic, length)
+        } else {
+            (format!("{} {}", mnemonic, operand), length)
         }
-        0xC8 => ("INY".to_string(), 1),
-        0xC9 => {
-            let operand = bus.read(addr + 1);
-            (format!("CMP #${:02X}", operand), 2)
+    }
+    
+    fn zero_page(&self, addr: u8) -> String {
+        match self.labels.get(&(addr as u16)) {
+            Some(label) => label.clone(),
+            None => format!("${:02X}", addr),
         }
-        0xCA => ("DEX".to_string(), 1),
-        0xD0 => {
-            let offset = bus.read(addr + 1) as i8;
-            let target = (addr as i32 + 2 + offset as i32) as u16;
-            (format!("BNE ${:04X}", target), 2)
+    }
+    
+    fn absolute(&self, addr: u16) -> String {
+        match self.labels.get(&addr) {
+            Some(label) => label.clone(),
+            None => format!("${:04X}", addr),
         }
-        0xE8 => ("INX".to_string(), 1),
-        0xEA => ("NOP".to_string(), 1),
-        0xF0 => {
-            let offset = bus.read(addr + 1) as i8;
-            let target = (addr as i32 + 2 + offset as i32) as u16;
-            (format!("BEQ ${:04X}", target), 2)
+    }
+}
+
+/// Disassemble without labels
+pub fn disassemble(bus: &Bus, address: u16, count: usize) -> Vec<String> {
+    Disassembler::new().disassemble(bus, address, count)
+}
+
+/// Parse an FCEUX `.nl` label file: one `$ADDR#Name#Comment` per line,
+/// where the address may carry a `/size` suffix for arrays. Lines that
+/// don't fit the format (blank, comments, unnamed entries) are skipped.
+pub fn parse_nl_labels(text: &str) -> HashMap<u16, String> {
+    let mut labels = HashMap::new();
+    for line in text.lines() {
+        let mut fields = line.trim().splitn(3, '#');
+        let (Some(addr), Some(name)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let addr = addr.strip_prefix('$').unwrap_or(addr);
+        let addr = addr.split('/').next().unwrap_or(addr);
+        let name = name.trim();
+        if let (Ok(addr), false) = (u16::from_str_radix(addr, 16), name.is_empty()) {
+            labels.insert(addr, name.to_string());
         }
-        _ => (format!(".db ${:02X}", opcode), 1),
     }
+    labels
 }
 
 // Memory dump
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::{Apu, BatterySave, Cartridge, Controller, ControllerButton, Nes, NesError, Region};
+use crate::savestate::SaveStateManager;
+
+#[derive(Debug, Clone)]
+pub struct EmulatorConfig {
+    /// Forces a region instead of detecting it per game (`Region::detect`).
+    /// Only NTSC timing is emulated so far; frontends can use it for pacing.
+    pub region: Option<Region>,
+    pub sample_rate: u32,
+    pub audio_latency_ms: f32,
+    /// Replacement for the built-in RGB palette
+    pub palette: Option<[u32; 64]>,
+    /// Frontend key name -> (controller port, button)
+    pub key_map: HashMap<String, (usize, ControllerButton)>,
+    pub save_dir: PathBuf,
+    pub save_slots: usize,
+}
+
+impl Default for EmulatorConfig {
+    fn default() -> Self {
+        let key_map = [
+            ("KeyZ", ControllerButton::A),
+            ("KeyX", ControllerButton::B),
+            ("ShiftRight", ControllerButton::SELECT),
+            ("Enter", ControllerButton::START),
+            ("ArrowUp", ControllerButton::UP),
+            ("ArrowDown", ControllerButton::DOWN),
+            ("ArrowLeft", ControllerButton::LEFT),
+            ("ArrowRight", ControllerButton::RIGHT),
+        ]
+        .iter()
+        .map(|&(key, button)| (key.to_string(), (0, button)))
+        .collect();
+        
+        Self {
+            region: None,
+            sample_rate: 44100,
+            audio_latency_ms: 20.0,
+            palette: None,
+            key_map,
+            save_dir: PathBuf::from("saves"),
+            save_slots: 10,
+        }
+    }
+}
+
+/// Batteries-included wrapper around `Nes` with controllers, save slots,
+/// battery saves and audio/video settings. The underlying `Nes` stays reachable.
+/// Battery-backed RAM is flushed a few seconds after the game last wrote it
+/// and when the emulator is dropped.
+pub struct Emulator {
+    nes: Nes,
+    config: EmulatorConfig,
+    controllers: [Controller; 2],
+    save_states: Option<SaveStateManager>,
+    battery: Option<BatterySave>,
+}
+
+impl Emulator {
+    pub fn new(config: EmulatorConfig) -> Self {
+        let mut emulator = Self {
+            nes: Nes::new(),
+            config,
+            controllers: [Controller::new(), Controller::new()],
+            save_states: None,
+            battery: None,
+        };
+        emulator.apply_config();
+        emulator
+    }
+    
+    fn apply_config(&mut self) {
+        self.nes.bus.apu = Apu::with_sample_rate(self.config.sample_rate);
+        self.nes.bus.apu.set_output_latency(self.config.audio_latency_ms);
+        self.nes.bus.ppu.set_color_table(self.config.palette);
+    }
+    
+    /// Load a ROM file in any supported format; save slots are named after the file
+    pub fn load_rom<P: AsRef<Path>>(&mut self, path: P) -> Result<(), NesError> {
+        let path = path.as_ref();
+        let cartridge = Cartridge::from_bytes(&std::fs::read(path)?)?;
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("rom");
+        self.insert_cartridge(cartridge, name);
+        Ok(())
+    }
+    
+    /// Load a ROM image from memory (e.g. a browser file upload)
+    pub fn load_rom_bytes(&mut self, data: &[u8], name: &str) -> Result<(), NesError> {
+        let cartridge = Cartridge::from_bytes(data)?;
+        self.insert_cartridge(cartridge, name);
+        Ok(())
+    }
+    
+    fn insert_cartridge(&mut self, mut cartridge: Cartridge, name: &str) {
+        if let Err(e) = self.flush_battery() {
+            log::warn!("Failed to write battery save: {}", e);
+        }
+        
+        let battery = BatterySave::new(
+            self.config.save_dir.join(format!("{}.sav", name)),
+            BatterySave::DEFAULT_FLUSH_DELAY,
+        );
+        if let Err(e) = battery.load(&mut cartridge) {
+            log::warn!("Failed to read {}: {}", battery.path().display(), e);
+        }
+        self.battery = Some(battery);
+        
+        let region = Region::detect(self.config.region, &cartridge, name);
+        self.nes.load_cartridge(cartridge);
+        self.nes.set_region(region);
+        self.save_states = Some(SaveStateManager::new(
+            self.config.save_dir.join(name),
+            self.config.save_slots,
+        ));
+    }
+    
+    pub fn reset(&mut self) {
+        self.nes.reset();
+    }
+    
+    pub fn power_cycle(&mut self) {
+        self.nes.power_cycle();
+    }
+    
+    pub fn run_frame(&mut self) -> Result<(), NesError> {
+        self.nes.set_controller1_from_controller(&self.controllers[0]);
+        self.nes.set_controller2_from_controller(&self.controllers[1]);
+        self.nes.run_frame()?;
+        
+        if let (Some(battery), Some(cartridge)) = (&mut self.battery, &mut self.nes.bus.cartridge) {
+            if let Err(e) = battery.end_frame(cartridge) {
+                log::warn!("Failed to write {}: {}", battery.path().display(), e);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Write battery-backed RAM now if the game changed it
+    pub fn flush_battery(&mut self) -> Result<(), NesError> {
+        if let (Some(battery), Some(cartridge)) = (&mut self.battery, &mut self.nes.bus.cartridge) {
+            battery.flush(cartridge)?;
+        }
+        Ok(())
+    }
+    
+    /// Where the loaded game's battery save lives
+    pub fn battery_path(&self) -> Option<&Path> {
+        self.battery.as_ref().map(|battery| battery.path())
+    }
+    
+    pub fn set_button(&mut self, port: usize, button: ControllerButton, pressed: bool) {
+        if let Some(controller) = self.controllers.get_mut(port) {
+            controller.set_button(button, pressed);
+        }
+    }
+    
+    /// Route a frontend key through the configured key map.
+    /// Returns false if the key isn't bound.
+    pub fn key_event(&mut self, key: &str, pressed: bool) -> bool {
+        match self.config.key_map.get(key) {
+            Some(&(port, button)) => {
+                self.set_button(port, button, pressed);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn controller(&self, port: usize) -> Option<&Controller> {
+        self.controllers.get(port)
+    }
+    
+    pub fn save_slot(&self, slot: usize) -> Result<(), NesError> {
+        let manager = self.save_states.as_ref().ok_or(NesError::NoCartridge)?;
+        Ok(manager.save_slot(&self.nes, slot)?)
+    }
+    
+    pub fn load_slot(&mut self, slot: usize) -> Result<(), NesError> {
+        let manager = self.save_states.as_ref().ok_or(NesError::NoCartridge)?;
+        Ok(manager.load_slot(&mut self.nes, slot)?)
+    }
+    
+    pub fn slot_exists(&self, slot: usize) -> bool {
+        self.save_states.as_ref().is_some_and(|manager| manager.slot_exists(slot))
+    }
+    
+    /// Current frame as packed RGB24, 256x240
+    pub fn screenshot(&self) -> Vec<u8> {
+        let framebuffer = self.nes.get_framebuffer();
+        let mut rgb = Vec::with_capacity(framebuffer.len() * 3);
+        for &color in framebuffer {
+            rgb.push((color >> 16) as u8);
+            rgb.push((color >> 8) as u8);
+            rgb.push(color as u8);
+        }
+        rgb
+    }
+    
+    /// Samples generated since the last call
+    pub fn audio_samples(&mut self) -> Vec<f32> {
+        self.nes.bus.apu.get_samples()
+    }
+    
+    pub fn framebuffer(&self) -> &[u32] {
+        self.nes.get_framebuffer()
+    }
+    
+    /// Region of the loaded game, NTSC until one is loaded
+    pub fn region(&self) -> Region {
+        self.nes.region()
+    }
+    
+    pub fn config(&self) -> &EmulatorConfig {
+        &self.config
+    }
+    
+    pub fn nes(&self) -> &Nes {
+        &self.nes
+    }
+    
+    pub fn nes_mut(&mut self) -> &mut Nes {
+        &mut self.nes
+    }
+}
+
+impl Drop for Emulator {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush_battery() {
+            log::warn!("Failed to write battery save: {}", e);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use alloc::boxed::Box;
+
+/// Expansion port hardware (Vs. System DIP switches, Famicom expansion
+/// audio, etc.) that sits next to the standard controllers and cartridge
+pub trait ExpansionDevice: core::fmt::Debug {
+    /// Read from $4016-$FFFF. For $4016/$4017 the returned bits are ORed
+    /// with the controller data; elsewhere `Some` replaces the normal read.
+    fn read(&mut self, _addr: u16) -> Option<u8> {
+        None
+    }
+    
+    /// Write to $4016-$FFFF. $4016/$4017 writes always reach the controllers
+    /// and APU as well; elsewhere returning true keeps the write from the cartridge.
+    fn write(&mut self, _addr: u16, _value: u8) -> bool {
+        false
+    }
+    
+    /// Boxed copy of the device, used when cloning a running `Nes`.
+    fn clone_box(&self) -> Box<dyn ExpansionDevice>;
+}
+
+impl Clone for Box<dyn ExpansionDevice> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
\ No newline at end of file
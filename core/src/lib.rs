@@ -1,3 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod cpu;
 pub mod ppu;
 pub mod apu;
@@ -5,8 +9,17 @@ pub mod cartridge;
 pub mod controller;
 pub mod bus;
 pub mod nes;
+#[cfg(feature = "std")]
 pub mod savestate;
+#[cfg(feature = "std")]
 pub mod debugger;
+pub mod expansion;
+pub mod coverage;
+pub mod sink;
+#[cfg(feature = "std")]
+pub mod emulator;
+#[cfg(feature = "std")]
+pub mod battery;
 
 pub mod test_rom;
 
@@ -15,14 +28,73 @@ pub use ppu::Ppu;
 pub use apu::Apu;
 pub use cartridge::Cartridge;
 pub use controller::{Controller, ControllerButton};
-pub use bus::Bus;
-pub use nes::Nes;
+pub use bus::{AccessWarning, AccessWarningKind, Bus, StackWarning};
+pub use nes::{FrameStats, Nes, NesError, ScanlineStep};
+#[cfg(feature = "std")]
 pub use savestate::{SaveState, SaveStateError};
-pub use debugger::{Debugger, DebuggerState, Breakpoint, BreakpointType, DebugInfo};
+#[cfg(feature = "std")]
+pub use debugger::{Debugger, DebuggerState, Breakpoint, BreakpointType, DebugInfo, Disassembler};
+pub use expansion::ExpansionDevice;
+pub use coverage::CoverageMap;
+pub use sink::{AudioSink, VideoSink};
+#[cfg(feature = "std")]
+pub use emulator::{Emulator, EmulatorConfig};
+#[cfg(feature = "std")]
+pub use battery::BatterySave;
 
-#[derive(Debug, Clone, Copy)]
+/// Time since power on or reset. The PPU runs 3 dots and the APU one
+/// cycle per CPU cycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Clock {
     pub cpu_cycles: u64,
     pub ppu_cycles: u64,
     pub apu_cycles: u64,
+    /// Frames the PPU has finished
+    pub frames: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Ntsc,
+    Pal,
+}
+
+impl Region {
+    pub fn frame_rate(&self) -> f64 {
+        match self {
+            Region::Ntsc => 60.0988,
+            Region::Pal => 50.0070,
+        }
+    }
+    
+    /// CPU clock in Hz, which the APU channels divide down
+    pub fn cpu_clock(&self) -> f64 {
+        match self {
+            Region::Ntsc => 1_789_773.0,
+            Region::Pal => 1_662_607.0,
+        }
+    }
+    
+    /// Guess from a GoodNES/No-Intro style tag such as "(E)" or "(USA)"
+    pub fn from_file_name(name: &str) -> Option<Region> {
+        const PAL_TAGS: &[&str] = &["(E)", "(Europe)", "(PAL)", "(A)", "(Australia)"];
+        const NTSC_TAGS: &[&str] = &["(U)", "(USA)", "(J)", "(Japan)", "(JU)", "(NTSC)"];
+        
+        if PAL_TAGS.iter().any(|tag| name.contains(tag)) {
+            Some(Region::Pal)
+        } else if NTSC_TAGS.iter().any(|tag| name.contains(tag)) {
+            Some(Region::Ntsc)
+        } else {
+            None
+        }
+    }
+    
+    /// Region to run a game in. An explicit choice wins, then the TV system
+    /// in the header, then a region tag in the file name, then NTSC.
+    pub fn detect(explicit: Option<Region>, cartridge: &Cartridge, file_name: &str) -> Region {
+        explicit
+            .or(cartridge.region_hint())
+            .or_else(|| Region::from_file_name(file_name))
+            .unwrap_or(Region::Ntsc)
+    }
 }
\ No newline at end of file
@@ -1,9 +1,68 @@
-use crate::{Cpu, Ppu, Apu, Bus, Cartridge, Clock, Controller};
+use crate::{Cpu, Ppu, Apu, AudioSink, Bus, Cartridge, Clock, Controller, CoverageMap, ExpansionDevice, Region, VideoSink};
+use crate::bus::{AccessWarningHook, StackWarningHook};
+use crate::cartridge::CartridgeError;
+use crate::coverage::Access;
+use crate::cpu::StatusFlags;
+use alloc::boxed::Box;
+use thiserror::Error;
 
+/// NTSC frame length: 262 lines of 341 dots, one dot shorter on every
+/// other frame, at 3 dots per CPU cycle
+const CPU_CYCLES_PER_FRAME: f64 = 89341.5 / 3.0;
+
+/// Errors from the high-level `Nes` and `Emulator` APIs. The wrapped
+/// `CartridgeError` and `SaveStateError` stay available for matching.
+#[derive(Debug, Error)]
+pub enum NesError {
+    #[error("No cartridge loaded")]
+    NoCartridge,
+    #[error(transparent)]
+    Cartridge(#[from] CartridgeError),
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    SaveState(#[from] crate::SaveStateError),
+    #[cfg(feature = "std")]
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Work done by the last `run_frame`, for profiling and spotting odd frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Instructions executed, not counting DMA stall cycles
+    pub instructions: u32,
+    pub cpu_cycles: u64,
+    pub ppu_dots: u64,
+    pub audio_samples: u64,
+    /// Interrupts the CPU serviced, i.e. jumped to the handler for
+    pub nmi_count: u32,
+    pub irq_count: u32,
+    /// False when `set_frame_skip` dropped the frame and the framebuffer
+    /// still holds an earlier one
+    pub drawn: bool,
+}
+
+/// What the last `step_scanline` ran through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanlineStep {
+    /// The line that was finished, see `Ppu::scanline`
+    pub scanline: u16,
+    /// The CPU jumped to the handler during the line
+    pub nmi: bool,
+    pub irq: bool,
+}
+
+#[derive(Clone)]
 pub struct Nes {
     pub cpu: Cpu,
     pub bus: Bus,
     pub clock: Clock,
+    last_frame_stats: FrameStats,
+    idle_skip: bool,
+    region: Region,
+    frame_skip: u8,
+    // Frames dropped since the last drawn one
+    skipped_frames: u8,
 }
 
 impl Nes {
@@ -16,46 +75,425 @@ impl Nes {
         Self {
             cpu,
             bus,
-            clock: Clock {
-                cpu_cycles: 0,
-                ppu_cycles: 0,
-                apu_cycles: 0,
-            },
+            clock: Clock::default(),
+            last_frame_stats: FrameStats::default(),
+            idle_skip: false,
+            region: Region::Ntsc,
+            frame_skip: 0,
+            skipped_frames: 0,
         }
     }
     
+    /// Also picks the region from the header, falling back to NTSC
     pub fn load_cartridge(&mut self, cartridge: Cartridge) {
+        self.region = cartridge.region_hint().unwrap_or(Region::Ntsc);
         self.bus.load_cartridge(cartridge);
         self.reset();
     }
     
+    /// Load a ROM image of any supported format, see `Cartridge::from_bytes`
+    pub fn load_rom(&mut self, data: &[u8]) -> Result<(), NesError> {
+        let cartridge = Cartridge::from_bytes(data)?;
+        self.load_cartridge(cartridge);
+        Ok(())
+    }
+    
+    /// The console's reset button. RAM, VRAM, OAM and the mapper are left
+    /// alone; the CPU restarts from the reset vector and the PPU and APU
+    /// only clear what the reset line touches (see `Ppu::reset` and
+    /// `Apu::reset`).
     pub fn reset(&mut self) {
+        self.bus.reset();
         self.cpu.reset(&mut self.bus);
-        self.clock = Clock {
-            cpu_cycles: 0,
-            ppu_cycles: 0,
-            apu_cycles: 0,
-        };
+        self.clock = Clock::default();
+    }
+    
+    /// End the PPU's post-reset warm-up now rather than at the first
+    /// pre-render line, so code can program the PPU straight after loading
+    /// a cartridge. A testing and tooling convenience; on hardware the
+    /// writes made before then are lost.
+    pub fn skip_ppu_warmup(&mut self) {
+        self.bus.ppu.set_warming_up(false);
+    }
+    
+    /// Switch the console off and on again: RAM and the PPU/APU return to
+    /// their power-on state (see `Bus::power_on`), then the CPU starts from
+    /// the reset vector. RAM comes up zeroed rather than random so runs stay
+    /// deterministic.
+    pub fn power_cycle(&mut self) {
+        self.bus.power_on();
+        self.reset();
+    }
+    
+    /// Only NTSC timing is emulated; frontends use this for frame pacing
+    pub fn region(&self) -> Region {
+        self.region
+    }
+    
+    /// Override the detected region, see `Region::detect`
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+    
+    /// False until a cartridge is loaded; `step`/`run_frame` refuse to run then
+    pub fn is_ready(&self) -> bool {
+        self.bus.cartridge.is_some()
+    }
+    
+    /// Run one CPU instruction and the PPU/APU cycles it takes
+    pub fn step(&mut self) -> Result<(), NesError> {
+        if !self.is_ready() {
+            return Err(NesError::NoCartridge);
+        }
+        self.step_unchecked();
+        Ok(())
     }
     
-    pub fn step(&mut self) {
+    /// `step` without the cartridge check. With no cartridge the CPU
+    /// executes whatever open bus returns.
+    pub fn step_unchecked(&mut self) {
+        self.step_instruction();
+    }
+    
+    /// Returns true if a frame finished during the instruction
+    fn step_instruction(&mut self) -> bool {
         let cpu_cycles = self.cpu.step(&mut self.bus);
+        self.run_bus_cycles(cpu_cycles)
+    }
+    
+    fn run_bus_cycles(&mut self, cpu_cycles: u32) -> bool {
         self.clock.cpu_cycles += cpu_cycles as u64;
         
+        self.clock.ppu_cycles += cpu_cycles as u64 * 3;
+        self.clock.apu_cycles += cpu_cycles as u64;
+        
         // Bus tick handles PPU and APU timing
-        for _ in 0..cpu_cycles {
-            self.bus.tick(&mut self.cpu);
-            self.clock.ppu_cycles += 3;
-            self.clock.apu_cycles += 1;
+        let frame_complete = self.cpu.tick_bus(&mut self.bus, cpu_cycles);
+        if frame_complete {
+            self.clock.frames += 1;
+        }
+        frame_complete
+    }
+    
+    pub fn run_frame(&mut self) -> Result<(), NesError> {
+        if !self.is_ready() {
+            return Err(NesError::NoCartridge);
+        }
+        self.run_frame_unchecked();
+        Ok(())
+    }
+    
+    /// `run_frame`, then push the frame and the audio it produced into the
+    /// sinks instead of leaving them to be pulled. Drains `Apu::get_samples`.
+    pub fn run_frame_with(&mut self, video: &mut impl VideoSink, audio: &mut impl AudioSink) -> Result<(), NesError> {
+        self.run_frame()?;
+        if self.last_frame_stats.drawn {
+            video.present(self.get_framebuffer());
+        }
+        audio.submit(&self.bus.apu.get_samples());
+        Ok(())
+    }
+    
+    /// Run `frames` frames in one call, e.g. to catch up after the frontend
+    /// stalled. Samples keep accumulating in `Apu::get_samples` until
+    /// drained, so the audio matches running the frames one at a time.
+    /// Returns the number of samples produced; `last_frame_stats` covers
+    /// the final frame only.
+    pub fn run_frames(&mut self, frames: u32) -> Result<u64, NesError> {
+        if !self.is_ready() {
+            return Err(NesError::NoCartridge);
+        }
+        let start_samples = self.bus.apu.samples_generated();
+        for _ in 0..frames {
+            self.run_frame_unchecked();
+        }
+        Ok(self.bus.apu.samples_generated() - start_samples)
+    }
+    
+    /// Run `fraction` (0 to 1) of a frame's CPU cycles, rounded up to a
+    /// whole instruction, stopping early if the frame ends. Returns true if
+    /// it did. The framebuffer is partly redrawn after each call, so a
+    /// frontend can present it for smooth slow motion. Idle skip and
+    /// `last_frame_stats` aren't used.
+    pub fn run_partial_frame(&mut self, fraction: f32) -> Result<bool, NesError> {
+        if !self.is_ready() {
+            return Err(NesError::NoCartridge);
+        }
+        self.bus.ppu.set_frame_output(true);
+        let budget = (fraction.clamp(0.0, 1.0) as f64 * CPU_CYCLES_PER_FRAME) as u64;
+        let end = self.clock.cpu_cycles + budget;
+        while self.clock.cpu_cycles < end {
+            if self.step_instruction() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+    
+    /// Run instructions until the PPU moves on to the next scanline, for
+    /// stepping through raster effects in a debugger. The instruction that
+    /// crosses into the next line is finished, so the machine stops a few
+    /// dots into it. Idle skip and `last_frame_stats` aren't used.
+    pub fn step_scanline(&mut self) -> Result<ScanlineStep, NesError> {
+        if !self.is_ready() {
+            return Err(NesError::NoCartridge);
+        }
+        self.bus.ppu.set_frame_output(true);
+        let scanline = self.bus.ppu.scanline();
+        let start_interrupts = (self.cpu.nmi_count(), self.cpu.irq_count());
+        while self.bus.ppu.scanline() == scanline {
+            self.step_instruction();
+        }
+        Ok(ScanlineStep {
+            scanline,
+            nmi: self.cpu.nmi_count() != start_interrupts.0,
+            irq: self.cpu.irq_count() != start_interrupts.1,
+        })
+    }
+    
+    /// `run_frame` without the cartridge check
+    pub fn run_frame_unchecked(&mut self) {
+        let start = self.clock;
+        let start_samples = self.bus.apu.samples_generated();
+        let start_interrupts = (self.cpu.nmi_count(), self.cpu.irq_count());
+        let mut instructions = 0;
+        
+        let drawn = self.skipped_frames >= self.frame_skip;
+        self.skipped_frames = if drawn { 0 } else { self.skipped_frames + 1 };
+        self.bus.ppu.set_frame_output(drawn);
+        
+        // Run until the PPU wraps past the pre-render line. The
+        // instruction that straddles the boundary is finished first.
+        loop {
+            if self.idle_skip {
+                if let Some(frame_complete) = self.skip_idle_loop() {
+                    if frame_complete {
+                        break;
+                    }
+                    continue;
+                }
+            }
+            if self.cpu.stall_cycles == 0 {
+                instructions += 1;
+            }
+            if self.step_instruction() {
+                break;
+            }
+        }
+        
+        self.last_frame_stats = FrameStats {
+            instructions,
+            cpu_cycles: self.clock.cpu_cycles - start.cpu_cycles,
+            ppu_dots: self.clock.ppu_cycles - start.ppu_cycles,
+            audio_samples: self.bus.apu.samples_generated() - start_samples,
+            nmi_count: (self.cpu.nmi_count() - start_interrupts.0) as u32,
+            irq_count: (self.cpu.irq_count() - start_interrupts.1) as u32,
+            drawn,
+        };
+    }
+    
+    /// Let `run_frame` fast-forward through `JMP *` and branch-to-self
+    /// loops that games park in while waiting for NMI. The PPU and APU still
+    /// run every cycle and interrupts land on the same cycle, but the CPU
+    /// doesn't execute the loop, so instruction counts in `FrameStats` drop
+    /// and a debugger won't see those iterations. Off by default.
+    pub fn set_idle_skip(&mut self, enabled: bool) {
+        self.idle_skip = enabled;
+    }
+    
+    pub fn idle_skip(&self) -> bool {
+        self.idle_skip
+    }
+    
+    /// Draw only one frame in every `frames + 1`. The others are still
+    /// emulated in full, so game logic, timing and audio don't change, but
+    /// the PPU skips the pixel output (see `Ppu::set_frame_output`).
+    /// `FrameStats::drawn` tells which frames were drawn. 0, the default,
+    /// draws every frame.
+    pub fn set_frame_skip(&mut self, frames: u8) {
+        self.frame_skip = frames;
+        self.skipped_frames = frames;
+        self.bus.ppu.set_frame_output(true);
+    }
+    
+    pub fn frame_skip(&self) -> u8 {
+        self.frame_skip
+    }
+    
+    /// Turn sample generation off for headless runs, see `Apu::set_audio_enabled`
+    pub fn set_audio_enabled(&mut self, enabled: bool) {
+        self.bus.apu.set_audio_enabled(enabled);
+    }
+    
+    pub fn audio_enabled(&self) -> bool {
+        self.bus.apu.audio_enabled()
+    }
+    
+    /// Record which CPU addresses get executed, read or written from now
+    /// on, see `coverage`. Off by default; enabling it again keeps the map.
+    pub fn enable_coverage(&mut self) {
+        self.bus.set_coverage_enabled(true);
+    }
+    
+    pub fn disable_coverage(&mut self) {
+        self.bus.set_coverage_enabled(false);
+    }
+    
+    /// `None` unless `enable_coverage` was called
+    pub fn coverage(&self) -> Option<&CoverageMap> {
+        self.bus.coverage()
+    }
+    
+    /// Report reads of unmapped addresses, writes to ROM and accesses to
+    /// mapper registers that aren't implemented, see `AccessWarning`
+    pub fn set_access_warning_hook(&mut self, hook: AccessWarningHook) {
+        self.bus.set_access_warning_hook(Some(hook));
+    }
+    
+    pub fn clear_access_warning_hook(&mut self) {
+        self.bus.set_access_warning_hook(None);
+    }
+    
+    /// Report pushes past $0100 and pulls past $01FF. The stack still wraps
+    /// as on hardware, but a game doing it has almost always lost track of
+    /// its stack. Costs nothing while unset.
+    pub fn set_stack_warning_hook(&mut self, hook: StackWarningHook) {
+        self.bus.set_stack_warning_hook(Some(hook));
+    }
+    
+    pub fn clear_stack_warning_hook(&mut self) {
+        self.bus.set_stack_warning_hook(None);
+    }
+    
+    /// If the CPU sits in an idle loop, run whole iterations of it until an
+    /// interrupt is due or the frame ends. Returns whether the frame ended,
+    /// or `None` if the CPU isn't idle.
+    fn skip_idle_loop(&mut self) -> Option<bool> {
+        if self.cpu.stall_cycles > 0 || self.cpu.has_pending_interrupt() {
+            return None;
+        }
+        let cycles = self.idle_loop_cycles()?;
+        self.bus.record_access(self.cpu.pc, Access::EXECUTED);
+        
+        let mut frame_complete = false;
+        while !frame_complete && !self.cpu.has_pending_interrupt() {
+            self.cpu.cycles += cycles;
+            frame_complete = self.run_bus_cycles(cycles);
+        }
+        Some(frame_complete)
+    }
+    
+    /// Cycles per iteration if the instruction at PC jumps to itself.
+    /// Only ROM is checked, so the lookahead has no side effects.
+    fn idle_loop_cycles(&self) -> Option<u32> {
+        let pc = self.cpu.pc;
+        if pc < 0x8000 {
+            return None;
+        }
+        let cartridge = self.bus.cartridge.as_ref()?;
+        let byte = |offset: u16| cartridge.read_prg(pc.wrapping_add(offset));
+        
+        match byte(0) {
+            // JMP absolute
+            0x4C if u16::from_le_bytes([byte(1), byte(2)]) == pc => Some(3),
+            // Taken branch with offset -2; bits 6-7 pick the flag, bit 5
+            // the value it branches on
+            opcode @ (0x10 | 0x30 | 0x50 | 0x70 | 0x90 | 0xB0 | 0xD0 | 0xF0) if byte(1) == 0xFE => {
+                let flag = match opcode >> 6 {
+                    0 => StatusFlags::NEGATIVE,
+                    1 => StatusFlags::OVERFLOW,
+                    2 => StatusFlags::CARRY,
+                    _ => StatusFlags::ZERO,
+                };
+                if self.cpu.status.contains(flag) != (opcode & 0x20 != 0) {
+                    return None;
+                }
+                let page_crossed = pc.wrapping_add(2) & 0xFF00 != pc & 0xFF00;
+                Some(if page_crossed { 4 } else { 3 })
+            }
+            _ => None,
         }
     }
     
-    pub fn run_frame(&mut self) {
-        // Run until we've completed a frame (roughly 29780 CPU cycles)
-        let target_cycles = self.clock.cpu_cycles + 29780;
-        while self.clock.cpu_cycles < target_cycles {
-            self.step();
+    /// Hold each entry's controller 1/2 states (`ControllerButton` bits)
+    /// for its number of frames, then return the framebuffer. Meant for
+    /// headless screenshots and test fixtures.
+    ///
+    /// Emulation is deterministic: nothing depends on host time or
+    /// randomness, so the same script run from the same state (e.g. a
+    /// freshly loaded cartridge or a clone) always ends on the same
+    /// picture, RAM and audio.
+    pub fn run_script(&mut self, script: &[(u32, [u8; 2])]) -> Result<&[u32], NesError> {
+        if !self.is_ready() {
+            return Err(NesError::NoCartridge);
+        }
+        for &(frames, [controller1, controller2]) in script {
+            self.set_controller1(controller1);
+            self.set_controller2(controller2);
+            for _ in 0..frames {
+                self.run_frame_unchecked();
+            }
         }
+        Ok(self.get_framebuffer())
+    }
+    
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+    
+    // Component accessors. Prefer these over the public fields, which
+    // may become private.
+    
+    pub fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+    
+    pub fn cpu_mut(&mut self) -> &mut Cpu {
+        &mut self.cpu
+    }
+    
+    pub fn bus(&self) -> &Bus {
+        &self.bus
+    }
+    
+    /// Reads through the bus have side effects (PPU/controller registers),
+    /// so even inspecting memory needs this
+    pub fn bus_mut(&mut self) -> &mut Bus {
+        &mut self.bus
+    }
+    
+    pub fn ppu(&self) -> &Ppu {
+        &self.bus.ppu
+    }
+    
+    pub fn ppu_mut(&mut self) -> &mut Ppu {
+        &mut self.bus.ppu
+    }
+    
+    pub fn apu(&self) -> &Apu {
+        &self.bus.apu
+    }
+    
+    pub fn apu_mut(&mut self) -> &mut Apu {
+        &mut self.bus.apu
+    }
+    
+    pub fn cartridge(&self) -> Option<&Cartridge> {
+        self.bus.cartridge.as_ref()
+    }
+    
+    pub fn cartridge_mut(&mut self) -> Option<&mut Cartridge> {
+        self.bus.cartridge.as_mut()
+    }
+    
+    pub fn last_frame_stats(&self) -> FrameStats {
+        self.last_frame_stats
+    }
+    
+    /// False for frames drawn with rendering off, e.g. blank loading
+    /// screens a recorder can drop
+    pub fn was_rendering_this_frame(&self) -> bool {
+        self.bus.ppu.was_rendering_this_frame()
     }
     
     pub fn get_framebuffer(&self) -> &[u32] {
@@ -70,11 +508,37 @@ impl Nes {
         self.bus.set_controller2(state);
     }
     
+    /// Set both pads at once, e.g. from a full gamepad poll each frame.
+    /// One bit per button, as in `ControllerButton`: bit 0 A, 1 B, 2 Select,
+    /// 3 Start, 4 Up, 5 Down, 6 Left, 7 Right (1 = pressed).
+    pub fn set_controllers(&mut self, state1: u8, state2: u8) {
+        self.bus.set_controller1(state1);
+        self.bus.set_controller2(state2);
+    }
+    
+    /// Mark a controller port (0 or 1) as empty; reads then return the idle
+    /// value instead of button state, for games that probe for a second pad
+    pub fn set_controller_connected(&mut self, port: usize, connected: bool) {
+        self.bus.set_controller_connected(port, connected);
+    }
+    
     pub fn set_controller1_from_controller(&mut self, controller: &Controller) {
         self.bus.set_controller1(controller.get_state());
     }
     
     pub fn set_controller2_from_controller(&mut self, controller: &Controller) {
         self.bus.set_controller2(controller.get_state());
+        self.bus.set_microphone(controller.microphone());
+    }
+    
+    /// Famicom mode only: the microphone on the Famicom's hardwired second
+    /// controller, read live on bit 2 of $4016 (Pols Voice in Zelda, Raid on
+    /// Bungeling Bay). NES controllers have none, so leave it off for NES games.
+    pub fn set_microphone(&mut self, active: bool) {
+        self.bus.set_microphone(active);
+    }
+    
+    pub fn set_expansion_device(&mut self, device: Option<Box<dyn ExpansionDevice>>) {
+        self.bus.set_expansion_device(device);
     }
 }
\ No newline at end of file
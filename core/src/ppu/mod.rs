@@ -1,4 +1,8 @@
-use crate::cartridge::Cartridge;
+use bitflags::bitflags;
+use crate::cartridge::{Cartridge, ExtendedAttribute, Mirroring};
+use alloc::{boxed::Box, vec, vec::Vec};
+use serde::{Serialize, Deserialize};
+use serde_big_array::BigArray;
 
 mod palette;
 use palette::NES_PALETTE;
@@ -7,7 +11,44 @@ pub mod optimized;
 pub const SCREEN_WIDTH: usize = 256;
 pub const SCREEN_HEIGHT: usize = 240;
 
-#[derive(Debug, Clone)]
+// Every OAM entry can land on one line once the sprite limit is lifted
+const MAX_SPRITES: usize = 64;
+
+bitflags! {
+    /// Debug overrides applied on top of PPUMASK when composing output pixels.
+    /// The game-visible mask register, sprite 0 hit and overflow are unaffected.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct LayerMask: u8 {
+        const HIDE_BACKGROUND = 0b00000001;
+        const HIDE_SPRITES    = 0b00000010;
+        const NO_CLIPPING     = 0b00000100;  // Ignore the left 8 pixel clip bits
+    }
+}
+
+/// Selects between the fast and the hardware-accurate implementation where
+/// the PPU has both. Currently this only affects sprite evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AccuracyLevel {
+    /// `optimized::evaluate_sprites_fast`: overflow is set only when a ninth
+    /// sprite really is on the line
+    Fast,
+    /// `evaluate_sprites`: reproduces the diagonal OAM scan that gives the
+    /// real 2C02 its false positive/negative sprite overflow flag
+    #[default]
+    Accurate,
+}
+
+/// What happened during one `Ppu::step`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PpuStep {
+    /// Rising edge of the NMI line
+    pub nmi: bool,
+    /// The last dot of the pre-render line was processed; the framebuffer
+    /// holds a complete picture
+    pub frame_complete: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ppu {
     // PPU registers
     ctrl: u8,      // $2000 PPUCTRL
@@ -19,8 +60,12 @@ pub struct Ppu {
     addr: u16,
     
     // Internal memory
-    vram: [u8; 0x800],     // 2KB VRAM (name tables)
+    // 2KB console VRAM (name tables)
+    #[serde(with = "BigArray")]
+    vram: [u8; 0x800],
+    mirroring: Mirroring,
     pub palette: [u8; 32],     // Palette RAM
+    #[serde(with = "BigArray")]
     pub oam: [u8; 256],        // Object Attribute Memory
     secondary_oam: [u8; 32], // Secondary OAM for sprite evaluation
     
@@ -30,7 +75,6 @@ pub struct Ppu {
     frame: u64,
     
     // Temporary variables
-    write_toggle: bool,
     buffer: u8,
     
     // PPU open bus
@@ -39,6 +83,10 @@ pub struct Ppu {
     // Suppress VBlank flag
     suppress_vbl: bool,
     
+    // From reset to the next pre-render line, while $2000, $2001, $2005
+    // and $2006 ignore writes
+    warming_up: bool,
+    
     // Internal latches and shift registers
     v: u16,        // Current VRAM address
     t: u16,        // Temporary VRAM address
@@ -56,28 +104,123 @@ pub struct Ppu {
     bg_next_tile_attrib: u8,
     bg_next_tile_lsb: u8,
     bg_next_tile_msb: u8,
+    /// Mapper override for the tile being fetched
+    bg_next_tile_ext: Option<ExtendedAttribute>,
     
     // Sprite rendering
     sprite_count: u8,
-    sprite_patterns_lo: [u8; 8],
-    sprite_patterns_hi: [u8; 8],
-    sprite_positions: [u8; 8],
-    sprite_priorities: [u8; 8],
-    sprite_indexes: [u8; 8],
-    sprite_attributes: [u8; 8],
+    #[serde(with = "BigArray")]
+    sprite_patterns_lo: [u8; MAX_SPRITES],
+    #[serde(with = "BigArray")]
+    sprite_patterns_hi: [u8; MAX_SPRITES],
+    #[serde(with = "BigArray")]
+    sprite_positions: [u8; MAX_SPRITES],
+    #[serde(with = "BigArray")]
+    sprite_priorities: [u8; MAX_SPRITES],
+    #[serde(with = "BigArray")]
+    sprite_indexes: [u8; MAX_SPRITES],
+    #[serde(with = "BigArray")]
+    sprite_attributes: [u8; MAX_SPRITES],
     
     // Frame buffer
     pub framebuffer: Vec<u32>,
+    #[cfg(feature = "raw-output")]
+    #[serde(skip, default = "raw_output_buffer")]
+    raw_output: Vec<u16>,
     
     // NMI output
     nmi_output: bool,
     nmi_occurred: bool,
+    // NMI line level at the previous dot, for edge detection
+    nmi_line: bool,
+    // Set by a $2002 read racing the start of VBlank
+    nmi_cancelled: bool,
     
     // Odd frame flag
     odd_frame: bool,
     
+    // PPUMASK had background or sprites on at some visible dot this frame
+    rendering_this_frame: bool,
+    
     // Optimized rendering tables
+    #[serde(skip, default = "optimized::RenderingTables::new")]
     rendering_tables: optimized::RenderingTables,
+    
+    // Fast vs accurate implementation choice
+    accuracy: AccuracyLevel,
+    
+    // Debug layer override (frontend only, not visible to the game)
+    layer_override: Option<LayerMask>,
+    
+    // Apply the PPUMASK greyscale bit to $2007 palette reads
+    greyscale_palette_reads: bool,
+    
+    // User supplied RGB colors replacing NES_PALETTE; a frontend setting,
+    // so it isn't part of saved state
+    #[serde(skip)]
+    color_table: Option<[u32; 64]>,
+    
+    // Sprites drawn per scanline, None for no limit
+    sprite_limit: Option<u8>,
+    
+    // Cross-check the fast and accurate paths; a debugging aid, not state
+    #[serde(skip)]
+    verify_fast_path: bool,
+    
+    // Frontend post effect on each finished frame
+    #[serde(skip)]
+    output_filter: OutputFilterSlot,
+    
+    // Off while frame skip drops the frame; only sprite 0 hit is worked out
+    #[serde(skip, default = "frame_output_default")]
+    frame_output: bool,
+}
+
+fn frame_output_default() -> bool {
+    true
+}
+
+/// Cosmetic post-processing of a finished frame, see `Ppu::set_output_filter`
+pub type OutputFilter = Box<dyn FnMut(&mut [u32])>;
+
+/// Clones start without the filter, like the bus's access warning hook
+#[derive(Default)]
+struct OutputFilterSlot(Option<OutputFilter>);
+
+impl Clone for OutputFilterSlot {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+impl core::fmt::Debug for OutputFilterSlot {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+/// What sprite evaluation left behind, for comparing the two paths
+#[derive(Debug, PartialEq)]
+struct SpriteEvaluation {
+    count: u8,
+    secondary_oam: [u8; 32],
+    positions: [u8; MAX_SPRITES],
+    attributes: [u8; MAX_SPRITES],
+    indexes: [u8; MAX_SPRITES],
+    overflow: bool,
+}
+
+impl SpriteEvaluation {
+    fn capture(ppu: &Ppu) -> Self {
+        Self {
+            count: ppu.sprite_count,
+            secondary_oam: ppu.secondary_oam,
+            positions: ppu.sprite_positions,
+            attributes: ppu.sprite_attributes,
+            indexes: ppu.sprite_indexes,
+            overflow: ppu.status & 0x20 != 0,
+        }
+    }
 }
 
 impl Ppu {
@@ -91,16 +234,17 @@ impl Ppu {
             scroll_y: 0,
             addr: 0,
             vram: [0; 0x800],
+            mirroring: Mirroring::Horizontal,
             palette: [0; 32],
             oam: [0; 256],
             secondary_oam: [0; 32],
             scanline: 0,
             cycle: 0,
             frame: 0,
-            write_toggle: false,
             buffer: 0,
             open_bus: 0,
             suppress_vbl: false,
+            warming_up: false,
             v: 0,
             t: 0,
             x: 0,
@@ -113,22 +257,74 @@ impl Ppu {
             bg_next_tile_attrib: 0,
             bg_next_tile_lsb: 0,
             bg_next_tile_msb: 0,
+            bg_next_tile_ext: None,
             sprite_count: 0,
-            sprite_patterns_lo: [0; 8],
-            sprite_patterns_hi: [0; 8],
-            sprite_positions: [0; 8],
-            sprite_priorities: [0; 8],
-            sprite_indexes: [0; 8],
-            sprite_attributes: [0; 8],
+            sprite_patterns_lo: [0; MAX_SPRITES],
+            sprite_patterns_hi: [0; MAX_SPRITES],
+            sprite_positions: [0; MAX_SPRITES],
+            sprite_priorities: [0; MAX_SPRITES],
+            sprite_indexes: [0; MAX_SPRITES],
+            sprite_attributes: [0; MAX_SPRITES],
             framebuffer: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            #[cfg(feature = "raw-output")]
+            raw_output: raw_output_buffer(),
             nmi_output: false,
             nmi_occurred: false,
+            nmi_line: false,
+            nmi_cancelled: false,
             odd_frame: false,
+            rendering_this_frame: false,
             rendering_tables: optimized::RenderingTables::new(),
+            accuracy: AccuracyLevel::default(),
+            layer_override: None,
+            greyscale_palette_reads: true,
+            color_table: None,
+            sprite_limit: Some(8),
+            verify_fast_path: false,
+            output_filter: OutputFilterSlot::default(),
+            frame_output: true,
         }
     }
     
+    /// Reset button: PPUCTRL, PPUMASK, the scroll, the write toggle and the
+    /// read buffer are cleared. VRAM, palette, OAM and PPUADDR keep their
+    /// contents and the PPU keeps its place in the frame.
+    pub fn reset(&mut self) {
+        self.ctrl = 0;
+        self.mask = 0;
+        self.nmi_output = false;
+        self.scroll_x = 0;
+        self.scroll_y = 0;
+        self.t = 0;
+        self.x = 0;
+        self.w = false;
+        self.buffer = 0;
+        self.odd_frame = false;
+        self.warming_up = true;
+    }
+    
+    /// Power-on state, as `new` but keeping the mirroring and the frontend
+    /// settings (accuracy, layers, colors, sprite limit)
+    pub fn power_on(&mut self) {
+        let settings = core::mem::replace(self, Self::new());
+        self.mirroring = settings.mirroring;
+        self.accuracy = settings.accuracy;
+        self.layer_override = settings.layer_override;
+        self.greyscale_palette_reads = settings.greyscale_palette_reads;
+        self.color_table = settings.color_table;
+        self.sprite_limit = settings.sprite_limit;
+        self.verify_fast_path = settings.verify_fast_path;
+        self.output_filter = settings.output_filter;
+        self.frame_output = settings.frame_output;
+    }
+    
+    /// Register read without a cartridge: PPUDATA only sees console VRAM
     pub fn read_register(&mut self, reg: u8) -> u8 {
+        self.read_register_with_cartridge(reg, None)
+    }
+    
+    /// Register read that lets the mapper serve nametables it claims
+    pub fn read_register_with_cartridge(&mut self, reg: u8, cartridge: Option<&Cartridge>) -> u8 {
         match reg {
             0 => self.open_bus, // PPUCTRL is write-only
             1 => self.open_bus, // PPUMASK is write-only
@@ -136,11 +332,25 @@ impl Ppu {
                 // PPUSTATUS
                 let value = (self.status & 0xE0) | (self.open_bus & 0x1F);
                 self.status &= !0x80;  // Clear vblank flag
+                self.nmi_occurred = false;
                 self.w = false;         // Reset write toggle
                 
-                // VBlank suppression check
-                if self.scanline == 241 && self.cycle == 0 {
-                    self.suppress_vbl = true;
+                // Races with VBlank, dot 1 of scanline 241. `cycle` is the
+                // next dot to run, so 1 means the read lands on dot 0.
+                // `Nes` runs a whole instruction before clocking the PPU
+                // for it, so there the race follows the dot the reading
+                // instruction starts on, not its read cycle. Catching the
+                // PPU up per access is out of scope for now.
+                if self.scanline == 241 {
+                    match self.cycle {
+                        // One dot early: reads clear, and the flag and NMI
+                        // never happen this frame
+                        1 => self.suppress_vbl = true,
+                        // On the dot the flag was set, or one dot later:
+                        // reads set but still takes back the NMI
+                        2 | 3 => self.nmi_cancelled = true,
+                        _ => {}
+                    }
                 }
                 
                 self.open_bus = value;
@@ -168,20 +378,15 @@ impl Ppu {
                 
                 // Palette reads are immediate
                 if addr >= 0x3F00 {
-                    let mut palette_addr = (addr & 0x1F) as usize;
-                    if palette_addr >= 0x10 && palette_addr % 4 == 0 {
-                        palette_addr &= 0x0F;
-                    }
-                    value = self.palette[palette_addr] & (if self.mask & 0x01 != 0 { 0x30 } else { 0x3F });
-                    // Buffer gets nametable data at addr - 0x1000
-                    if addr < 0x3F00 {
-                        self.buffer = self.vram[self.mirror_address(addr - 0x1000) as usize];
-                    }
+                    // Bits 6-7 are PPU open bus; greyscale masks the rest like the video output
+                    value = (self.palette[palette_index(addr)] & self.palette_read_mask()) | (self.open_bus & 0xC0);
+                    // Buffer gets the nametable byte "underneath" the palette
+                    self.buffer = self.read_nametable(addr - 0x1000, cartridge);
                 } else if addr >= 0x2000 {
-                    self.buffer = self.vram[self.mirror_address(addr) as usize];
+                    self.buffer = self.read_nametable(addr, cartridge);
                 } else {
-                    // CHR reads need cartridge reference
-                    self.buffer = self.open_bus; // Use open bus for now
+                    // Pattern tables; without a cartridge nothing drives the bus
+                    self.buffer = cartridge.map_or(self.open_bus, |cartridge| cartridge.read_chr(addr));
                 }
                 
                 self.v = self.v.wrapping_add(self.addr_increment());
@@ -192,8 +397,19 @@ impl Ppu {
         }
     }
     
+    /// Register write without a cartridge: PPUDATA only reaches console VRAM
     pub fn write_register(&mut self, reg: u8, value: u8) {
+        self.write_register_with_cartridge(reg, value, None);
+    }
+    
+    /// Register write that lets the mapper take nametables it claims.
+    /// Until the first pre-render line after a reset, about 29658 CPU
+    /// cycles, writes to $2000, $2001, $2005 and $2006 are dropped.
+    pub fn write_register_with_cartridge(&mut self, reg: u8, value: u8, cartridge: Option<&mut Cartridge>) {
         self.open_bus = value; // All writes update open bus
+        if self.warming_up && matches!(reg, 0 | 1 | 5 | 6) {
+            return;
+        }
         
         match reg {
             0 => {
@@ -233,20 +449,16 @@ impl Ppu {
             }
             7 => {
                 // PPUDATA
-                // Note: This is a simplified version - in real implementation,
-                // we'd need the cartridge reference here
                 let addr = self.v & 0x3FFF;
                 if addr >= 0x3F00 {
                     // Palette write
-                    let mut palette_addr = (addr & 0x1F) as usize;
-                    if palette_addr >= 0x10 && palette_addr % 4 == 0 {
-                        palette_addr &= 0x0F;
-                    }
-                    self.palette[palette_addr] = value;
+                    self.palette[palette_index(addr)] = value;
                 } else if addr >= 0x2000 {
                     // Name table write
-                    let mirrored = self.mirror_address(addr);
-                    self.vram[mirrored as usize] = value;
+                    self.write_nametable(addr, value, cartridge);
+                } else if let Some(cartridge) = cartridge {
+                    // Pattern tables: CHR RAM takes it, CHR ROM ignores it
+                    cartridge.write_chr(addr, value);
                 }
                 self.v = self.v.wrapping_add(self.addr_increment());
             }
@@ -278,15 +490,11 @@ impl Ppu {
             }
             0x2000..=0x3EFF => {
                 // Name tables and mirrors
-                self.vram[self.mirror_address(addr) as usize]
+                self.read_nametable(addr, Some(cartridge))
             }
             0x3F00..=0x3FFF => {
                 // Palette
-                let mut palette_addr = (addr & 0x1F) as usize;
-                if palette_addr >= 0x10 && palette_addr % 4 == 0 {
-                    palette_addr &= 0x0F;
-                }
-                self.palette[palette_addr] & if self.mask & 0x01 != 0 { 0x30 } else { 0x3F }
+                self.palette[palette_index(addr)] & 0x3F
             }
             _ => 0,
         }
@@ -300,103 +508,70 @@ impl Ppu {
             }
             0x2000..=0x3EFF => {
                 // Name tables
-                let mirrored = self.mirror_address(addr);
-                self.vram[mirrored as usize] = value;
+                self.write_nametable(addr, value, Some(cartridge));
             }
             0x3F00..=0x3FFF => {
                 // Palette
-                let mut palette_addr = (addr & 0x1F) as usize;
-                if palette_addr >= 0x10 && palette_addr % 4 == 0 {
-                    palette_addr &= 0x0F;
-                }
-                self.palette[palette_addr] = value;
+                self.palette[palette_index(addr)] = value;
             }
             _ => {}
         }
     }
     
-    fn mirror_address(&self, addr: u16) -> u16 {
-        // Simple horizontal mirroring for now
-        // TODO: Get actual mirroring mode from cartridge
-        let addr = (addr - 0x2000) % 0x1000;
-        match addr / 0x400 {
-            0 => addr,
-            1 => addr - 0x400,
-            2 => addr - 0x400,
-            3 => addr - 0x800,
-            _ => unreachable!(),
-        }
+    /// Nametable byte from the mapper if it claims `addr`, else from `vram`
+    /// per the cartridge's page select or current mirroring
+    fn read_nametable(&self, addr: u16, cartridge: Option<&Cartridge>) -> u8 {
+        let Some(cartridge) = cartridge else {
+            return self.vram[mirror_address(addr, self.mirroring) as usize];
+        };
+        cartridge
+            .read_nametable(0x2000 | (addr & 0x0FFF))
+            .unwrap_or_else(|| self.vram[vram_address(addr, cartridge) as usize])
+    }
+    
+    fn write_nametable(&mut self, addr: u16, value: u8, cartridge: Option<&mut Cartridge>) {
+        let index = match cartridge {
+            Some(cartridge) => {
+                if cartridge.write_nametable(0x2000 | (addr & 0x0FFF), value) {
+                    return;
+                }
+                vram_address(addr, cartridge)
+            }
+            None => mirror_address(addr, self.mirroring),
+        };
+        self.vram[index as usize] = value;
     }
     
-    pub fn step(&mut self, cartridge: &Cartridge) -> bool {
+    pub fn step(&mut self, cartridge: &Cartridge) -> PpuStep {
         // Visible scanlines (0-239)
         if self.scanline >= 0 && self.scanline < 240 {
-            if self.scanline == 0 && self.cycle == 0 {
-                self.cycle = 1;
+            if self.scanline == 0 && self.cycle == 1 {
+                self.rendering_this_frame = false;
             }
             
             if self.cycle >= 1 && self.cycle <= 256 {
+                self.rendering_this_frame |= self.mask & 0x18 != 0;
                 self.render_pixel(cartridge);
             }
             
-            // Background fetch cycles
-            if (self.cycle >= 1 && self.cycle <= 256) || (self.cycle >= 321 && self.cycle <= 336) {
-                self.update_shifters();
-                
-                match (self.cycle - 1) % 8 {
-                    0 => {
-                        self.load_background_shifters();
-                        self.bg_next_tile_id = self.fetch_nametable_byte(cartridge);
-                    }
-                    2 => {
-                        self.bg_next_tile_attrib = self.fetch_attribute_byte(cartridge);
-                    }
-                    4 => {
-                        self.bg_next_tile_lsb = self.fetch_pattern_byte(0, cartridge);
-                    }
-                    6 => {
-                        self.bg_next_tile_msb = self.fetch_pattern_byte(1, cartridge);
-                    }
-                    7 => {
-                        self.increment_x();
-                    }
-                    _ => {}
-                }
-            }
+            self.fetch_background(cartridge);
             
-            if self.cycle == 256 {
-                self.increment_y();
-            }
-            
-            if self.cycle == 257 {
-                self.load_background_shifters();
-                if self.mask & 0x08 != 0 {
-                    self.v = (self.v & 0xFBE0) | (self.t & 0x041F);
+            // Sprite evaluation for next scanline
+            if self.cycle == 257 && self.scanline < 239 {
+                if self.verify_fast_path {
+                    self.verify_sprite_evaluation(self.scanline + 1);
                 }
-                
-                // Sprite evaluation for next scanline
-                if self.scanline >= -1 && self.scanline < 239 {
-                    // Use optimized sprite evaluation if rendering is enabled
-                    if self.is_rendering() {
-                        optimized::evaluate_sprites_fast(self, self.scanline + 1);
-                    } else {
-                        self.evaluate_sprites(self.scanline + 1);
-                    }
+                match self.accuracy {
+                    AccuracyLevel::Fast => optimized::evaluate_sprites_fast(self, self.scanline + 1),
+                    AccuracyLevel::Accurate => self.evaluate_sprites(self.scanline + 1),
                 }
             }
             
-            if self.cycle == 338 || self.cycle == 340 {
-                self.fetch_nametable_byte(cartridge);
-            }
-            
             // Fetch sprite data
             if self.cycle >= 257 && self.cycle <= 320 {
                 self.fetch_sprite_data(cartridge);
             }
             
-            if self.scanline == 239 && self.cycle == 340 {
-                // Frame complete
-            }
         }
         
         // Post-render scanline (240)
@@ -419,14 +594,32 @@ impl Ppu {
             }
         }
         
-        // Pre-render scanline (261 / -1)
+        // Pre-render scanline
         if self.scanline == 261 {
+            // Same fetches as a visible line, so the first two tiles of
+            // scanline 0 are in the shifters by dot 336
+            if self.mask & 0x18 != 0 {
+                self.fetch_background(cartridge);
+            }
+            
             if self.cycle == 1 {
+                self.warming_up = false;
                 self.status &= !0x80;  // Clear vblank flag
+                self.nmi_occurred = false;
                 self.status &= !0x40;  // Clear sprite 0 hit
                 self.status &= !0x20;  // Clear sprite overflow
             }
             
+            // No sprites are evaluated for scanline 0, but the fetch slots
+            // still run with every slot empty
+            if self.cycle == 257 {
+                self.sprite_count = 0;
+                self.secondary_oam.fill(0xFF);
+            }
+            if self.cycle >= 257 && self.cycle <= 320 {
+                self.fetch_sprite_data(cartridge);
+            }
+            
             if self.cycle >= 280 && self.cycle <= 304 {
                 if self.mask & 0x18 != 0 {
                     self.v = (self.v & 0x841F) | (self.t & 0x7BE0);
@@ -438,7 +631,13 @@ impl Ppu {
             }
         }
         
-        let nmi = self.nmi_occurred && self.nmi_output;
+        // NMI is edge triggered: only a low -> high transition of the
+        // line is reported, e.g. VBlank start or PPUCTRL bit 7 being set
+        // while the VBlank flag is still up
+        let nmi_line = self.nmi_occurred && self.nmi_output;
+        let nmi = nmi_line && !self.nmi_line;
+        self.nmi_line = nmi_line;
+        let mut frame_complete = false;
         
         self.cycle += 1;
         if self.cycle > 340 {
@@ -446,19 +645,28 @@ impl Ppu {
             self.scanline += 1;
             
             if self.scanline > 261 {
-                self.scanline = -1;
+                self.scanline = 0;
                 self.frame += 1;
                 self.odd_frame = !self.odd_frame;
+                frame_complete = true;
+                if let Some(filter) = self.output_filter.0.as_mut().filter(|_| self.frame_output) {
+                    filter(&mut self.framebuffer);
+                }
             }
         }
         
-        nmi
+        PpuStep { nmi, frame_complete }
     }
     
     fn render_pixel(&mut self, _cartridge: &Cartridge) {
         let x = (self.cycle - 1) as usize;
         let y = self.scanline as usize;
         
+        if !self.frame_output {
+            self.update_sprite_zero_hit(x);
+            return;
+        }
+        
         if x < SCREEN_WIDTH && y < SCREEN_HEIGHT {
             let pixel_offset = y * SCREEN_WIDTH + x;
             
@@ -477,6 +685,10 @@ impl Ppu {
                 let bg_pal1 = ((self.bg_shift_attrib_hi & bit_mux) > 0) as u8;
                 bg_palette = (bg_pal1 << 1) | bg_pal0;
             }
+            if self.verify_fast_path {
+                let fast = optimized::render_background_pixel_fast(self, x, &self.rendering_tables);
+                assert_eq!(fast, (bg_pixel, bg_palette), "Fast background pixel differs at ({}, {})", x, y);
+            }
             
             // Check sprite rendering
             let mut sprite_pixel = 0;
@@ -484,6 +696,10 @@ impl Ppu {
             let mut sprite_priority = false;
             let mut sprite_zero_hit = false;
             
+            // Left 8 pixel clipping from PPUMASK bits 1 and 2
+            let clip_bg = x < 8 && self.mask & 0x02 == 0;
+            let clip_sprites = x < 8 && self.mask & 0x04 == 0;
+            
             if self.mask & 0x10 != 0 {
                 // Sprites enabled
                 for i in 0..self.sprite_count {
@@ -504,7 +720,8 @@ impl Ppu {
                         let pixel = (hi << 1) | lo;
                         
                         if pixel != 0 {
-                            if i == 0 && self.sprite_indexes[0] == 0 && bg_pixel != 0 {
+                            if i == 0 && self.sprite_indexes[0] == 0 && bg_pixel != 0
+                                && !clip_bg && !clip_sprites {
                                 // Sprite 0 hit
                                 sprite_zero_hit = true;
                             }
@@ -519,6 +736,16 @@ impl Ppu {
                 }
             }
             
+            // Apply clipping and any debug layer override to the output only
+            let layers = self.layer_override.unwrap_or(LayerMask::empty());
+            let no_clip = layers.contains(LayerMask::NO_CLIPPING);
+            if layers.contains(LayerMask::HIDE_BACKGROUND) || (clip_bg && !no_clip) {
+                bg_pixel = 0;
+            }
+            if layers.contains(LayerMask::HIDE_SPRITES) || (clip_sprites && !no_clip) {
+                sprite_pixel = 0;
+            }
+            
             // Determine final pixel
             let (final_pixel, final_palette) = if bg_pixel == 0 && sprite_pixel == 0 {
                 (0, 0)
@@ -535,7 +762,9 @@ impl Ppu {
                 }
             };
             
-            if sprite_zero_hit && x < 255 {
+            // Both layers must be enabled at this exact dot; a mid-scanline
+            // PPUMASK write turning either off stops further hits
+            if sprite_zero_hit && x < 255 && self.mask & 0x18 == 0x18 {
                 self.status |= 0x40; // Set sprite 0 hit flag
             }
             let palette_addr = if final_pixel == 0 {
@@ -544,10 +773,85 @@ impl Ppu {
                 (final_palette << 2) | final_pixel
             };
             
-            let palette_index = self.palette[palette_addr as usize] & 0x3F;
-            let color = NES_PALETTE[palette_index as usize];
+            let palette_index = self.palette[palette_addr as usize] & self.output_mask();
+            let color = self.color_table.as_ref().unwrap_or(&NES_PALETTE)[palette_index as usize];
             
             self.framebuffer[pixel_offset] = color;
+            #[cfg(feature = "raw-output")]
+            {
+                self.raw_output[pixel_offset] = palette_index as u16 | ((self.mask as u16 & 0xE0) << 1);
+            }
+        }
+    }
+    
+    /// The only part of `render_pixel` a game can observe, for frames that
+    /// aren't drawn. Sprite 0 sits in slot 0 when it's on the line, in
+    /// front of every other sprite, so only that slot has to be looked at.
+    fn update_sprite_zero_hit(&mut self, x: usize) {
+        if self.mask & 0x18 != 0x18 || self.status & 0x40 != 0 || x == 255 {
+            return;
+        }
+        if self.sprite_count == 0 || self.sprite_indexes[0] != 0 {
+            return;
+        }
+        // Left 8 pixel clipping of either layer hides the hit
+        if x < 8 && self.mask & 0x06 != 0x06 {
+            return;
+        }
+        
+        let x_diff = x as i32 - self.sprite_positions[0] as i32;
+        if !(0..8).contains(&x_diff) {
+            return;
+        }
+        let bit = if self.sprite_attributes[0] & 0x40 != 0 { x_diff } else { 7 - x_diff };
+        let sprite_opaque = ((self.sprite_patterns_lo[0] | self.sprite_patterns_hi[0]) >> bit) & 1 != 0;
+        let bg_opaque = (self.bg_shift_pattern_lo | self.bg_shift_pattern_hi) & (0x8000 >> self.x) != 0;
+        if sprite_opaque && bg_opaque {
+            self.status |= 0x40;
+        }
+    }
+    
+    /// Background tile fetches, shifter loads and scroll increments for the
+    /// current dot of a visible or pre-render scanline
+    fn fetch_background(&mut self, cartridge: &Cartridge) {
+        if (self.cycle >= 1 && self.cycle <= 256) || (self.cycle >= 321 && self.cycle <= 336) {
+            self.update_shifters();
+            
+            match (self.cycle - 1) % 8 {
+                0 => {
+                    self.load_background_shifters();
+                    self.bg_next_tile_id = self.fetch_nametable_byte(cartridge);
+                    self.bg_next_tile_ext = cartridge.extended_attribute(0x2000 | (self.v & 0x0FFF));
+                }
+                2 => {
+                    self.bg_next_tile_attrib = self.fetch_attribute_byte(cartridge);
+                }
+                4 => {
+                    self.bg_next_tile_lsb = self.fetch_pattern_byte(0, cartridge);
+                }
+                6 => {
+                    self.bg_next_tile_msb = self.fetch_pattern_byte(1, cartridge);
+                }
+                7 => {
+                    self.increment_x();
+                }
+                _ => {}
+            }
+        }
+        
+        if self.cycle == 256 {
+            self.increment_y();
+        }
+        
+        if self.cycle == 257 {
+            self.load_background_shifters();
+            if self.mask & 0x08 != 0 {
+                self.v = (self.v & 0xFBE0) | (self.t & 0x041F);
+            }
+        }
+        
+        if self.cycle == 338 || self.cycle == 340 {
+            self.fetch_nametable_byte(cartridge);
         }
     }
     
@@ -601,6 +905,9 @@ impl Ppu {
     }
     
     fn fetch_attribute_byte(&self, cartridge: &Cartridge) -> u8 {
+        if let Some(ext) = self.bg_next_tile_ext {
+            return ext.palette;
+        }
         let v = self.v;
         let addr = 0x23C0 | (v & 0x0C00) | ((v >> 4) & 0x38) | ((v >> 2) & 0x07);
         let shift = ((v >> 4) & 4) | (v & 2);
@@ -612,7 +919,64 @@ impl Ppu {
         let fine_y = (self.v >> 12) & 0x07;
         let table = if self.ctrl & 0x10 != 0 { 0x1000 } else { 0x0000 };
         let addr = table | ((self.bg_next_tile_id as u16) << 4) | ((plane as u16) << 3) | fine_y;
-        self.read_byte(addr, cartridge)
+        match self.bg_next_tile_ext {
+            Some(ext) => cartridge.read_chr_rom(ext.chr_offset + (addr & 0x0FFF) as usize),
+            None => self.read_byte(addr, cartridge),
+        }
+    }
+    
+    /// Whether background or sprite rendering was on at any visible dot of
+    /// the current frame. Cleared when scanline 0 starts, so after
+    /// `Nes::run_frame` it describes the frame just drawn.
+    pub fn was_rendering_this_frame(&self) -> bool {
+        self.rendering_this_frame
+    }
+    
+    /// True once after a $2002 read suppressed an NMI already reported by `step`
+    pub fn take_nmi_cancelled(&mut self) -> bool {
+        core::mem::take(&mut self.nmi_cancelled)
+    }
+    
+    /// The $2005/$2006 write toggle: true between the first and second write
+    pub fn write_toggle(&self) -> bool {
+        self.w
+    }
+    
+    /// Off skips drawing: the framebuffer (and raw output) keep the last
+    /// drawn frame and the output filter isn't run, while scrolling, sprite
+    /// 0 hit, sprite overflow and mapper IRQs behave as before. Used by
+    /// `Nes::set_frame_skip`; on by default.
+    pub fn set_frame_output(&mut self, enabled: bool) {
+        self.frame_output = enabled;
+    }
+    
+    pub fn frame_output(&self) -> bool {
+        self.frame_output
+    }
+    
+    /// 0-239 are visible, vblank starts on 241 and 261 is the pre-render line
+    pub fn scanline(&self) -> u16 {
+        self.scanline as u16
+    }
+    
+    /// Next dot to run on the current scanline, 0-340
+    pub fn dot(&self) -> u16 {
+        self.cycle as u16
+    }
+    
+    /// Odd frames drop a dot at the end of the pre-render line while rendering
+    pub fn odd_frame(&self) -> bool {
+        self.odd_frame
+    }
+    
+    /// True from a reset until the PPU accepts $2000, $2001, $2005 and
+    /// $2006 writes. A PPU made with `new` starts out warmed up.
+    pub fn warming_up(&self) -> bool {
+        self.warming_up
+    }
+    
+    pub fn set_warming_up(&mut self, warming_up: bool) {
+        self.warming_up = warming_up;
     }
     
     pub fn get_nmi_output(&self) -> bool {
@@ -631,6 +995,204 @@ impl Ppu {
         self.ctrl
     }
     
+    pub fn set_accuracy(&mut self, accuracy: AccuracyLevel) {
+        self.accuracy = accuracy;
+    }
+    
+    pub fn accuracy(&self) -> AccuracyLevel {
+        self.accuracy
+    }
+    
+    /// Post-process the RGB framebuffer (sepia, scanline darkening, ...)
+    /// without the core committing to an effect. Runs once per completed
+    /// frame, after greyscale and emphasis were applied by the renderer,
+    /// on the whole 256x240 buffer, which it must not resize. `raw_output`
+    /// isn't affected. Clones of the PPU start without a filter. `None`
+    /// removes it.
+    pub fn set_output_filter(&mut self, filter: Option<OutputFilter>) {
+        self.output_filter = OutputFilterSlot(filter);
+    }
+    
+    pub(crate) fn take_output_filter(&mut self) -> Option<OutputFilter> {
+        self.output_filter.0.take()
+    }
+    
+    /// Run the fast and the accurate implementations side by side and
+    /// panic where they disagree. Slow; meant for tests and debugging.
+    pub fn set_verify_fast_path(&mut self, enabled: bool) {
+        self.verify_fast_path = enabled;
+    }
+    
+    pub fn verify_fast_path(&self) -> bool {
+        self.verify_fast_path
+    }
+    
+    /// Force layers on/off for debugging without touching PPUMASK
+    pub fn set_layer_override(&mut self, layers: Option<LayerMask>) {
+        self.layer_override = layers;
+    }
+    
+    pub fn layer_override(&self) -> Option<LayerMask> {
+        self.layer_override
+    }
+    
+    /// Whether $2007 palette reads see the greyscale mask (on for 2C02)
+    pub fn set_greyscale_palette_reads(&mut self, enabled: bool) {
+        self.greyscale_palette_reads = enabled;
+    }
+    
+    pub fn greyscale_palette_reads(&self) -> bool {
+        self.greyscale_palette_reads
+    }
+    
+    /// Replace the built-in 64-color palette, `None` restores the default
+    pub fn set_color_table(&mut self, colors: Option<[u32; 64]>) {
+        self.color_table = colors;
+    }
+    
+    pub fn color_table(&self) -> Option<[u32; 64]> {
+        self.color_table
+    }
+    
+    /// Palette RAM in $3F00-$3F1F order as 0x00RRGGBB through the active
+    /// color table, for palette viewers. $3F10/$14/$18/$1C show the
+    /// entries they mirror. Greyscale and emphasis aren't applied.
+    pub fn current_colors(&self) -> [u32; 32] {
+        let colors = self.color_table.as_ref().unwrap_or(&NES_PALETTE);
+        core::array::from_fn(|i| {
+            colors[(self.palette[palette_index(0x3F00 + i as u16)] & 0x3F) as usize]
+        })
+    }
+    
+    /// Brightness (0-255, Rec. 601 weights) of the framebuffer pixel at
+    /// (`x`, `y`), for light gun detection and brightness maps. Computed
+    /// from the final RGB pixel, so sprites, emphasis and the color table
+    /// count, the output filter doesn't until the frame is finished. Rows
+    /// the current frame hasn't reached yet still hold the previous frame,
+    /// so a Zapper should look at the dots the PPU has just drawn.
+    /// Off-screen positions read as black.
+    pub fn luminance_at(&self, x: usize, y: usize) -> u8 {
+        if x >= SCREEN_WIDTH || y >= SCREEN_HEIGHT {
+            return 0;
+        }
+        let color = self.framebuffer[y * SCREEN_WIDTH + x];
+        let (r, g, b) = ((color >> 16) & 0xFF, (color >> 8) & 0xFF, color & 0xFF);
+        ((r * 299 + g * 587 + b * 114) / 1000) as u8
+    }
+    
+    /// What the PPU emitted for each pixel of `framebuffer`, before any
+    /// color conversion: the 6-bit palette index (greyscale applied) in
+    /// bits 0-5 and the PPUMASK emphasis bits in 6-8. That is the 9-bit
+    /// index into a 512-entry NES 2.0 palette, and the input NTSC filters
+    /// expect.
+    #[cfg(feature = "raw-output")]
+    pub fn raw_output(&self) -> &[u16] {
+        &self.raw_output
+    }
+    
+    /// The nametable entry drawn at screen pixel (`x`, `y`) with the scroll
+    /// the next frame starts from (`t` and fine X; `v` is reloaded from
+    /// them on the pre-render line), as (nametable address, tile id,
+    /// palette). The address is the $2000-$2FFF one, before mirroring.
+    /// Mid-frame scroll changes such as status bar splits aren't followed.
+    /// None off-screen.
+    pub fn tile_at_screen(&self, x: usize, y: usize, cartridge: Option<&Cartridge>) -> Option<(u16, u8, u8)> {
+        if x >= SCREEN_WIDTH || y >= SCREEN_HEIGHT {
+            return None;
+        }
+        
+        // Horizontally the coarse X and nametable bits simply count on
+        let column = (self.t & 0x1F) as usize + (self.x as usize + x) / 8;
+        let mut nametable = (self.t >> 10) & 0x03;
+        nametable ^= ((column / 32) & 1) as u16;
+        let coarse_x = (column % 32) as u16;
+        
+        // Vertically row 29 wraps to the next nametable, while rows 30
+        // and 31 (attribute data) wrap to 0 in the same one
+        let rows = ((self.t >> 12) & 0x07) as usize + y;
+        let mut coarse_y = (self.t >> 5) & 0x1F;
+        for _ in 0..rows / 8 {
+            coarse_y = match coarse_y {
+                29 => {
+                    nametable ^= 0x02;
+                    0
+                }
+                31 => 0,
+                row => row + 1,
+            };
+        }
+        
+        let v = (nametable << 10) | (coarse_y << 5) | coarse_x;
+        let nametable_addr = 0x2000 | v;
+        let tile_id = self.read_nametable(nametable_addr, cartridge);
+        let attribute_addr = 0x23C0 | (v & 0x0C00) | ((v >> 4) & 0x38) | ((v >> 2) & 0x07);
+        let shift = ((v >> 4) & 4) | (v & 2);
+        let palette = (self.read_nametable(attribute_addr, cartridge) >> shift) & 0x03;
+        Some((nametable_addr, tile_id, palette))
+    }
+    
+    // Raw memory snapshots for tooling (asset rippers, "export VRAM").
+    // They bypass $2007 and leave the rendering pipeline untouched.
+    
+    pub fn dump_oam(&self) -> [u8; 256] {
+        self.oam
+    }
+    
+    /// The 2KB of console VRAM, i.e. two physical nametables. The four
+    /// logical ones ($2000/$2400/$2800/$2C00) map onto these halves per the
+    /// mirroring mode, see `mirror_address`. Four-screen boards keep the
+    /// other two on the cartridge, see `Bus::dump_vram` for all of them.
+    pub fn dump_vram(&self) -> &[u8; 0x800] {
+        &self.vram
+    }
+    
+    /// Palette RAM in $3F00-$3F1F order. Writes to $3F10/$14/$18/$1C land in
+    /// $3F00/$04/$08/$0C, so those four entries stay unused here.
+    pub fn dump_palette(&self) -> [u8; 32] {
+        self.palette
+    }
+    
+    pub fn load_oam(&mut self, oam: &[u8; 256]) {
+        self.oam = *oam;
+    }
+    
+    pub fn load_vram(&mut self, vram: &[u8; 0x800]) {
+        self.vram = *vram;
+    }
+    
+    pub fn load_palette(&mut self, palette: &[u8; 32]) {
+        self.palette = *palette;
+    }
+    
+    /// Nametable layout for accesses made without a cartridge. With one,
+    /// the cartridge's current mirroring applies instead.
+    pub fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+    
+    pub fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+    
+    /// Sprites drawn per scanline: `Some(8)` matches hardware, `None`
+    /// removes the limit (and the flicker games use to work around it).
+    /// Only rendering changes; the overflow flag is set as if the limit applied.
+    pub fn set_sprite_limit(&mut self, limit: Option<u8>) {
+        self.sprite_limit = limit;
+    }
+    
+    pub fn sprite_limit(&self) -> Option<u8> {
+        self.sprite_limit
+    }
+    
+    fn output_mask(&self) -> u8 {
+        if self.mask & 0x01 != 0 { 0x30 } else { 0x3F }
+    }
+    
+    fn palette_read_mask(&self) -> u8 {
+        if self.greyscale_palette_reads { self.output_mask() } else { 0x3F }
+    }
+    
     fn is_rendering(&self) -> bool {
         (self.mask & 0x18) != 0
     }
@@ -648,13 +1210,37 @@ impl Ppu {
         }
     }
     
+    /// Run both sprite evaluations for `scanline` and panic if they pick
+    /// different sprites. The overflow flag is only logged, since the fast
+    /// path leaves out the hardware bug on purpose. The caller runs the
+    /// configured evaluation afterwards, so its result is what gets used.
+    fn verify_sprite_evaluation(&mut self, scanline: i32) {
+        let status = self.status;
+        optimized::evaluate_sprites_fast(self, scanline);
+        let fast = SpriteEvaluation::capture(self);
+        self.status = status;
+        self.evaluate_sprites(scanline);
+        let accurate = SpriteEvaluation::capture(self);
+        self.status = status;
+        
+        if fast.overflow != accurate.overflow {
+            log::debug!("Sprite overflow differs on line {}: fast {}, accurate {}", scanline, fast.overflow, accurate.overflow);
+        }
+        assert_eq!(
+            SpriteEvaluation { overflow: false, ..fast },
+            SpriteEvaluation { overflow: false, ..accurate },
+            "Sprite evaluation differs on line {}", scanline
+        );
+    }
+    
+    /// Sprite evaluation including the hardware overflow bug
     fn evaluate_sprites(&mut self, scanline: i32) {
         self.sprite_count = 0;
         
         let sprite_height = if self.ctrl & 0x20 != 0 { 16 } else { 8 };
         
         // Clear secondary OAM
-        for i in 0..8 {
+        for i in 0..MAX_SPRITES {
             self.sprite_patterns_lo[i] = 0;
             self.sprite_patterns_hi[i] = 0;
             self.sprite_positions[i] = 0xFF;
@@ -706,84 +1292,147 @@ impl Ppu {
                     break;
                 }
                 
-                // Increment with overflow bug
+                // Hardware bug: n and m both advance, so the scan walks
+                // diagonally through tile/attribute/X bytes
+                n += 1;
                 m = (m + 1) & 3;
-                if m == 0 {
-                    n += 1;
+            }
+        }
+        
+        self.apply_sprite_limit(scanline, sprite_height);
+    }
+    
+    /// Grow or shrink the sprite list from the hardware's eight to `sprite_limit`.
+    /// Runs after evaluation, so the overflow flag still behaves as on hardware.
+    fn apply_sprite_limit(&mut self, scanline: i32, sprite_height: i32) {
+        let limit = self.sprite_limit.map_or(MAX_SPRITES, |limit| (limit as usize).min(MAX_SPRITES));
+        
+        if self.sprite_count == 8 && limit > 8 {
+            let start = self.sprite_indexes[7] as usize + 1;
+            for n in start..64 {
+                if self.sprite_count as usize >= limit {
+                    break;
+                }
+                
+                let y_diff = scanline - self.oam[n * 4] as i32;
+                if y_diff >= 0 && y_diff < sprite_height {
+                    let idx = self.sprite_count as usize;
+                    self.sprite_positions[idx] = self.oam[n * 4 + 3];
+                    self.sprite_attributes[idx] = self.oam[n * 4 + 2];
+                    self.sprite_indexes[idx] = n as u8;
+                    self.sprite_count += 1;
                 }
             }
         }
+        
+        self.sprite_count = self.sprite_count.min(limit as u8);
     }
     
     fn fetch_sprite_data(&mut self, cartridge: &Cartridge) {
         if self.cycle >= 257 && self.cycle < 321 {
+            // Sprites past the eighth have no fetch slots of their own;
+            // load them all once the real slots are done
+            if self.cycle == 320 {
+                for idx in 8..self.sprite_count as usize {
+                    let n = self.sprite_indexes[idx] as usize * 4;
+                    let (y, tile, attr) = (self.oam[n] as i32, self.oam[n + 1], self.oam[n + 2]);
+                    self.sprite_patterns_lo[idx] = self.fetch_sprite_row(y, tile, attr, 0, cartridge);
+                    self.sprite_patterns_hi[idx] = self.fetch_sprite_row(y, tile, attr, 8, cartridge);
+                }
+            }
+            
             let sprite_idx = ((self.cycle - 257) / 8) as usize;
+            let cycle_in_fetch = (self.cycle - 257) % 8;
+            if cycle_in_fetch != 4 && cycle_in_fetch != 6 {
+                return;
+            }
+            let plane = if cycle_in_fetch == 4 { 0 } else { 8 };
             
-            if sprite_idx < self.sprite_count as usize {
-                let cycle_in_fetch = (self.cycle - 257) % 8;
+            if sprite_idx < (self.sprite_count as usize).min(8) {
+                let y = self.secondary_oam[sprite_idx * 4] as i32;
+                let tile = self.secondary_oam[sprite_idx * 4 + 1];
+                let attr = self.secondary_oam[sprite_idx * 4 + 2];
                 
-                if cycle_in_fetch == 4 {
-                    // Fetch low sprite byte
-                    let y = self.secondary_oam[sprite_idx * 4] as i32;
-                    let tile = self.secondary_oam[sprite_idx * 4 + 1];
-                    let attr = self.secondary_oam[sprite_idx * 4 + 2];
-                    
-                    let sprite_height = if self.ctrl & 0x20 != 0 { 16 } else { 8 };
-                    let y_in_tile = (self.scanline - y) as u16;
-                    
-                    let mut y_offset = y_in_tile;
-                    if attr & 0x80 != 0 {
-                        // Vertical flip
-                        y_offset = (sprite_height - 1) as u16 - y_offset;
-                    }
-                    
-                    let pattern_addr = if sprite_height == 8 {
-                        let table = if self.ctrl & 0x08 != 0 { 0x1000 } else { 0x0000 };
-                        table | ((tile as u16) << 4) | y_offset
-                    } else {
-                        // 8x16 sprites
-                        let table = ((tile & 1) as u16) << 12;
-                        let tile_num = (tile & 0xFE) as u16;
-                        if y_offset >= 8 {
-                            table | ((tile_num + 1) << 4) | (y_offset - 8)
-                        } else {
-                            table | (tile_num << 4) | y_offset
-                        }
-                    };
-                    
-                    self.sprite_patterns_lo[sprite_idx] = self.read_byte(pattern_addr, cartridge);
-                } else if cycle_in_fetch == 6 {
-                    // Fetch high sprite byte
-                    let y = self.secondary_oam[sprite_idx * 4] as i32;
-                    let tile = self.secondary_oam[sprite_idx * 4 + 1];
-                    let attr = self.secondary_oam[sprite_idx * 4 + 2];
-                    
-                    let sprite_height = if self.ctrl & 0x20 != 0 { 16 } else { 8 };
-                    let y_in_tile = (self.scanline - y) as u16;
-                    
-                    let mut y_offset = y_in_tile;
-                    if attr & 0x80 != 0 {
-                        // Vertical flip
-                        y_offset = (sprite_height - 1) as u16 - y_offset;
-                    }
-                    
-                    let pattern_addr = if sprite_height == 8 {
-                        let table = if self.ctrl & 0x08 != 0 { 0x1000 } else { 0x0000 };
-                        table | ((tile as u16) << 4) | y_offset | 8
-                    } else {
-                        // 8x16 sprites  
-                        let table = ((tile & 1) as u16) << 12;
-                        let tile_num = (tile & 0xFE) as u16;
-                        if y_offset >= 8 {
-                            table | ((tile_num + 1) << 4) | (y_offset - 8) | 8
-                        } else {
-                            table | (tile_num << 4) | y_offset | 8
-                        }
-                    };
-                    
-                    self.sprite_patterns_hi[sprite_idx] = self.read_byte(pattern_addr, cartridge);
+                let value = self.fetch_sprite_row(y, tile, attr, plane, cartridge);
+                if plane == 0 {
+                    self.sprite_patterns_lo[sprite_idx] = value;
+                } else {
+                    self.sprite_patterns_hi[sprite_idx] = value;
                 }
+            } else {
+                // Empty slots still fetch tile $FF so the mapper sees the same
+                // A12 pattern as on hardware; the data is discarded
+                self.read_byte(self.sprite_pattern_addr(0xFF, 0) | plane, cartridge);
             }
         }
     }
+    
+    // One pattern plane of a sprite's row on the next scanline
+    fn fetch_sprite_row(&self, y: i32, tile: u8, attr: u8, plane: u16, cartridge: &Cartridge) -> u8 {
+        let sprite_height = if self.ctrl & 0x20 != 0 { 16 } else { 8 };
+        let mut y_offset = (self.scanline + 1 - y) as u16;
+        if attr & 0x80 != 0 {
+            // Vertical flip
+            y_offset = (sprite_height - 1) as u16 - y_offset;
+        }
+        
+        self.read_byte(self.sprite_pattern_addr(tile, y_offset) | plane, cartridge)
+    }
+    
+    fn sprite_pattern_addr(&self, tile: u8, y_offset: u16) -> u16 {
+        if self.ctrl & 0x20 == 0 {
+            let table = if self.ctrl & 0x08 != 0 { 0x1000 } else { 0x0000 };
+            table | ((tile as u16) << 4) | y_offset
+        } else {
+            // 8x16 sprites
+            let table = ((tile & 1) as u16) << 12;
+            let tile_num = (tile & 0xFE) as u16;
+            if y_offset >= 8 {
+                table | ((tile_num + 1) << 4) | (y_offset - 8)
+            } else {
+                table | (tile_num << 4) | y_offset
+            }
+        }
+    }
+}
+
+#[cfg(feature = "raw-output")]
+fn raw_output_buffer() -> Vec<u16> {
+    vec![0; SCREEN_WIDTH * SCREEN_HEIGHT]
+}
+
+/// Map a nametable address ($2000-$3EFF) to an offset into `vram`
+fn mirror_address(addr: u16, mirroring: Mirroring) -> u16 {
+    let addr = (addr - 0x2000) % 0x1000;
+    let table = addr / 0x400;
+    let physical = match mirroring {
+        Mirroring::Horizontal => table / 2,
+        Mirroring::Vertical => table % 2,
+        Mirroring::SingleScreenLow => 0,
+        Mirroring::SingleScreenHigh => 1,
+        // The cartridge serves $2800-$2FFF itself
+        Mirroring::FourScreen => table % 2,
+    };
+    physical * 0x400 + addr % 0x400
+}
+
+/// Map a nametable address to an offset into `vram` for the inserted
+/// cartridge, whose mapper may pick the page itself
+fn vram_address(addr: u16, cartridge: &Cartridge) -> u16 {
+    match cartridge.ciram_page(0x2000 | (addr & 0x0FFF)) {
+        Some(page) => page * 0x400 + addr % 0x400,
+        None => mirror_address(addr, cartridge.mirroring()),
+    }
+}
+
+/// Map a palette address ($3F00-$3FFF) to an offset into `palette`. The
+/// backdrop entries of the sprite palettes, $3F10/$14/$18/$1C, are the
+/// same bytes as $3F00/$04/$08/$0C, whichever way they're accessed.
+fn palette_index(addr: u16) -> usize {
+    let index = (addr & 0x1F) as usize;
+    if index >= 0x10 && index & 0x03 == 0 {
+        index & 0x0F
+    } else {
+        index
+    }
 }
\ No newline at end of file
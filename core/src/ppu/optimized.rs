@@ -56,7 +56,7 @@ pub fn render_background_pixel_fast(
     (pixel, palette)
 }
 
-/// Optimized sprite evaluation
+/// Optimized sprite evaluation, selected with `AccuracyLevel::Fast`
 #[inline(always)]
 pub fn evaluate_sprites_fast(
     ppu: &mut Ppu,
@@ -67,14 +67,15 @@ pub fn evaluate_sprites_fast(
     
     // Clear sprite data
     unsafe {
-        // Use unsafe for performance - we know these arrays are 8 elements
-        std::ptr::write_bytes(ppu.sprite_patterns_lo.as_mut_ptr(), 0, 8);
-        std::ptr::write_bytes(ppu.sprite_patterns_hi.as_mut_ptr(), 0, 8);
-        std::ptr::write_bytes(ppu.sprite_positions.as_mut_ptr(), 0xFF, 8);
-        std::ptr::write_bytes(ppu.sprite_priorities.as_mut_ptr(), 0, 8);
-        std::ptr::write_bytes(ppu.sprite_indexes.as_mut_ptr(), 0xFF, 8);
-        std::ptr::write_bytes(ppu.sprite_attributes.as_mut_ptr(), 0, 8);
+        // Use unsafe for performance - we know these arrays are MAX_SPRITES elements
+        core::ptr::write_bytes(ppu.sprite_patterns_lo.as_mut_ptr(), 0, MAX_SPRITES);
+        core::ptr::write_bytes(ppu.sprite_patterns_hi.as_mut_ptr(), 0, MAX_SPRITES);
+        core::ptr::write_bytes(ppu.sprite_positions.as_mut_ptr(), 0xFF, MAX_SPRITES);
+        core::ptr::write_bytes(ppu.sprite_priorities.as_mut_ptr(), 0, MAX_SPRITES);
+        core::ptr::write_bytes(ppu.sprite_indexes.as_mut_ptr(), 0xFF, MAX_SPRITES);
+        core::ptr::write_bytes(ppu.sprite_attributes.as_mut_ptr(), 0, MAX_SPRITES);
     }
+    ppu.secondary_oam.fill(0xFF);
     
     // Fast sprite evaluation loop
     let mut oam_offset = 0;
@@ -87,7 +88,7 @@ pub fn evaluate_sprites_fast(
             
             // Copy sprite data
             unsafe {
-                std::ptr::copy_nonoverlapping(
+                core::ptr::copy_nonoverlapping(
                     ppu.oam.as_ptr().add(oam_offset),
                     ppu.secondary_oam.as_mut_ptr().add(idx * 4),
                     4
@@ -118,6 +119,8 @@ pub fn evaluate_sprites_fast(
             oam_offset += 4;
         }
     }
+    
+    ppu.apply_sprite_limit(scanline, sprite_height);
 }
 
 /// Batch pixel rendering for better cache efficiency
@@ -1,5 +1,5 @@
 use serde::{Serialize, Deserialize};
-use crate::{Cpu, Bus};
+use crate::{Cpu, Bus, Clock};
 use std::io::{Read, Write};
 use thiserror::Error;
 
@@ -13,10 +13,64 @@ pub enum SaveStateError {
     
     #[error("Invalid save state version")]
     InvalidVersion,
+    
+    #[error("Save state is truncated or corrupted")]
+    Corrupted,
 }
 
-const SAVE_STATE_VERSION: u32 = 2;
+const SAVE_STATE_VERSION: u32 = 14;
 const SAVE_STATE_MAGIC: &[u8; 4] = b"CCNS";
+const FULL_STATE_VERSION: u32 = 18;
+const FULL_STATE_MAGIC: &[u8; 4] = b"CCNF";
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-32 (IEEE), the same checksum zip and PNG use
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Write the serialized state followed by its CRC-32, little endian
+fn write_checked<W: Write, T: Serialize>(mut writer: W, state: &T) -> Result<(), SaveStateError> {
+    let payload = bincode::serialize(state)?;
+    writer.write_all(&payload)?;
+    writer.write_all(&crc32(&payload).to_le_bytes())?;
+    Ok(())
+}
+
+/// Read a `write_checked` state, refusing it if the checksum doesn't match
+fn read_checked<R: Read, T: for<'de> Deserialize<'de>>(mut reader: R) -> Result<T, SaveStateError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.len() < 4 {
+        return Err(SaveStateError::Corrupted);
+    }
+    let (payload, checksum) = data.split_at(data.len() - 4);
+    if crc32(payload).to_le_bytes() != checksum {
+        return Err(SaveStateError::Corrupted);
+    }
+    Ok(bincode::deserialize(payload)?)
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct SaveState {
@@ -31,10 +85,15 @@ pub struct SaveState {
     cpu_pc: u16,
     cpu_status: u8,
     cpu_cycles: u32,
+    cpu_stall_cycles: u32,
+    cpu_jammed: bool,
     
     // Essential PPU state (publicly accessible)
     ppu_palette: Vec<u8>,
     ppu_oam: Vec<u8>,
+    ppu_warming_up: bool,
+    // 4KB on four-screen boards, 2KB otherwise
+    ppu_vram: Vec<u8>,
     
     // Essential memory state
     ram: Vec<u8>,
@@ -43,10 +102,17 @@ pub struct SaveState {
     mapper_number: u8,
     mapper_state: crate::cartridge::MapperState,
     mirroring: crate::cartridge::Mirroring,
+    // Every bank of it, empty without PRG RAM
+    prg_ram: Vec<u8>,
+    // Tiles the game uploaded, empty for CHR ROM
+    chr_ram: Vec<u8>,
     
     // Controller state
     controller1_state: u8,
     controller2_state: u8,
+    
+    // Controller shift registers and in-flight OAM DMA
+    bus_io: crate::bus::BusIoState,
 }
 
 impl SaveState {
@@ -63,10 +129,14 @@ impl SaveState {
             cpu_pc: cpu.pc,
             cpu_status: cpu.status.bits(),
             cpu_cycles: cpu.cycles,
+            cpu_stall_cycles: cpu.stall_cycles,
+            cpu_jammed: cpu.is_jammed(),
             
             // Essential PPU state
             ppu_palette: bus.ppu.palette.to_vec(),
             ppu_oam: bus.ppu.oam.to_vec(),
+            ppu_warming_up: bus.ppu.warming_up(),
+            ppu_vram: bus.dump_vram(),
             
             // Essential memory state
             ram: bus.get_ram().to_vec(),
@@ -75,10 +145,13 @@ impl SaveState {
             mapper_number: bus.cartridge.as_ref().map_or(255, |c| c.get_mapper_number()),
             mapper_state: bus.cartridge.as_ref().map_or(crate::cartridge::MapperState::Other, |c| c.get_mapper_state()),
             mirroring: bus.cartridge.as_ref().map_or(crate::cartridge::Mirroring::Horizontal, |c| c.mirroring()),
+            prg_ram: cartridge_prg_ram(bus).to_vec(),
+            chr_ram: cartridge_chr_ram(bus).to_vec(),
             
             // Controller state
             controller1_state: bus.get_controller1_state(),
             controller2_state: bus.get_controller2_state(),
+            bus_io: bus.get_io_state(),
         }
     }
     
@@ -90,6 +163,15 @@ impl SaveState {
         if self.version != SAVE_STATE_VERSION {
             return Err(SaveStateError::InvalidVersion);
         }
+        if self.ppu_palette.len() != bus.ppu.palette.len()
+            || self.ppu_oam.len() != bus.ppu.oam.len()
+            || self.ppu_vram.len() != bus.dump_vram().len()
+            || self.ram.len() != bus.get_ram().len()
+            || self.prg_ram.len() != cartridge_prg_ram(bus).len()
+            || self.chr_ram.len() != cartridge_chr_ram(bus).len()
+        {
+            return Err(SaveStateError::Corrupted);
+        }
         
         // Restore CPU state
         cpu.a = self.cpu_a;
@@ -99,10 +181,14 @@ impl SaveState {
         cpu.pc = self.cpu_pc;
         cpu.status = crate::cpu::StatusFlags::from_bits_truncate(self.cpu_status);
         cpu.cycles = self.cpu_cycles;
+        cpu.stall_cycles = self.cpu_stall_cycles;
+        cpu.set_jammed(self.cpu_jammed);
         
         // Restore essential PPU state
         bus.ppu.palette.copy_from_slice(&self.ppu_palette);
         bus.ppu.oam.copy_from_slice(&self.ppu_oam);
+        bus.ppu.set_warming_up(self.ppu_warming_up);
+        bus.load_vram(&self.ppu_vram);
         
         // Restore memory state
         bus.set_ram(&self.ram);
@@ -110,25 +196,75 @@ impl SaveState {
         // Restore mapper state
         if let Some(cartridge) = &mut bus.cartridge {
             cartridge.set_mapper_state(&self.mapper_state);
+            if let Some(ram) = cartridge.prg_ram_mut() {
+                ram.copy_from_slice(&self.prg_ram);
+            }
+            if let Some(ram) = cartridge.chr_ram_mut() {
+                ram.copy_from_slice(&self.chr_ram);
+            }
         }
         
         // Restore controller state
         bus.set_controller_states(self.controller1_state, self.controller2_state);
+        bus.set_io_state(&self.bus_io);
         
         Ok(())
     }
     
+    /// The state is followed by a CRC-32 so `load` can spot damaged files
     pub fn save<W: Write>(&self, writer: W) -> Result<(), SaveStateError> {
-        bincode::serialize_into(writer, self)?;
-        Ok(())
+        write_checked(writer, self)
     }
     
+    /// Fails with `Corrupted` if the data doesn't match its checksum
     pub fn load<R: Read>(reader: R) -> Result<Self, SaveStateError> {
-        let state = bincode::deserialize_from(reader)?;
-        Ok(state)
+        read_checked(reader)
     }
 }
 
+/// The whole machine, borrowed for `Nes::save_full_state`
+#[derive(Serialize)]
+struct FullStateRef<'a> {
+    magic: [u8; 4],
+    version: u32,
+    cpu: &'a Cpu,
+    bus: &'a Bus,
+    mapper_state: crate::cartridge::MapperState,
+    prg_ram: &'a [u8],
+    chr_ram: &'a [u8],
+    nametable_ram: &'a [u8],
+    clock: Clock,
+}
+
+/// Owned counterpart of `FullStateRef`, the field order has to match
+#[derive(Deserialize)]
+struct FullState {
+    magic: [u8; 4],
+    version: u32,
+    cpu: Cpu,
+    bus: Bus,
+    mapper_state: crate::cartridge::MapperState,
+    prg_ram: Vec<u8>,
+    chr_ram: Vec<u8>,
+    nametable_ram: Vec<u8>,
+    clock: Clock,
+}
+
+/// PRG RAM of the inserted cartridge, empty if it has none
+fn cartridge_prg_ram(bus: &Bus) -> &[u8] {
+    bus.cartridge.as_ref().and_then(|c| c.prg_ram()).unwrap_or(&[])
+}
+
+/// CHR RAM of the inserted cartridge, empty for CHR ROM
+fn cartridge_chr_ram(bus: &Bus) -> &[u8] {
+    bus.cartridge.as_ref().and_then(|c| c.chr_ram()).unwrap_or(&[])
+}
+
+/// Four-screen nametable RAM of the inserted cartridge, empty for the rest
+fn cartridge_nametable_ram(bus: &Bus) -> &[u8] {
+    bus.cartridge.as_ref().map_or(&[], |c| c.nametable_ram())
+}
+
 // Helper methods for Nes struct
 use crate::Nes;
 
@@ -166,6 +302,54 @@ impl Nes {
     pub fn load_state_from_slice(&mut self, data: &[u8]) -> Result<(), SaveStateError> {
         self.load_state(std::io::Cursor::new(data))
     }
+    
+    /// Serialize every CPU, PPU, APU and bus field plus the mapper state.
+    /// Bigger than `save_state`. The cartridge ROM isn't included, and
+    /// frontend settings like the colors, sprite limit and sample rate stay
+    /// as they are when loading.
+    pub fn save_full_state<W: Write>(&self, writer: W) -> Result<(), SaveStateError> {
+        let state = FullStateRef {
+            magic: *FULL_STATE_MAGIC,
+            version: FULL_STATE_VERSION,
+            cpu: &self.cpu,
+            bus: &self.bus,
+            mapper_state: self.bus.cartridge.as_ref().map_or(crate::cartridge::MapperState::Other, |c| c.get_mapper_state()),
+            prg_ram: cartridge_prg_ram(&self.bus),
+            chr_ram: cartridge_chr_ram(&self.bus),
+            nametable_ram: cartridge_nametable_ram(&self.bus),
+            clock: self.clock,
+        };
+        write_checked(writer, &state)
+    }
+    
+    /// Restore a `save_full_state` snapshot taken with the same cartridge
+    pub fn load_full_state<R: Read>(&mut self, reader: R) -> Result<(), SaveStateError> {
+        let state: FullState = read_checked(reader)?;
+        if &state.magic != FULL_STATE_MAGIC || state.version != FULL_STATE_VERSION {
+            return Err(SaveStateError::InvalidVersion);
+        }
+        if state.prg_ram.len() != cartridge_prg_ram(&self.bus).len()
+            || state.chr_ram.len() != cartridge_chr_ram(&self.bus).len()
+            || state.nametable_ram.len() != cartridge_nametable_ram(&self.bus).len()
+        {
+            return Err(SaveStateError::Corrupted);
+        }
+        
+        self.cpu = state.cpu;
+        self.bus.restore(state.bus);
+        if let Some(cartridge) = &mut self.bus.cartridge {
+            cartridge.set_mapper_state(&state.mapper_state);
+            if let Some(ram) = cartridge.prg_ram_mut() {
+                ram.copy_from_slice(&state.prg_ram);
+            }
+            if let Some(ram) = cartridge.chr_ram_mut() {
+                ram.copy_from_slice(&state.chr_ram);
+            }
+            cartridge.nametable_ram_mut().copy_from_slice(&state.nametable_ram);
+        }
+        self.clock = state.clock;
+        Ok(())
+    }
 }
 
 // Save state slot management
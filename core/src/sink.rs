@@ -0,0 +1,37 @@
+use alloc::vec::Vec;
+
+/// Receives each finished frame from `Nes::run_frame_with`
+pub trait VideoSink {
+    /// 256x240 pixels, 0x00RRGGBB
+    fn present(&mut self, frame: &[u32]);
+}
+
+/// Receives the audio generated during each `Nes::run_frame_with`
+pub trait AudioSink {
+    /// Mono samples at the APU's output rate
+    fn submit(&mut self, samples: &[f32]);
+}
+
+/// Keeps the most recent frame
+impl VideoSink for Vec<u32> {
+    fn present(&mut self, frame: &[u32]) {
+        self.clear();
+        self.extend_from_slice(frame);
+    }
+}
+
+/// Appends every sample
+impl AudioSink for Vec<f32> {
+    fn submit(&mut self, samples: &[f32]) {
+        self.extend_from_slice(samples);
+    }
+}
+
+/// Discards everything, for runs that only need one of the two outputs
+impl VideoSink for () {
+    fn present(&mut self, _frame: &[u32]) {}
+}
+
+impl AudioSink for () {
+    fn submit(&mut self, _samples: &[f32]) {}
+}
\ No newline at end of file
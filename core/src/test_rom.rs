@@ -1,5 +1,7 @@
 // Simple test ROM for basic functionality testing
 
+use alloc::{vec, vec::Vec};
+
 pub fn create_test_rom() -> Vec<u8> {
     let mut rom = Vec::new();
     
@@ -64,7 +66,7 @@ pub fn create_test_rom() -> Vec<u8> {
         0x8D, 0x00, 0x20,  // STA $2000 (enable NMI)
         
         // Infinite loop
-        0x4C, 0x5A, 0xC0,  // JMP $C05A
+        0x4C, 0x4C, 0xC0,  // JMP $C04C (self)
     ];
     
     // Place program at start of ROM
@@ -72,6 +74,13 @@ pub fn create_test_rom() -> Vec<u8> {
         prg[i] = byte;
     }
     
+    // NMI handler at $C100 just returns
+    prg[0x0100] = 0x40;  // RTI
+    
+    // Set NMI vector
+    prg[0x3FFA] = 0x00;  // Low byte
+    prg[0x3FFB] = 0xC1;  // High byte ($C100)
+    
     // Set reset vector
     prg[0x3FFC] = 0x00;  // Low byte
     prg[0x3FFD] = 0xC0;  // High byte ($C000)
@@ -182,7 +191,7 @@ pub fn create_sprite_test_rom() -> Vec<u8> {
         0x8D, 0x00, 0x20,  // STA $2000 (enable NMI)
         
         // Infinite loop
-        0x4C, 0x80, 0xC0,  // JMP $C080
+        0x4C, 0x88, 0xC0,  // JMP $C088 (self)
     ];
     
     // Place program at start of ROM
@@ -190,6 +199,13 @@ pub fn create_sprite_test_rom() -> Vec<u8> {
         prg[i] = byte;
     }
     
+    // NMI handler at $C100 just returns
+    prg[0x0100] = 0x40;  // RTI
+    
+    // Set NMI vector
+    prg[0x3FFA] = 0x00;  // Low byte
+    prg[0x3FFB] = 0xC1;  // High byte ($C100)
+    
     // Set reset vector
     prg[0x3FFC] = 0x00;  // Low byte
     prg[0x3FFD] = 0xC0;  // High byte ($C000)
@@ -256,15 +272,15 @@ pub fn create_controller_test_rom() -> Vec<u8> {
         
         // Read 8 bits from controller
         0xA2, 0x08,        // LDX #$08
-        // Read loop (at $C00F)
+        // Read loop (at $C010)
         0xAD, 0x16, 0x40,  // LDA $4016
         0x4A,              // LSR A
         0x66, 0x00,        // ROR $00
         0xCA,              // DEX
-        0xD0, 0xF6,        // BNE -10 (back to $C00F)
+        0xD0, 0xF7,        // BNE -9 (back to $C010)
         
         // Infinite loop
-        0x4C, 0x18, 0xC0,  // JMP $C018
+        0x4C, 0x19, 0xC0,  // JMP $C019 (self)
     ];
     
     // Place program at start of ROM
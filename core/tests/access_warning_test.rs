@@ -0,0 +1,138 @@
+use ccnes_core::cpu::{CpuBus, StackWrap};
+use ccnes_core::{AccessWarning, AccessWarningKind, Cartridge, Nes, StackWarning};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn create_nes(rom_data: &[u8]) -> (Nes, Rc<RefCell<Vec<AccessWarning>>>) {
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(rom_data).unwrap());
+    let warnings = Rc::new(RefCell::new(Vec::new()));
+    let sink = warnings.clone();
+    nes.set_access_warning_hook(Box::new(move |warning| sink.borrow_mut().push(warning)));
+    (nes, warnings)
+}
+
+fn warning(kind: AccessWarningKind, addr: u16, value: Option<u8>, pc: u16) -> AccessWarning {
+    AccessWarning { kind, addr, value, pc }
+}
+
+#[test]
+fn test_no_warnings_from_a_well_behaved_rom() {
+    let (mut nes, warnings) = create_nes(&ccnes_core::test_rom::create_test_rom());
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    assert!(warnings.borrow().is_empty());
+}
+
+#[test]
+fn test_nrom_warnings_carry_the_instruction_address() {
+    let (mut nes, warnings) = create_nes(&ccnes_core::test_rom::create_test_rom());
+    
+    // STA $8000, LDA $5000, LDA $6000, STA $4018 from RAM
+    let program = [0x8D, 0x00, 0x80, 0xAD, 0x00, 0x50, 0xAD, 0x00, 0x60, 0x8D, 0x18, 0x40];
+    for (i, &byte) in program.iter().enumerate() {
+        nes.bus_mut().write(0x0300 + i as u16, byte);
+    }
+    nes.cpu_mut().pc = 0x0300;
+    nes.cpu_mut().a = 0x42;
+    for _ in 0..4 {
+        nes.step().unwrap();
+    }
+    
+    assert_eq!(*warnings.borrow(), [
+        warning(AccessWarningKind::ReadOnlyWrite, 0x8000, Some(0x42), 0x0300),
+        warning(AccessWarningKind::UnmappedRead, 0x5000, None, 0x0303),
+        warning(AccessWarningKind::UnmappedRead, 0x6000, None, 0x0306),
+        warning(AccessWarningKind::UnmappedWrite, 0x4018, Some(0x00), 0x0309),
+    ]);
+}
+
+#[test]
+fn test_mmc5_unimplemented_registers() {
+    let mut rom_data = vec![0; 16 + 0x4000 + 0x2000];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 1;
+    rom_data[5] = 1;
+    rom_data[6] = 0x50;
+    let (mut nes, warnings) = create_nes(&rom_data);
+    
+    let bus = nes.bus_mut();
+    bus.write(0x5100, 0x03);
+    bus.write(0x5C00, 0x01);
+    bus.write(0x6000, 0x01);
+    bus.write(0x8000, 0x01);
+    assert!(warnings.borrow().is_empty(), "Implemented registers and RAM");
+    
+    bus.write(0x5000, 0x30);
+    bus.read(0x5204);
+    bus.read(0x5100);
+    let kinds: Vec<_> = warnings.borrow().iter().map(|w| (w.kind, w.addr)).collect();
+    assert_eq!(kinds, [
+        (AccessWarningKind::UnimplementedRegister, 0x5000),
+        (AccessWarningKind::UnimplementedRegister, 0x5204),
+        (AccessWarningKind::UnmappedRead, 0x5100),
+    ]);
+}
+
+#[test]
+fn test_hook_can_be_cleared_and_is_not_cloned() {
+    let (mut nes, warnings) = create_nes(&ccnes_core::test_rom::create_test_rom());
+    
+    let mut copy = nes.clone();
+    copy.bus_mut().write(0x8000, 0);
+    assert!(warnings.borrow().is_empty());
+    
+    nes.clear_access_warning_hook();
+    nes.bus_mut().write(0x8000, 0);
+    assert!(warnings.borrow().is_empty());
+}
+
+#[test]
+fn test_stack_wrap_warnings() {
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&ccnes_core::test_rom::create_test_rom()).unwrap());
+    let warnings = Rc::new(RefCell::new(Vec::new()));
+    let sink = warnings.clone();
+    nes.set_stack_warning_hook(Box::new(move |warning| sink.borrow_mut().push(warning)));
+    
+    // PHA with SP at $01, PHA, PLA, PLA, then PLA with SP at $FF from RAM
+    let program = [0x48, 0x48, 0x68, 0x68, 0x9A, 0x68];
+    for (i, &byte) in program.iter().enumerate() {
+        nes.bus_mut().write(0x0300 + i as u16, byte);
+    }
+    nes.cpu_mut().pc = 0x0300;
+    nes.cpu_mut().sp = 0x01;
+    nes.cpu_mut().x = 0xFF;
+    for _ in 0..2 {
+        nes.step().unwrap();
+    }
+    // The push still wraps as on hardware
+    assert_eq!(nes.cpu.sp, 0xFF);
+    assert_eq!(*warnings.borrow(), [StackWarning { wrap: StackWrap::Overflow, pc: 0x0301 }]);
+    
+    // Pulling back across the wrap is an underflow, then TXS and one more
+    for _ in 0..4 {
+        nes.step().unwrap();
+    }
+    assert_eq!(nes.cpu.sp, 0x00);
+    assert_eq!(*warnings.borrow(), [
+        StackWarning { wrap: StackWrap::Overflow, pc: 0x0301 },
+        StackWarning { wrap: StackWrap::Underflow, pc: 0x0302 },
+        StackWarning { wrap: StackWrap::Underflow, pc: 0x0305 },
+    ]);
+    
+    // A normal frame doesn't come near the ends of the stack
+    warnings.borrow_mut().clear();
+    nes.reset();
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    assert!(warnings.borrow().is_empty());
+    
+    nes.clear_stack_warning_hook();
+    nes.cpu_mut().sp = 0x00;
+    nes.cpu_mut().pc = 0x0300;
+    nes.step().unwrap();
+    assert!(warnings.borrow().is_empty());
+}
@@ -1,4 +1,4 @@
-use ccnes_core::apu::{Apu, ResamplerQuality};
+use ccnes_core::apu::{Apu, ApuChannel, MixingMode, ResamplerQuality, WAVEFORM_LENGTH};
 
 #[test]
 fn test_improved_audio_generation() {
@@ -80,6 +80,36 @@ fn test_resampler_quality_settings() {
     assert!(!apu.get_samples().is_empty());
 }
 
+#[test]
+fn test_quality_switch_mid_stream_has_no_click() {
+    let mut apu = Apu::with_sample_rate(44100);
+    assert_eq!(apu.quality(), ResamplerQuality::Medium);
+    
+    // Slow triangle so neighbouring output samples are close together
+    apu.write_register(0x4015, 0x04);
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400A, 0xFF);
+    apu.write_register(0x400B, 0x03);
+    
+    for quality in [ResamplerQuality::High, ResamplerQuality::Low, ResamplerQuality::Medium] {
+        for _ in 0..20000 {
+            apu.step();
+        }
+        let samples = apu.get_samples();
+        let before = *samples.last().unwrap();
+        let largest_step = samples.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f32::max);
+        
+        apu.set_quality(quality);
+        assert_eq!(apu.quality(), quality);
+        for _ in 0..200 {
+            apu.step();
+        }
+        let after = apu.get_samples()[0];
+        assert!((after - before).abs() <= largest_step * 2.0 + 0.001,
+            "Switching to {:?}: {} -> {}, largest step {}", quality, before, after, largest_step);
+    }
+}
+
 #[test]
 fn test_buffer_management() {
     let mut apu = Apu::with_sample_rate(44100);
@@ -164,4 +194,436 @@ fn test_mixed_channel_output() {
     
     assert!(rms > 0.01); // Should have some signal energy
     assert!(rms < 1.0);  // Should not be clipping
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_stereo_delay_output() {
+    let mut apu = Apu::with_sample_rate(44100);
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, 0xBF); // Constant max volume
+    apu.write_register(0x4002, 0xFD);
+    apu.write_register(0x4003, 0x00);
+    
+    for _ in 0..29780 {
+        apu.step();
+    }
+    
+    // No delay: both channels carry the mono mix
+    let stereo = apu.get_stereo_samples();
+    assert!(!stereo.is_empty());
+    assert_eq!(stereo.len() % 2, 0);
+    assert!(stereo.chunks(2).all(|pair| pair[0] == pair[1]));
+    assert!(apu.get_samples().is_empty(), "Stereo read drains the mono buffer");
+    
+    // 5ms delay: the right channel lags the left by 220 samples
+    apu.set_stereo_delay_ms(5.0);
+    let mut stereo = Vec::new();
+    while stereo.len() < 1000 {
+        apu.step();
+        stereo.extend(apu.get_stereo_samples());
+    }
+    let left: Vec<f32> = stereo.iter().step_by(2).copied().collect();
+    let right: Vec<f32> = stereo.iter().skip(1).step_by(2).copied().collect();
+    assert!(right[..220].iter().all(|&s| s == 0.0));
+    assert_eq!(&right[220..], &left[..left.len() - 220]);
+}
+
+const FRAME_CYCLES: usize = 29830;
+
+fn run_cycles(apu: &mut Apu, cycles: usize) -> Vec<f32> {
+    let mut samples = Vec::new();
+    for _ in 0..cycles {
+        apu.step();
+        samples.extend(apu.get_samples());
+    }
+    samples
+}
+
+fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0, |max, &s| max.max(s.abs()))
+}
+
+// Pulse 1 with the given $4000 value, started and left running
+fn start_pulse(reg0: u8) -> Apu {
+    let mut apu = Apu::with_sample_rate(44100);
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, reg0);
+    apu.write_register(0x4002, 0xFD);
+    apu.write_register(0x4003, 0x08); // Length index 1: 254 half frames
+    apu
+}
+
+#[test]
+fn test_envelope_one_shot_vs_loop() {
+    // Envelope period 1: 15 -> 0 in 32 quarter frames
+    let mut one_shot = start_pulse(0x81);
+    let mut looped = start_pulse(0xA1);
+    
+    run_cycles(&mut one_shot, FRAME_CYCLES * 12);
+    run_cycles(&mut looped, FRAME_CYCLES * 12);
+    
+    assert!(peak(&run_cycles(&mut one_shot, FRAME_CYCLES)) < 0.01, "One-shot envelope should have decayed to silence");
+    assert!(peak(&run_cycles(&mut looped, FRAME_CYCLES)) > 0.05, "Looping envelope should still be audible");
+}
+
+#[test]
+fn test_envelope_decays_under_constant_volume() {
+    // Constant volume 1, which is also the envelope period: 15 -> 0 in
+    // 32 quarter frames
+    let mut apu = start_pulse(0x91);
+    run_cycles(&mut apu, FRAME_CYCLES * 12);
+    
+    // The one-shot envelope decayed underneath in the meantime
+    apu.write_register(0x4000, 0x81);
+    assert!(peak(&run_cycles(&mut apu, FRAME_CYCLES)) < 0.01);
+}
+
+#[test]
+fn test_dmc_direct_load_square_wave() {
+    let mut apu = Apu::with_sample_rate(44100);
+    
+    // Every $4011 edge is heard as a click...
+    for half in 0..8 {
+        apu.write_register(0x4011, if half % 2 == 0 { 0x7F } else { 0x00 });
+        assert!(peak(&run_cycles(&mut apu, 4000)) > 0.01, "Edge {} was inaudible", half);
+    }
+    
+    // ...while a level that is held decays to silence
+    apu.write_register(0x4011, 0x7F);
+    run_cycles(&mut apu, FRAME_CYCLES);
+    assert!(peak(&run_cycles(&mut apu, FRAME_CYCLES)) < 0.001);
+}
+
+#[test]
+fn test_dmc_fast_writes_average_out() {
+    let mut full = Apu::with_sample_rate(44100);
+    full.write_register(0x4011, 0x7F);
+    let full_peak = peak(&run_cycles(&mut full, 4000));
+    
+    // Toggling every cycle is far above the sample rate: it should sound
+    // like a step to the mid level, not alias into noise
+    let mut pwm = Apu::with_sample_rate(44100);
+    let mut samples = Vec::new();
+    for cycle in 0..4000 {
+        pwm.write_register(0x4011, if cycle % 2 == 0 { 0x7F } else { 0x00 });
+        samples.extend(run_cycles(&mut pwm, 1));
+    }
+    
+    let pwm_peak = peak(&samples);
+    assert!(pwm_peak > full_peak * 0.3 && pwm_peak < full_peak * 0.8,
+        "50% PWM peak {} vs full step {}", pwm_peak, full_peak);
+}
+
+#[test]
+fn test_dmc_pwm_matches_its_average_level() {
+    // Eight cycle PWM is ~220kHz; once band limited it should sound like
+    // the level it averages to, whichever resampler picks the samples
+    for quality in [ResamplerQuality::Low, ResamplerQuality::Medium] {
+        let mut steady = Apu::with_sample_rate(44100);
+        steady.set_mixing(MixingMode::Linear);
+        steady.set_quality(quality);
+        steady.write_register(0x4011, 0x40);
+        let steady_samples = run_cycles(&mut steady, 8000);
+        
+        let mut pwm = Apu::with_sample_rate(44100);
+        pwm.set_mixing(MixingMode::Linear);
+        pwm.set_quality(quality);
+        let mut pwm_samples = Vec::new();
+        for cycle in 0..8000 {
+            if cycle % 4 == 0 {
+                pwm.write_register(0x4011, if cycle % 8 == 0 { 0x7F } else { 0x01 });
+            }
+            pwm_samples.extend(run_cycles(&mut pwm, 1));
+        }
+        
+        let level = peak(&steady_samples);
+        let error = steady_samples.iter().zip(&pwm_samples).map(|(a, b)| (a - b).abs()).fold(0.0, f32::max);
+        assert!(error < level * 0.1, "{:?}: PWM off by {} from level {}", quality, error, level);
+    }
+}
+
+// Half-frame clocks of the 4-step sequence, counted in APU steps from power
+// on; the sequence repeats every `FRAME_SEQUENCE_STEPS`
+const HALF_FRAME_STEP: usize = 14913;
+const SECOND_HALF_FRAME_STEP: usize = 29829;
+const FRAME_SEQUENCE_STEPS: usize = 29829;
+const PULSE1_ACTIVE: u8 = 0x01;
+
+// Pulse 1 enabled and stepped to just before the first half-frame clock
+fn pulse_before_half_frame(reg0: u8, length_index: Option<u8>) -> Apu {
+    let mut apu = Apu::with_sample_rate(44100);
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, reg0);
+    if let Some(index) = length_index {
+        apu.write_register(0x4003, index << 3);
+    }
+    run_cycles(&mut apu, HALF_FRAME_STEP - 1);
+    apu
+}
+
+// Half-frame clocks, after `steps` so far, until pulse 1's length counter
+// reaches zero
+fn half_frames_until_silent(apu: &mut Apu, mut steps: usize) -> usize {
+    let mut half_frames = 0;
+    while apu.read_register(0x4015) & PULSE1_ACTIVE != 0 {
+        apu.step();
+        steps += 1;
+        if [HALF_FRAME_STEP, 0].contains(&(steps % FRAME_SEQUENCE_STEPS)) {
+            half_frames += 1;
+        }
+    }
+    half_frames
+}
+
+#[test]
+fn test_length_reload_races_half_frame_clock() {
+    // Reloading a non-zero counter on the clock cycle is ignored; the clock
+    // takes 2 down to 1, so the next half frame silences it
+    let mut apu = pulse_before_half_frame(0x10, Some(3));
+    apu.write_register(0x4003, 1 << 3); // Would load 254
+    run_cycles(&mut apu, 1);
+    assert_eq!(half_frames_until_silent(&mut apu, HALF_FRAME_STEP), 1);
+    
+    // Reloading a zero counter wins and skips the clock: 2 remains
+    let mut apu = pulse_before_half_frame(0x10, None);
+    apu.write_register(0x4003, 3 << 3);
+    run_cycles(&mut apu, 1);
+    assert_eq!(half_frames_until_silent(&mut apu, HALF_FRAME_STEP), 2);
+    
+    // One cycle earlier there is no race: the reload lands and is clocked
+    let mut apu = pulse_before_half_frame(0x10, Some(3));
+    run_cycles(&mut apu, 1);
+    apu.write_register(0x4003, 1 << 3);
+    assert_eq!(half_frames_until_silent(&mut apu, HALF_FRAME_STEP), 254);
+}
+
+#[test]
+fn test_length_halt_applies_after_half_frame_clock() {
+    // Setting halt on the clock cycle is too late for that clock
+    let mut apu = pulse_before_half_frame(0x10, Some(3));
+    apu.write_register(0x4000, 0x30);
+    run_cycles(&mut apu, 1);
+    apu.write_register(0x4000, 0x10);
+    assert_eq!(half_frames_until_silent(&mut apu, HALF_FRAME_STEP), 1);
+    
+    // Clearing it on the clock cycle still protects the counter for that clock
+    let mut apu = pulse_before_half_frame(0x30, Some(3));
+    run_cycles(&mut apu, SECOND_HALF_FRAME_STEP - HALF_FRAME_STEP);
+    apu.write_register(0x4000, 0x10);
+    run_cycles(&mut apu, 1);
+    assert_eq!(half_frames_until_silent(&mut apu, SECOND_HALF_FRAME_STEP), 2);
+}
+
+const DMC_ACTIVE: u8 = 0x10;
+const DMC_IRQ: u8 = 0x80;
+
+// Cycles until the DMC raises its IRQ, giving up after `limit`
+fn cycles_until_dmc_irq(apu: &mut Apu, limit: usize) -> Option<usize> {
+    (1..=limit).find(|_| {
+        apu.step();
+        apu.read_register(0x4015) & DMC_IRQ != 0
+    })
+}
+
+#[test]
+fn test_dmc_enable_disable_and_irq() {
+    let mut apu = Apu::with_sample_rate(44100);
+    apu.write_register(0x4010, 0x8F); // IRQ on, fastest rate
+    apu.write_register(0x4012, 0x00);
+    apu.write_register(0x4013, 0x00); // 1 byte
+    assert_eq!(apu.read_register(0x4015) & (DMC_ACTIVE | DMC_IRQ), 0);
+    
+    apu.write_register(0x4015, 0x10);
+    assert_eq!(apu.read_register(0x4015) & (DMC_ACTIVE | DMC_IRQ), DMC_ACTIVE);
+    
+    // The only byte is fetched straight away, which ends the sample
+    assert!(cycles_until_dmc_irq(&mut apu, 10).is_some());
+    assert_eq!(apu.read_register(0x4015) & (DMC_ACTIVE | DMC_IRQ), DMC_IRQ);
+    
+    // Enabling a finished sample restarts it and acknowledges the IRQ
+    apu.write_register(0x4015, 0x10);
+    assert_eq!(apu.read_register(0x4015) & (DMC_ACTIVE | DMC_IRQ), DMC_ACTIVE);
+    
+    // This time the fetch waits for the first byte to leave the buffer
+    assert!(cycles_until_dmc_irq(&mut apu, 10).is_none());
+    assert!(cycles_until_dmc_irq(&mut apu, 1000).is_some());
+    
+    // Disabling acknowledges it too and leaves nothing to play
+    apu.write_register(0x4015, 0x00);
+    assert_eq!(apu.read_register(0x4015) & (DMC_ACTIVE | DMC_IRQ), 0);
+    assert_eq!(cycles_until_dmc_irq(&mut apu, 10_000), None);
+}
+
+#[test]
+fn test_dmc_enable_does_not_restart_playing_sample() {
+    let start = || {
+        let mut apu = Apu::with_sample_rate(44100);
+        apu.write_register(0x4010, 0x8F);
+        apu.write_register(0x4013, 0x01); // 17 bytes
+        apu.write_register(0x4015, 0x10);
+        apu
+    };
+    let expected = cycles_until_dmc_irq(&mut start(), 100_000).unwrap();
+    
+    let mut apu = start();
+    run_cycles(&mut apu, expected / 2);
+    apu.write_register(0x4015, 0x10);
+    assert_eq!(apu.read_register(0x4015) & DMC_ACTIVE, DMC_ACTIVE);
+    assert_eq!(cycles_until_dmc_irq(&mut apu, 100_000), Some(expected - expected / 2));
+    
+    // Disabling mid-sample stops it before the IRQ
+    let mut apu = start();
+    run_cycles(&mut apu, expected / 2);
+    apu.write_register(0x4015, 0x00);
+    assert_eq!(apu.read_register(0x4015) & DMC_ACTIVE, 0);
+    assert_eq!(cycles_until_dmc_irq(&mut apu, 100_000), None);
+}
+
+// Rising edges per second of output audio. The thresholds sit a quarter
+// of the way in from the peaks, so ringing around an edge counts once.
+fn measure_frequency(samples: &[f32], sample_rate: u32) -> f32 {
+    let (low, high) = samples.iter().fold((f32::MAX, f32::MIN), |(low, high), &s| (low.min(s), high.max(s)));
+    let (falling, rising) = (low + (high - low) * 0.25, high - (high - low) * 0.25);
+    
+    let mut edges = Vec::new();
+    let mut armed = false;
+    for (i, &sample) in samples.iter().enumerate() {
+        if sample < falling {
+            armed = true;
+        } else if armed && sample > rising {
+            armed = false;
+            edges.push(i);
+        }
+    }
+    
+    // Whole periods between the first and last edge
+    let periods = (edges.len() - 1) as f32;
+    periods * sample_rate as f32 / (edges[edges.len() - 1] - edges[0]) as f32
+}
+
+#[test]
+fn test_pulse_pitch() {
+    // A4: 1789773 / (16 * (253 + 1)) = 440.4 Hz
+    let expected = 1789773.0 / (16.0 * 254.0);
+    
+    for sample_rate in [44100, 48000] {
+        let mut apu = Apu::with_sample_rate(sample_rate);
+        apu.write_register(0x4015, 0x01);
+        apu.write_register(0x4000, 0xBF); // 50% duty, halted length, constant max volume
+        apu.write_register(0x4002, 253);
+        apu.write_register(0x4003, 0x00);
+        
+        // One second of CPU time is one second of audio
+        let samples = run_cycles(&mut apu, 1789773);
+        assert!((samples.len() as i64 - sample_rate as i64).abs() <= 2,
+            "{} samples for one second at {} Hz", samples.len(), sample_rate);
+        
+        let frequency = measure_frequency(&samples[sample_rate as usize / 10..], sample_rate);
+        assert!((frequency - expected).abs() < expected * 0.01,
+            "Measured {} Hz, expected {} Hz at {} Hz output", frequency, expected, sample_rate);
+    }
+}
+
+#[test]
+fn test_linear_mixing_single_pulse() {
+    fn pulse_peak(mixing: MixingMode) -> f32 {
+        let mut apu = Apu::with_sample_rate(44100);
+        apu.set_mixing(mixing);
+        
+        // Pulse 1 alone, 50% duty, constant volume 15
+        apu.write_register(0x4015, 0x01);
+        apu.write_register(0x4000, 0xBF);
+        apu.write_register(0x4002, 0xFD);
+        apu.write_register(0x4003, 0x00);
+        
+        for _ in 0..30000 {
+            apu.step();
+        }
+        apu.get_samples().iter().fold(0.0f32, |peak, &s| peak.max(s.abs()))
+    }
+    
+    assert_eq!(Apu::with_sample_rate(44100).mixing(), MixingMode::NonLinear);
+    
+    // 95.88 / (8128 / 15 + 100) vs 0.00752 * 15, give or take the filters
+    let non_linear = pulse_peak(MixingMode::NonLinear);
+    let linear = pulse_peak(MixingMode::Linear);
+    assert!(linear > 0.0);
+    let ratio = linear / non_linear;
+    assert!((ratio - 0.755).abs() < 0.05, "linear/non-linear ratio {}", ratio);
+}
+
+#[test]
+fn test_channel_waveforms() {
+    let mut apu = Apu::with_sample_rate(44100);
+    assert!(apu.channel_waveforms().iter().all(|wave| wave.len() == WAVEFORM_LENGTH));
+    
+    // Pulse 2 alone, 50% duty at full constant volume, around 440Hz
+    apu.write_register(0x4015, 0x02);
+    apu.write_register(0x4004, 0xBF);
+    apu.write_register(0x4006, 0xFD);
+    apu.write_register(0x4007, 0x00);
+    for _ in 0..29780 {
+        apu.step();
+    }
+    
+    let [pulse1, pulse2, triangle, noise, dmc] = apu.channel_waveforms();
+    assert!(pulse2.iter().all(|&level| level == 0.0 || level == 1.0));
+    let high = pulse2.iter().filter(|&&level| level == 1.0).count();
+    assert!((96..=160).contains(&high), "{} of {} samples high", high, WAVEFORM_LENGTH);
+    for silent in [pulse1, triangle, noise, dmc] {
+        assert!(silent.iter().all(|&level| level == 0.0));
+    }
+    
+    // The newest sample is last: silencing the channel shows up at the end
+    apu.write_register(0x4015, 0x00);
+    for _ in 0..100 {
+        apu.step();
+    }
+    let pulse2 = apu.channel_waveforms()[1];
+    assert_eq!(pulse2[WAVEFORM_LENGTH - 1], 0.0);
+    assert!(pulse2[..WAVEFORM_LENGTH - 3].contains(&1.0));
+    
+    apu.reset_audio();
+    assert!(apu.channel_waveforms().iter().all(|wave| wave.iter().all(|&level| level == 0.0)));
+}
+
+#[test]
+fn test_channel_frequency() {
+    use ccnes_core::Region;
+    
+    let mut apu = Apu::new();
+    for channel in [ApuChannel::Pulse1, ApuChannel::Triangle] {
+        assert_eq!(apu.channel_frequency(channel, Region::Ntsc), None);
+    }
+    
+    // Pulse 1 and the triangle both on A4, periods 253 and 126
+    apu.write_register(0x4015, 0x0F);
+    apu.write_register(0x4000, 0x3F);
+    apu.write_register(0x4002, 0xFD);
+    apu.write_register(0x4003, 0x08);
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400A, 0x7E);
+    apu.write_register(0x400B, 0x08);
+    apu.write_register(0x400C, 0x3F);
+    apu.write_register(0x400F, 0x08);
+    
+    let pulse = apu.channel_frequency(ApuChannel::Pulse1, Region::Ntsc).unwrap();
+    assert!((pulse - 440.4).abs() < 0.1, "{} Hz", pulse);
+    let pal = apu.channel_frequency(ApuChannel::Pulse1, Region::Pal).unwrap();
+    assert!((pal - 409.1).abs() < 0.1, "{} Hz", pal);
+    
+    // The triangle's linear counter only loads on the first quarter frame
+    assert_eq!(apu.channel_frequency(ApuChannel::Triangle, Region::Ntsc), None);
+    for _ in 0..7457 {
+        apu.step();
+    }
+    let triangle = apu.channel_frequency(ApuChannel::Triangle, Region::Ntsc).unwrap();
+    assert!((triangle - 440.4).abs() < 0.1, "{} Hz", triangle);
+    
+    // Disabled or pitchless channels have none
+    assert_eq!(apu.channel_frequency(ApuChannel::Pulse2, Region::Ntsc), None);
+    assert_eq!(apu.channel_frequency(ApuChannel::Noise, Region::Ntsc), None);
+    assert_eq!(apu.channel_frequency(ApuChannel::Dmc, Region::Ntsc), None);
+    apu.write_register(0x4015, 0x00);
+    assert_eq!(apu.channel_frequency(ApuChannel::Pulse1, Region::Ntsc), None);
+}
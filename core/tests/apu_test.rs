@@ -19,7 +19,7 @@ fn test_apu_basic_functionality() {
     
     // Run for a few cycles to let APU process
     for _ in 0..1000 {
-        nes.step();
+        nes.step().unwrap();
     }
     
     // Check APU status
@@ -64,7 +64,7 @@ fn test_apu_all_channels() {
     nes.bus.write(0x400F, 0x00);
     
     // Run for a frame
-    nes.run_frame();
+    nes.run_frame().unwrap();
     
     // Check that all channels are still enabled
     let status = nes.bus.read(0x4015);
@@ -77,4 +77,139 @@ fn test_apu_all_channels() {
     // Verify samples aren't all zero
     let non_zero_samples = samples.iter().filter(|&&s| s != 0.0).count();
     assert!(non_zero_samples > 0, "Should have non-zero samples");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_frame_irq_inhibit() {
+    use ccnes_core::Apu;
+    
+    let mut apu = Apu::new();
+    apu.write_register(0x4017, 0x40);
+    let info = apu.debug_info();
+    assert!(info.frame_irq_inhibit);
+    assert!(!info.five_step_mode);
+    for _ in 0..2 * 29830 {
+        apu.step();
+    }
+    assert_eq!(apu.read_register(0x4015) & 0x40, 0, "Inhibited: no frame IRQ");
+    assert!(!apu.debug_info().frame_irq);
+    
+    // Lifting the inhibit lets the next sequence end raise it
+    apu.write_register(0x4017, 0x00);
+    for _ in 0..29830 {
+        apu.step();
+    }
+    assert_eq!(apu.read_register(0x4015) & 0x40, 0x40);
+    
+    // Setting it again acknowledges the pending IRQ
+    apu.write_register(0x4017, 0xC0);
+    let info = apu.debug_info();
+    assert!(!info.frame_irq);
+    assert!(info.five_step_mode);
+    assert_eq!(apu.read_register(0x4015) & 0x40, 0);
+}
+
+#[test]
+fn test_unreadable_apu_registers_read_open_bus() {
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&ccnes_core::test_rom::create_test_rom()).unwrap());
+    
+    // Whatever was last on the data bus comes back
+    nes.bus.write(0x0200, 0x5A);
+    assert_eq!(nes.bus.read(0x4009), 0x5A);
+    nes.bus.read(0x0200);
+    assert_eq!(nes.bus.read(0x4000), 0x5A);
+    
+    // LDA $4009: the last byte fetched is the operand's high byte
+    for (i, &byte) in [0xAD, 0x09, 0x40].iter().enumerate() {
+        nes.bus.write(0x0300 + i as u16, byte);
+    }
+    nes.cpu.pc = 0x0300;
+    nes.step().unwrap();
+    assert_eq!(nes.cpu.a, 0x40);
+    
+    // $4015 is still the status register, read without touching the bus
+    nes.bus.write(0x4015, 0x00);
+    assert_eq!(nes.bus.read(0x4015) & 0x1F, 0x00);
+    nes.bus.write(0x0200, 0xA5);
+    nes.bus.read(0x4015);
+    assert_eq!(nes.bus.read(0x4009), 0xA5);
+    
+    // The controller ports only drive their low five bits
+    assert_eq!(nes.bus.read(0x4016) & 0xE0, 0xA0);
+    assert_eq!(nes.bus.read(0x4017) & 0xE0, 0xA0);
+}
+
+/// CPU cycles until a length counter loaded with 2 runs out
+fn cycles_until_silent(apu: &mut ccnes_core::Apu) -> u32 {
+    apu.write_register(0x4003, 0x18);
+    let mut cycles = 0;
+    while apu.read_register(0x4015) & 0x01 != 0 {
+        apu.step();
+        cycles += 1;
+    }
+    cycles
+}
+
+#[test]
+fn test_frame_sequencer_cycles() {
+    use ccnes_core::Apu;
+    
+    // Half frames land on CPU cycles 14913 and 29829, and the 4-step
+    // sequence is 29830 cycles long
+    let mut apu = Apu::new();
+    apu.write_register(0x4015, 0x01);
+    assert_eq!(cycles_until_silent(&mut apu), 29829);
+    assert_eq!(cycles_until_silent(&mut apu), 29830);
+    
+    // The 5-step sequence's second half frame is at 37281, of 37282,
+    // counted from the write taking effect 3 cycles after it
+    let mut apu = Apu::new();
+    apu.write_register(0x4017, 0x80);
+    apu.write_register(0x4015, 0x01);
+    assert_eq!(cycles_until_silent(&mut apu), 37281 + 2);
+    assert_eq!(cycles_until_silent(&mut apu), 37282);
+    
+    // The frame IRQ is raised at 29828, two cycles before the sequence ends
+    let mut apu = Apu::new();
+    for _ in 0..29827 {
+        apu.step();
+    }
+    assert!(!apu.debug_info().frame_irq);
+    apu.step();
+    assert!(apu.debug_info().frame_irq);
+}
+
+#[test]
+fn test_frame_counter_write_restarts_sequence() {
+    use ccnes_core::Apu;
+    
+    // Switching to 4-step past the end of its sequence restarts it rather
+    // than stalling, 3 or 4 cycles after the write depending on parity
+    for (written_at, irq_at) in [(30000, 29828 + 2), (30001, 29828 + 3)] {
+        let mut apu = Apu::new();
+        apu.write_register(0x4017, 0x80);
+        for _ in 0..written_at {
+            apu.step();
+        }
+        apu.write_register(0x4017, 0x00);
+        let mut cycles = 0;
+        while !apu.debug_info().frame_irq {
+            assert!(cycles < 37282, "frame sequencer stalled");
+            apu.step();
+            cycles += 1;
+        }
+        assert_eq!(cycles, irq_at, "written on cycle {}", written_at);
+    }
+    
+    // Selecting 5-step clocks the length counters immediately
+    let mut apu = Apu::new();
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4003, 0x18);
+    // Past the cycle where the load would win over a length clock
+    apu.step();
+    apu.write_register(0x4017, 0x80);
+    assert_eq!(apu.read_register(0x4015) & 0x01, 0x01);
+    apu.write_register(0x4017, 0x80);
+    assert_eq!(apu.read_register(0x4015) & 0x01, 0x00);
+}
@@ -147,7 +147,7 @@ mod tests {
         
         // Run for several frames
         for frame in 0..120 {
-            nes.run_frame();
+            nes.run_frame().unwrap();
             
             let samples = nes.bus.apu.get_samples();
             if frame > 5 { // Give it time to initialize
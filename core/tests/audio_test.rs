@@ -152,7 +152,7 @@ fn test_audio_generation() {
     
     // Run for a few frames to generate audio
     for _ in 0..60 {
-        nes.run_frame();
+        nes.run_frame().unwrap();
         
         // Get audio samples
         let samples = nes.bus.apu.get_samples();
@@ -195,4 +195,67 @@ fn test_apu_direct() {
     for sample in &samples {
         assert!(*sample >= -1.0 && *sample <= 1.0, "Samples should be normalized");
     }
+}
+
+#[test]
+fn test_run_frames_matches_single_frames() {
+    let rom_data = create_audio_test_rom();
+    let mut single = Nes::new();
+    single.load_cartridge(Cartridge::from_ines(Cursor::new(rom_data.clone())).unwrap());
+    let mut batched = Nes::new();
+    batched.load_cartridge(Cartridge::from_ines(Cursor::new(rom_data)).unwrap());
+    
+    let mut expected = Vec::new();
+    for _ in 0..3 {
+        single.run_frame().unwrap();
+        expected.extend(single.bus.apu.get_samples());
+    }
+    
+    let produced = batched.run_frames(3).unwrap();
+    let samples = batched.bus.apu.get_samples();
+    assert_eq!(produced, samples.len() as u64);
+    assert_eq!(samples, expected);
+    assert_eq!(batched.get_framebuffer(), single.get_framebuffer());
+    
+    assert!(Nes::new().run_frames(1).is_err());
+}
+
+#[test]
+fn test_sample_rate_48k() {
+    let rom_data = create_audio_test_rom();
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines(Cursor::new(rom_data)).unwrap());
+    nes.apu_mut().set_sample_rate(48000);
+    assert_eq!(nes.apu().sample_rate(), 48000);
+    
+    // 48000 samples per second of emulated CPU time
+    let produced = nes.run_frames(60).unwrap();
+    let expected = 48000.0 * nes.clock.cpu_cycles as f64 / 1_789_773.0;
+    assert!((produced as f64 - expected).abs() < 2.0, "{} samples, expected {}", produced, expected);
+    assert_eq!(nes.apu_mut().get_samples().len() as u64, produced);
+}
+
+#[test]
+fn test_audio_disabled_keeps_apu_state() {
+    let rom_data = create_audio_test_rom();
+    let mut with_audio = Nes::new();
+    with_audio.load_cartridge(Cartridge::from_ines(Cursor::new(rom_data.clone())).unwrap());
+    let mut silent = Nes::new();
+    silent.load_cartridge(Cartridge::from_ines(Cursor::new(rom_data)).unwrap());
+    silent.set_audio_enabled(false);
+    assert!(with_audio.audio_enabled());
+    
+    for _ in 0..10 {
+        with_audio.run_frame().unwrap();
+        silent.run_frame().unwrap();
+        assert_eq!(silent.last_frame_stats().audio_samples, 0);
+        assert_eq!(silent.apu().read_register(0x4015), with_audio.apu().read_register(0x4015));
+        assert_eq!(silent.apu().debug_info(), with_audio.apu().debug_info());
+        assert_eq!(silent.clock, with_audio.clock);
+    }
+    assert!(silent.apu_mut().get_samples().is_empty());
+    
+    silent.set_audio_enabled(true);
+    silent.run_frame().unwrap();
+    assert!(silent.last_frame_stats().audio_samples > 0);
 }
\ No newline at end of file
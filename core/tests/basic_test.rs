@@ -1,4 +1,4 @@
-use ccnes_core::{Cartridge, Nes};
+use ccnes_core::{Cartridge, Clock, FrameStats, Nes, NesError, VideoSink};
 
 #[test]
 fn test_basic_rom_execution() {
@@ -15,7 +15,7 @@ fn test_basic_rom_execution() {
     
     // Run for a few frames
     for _ in 0..5 {
-        nes.run_frame();
+        nes.run_frame().unwrap();
     }
     
     // Check that PPU has non-zero framebuffer (background color set)
@@ -31,6 +31,171 @@ fn test_basic_rom_execution() {
     assert_eq!(nes.bus.ppu.get_ctrl() & 0x80, 0x80, "NMI should be enabled");
 }
 
+#[test]
+fn test_run_without_cartridge() {
+    let mut nes = Nes::new();
+    assert!(!nes.is_ready());
+    assert!(matches!(nes.run_frame(), Err(NesError::NoCartridge)));
+    assert!(matches!(nes.step(), Err(NesError::NoCartridge)));
+    assert_eq!(nes.cpu.cycles, 0, "Nothing should have run");
+    
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert!(nes.is_ready());
+    assert!(nes.run_frame().is_ok());
+}
+
+#[test]
+fn test_component_accessors() {
+    let mut nes = Nes::new();
+    assert!(nes.cartridge().is_none());
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert_eq!(nes.cartridge().map(|cartridge| cartridge.get_mapper_number()), Some(0));
+    nes.run_frame().unwrap();
+    
+    // The accessors see the same components as the public fields
+    assert_eq!(nes.cpu().cycles, nes.cpu.cycles);
+    assert_eq!(nes.bus().get_ram(), nes.bus.get_ram());
+    assert_eq!(nes.ppu().get_ctrl(), nes.bus.ppu.get_ctrl());
+    assert_eq!(nes.apu().debug_info().frame_cycle, nes.bus.apu.debug_info().frame_cycle);
+    
+    nes.cpu_mut().pc = 0x1234;
+    assert_eq!(nes.cpu.pc, 0x1234);
+    nes.bus_mut().set_ram(&[0x5A; 0x800]);
+    assert_eq!(nes.bus.get_ram()[0x10], 0x5A);
+    nes.ppu_mut().set_warming_up(true);
+    assert!(nes.bus.ppu.warming_up());
+    nes.apu_mut().write_register(0x4017, 0x80);
+    assert!(nes.bus.apu.debug_info().five_step_mode);
+    assert!(nes.cartridge_mut().is_some());
+}
+
+#[test]
+fn test_frame_stats() {
+    let mut nes = Nes::new();
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert_eq!(nes.last_frame_stats(), FrameStats::default());
+    
+    nes.run_frame().unwrap();
+    let stats = nes.last_frame_stats();
+    assert!(stats.cpu_cycles >= 29780 && stats.cpu_cycles < 29780 + 8);
+    assert_eq!(stats.ppu_dots, stats.cpu_cycles * 3);
+    // Every instruction takes 2-7 cycles
+    assert!(stats.instructions as u64 >= stats.cpu_cycles / 7);
+    assert!(stats.instructions as u64 <= stats.cpu_cycles / 2);
+    assert!(stats.audio_samples > 0);
+    
+    // The sample count matches what the frame left in the APU buffer
+    nes.bus.apu.get_samples();
+    nes.run_frame().unwrap();
+    assert_eq!(nes.last_frame_stats().audio_samples, nes.bus.apu.get_samples().len() as u64);
+}
+
+#[test]
+fn test_frame_stats_count_interrupts() {
+    let mut nes = Nes::new();
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    
+    // The ROM waits two vblanks before turning on NMI
+    nes.run_frame().unwrap();
+    assert_eq!(nes.last_frame_stats().nmi_count, 0);
+    for _ in 0..3 {
+        nes.run_frame().unwrap();
+    }
+    
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+        let stats = nes.last_frame_stats();
+        assert_eq!(stats.nmi_count, 1);
+        assert_eq!(stats.irq_count, 0);
+    }
+    assert!(nes.cpu.nmi_count() >= 5);
+}
+
+#[test]
+fn test_run_frame_with_sinks() {
+    struct FrameCounter(usize);
+    impl VideoSink for FrameCounter {
+        fn present(&mut self, frame: &[u32]) {
+            assert_eq!(frame.len(), 256 * 240);
+            self.0 += 1;
+        }
+    }
+    
+    let mut nes = Nes::new();
+    let mut frames = FrameCounter(0);
+    let mut audio = Vec::new();
+    assert!(matches!(nes.run_frame_with(&mut frames, &mut audio), Err(NesError::NoCartridge)));
+    
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    for _ in 0..3 {
+        nes.run_frame_with(&mut frames, &mut audio).unwrap();
+    }
+    assert_eq!(frames.0, 3);
+    assert!(!audio.is_empty());
+    assert!(nes.bus.apu.get_samples().is_empty(), "Samples go to the sink");
+    
+    let mut video = Vec::new();
+    audio.clear();
+    nes.run_frame_with(&mut video, &mut audio).unwrap();
+    assert_eq!(video, nes.get_framebuffer());
+    assert_eq!(audio.len() as u64, nes.last_frame_stats().audio_samples);
+    nes.run_frame_with(&mut (), &mut ()).unwrap();
+}
+
+#[test]
+fn test_clock_ratios() {
+    let mut nes = Nes::new();
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert_eq!(nes.clock(), Clock::default());
+    
+    for frame in 1..=3 {
+        nes.run_frame().unwrap();
+        let clock = nes.clock();
+        assert_eq!(clock.frames, frame);
+        assert_eq!(clock.ppu_cycles, clock.cpu_cycles * 3);
+        assert_eq!(clock.apu_cycles, clock.cpu_cycles);
+    }
+    
+    nes.reset();
+    assert_eq!(nes.clock(), Clock::default());
+}
+
+#[test]
+fn test_idle_skip() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut plain = Nes::new();
+    let mut skipping = Nes::new();
+    plain.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    skipping.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    skipping.set_idle_skip(true);
+    assert!(!plain.idle_skip());
+    
+    for frame in 0..7 {
+        plain.run_frame().unwrap();
+        skipping.run_frame().unwrap();
+        assert_eq!(skipping.clock(), plain.clock());
+        
+        // After waiting out two vblanks the test ROM parks in `JMP *`
+        if frame < 4 {
+            continue;
+        }
+        let plain_stats = plain.last_frame_stats();
+        let skipping_stats = skipping.last_frame_stats();
+        assert!(skipping_stats.instructions * 10 < plain_stats.instructions,
+            "{} vs {} instructions", skipping_stats.instructions, plain_stats.instructions);
+        assert_eq!(skipping_stats.cpu_cycles, plain_stats.cpu_cycles);
+        assert_eq!(skipping.cpu.pc, plain.cpu.pc);
+        assert_eq!(skipping.cpu.cycles, plain.cpu.cycles);
+        assert_eq!(skipping.get_framebuffer(), plain.get_framebuffer());
+    }
+}
+
 #[test] 
 fn test_cpu_basic_instructions() {
     use ccnes_core::Cpu;
@@ -84,4 +249,201 @@ fn test_cpu_basic_instructions() {
     let cycles_before = cpu.cycles;
     cpu.step(&mut bus);
     assert_eq!(cpu.cycles - cycles_before, 2);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_was_rendering_this_frame() {
+    use ccnes_core::cpu::CpuBus;
+    
+    let mut nes = Nes::new();
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    
+    // Rendering stays off while the ROM waits for the PPU to warm up
+    nes.run_frame().unwrap();
+    assert!(!nes.was_rendering_this_frame());
+    
+    for _ in 0..4 {
+        nes.run_frame().unwrap();
+    }
+    assert!(nes.was_rendering_this_frame());
+    
+    // Blank the screen from the next frame on
+    nes.bus.write(0x2001, 0x00);
+    nes.run_frame().unwrap();
+    assert!(!nes.was_rendering_this_frame());
+}
+
+#[test]
+fn test_reset_vs_power_cycle() {
+    use ccnes_core::cpu::CpuBus;
+    
+    let mut nes = Nes::new();
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    
+    let set_up = |nes: &mut Nes| {
+        nes.bus.write(0x0300, 0x55);
+        nes.ppu_mut().palette[1] = 0x21;
+        nes.bus.write(0x4015, 0x01);
+        nes.bus.write(0x4003, 0x08);
+        assert_eq!(nes.bus.read(0x4015) & 0x01, 0x01);
+    };
+    
+    // Reset button: RAM and palette survive, PPUCTRL and the channels don't
+    set_up(&mut nes);
+    nes.reset();
+    assert_eq!(nes.bus.read(0x0300), 0x55);
+    assert_eq!(nes.ppu().palette[1], 0x21);
+    assert_eq!(nes.ppu().get_ctrl(), 0);
+    assert_eq!(nes.bus.read(0x4015) & 0x01, 0);
+    assert_eq!(nes.cpu.pc, 0xC000);
+    
+    // Power cycle: everything comes back as from power on
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    set_up(&mut nes);
+    nes.power_cycle();
+    assert_eq!(nes.bus.read(0x0300), 0x00);
+    assert_eq!(nes.ppu().palette[1], 0x00);
+    assert_eq!(nes.ppu().get_ctrl(), 0);
+    assert_eq!(nes.bus.read(0x4015) & 0x01, 0);
+    assert_eq!(nes.cpu.pc, 0xC000);
+    assert_eq!(nes.clock, Clock::default());
+    
+    // And the game boots again
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    assert!(nes.was_rendering_this_frame());
+}
+
+#[test]
+fn test_run_partial_frame() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut whole = Nes::new();
+    let mut partial = Nes::new();
+    whole.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    partial.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    
+    assert!(!partial.run_partial_frame(0.0).unwrap());
+    assert_eq!(partial.clock().cpu_cycles, 0);
+    
+    assert!(!partial.run_partial_frame(0.5).unwrap());
+    let cycles = partial.clock().cpu_cycles;
+    assert!((14890..14890 + 8).contains(&cycles), "{} cycles", cycles);
+    
+    // Quarter steps land on the same frame boundary as run_frame
+    for _ in 0..3 {
+        whole.run_frame().unwrap();
+        let mut calls = 0;
+        while !partial.run_partial_frame(0.25).unwrap() {
+            calls += 1;
+            assert!(calls < 8);
+        }
+        assert_eq!(partial.clock(), whole.clock());
+        assert_eq!(partial.get_framebuffer(), whole.get_framebuffer());
+    }
+    
+    assert!(Nes::new().run_partial_frame(0.5).is_err());
+}
+
+#[test]
+fn test_frame_skip_keeps_game_state() {
+    let rom_data = ccnes_core::test_rom::create_sprite_test_rom();
+    let mut plain = Nes::new();
+    let mut skipping = Nes::new();
+    plain.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    skipping.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    skipping.set_frame_skip(2);
+    assert_eq!(skipping.frame_skip(), 2);
+    
+    let mut last_drawn = skipping.get_framebuffer().to_vec();
+    for frame in 0..12 {
+        plain.run_frame().unwrap();
+        skipping.run_frame().unwrap();
+        
+        // The game can't tell the difference
+        assert_eq!(skipping.clock(), plain.clock());
+        assert_eq!((skipping.cpu.pc, skipping.cpu.a, skipping.cpu.status), (plain.cpu.pc, plain.cpu.a, plain.cpu.status));
+        assert_eq!(skipping.bus.get_ram(), plain.bus.get_ram());
+        assert_eq!(skipping.bus.apu.get_samples(), plain.bus.apu.get_samples());
+        
+        let drawn = skipping.last_frame_stats().drawn;
+        assert_eq!(drawn, frame % 3 == 0, "frame {}", frame);
+        assert!(plain.last_frame_stats().drawn);
+        if drawn {
+            assert_eq!(skipping.get_framebuffer(), plain.get_framebuffer(), "frame {}", frame);
+            last_drawn = skipping.get_framebuffer().to_vec();
+        } else {
+            assert_eq!(skipping.get_framebuffer(), &last_drawn[..], "frame {}", frame);
+        }
+    }
+    
+    skipping.set_frame_skip(0);
+    skipping.run_frame().unwrap();
+    plain.run_frame().unwrap();
+    assert_eq!(skipping.get_framebuffer(), plain.get_framebuffer());
+}
+
+#[test]
+fn test_step_scanline() {
+    let mut nes = Nes::new();
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert!(Nes::new().step_scanline().is_err());
+    
+    // Get past the ROM's vblank waits so NMI is on, and onto line 0
+    for _ in 0..4 {
+        nes.run_frame().unwrap();
+    }
+    let line = nes.step_scanline().unwrap();
+    assert_eq!(line.scanline, 0);
+    assert_eq!(nes.ppu().scanline(), 1);
+    
+    // 262 lines later the frame has wrapped back around to line 1
+    let (frames, cycles) = (nes.clock().frames, nes.clock().cpu_cycles);
+    let mut nmi_lines = Vec::new();
+    for expected in 1..=262 {
+        let step = nes.step_scanline().unwrap();
+        assert_eq!(step.scanline, expected % 262);
+        assert!(!step.irq);
+        if step.nmi {
+            nmi_lines.push(step.scanline);
+        }
+    }
+    assert_eq!(nes.ppu().scanline(), 1);
+    assert_eq!(nes.clock().frames, frames + 1);
+    let cycles = nes.clock().cpu_cycles - cycles;
+    assert!((29770..29790).contains(&cycles), "{} cycles", cycles);
+    
+    // The NMI at the start of vblank is taken on line 241
+    assert_eq!(nmi_lines, [241]);
+}
+
+#[test]
+fn test_ppu_warmup() {
+    use ccnes_core::cpu::CpuBus;
+    
+    let mut nes = Nes::new();
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    
+    // PPUCTRL writes are dropped until the first pre-render line
+    assert!(nes.ppu().warming_up());
+    nes.bus.write(0x2000, 0x80);
+    assert!(!nes.ppu().get_nmi_output());
+    nes.skip_ppu_warmup();
+    nes.bus.write(0x2000, 0x80);
+    assert!(nes.ppu().get_nmi_output());
+    
+    // A reset starts it over, and a frame is enough to get through it
+    nes.reset();
+    assert!(nes.ppu().warming_up());
+    nes.run_frame().unwrap();
+    assert!(!nes.ppu().warming_up());
+}
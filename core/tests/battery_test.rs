@@ -0,0 +1,135 @@
+use ccnes_core::cartridge::Cartridge;
+use ccnes_core::cpu::CpuBus;
+use ccnes_core::{BatterySave, Emulator, EmulatorConfig};
+use tempfile::TempDir;
+
+// MMC3 with battery-backed PRG RAM; the reset vector points at a JMP to itself
+fn create_battery_rom() -> Vec<u8> {
+    let mut rom_data = vec![0; 16 + 2 * 0x4000 + 0x2000];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 2;
+    rom_data[5] = 1;
+    rom_data[6] = 0x40 | 0x02;
+
+    let prg_end = 16 + 2 * 0x4000;
+    rom_data[prg_end - 0x4000..prg_end - 0x4000 + 3].copy_from_slice(&[0x4C, 0x00, 0xC0]);
+    rom_data[prg_end - 4..prg_end - 2].copy_from_slice(&[0x00, 0xC0]);
+    rom_data
+}
+
+#[test]
+fn test_write_sets_dirty_and_flush_clears_it() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("game.sav");
+    let mut cartridge = Cartridge::from_ines_bytes(&create_battery_rom()).unwrap();
+    let mut battery = BatterySave::new(&path, 3);
+
+    assert!(cartridge.has_battery());
+    assert!(!cartridge.battery_ram_dirty());
+
+    cartridge.write_prg(0x6010, 0x42);
+    assert!(cartridge.battery_ram_dirty());
+
+    assert!(battery.flush(&mut cartridge).unwrap());
+    assert!(!cartridge.battery_ram_dirty());
+    assert!(!battery.flush(&mut cartridge).unwrap(), "Nothing changed since");
+
+    let data = std::fs::read(&path).unwrap();
+    assert_eq!(data[0x10], 0x42);
+    assert!(!dir.path().join("game.sav.tmp").exists());
+}
+
+#[test]
+fn test_periodic_flush_waits_for_quiet_frames() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("game.sav");
+    let mut cartridge = Cartridge::from_ines_bytes(&create_battery_rom()).unwrap();
+    let mut battery = BatterySave::new(&path, 3);
+
+    cartridge.write_prg(0x6000, 1);
+    assert!(!battery.end_frame(&mut cartridge).unwrap());
+    assert!(!battery.end_frame(&mut cartridge).unwrap());
+
+    // Another write restarts the countdown
+    cartridge.write_prg(0x6000, 2);
+    for _ in 0..3 {
+        assert!(!battery.end_frame(&mut cartridge).unwrap());
+    }
+    assert!(!path.exists());
+
+    assert!(battery.end_frame(&mut cartridge).unwrap());
+    assert_eq!(std::fs::read(&path).unwrap()[0], 2);
+    assert!(!battery.end_frame(&mut cartridge).unwrap());
+}
+
+#[test]
+fn test_load_restores_prg_ram() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("game.sav");
+    let mut battery = BatterySave::new(&path, 3);
+
+    let mut cartridge = Cartridge::from_ines_bytes(&create_battery_rom()).unwrap();
+    assert!(!battery.load(&mut cartridge).unwrap(), "No save yet");
+    cartridge.write_prg(0x7FFF, 0x99);
+    battery.flush(&mut cartridge).unwrap();
+
+    let mut cartridge = Cartridge::from_ines_bytes(&create_battery_rom()).unwrap();
+    assert!(battery.load(&mut cartridge).unwrap());
+    assert_eq!(cartridge.read_prg(0x7FFF), 0x99);
+    assert!(!cartridge.battery_ram_dirty());
+}
+
+#[test]
+fn test_no_battery_no_save() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("game.sav");
+    let mut rom_data = create_battery_rom();
+    rom_data[6] &= !0x02;
+    let mut cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    let mut battery = BatterySave::new(&path, 3);
+
+    cartridge.write_prg(0x6000, 1);
+    assert!(!cartridge.battery_ram_dirty());
+    assert!(!battery.flush(&mut cartridge).unwrap());
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_emulator_saves_on_drop() {
+    let dir = TempDir::new().unwrap();
+    let config = EmulatorConfig {
+        save_dir: dir.path().to_path_buf(),
+        ..EmulatorConfig::default()
+    };
+
+    let mut emulator = Emulator::new(config.clone());
+    emulator.load_rom_bytes(&create_battery_rom(), "game").unwrap();
+    assert_eq!(emulator.battery_path(), Some(dir.path().join("game.sav").as_path()));
+    emulator.run_frame().unwrap();
+    emulator.nes_mut().bus.write(0x6000, 0x5A);
+    emulator.run_frame().unwrap();
+    drop(emulator);
+
+    let mut emulator = Emulator::new(config);
+    emulator.load_rom_bytes(&create_battery_rom(), "game").unwrap();
+    assert_eq!(emulator.nes_mut().bus.read(0x6000), 0x5A);
+}
+
+#[test]
+fn test_mmc1_battery_ram() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("game.sav");
+    let mut rom_data = create_battery_rom();
+    rom_data[6] = 0x10 | 0x02;
+    let mut cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    let mut battery = BatterySave::new(&path, 3);
+
+    cartridge.write_prg(0x7FFF, 0x42);
+    assert_eq!(cartridge.read_prg(0x7FFF), 0x42);
+    assert!(cartridge.battery_ram_dirty());
+    assert!(battery.flush(&mut cartridge).unwrap());
+
+    let mut reloaded = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    assert!(battery.load(&mut reloaded).unwrap());
+    assert_eq!(reloaded.read_prg(0x7FFF), 0x42);
+}
@@ -1,5 +1,5 @@
 use ccnes_core::nes::Nes;
-use ccnes_core::cartridge::Cartridge;
+use ccnes_core::cartridge::{Cartridge, Mirroring};
 use ccnes_core::cpu::CpuBus;
 
 #[test]
@@ -151,4 +151,174 @@ fn test_mapper66_bus_conflicts() {
     nes.bus.write(0x8001, 0x22); // PRG bank 2, CHR bank 2
     
     assert!(true);
-}
\ No newline at end of file
+}
+
+// UxROM with each 16KB bank filled with its own number and a $01 at $C010
+fn create_uxrom(banks: usize, submapper: u8) -> Vec<u8> {
+    let mut rom_data = vec![0; 16 + banks * 0x4000];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = banks as u8;
+    rom_data[6] = 0x20;
+    rom_data[7] = 0x08; // NES 2.0
+    rom_data[8] = submapper << 4;
+    
+    for bank in 0..banks {
+        rom_data[16 + bank * 0x4000..16 + (bank + 1) * 0x4000].fill(bank as u8);
+    }
+    rom_data[16 + (banks - 1) * 0x4000 + 0x10] = 0x01;
+    rom_data
+}
+
+#[test]
+fn test_uxrom_256k_switches_to_highest_bank() {
+    let cartridge = Cartridge::from_ines(&create_uxrom(16, 0)[..]).unwrap();
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    
+    assert_eq!(nes.bus.read(0x8000), 0);
+    assert_eq!(nes.bus.read(0xC000), 15);
+    
+    nes.bus.write(0x8000, 0x0F);
+    assert_eq!(nes.bus.read(0x8000), 15);
+    assert_eq!(nes.bus.read(0xBFFF), 15);
+    
+    // Bits above the 16 banks are ignored
+    nes.bus.write(0x8000, 0x13);
+    assert_eq!(nes.bus.read(0x8000), 3);
+    assert_eq!(nes.bus.read(0xFFFF), 15);
+}
+
+#[test]
+fn test_uxrom_128k_masks_bank() {
+    let cartridge = Cartridge::from_ines(&create_uxrom(8, 0)[..]).unwrap();
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    
+    nes.bus.write(0x8000, 0x0E);
+    assert_eq!(nes.bus.read(0x8000), 6);
+    assert_eq!(nes.bus.read(0xC000), 7);
+}
+
+#[test]
+fn test_uxrom_bus_conflicts_follow_submapper() {
+    // Submapper 1: no conflicts, the $01 under $C010 doesn't matter
+    let cartridge = Cartridge::from_ines(&create_uxrom(16, 1)[..]).unwrap();
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    nes.bus.write(0xC010, 0x03);
+    assert_eq!(nes.bus.read(0x8000), 3);
+    
+    // Submapper 2: the written value is ANDed with the ROM byte
+    let cartridge = Cartridge::from_ines(&create_uxrom(16, 2)[..]).unwrap();
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    nes.bus.write(0xC010, 0x03);
+    assert_eq!(nes.bus.read(0x8000), 1);
+    nes.bus.write(0xC000, 0x0B);
+    assert_eq!(nes.bus.read(0x8000), 0x0B);
+}
+
+// CNROM with 32KB of $FF PRG except a $00 at $8010, and each 8KB CHR bank
+// filled with its own number
+fn create_cnrom(chr_banks: usize, submapper: u8) -> Vec<u8> {
+    let mut rom_data = vec![0xFF; 16 + 0x8000 + chr_banks * 0x2000];
+    rom_data[0..16].fill(0);
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 2;
+    rom_data[5] = chr_banks as u8;
+    rom_data[6] = 0x30;
+    rom_data[7] = 0x08; // NES 2.0
+    rom_data[8] = submapper << 4;
+    rom_data[16 + 0x10] = 0x00;
+    
+    for bank in 0..chr_banks {
+        let start = 16 + 0x8000 + bank * 0x2000;
+        rom_data[start..start + 0x2000].fill(bank as u8);
+    }
+    rom_data
+}
+
+#[test]
+fn test_cnrom_selects_and_wraps_chr_banks() {
+    let mut cartridge = Cartridge::from_ines(&create_cnrom(2, 0)[..]).unwrap();
+    assert_eq!(cartridge.read_chr(0x0000), 0);
+    
+    cartridge.write_prg(0x8000, 0x01);
+    assert_eq!(cartridge.read_chr(0x0000), 1);
+    assert_eq!(cartridge.read_chr(0x1FFF), 1);
+    
+    // Only two banks: 2 wraps to 0 and 3 to 1
+    cartridge.write_prg(0x8000, 0x02);
+    assert_eq!(cartridge.read_chr(0x0123), 0);
+    cartridge.write_prg(0x8000, 0x03);
+    assert_eq!(cartridge.read_chr(0x0123), 1);
+}
+
+#[test]
+fn test_cnrom_bus_conflicts_follow_submapper() {
+    // Submapper 2: the $00 under $8010 wins over the written bank number
+    let mut cartridge = Cartridge::from_ines(&create_cnrom(4, 2)[..]).unwrap();
+    cartridge.write_prg(0x8010, 0x03);
+    assert_eq!(cartridge.read_chr(0x0000), 0);
+    
+    // Submapper 1 and unspecified boards have no conflicts
+    for submapper in [0, 1] {
+        let mut cartridge = Cartridge::from_ines(&create_cnrom(4, submapper)[..]).unwrap();
+        cartridge.write_prg(0x8010, 0x03);
+        assert_eq!(cartridge.read_chr(0x0000), 3);
+    }
+}
+
+// AxROM with each 32KB bank filled with $E0 plus its number, and $00/$FF
+// at $8010/$8011 of bank 0
+fn create_axrom(banks: usize, submapper: u8) -> Vec<u8> {
+    let mut rom_data = vec![0; 16 + banks * 0x8000];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = (banks * 2) as u8;
+    rom_data[6] = 0x70;
+    rom_data[7] = 0x08; // NES 2.0
+    rom_data[8] = submapper << 4;
+    
+    for bank in 0..banks {
+        rom_data[16 + bank * 0x8000..16 + (bank + 1) * 0x8000].fill(0xE0 | bank as u8);
+    }
+    rom_data[16 + 0x10] = 0x00;
+    rom_data[16 + 0x11] = 0xFF;
+    rom_data
+}
+
+#[test]
+fn test_axrom_switches_32k_banks() {
+    let cartridge = Cartridge::from_ines(&create_axrom(8, 0)[..]).unwrap();
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    assert_eq!(nes.bus.read(0x8000), 0xE0);
+    
+    // The whole $8000-$FFFF window follows the bank, vectors included
+    nes.bus.write(0x8000, 0x05);
+    assert_eq!(nes.bus.read(0x8000), 0xE5);
+    assert_eq!(nes.bus.read(0xC000), 0xE5);
+    assert_eq!(nes.bus.read(0xFFFC), 0xE5);
+    
+    // 256KB: bank 9 wraps to 1, and bit 4 is the nametable page
+    nes.bus.write(0x8000, 0x19);
+    assert_eq!(nes.bus.read(0xFFFF), 0xE1);
+}
+
+#[test]
+fn test_axrom_bus_conflicts_follow_submapper() {
+    // ANROM/AOROM: no conflicts, the $00 under $8010 doesn't matter
+    let mut cartridge = Cartridge::from_ines(&create_axrom(4, 1)[..]).unwrap();
+    cartridge.write_prg(0x8010, 0x13);
+    assert_eq!(cartridge.read_prg(0x9000), 0xE3);
+    assert_eq!(cartridge.mirroring(), Mirroring::SingleScreenHigh);
+    
+    // AMROM: the written value is ANDed with the ROM byte
+    let mut cartridge = Cartridge::from_ines(&create_axrom(4, 2)[..]).unwrap();
+    cartridge.write_prg(0x8010, 0x13);
+    assert_eq!(cartridge.read_prg(0x9000), 0xE0);
+    assert_eq!(cartridge.mirroring(), Mirroring::SingleScreenLow);
+    cartridge.write_prg(0x8011, 0x12);
+    assert_eq!(cartridge.read_prg(0x9000), 0xE2);
+    assert_eq!(cartridge.mirroring(), Mirroring::SingleScreenHigh);
+}
@@ -0,0 +1,341 @@
+use ccnes_core::cartridge::{supported_mappers, Cartridge, CartridgeError, HeaderInfo, RomFormat};
+use ccnes_core::{Nes, NesError};
+use ccnes_core::Region;
+
+fn create_rom(mapper: u8, prg_banks: u8, chr_banks: u8) -> Vec<u8> {
+    let size = 16 + prg_banks as usize * 0x4000 + chr_banks as usize * 0x2000;
+    let mut rom_data = vec![0; size];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = prg_banks;
+    rom_data[5] = chr_banks;
+    rom_data[6] = mapper << 4;
+    rom_data[7] = mapper & 0xF0;
+    rom_data
+}
+
+#[test]
+fn test_mapper_names() {
+    let expected = [
+        (0, "NROM"),
+        (1, "MMC1"),
+        (2, "UxROM"),
+        (3, "CNROM"),
+        (4, "MMC3"),
+        (5, "MMC5"),
+        (7, "AxROM"),
+        (9, "MMC2"),
+        (11, "Color Dreams"),
+        (66, "GxROM"),
+    ];
+    
+    for (mapper, name) in expected {
+        let cartridge = Cartridge::from_ines_bytes(&create_rom(mapper, 2, 1)).unwrap();
+        assert_eq!(cartridge.get_mapper_number(), mapper);
+        assert_eq!(cartridge.mapper_name(), name, "mapper {}", mapper);
+    }
+}
+
+#[test]
+fn test_supported_mappers() {
+    let supported = supported_mappers();
+    assert!(supported.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    
+    // The list is exactly what the loader accepts
+    for mapper in 0..=255u8 {
+        let name = supported.iter().find(|(number, _)| *number == mapper).map(|(_, name)| *name);
+        match (Cartridge::from_ines_bytes(&create_rom(mapper, 2, 1)), name) {
+            (Ok(cartridge), Some(name)) => assert_eq!(cartridge.mapper_name(), name),
+            (Err(CartridgeError::UnsupportedMapper(number)), None) => assert_eq!(number, mapper),
+            (result, name) => panic!("mapper {}: {:?} vs {:?}", mapper, result.map(|_| ()), name),
+        }
+    }
+}
+
+#[test]
+fn test_rom_sizes() {
+    let cartridge = Cartridge::from_ines_bytes(&create_rom(4, 16, 16)).unwrap();
+    assert_eq!(cartridge.prg_rom_size(), 256 * 1024);
+    assert_eq!(cartridge.chr_rom_size(), 128 * 1024);
+    
+    // CHR RAM board
+    let cartridge = Cartridge::from_ines_bytes(&create_rom(2, 8, 0)).unwrap();
+    assert_eq!(cartridge.chr_rom_size(), 0);
+}
+
+#[test]
+fn test_header_info() {
+    let mut rom_data = create_rom(4, 16, 16);
+    rom_data[6] |= 0x02; // Battery
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    assert_eq!(cartridge.header_info(), HeaderInfo {
+        mapper_number: 4,
+        mapper_name: "MMC3",
+        prg_rom_size: 256 * 1024,
+        chr_rom_size: 128 * 1024,
+        prg_ram_size: 0x2000,
+        battery: true,
+        four_screen: false,
+        region: None,
+    });
+}
+
+#[test]
+fn test_playchoice_dump() {
+    let mut rom_data = create_rom(1, 2, 1);
+    rom_data[7] |= 0x02;
+    let chr_end = rom_data.len();
+    rom_data[chr_end - 1] = 0xAB;
+    // 8KB INST-ROM, then the PROM data and counter out
+    rom_data.resize(chr_end + 0x2000 + 32, 0xEE);
+    
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    assert!(cartridge.is_playchoice());
+    assert_eq!(cartridge.get_mapper_number(), 1);
+    assert_eq!(cartridge.prg_rom_size(), 32 * 1024);
+    assert_eq!(cartridge.chr_rom_size(), 8 * 1024);
+    assert_eq!(cartridge.read_chr(0x1FFF), 0xAB, "INST-ROM must not shift CHR");
+    
+    let plain = Cartridge::from_ines_bytes(&create_rom(1, 2, 1)).unwrap();
+    assert!(!plain.is_playchoice());
+}
+
+#[test]
+fn test_header_padding_garbage() {
+    // MMC1 dump with "DiskDude!" over bytes 7-15
+    let mut rom_data = create_rom(1, 2, 1);
+    rom_data[7..16].copy_from_slice(b"DiskDude!");
+    
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    assert_eq!(cartridge.get_mapper_number(), 1);
+    assert!(!cartridge.is_playchoice());
+}
+
+#[test]
+fn test_region_hint() {
+    let hint = |rom: &[u8]| Cartridge::from_ines_bytes(rom).unwrap().region_hint();
+    
+    let mut rom = create_rom(0, 1, 1);
+    assert_eq!(hint(&rom), None, "iNES zeros don't mean NTSC");
+    rom[9] = 0x01;
+    assert_eq!(hint(&rom), Some(Region::Pal));
+    rom[9] = 0;
+    rom[10] = 0x02;
+    assert_eq!(hint(&rom), Some(Region::Pal));
+    
+    // Ignored in a header with garbage padding
+    rom[12..16].copy_from_slice(b"Dude");
+    assert_eq!(hint(&rom), None);
+    
+    // NES 2.0: 0 NTSC, 1 PAL, 2 multi-region, 3 Dendy
+    let mut rom = create_rom(0, 1, 1);
+    rom[7] = 0x08;
+    for (timing, expected) in [(0, Some(Region::Ntsc)), (1, Some(Region::Pal)), (2, None), (3, Some(Region::Pal))] {
+        rom[12] = timing;
+        assert_eq!(hint(&rom), expected, "timing {}", timing);
+    }
+}
+
+#[test]
+fn test_region_detect_precedence() {
+    let plain = Cartridge::from_ines_bytes(&create_rom(0, 1, 1)).unwrap();
+    let mut rom = create_rom(0, 1, 1);
+    rom[9] = 0x01;
+    let pal = Cartridge::from_ines_bytes(&rom).unwrap();
+    
+    assert_eq!(Region::from_file_name("Elite (E).nes"), Some(Region::Pal));
+    assert_eq!(Region::from_file_name("Zelda (USA).nes"), Some(Region::Ntsc));
+    assert_eq!(Region::from_file_name("homebrew.nes"), None);
+    
+    assert_eq!(Region::detect(None, &plain, "homebrew.nes"), Region::Ntsc);
+    assert_eq!(Region::detect(None, &plain, "Elite (E).nes"), Region::Pal);
+    assert_eq!(Region::detect(None, &pal, "Zelda (USA).nes"), Region::Pal);
+    assert_eq!(Region::detect(Some(Region::Ntsc), &pal, "Elite (E).nes"), Region::Ntsc);
+}
+
+#[test]
+fn test_rom_format_detection() {
+    let ines = create_rom(0, 1, 1);
+    let mut nes2 = ines.clone();
+    nes2[7] |= 0x08;
+    let mut fds = b"FDS\x1A\x01".to_vec();
+    fds.resize(16 + 65500, 0);
+    let mut headerless_fds = b"\x01*NINTENDO-HVC*".to_vec();
+    headerless_fds.resize(65500, 0);
+    let mut unif = b"UNIF".to_vec();
+    unif.resize(32, 0);
+    
+    assert_eq!(RomFormat::detect(&ines), Some(RomFormat::INes));
+    assert_eq!(RomFormat::detect(&nes2), Some(RomFormat::Nes2));
+    assert_eq!(RomFormat::detect(&fds), Some(RomFormat::Fds));
+    assert_eq!(RomFormat::detect(&headerless_fds), Some(RomFormat::Fds));
+    assert_eq!(RomFormat::detect(&unif), Some(RomFormat::Unif));
+    assert_eq!(RomFormat::detect(b"PK\x03\x04"), None);
+    assert_eq!(RomFormat::detect(&[]), None);
+    
+    assert!(Cartridge::from_bytes(&ines).is_ok());
+    assert!(Cartridge::from_bytes(&nes2).is_ok());
+    assert!(matches!(
+        Cartridge::from_bytes(&fds),
+        Err(CartridgeError::UnsupportedFormat(RomFormat::Fds))
+    ));
+    assert!(matches!(Cartridge::from_bytes(&[0; 64]), Err(CartridgeError::UnknownFormat)));
+}
+
+#[test]
+fn test_nes_load_rom_dispatches() {
+    let mut nes = Nes::new();
+    nes.load_rom(&create_rom(2, 2, 0)).unwrap();
+    assert!(nes.is_ready());
+    assert_eq!(nes.bus.cartridge.as_ref().unwrap().mapper_name(), "UxROM");
+    
+    let mut headerless_fds = b"\x01*NINTENDO-HVC*".to_vec();
+    headerless_fds.resize(65500, 0);
+    let error = Nes::new().load_rom(&headerless_fds).unwrap_err();
+    assert!(matches!(error, NesError::Cartridge(CartridgeError::UnsupportedFormat(RomFormat::Fds))));
+    assert_eq!(error.to_string(), "FDS images aren't supported");
+    
+    let error = Nes::new().load_rom(b"not a rom").unwrap_err();
+    assert_eq!(error.to_string(), "Unrecognized ROM format");
+}
+
+fn unif_chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut chunk = id.to_vec();
+    chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(body);
+    chunk
+}
+
+fn create_unif(board: &str, chunks: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = b"UNIF".to_vec();
+    data.extend_from_slice(&7u32.to_le_bytes());
+    data.resize(32, 0);
+    data.extend(unif_chunk(b"MAPR", format!("{}\0", board).as_bytes()));
+    for chunk in chunks {
+        data.extend_from_slice(chunk);
+    }
+    data
+}
+
+#[test]
+fn test_unif_nrom() {
+    let mut prg = vec![0xEA; 0x8000];
+    // Reset vector at $8000
+    prg[0x7FFC] = 0x00;
+    prg[0x7FFD] = 0x80;
+    let chr = vec![0x55; 0x2000];
+    let unif = create_unif("NES-NROM-256", &[
+        unif_chunk(b"PRG0", &prg),
+        unif_chunk(b"CHR0", &chr),
+        unif_chunk(b"MIRR", &[1]),
+        unif_chunk(b"BATR", &[0]),
+        unif_chunk(b"TVCI", &[1]),
+    ]);
+    
+    let cartridge = Cartridge::from_bytes(&unif).unwrap();
+    assert_eq!(cartridge.get_mapper_number(), 0);
+    assert_eq!(cartridge.prg_rom_size(), 0x8000);
+    assert_eq!(cartridge.chr_rom_size(), 0x2000);
+    assert_eq!(cartridge.read_chr(0x1234), 0x55);
+    assert_eq!(cartridge.mirroring(), ccnes_core::cartridge::Mirroring::Vertical);
+    assert!(cartridge.has_battery());
+    assert_eq!(cartridge.region_hint(), Some(Region::Pal));
+    
+    let mut nes = Nes::new();
+    nes.load_rom(&unif).unwrap();
+    assert_eq!(nes.cpu.pc, 0x8000);
+}
+
+#[test]
+fn test_unif_banks_and_boards() {
+    // PRG1 follows PRG0 whatever order the chunks come in
+    let unif = create_unif("UNROM", &[
+        unif_chunk(b"PRG1", &[2; 0x4000]),
+        unif_chunk(b"PRG0", &[1; 0x4000]),
+    ]);
+    let mut cartridge = Cartridge::from_unif_bytes(&unif).unwrap();
+    assert_eq!(cartridge.mapper_name(), "UxROM");
+    assert_eq!(cartridge.read_prg(0x8000), 1);
+    assert_eq!(cartridge.read_prg(0xC000), 2);
+    
+    // No CHR chunk means CHR RAM
+    assert!(cartridge.has_chr_ram());
+    cartridge.write_chr(0x0010, 0x99);
+    assert_eq!(cartridge.read_chr(0x0010), 0x99);
+    
+    let amrom = create_unif("NES-AMROM", &[unif_chunk(b"PRG0", &[0; 0x8000])]);
+    let amrom = Cartridge::from_unif_bytes(&amrom).unwrap();
+    assert_eq!(amrom.get_mapper_number(), 7);
+    assert_eq!(ccnes_core::cartridge::board_mapper("NES-AMROM"), Some((7, 2)));
+    
+    assert_eq!(ccnes_core::cartridge::board_mapper("HVC-TLROM"), Some((4, 0)));
+    assert_eq!(ccnes_core::cartridge::board_mapper("MMC3"), Some((4, 0)));
+    assert_eq!(ccnes_core::cartridge::board_mapper("UNL-SOMETHING"), None);
+    
+    let unknown = create_unif("UNL-SOMETHING", &[unif_chunk(b"PRG0", &[0; 0x4000])]);
+    assert!(matches!(
+        Cartridge::from_bytes(&unknown),
+        Err(CartridgeError::UnsupportedBoard(board)) if board == "UNL-SOMETHING"
+    ));
+    
+    // A chunk running past the end of the file
+    let mut truncated = create_unif("NROM", &[unif_chunk(b"PRG0", &[0; 0x4000])]);
+    truncated.truncate(truncated.len() - 1);
+    assert!(matches!(Cartridge::from_bytes(&truncated), Err(CartridgeError::Truncated)));
+}
+
+#[test]
+fn test_prg_ram_size_from_header() {
+    // iNES 1.0 leaves it to the board
+    assert_eq!(Cartridge::from_ines_bytes(&create_rom(1, 2, 1)).unwrap().prg_ram_size(), 0x2000);
+    assert_eq!(Cartridge::from_ines_bytes(&create_rom(5, 2, 1)).unwrap().prg_ram_size(), 0x8000);
+    assert_eq!(Cartridge::from_ines_bytes(&create_rom(0, 2, 1)).unwrap().prg_ram_size(), 0);
+    
+    // Byte 8 counts 8KB units
+    let mut rom_data = create_rom(5, 2, 1);
+    rom_data[8] = 2;
+    assert_eq!(Cartridge::from_ines_bytes(&rom_data).unwrap().prg_ram_size(), 0x4000);
+    
+    // NES 2.0 adds the volatile and battery backed shift counts
+    let mut rom_data = create_rom(1, 2, 1);
+    rom_data[7] |= 0x08;
+    rom_data[10] = 0x77;
+    assert_eq!(Cartridge::from_ines_bytes(&rom_data).unwrap().prg_ram_size(), 0x4000);
+    rom_data[10] = 0x00;
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    assert_eq!(cartridge.prg_ram_size(), 0);
+    assert!(cartridge.prg_ram().is_none());
+    
+    let elrom = create_unif("NES-ELROM", &[unif_chunk(b"PRG0", &[0; 0x8000])]);
+    assert_eq!(Cartridge::from_unif_bytes(&elrom).unwrap().prg_ram_size(), 0);
+    let sorom = create_unif("NES-SOROM", &[unif_chunk(b"PRG0", &[0; 0x8000])]);
+    assert_eq!(Cartridge::from_unif_bytes(&sorom).unwrap().prg_ram_size(), 0x4000);
+}
+
+#[test]
+fn test_sxrom_prg_ram_banks() {
+    // NES 2.0 header asking for 32KB of battery backed PRG RAM
+    let mut rom_data = create_rom(1, 2, 0);
+    rom_data[6] |= 0x02;
+    rom_data[7] |= 0x08;
+    rom_data[10] = 0x90;
+    let mut cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    assert_eq!(cartridge.prg_ram_size(), 0x8000);
+    
+    // MMC1 serial write of a 5 bit value to a register
+    let write_register = |cartridge: &mut Cartridge, addr: u16, value: u8| {
+        for bit in 0..5 {
+            cartridge.write_prg(addr, (value >> bit) & 1);
+        }
+    };
+    // CHR bank 0 bits 2-3 pick the 8KB RAM bank
+    for bank in 0..4 {
+        write_register(&mut cartridge, 0xA000, bank << 2);
+        cartridge.write_prg(0x6000, 0x40 + bank);
+    }
+    for bank in 0..4 {
+        write_register(&mut cartridge, 0xA000, bank << 2);
+        assert_eq!(cartridge.read_prg(0x6000), 0x40 + bank);
+    }
+    let ram = cartridge.prg_ram().unwrap();
+    assert_eq!([ram[0], ram[0x2000], ram[0x4000], ram[0x6000]], [0x40, 0x41, 0x42, 0x43]);
+}
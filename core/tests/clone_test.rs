@@ -0,0 +1,50 @@
+use ccnes_core::{Cartridge, Nes};
+
+fn create_nes() -> Nes {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines(std::io::Cursor::new(rom_data))
+        .expect("Failed to load ROM");
+
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    nes
+}
+
+#[test]
+fn test_clone_runs_in_lockstep() {
+    let mut nes = create_nes();
+    for _ in 0..10 {
+        nes.run_frame().unwrap();
+    }
+
+    let mut rollback = nes.clone();
+    assert_eq!(rollback.cpu.pc, nes.cpu.pc);
+
+    // Same input on both copies must give the same output
+    for frame in 0..30u8 {
+        let input = frame.wrapping_mul(37);
+        nes.set_controller1(input);
+        rollback.set_controller1(input);
+        nes.run_frame().unwrap();
+        rollback.run_frame().unwrap();
+
+        assert_eq!(nes.get_framebuffer(), rollback.get_framebuffer(), "frame {} diverged", frame);
+        assert_eq!(nes.cpu.pc, rollback.cpu.pc);
+        assert_eq!(nes.clock.cpu_cycles, rollback.clock.cpu_cycles);
+    }
+}
+
+#[test]
+fn test_clone_is_independent() {
+    let mut nes = create_nes();
+    nes.run_frame().unwrap();
+
+    let snapshot = nes.clone();
+    let pc = snapshot.cpu.pc;
+    let cycles = snapshot.clock.cpu_cycles;
+
+    nes.run_frame().unwrap();
+    assert_eq!(snapshot.cpu.pc, pc);
+    assert_eq!(snapshot.clock.cpu_cycles, cycles);
+    assert_ne!(nes.clock.cpu_cycles, cycles);
+}
\ No newline at end of file
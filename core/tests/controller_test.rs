@@ -21,7 +21,7 @@ fn test_controller_input() {
     
     // Run for a bit to let the ROM read controller
     for _ in 0..100 {
-        nes.step();
+        nes.step().unwrap();
     }
     
     // The test ROM should store controller state in RAM
@@ -55,6 +55,57 @@ fn test_controller_strobe() {
     assert_eq!(result, 0xFF);
 }
 
+#[test]
+fn test_set_controllers_sets_both_ports() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    
+    let start = ControllerButton::START.bits();
+    let left_b = (ControllerButton::LEFT | ControllerButton::B).bits();
+    nes.set_controllers(start, left_b);
+    
+    nes.bus.write(0x4016, 0x01);
+    nes.bus.write(0x4016, 0x00);
+    let (mut port1, mut port2) = (0u8, 0u8);
+    for i in 0..8 {
+        port1 |= (nes.bus.read(0x4016) & 0x01) << i;
+        port2 |= (nes.bus.read(0x4017) & 0x01) << i;
+    }
+    
+    assert_eq!(port1, start);
+    assert_eq!(port2, left_b);
+}
+
+#[test]
+fn test_disconnected_controller_reads_idle() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines(std::io::Cursor::new(rom_data))
+        .expect("Failed to load ROM");
+    
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    assert!(nes.bus.controller_connected(0));
+    assert!(nes.bus.controller_connected(1));
+    
+    // Stale input on port 2 must not leak through once it's unplugged
+    nes.set_controller2(0xFF);
+    nes.set_controller_connected(1, false);
+    
+    nes.bus.write(0x4016, 0x01);
+    nes.bus.write(0x4016, 0x00);
+    
+    // A connected pad reports 1s after its 8 buttons; an empty port never does
+    for _ in 0..16 {
+        assert_eq!(nes.bus.read(0x4017), 0x00);
+    }
+    
+    nes.set_controller_connected(1, true);
+    nes.bus.write(0x4016, 0x01);
+    nes.bus.write(0x4016, 0x00);
+    assert_eq!(nes.bus.read(0x4017) & 0x01, 0x01);
+}
+
 #[test]
 fn test_controller_api() {
     let mut controller = Controller::new();
@@ -77,4 +128,81 @@ fn test_controller_api() {
     // Test clearing
     controller.clear();
     assert_eq!(controller.get_state(), 0x00);
+}
+
+#[test]
+fn test_run_script() {
+    let rom_data = ccnes_core::test_rom::create_controller_test_rom();
+    let script = [
+        (30, [0, 0]),
+        (2, [ControllerButton::START.bits(), 0]),
+        (10, [ControllerButton::A.bits() | ControllerButton::UP.bits(), 0]),
+    ];
+    
+    let mut nes = Nes::new();
+    assert!(nes.run_script(&script).is_err());
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    let framebuffer = nes.run_script(&script).unwrap().to_vec();
+    assert_eq!(framebuffer.len(), 256 * 240);
+    assert_eq!(nes.clock().frames, 42);
+    assert_eq!(nes.bus.get_controller1_state(), ControllerButton::A.bits() | ControllerButton::UP.bits());
+    
+    // Same script, same result
+    let mut replay = Nes::new();
+    replay.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert_eq!(replay.run_script(&script).unwrap(), &framebuffer[..]);
+    assert_eq!(replay.bus.get_ram(), nes.bus.get_ram());
+}
+
+#[test]
+fn test_famicom_microphone() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    nes.set_controller1(ControllerButton::A.bits());
+    
+    nes.set_microphone(true);
+    nes.bus.write(0x4016, 0x01);
+    nes.bus.write(0x4016, 0x00);
+    
+    // Bit 2 is the live mic level on every read, next to the serial button bit
+    assert_eq!(nes.bus.read(0x4016), 0x05);
+    for _ in 0..7 {
+        assert_eq!(nes.bus.read(0x4016), 0x04);
+    }
+    assert_eq!(nes.bus.read(0x4017) & 0x04, 0);
+    
+    let mut controller = Controller::new();
+    controller.set_microphone(false);
+    nes.set_controller2_from_controller(&controller);
+    assert_eq!(nes.bus.read(0x4016) & 0x04, 0);
+    
+    // Unplugging the second controller takes the mic with it
+    nes.set_microphone(true);
+    nes.set_controller_connected(1, false);
+    assert_eq!(nes.bus.read(0x4016) & 0x04, 0);
+}
+
+#[test]
+fn test_strobe_high_reads_live_a_button() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    
+    nes.bus.write(0x4016, 0x01);
+    nes.set_controller1(ControllerButton::A.bits() | ControllerButton::B.bits());
+    for _ in 0..3 {
+        assert_eq!(nes.bus.read(0x4016) & 0x01, 1);
+    }
+    nes.set_controller1(ControllerButton::B.bits());
+    for _ in 0..3 {
+        assert_eq!(nes.bus.read(0x4016) & 0x01, 0, "Released A shows up immediately");
+    }
+    
+    // Dropping strobe latches the buttons held at that moment, A first
+    nes.set_controller1(ControllerButton::A.bits() | ControllerButton::START.bits());
+    nes.bus.write(0x4016, 0x00);
+    nes.set_controller1(0);
+    let bits: Vec<u8> = (0..9).map(|_| nes.bus.read(0x4016) & 0x01).collect();
+    assert_eq!(bits, [1, 0, 0, 1, 0, 0, 0, 0, 1]);
 }
\ No newline at end of file
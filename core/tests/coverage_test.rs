@@ -0,0 +1,61 @@
+use ccnes_core::coverage::Access;
+use ccnes_core::{Cartridge, Nes};
+
+fn create_nes() -> Nes {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    nes
+}
+
+#[test]
+fn test_coverage_off_by_default() {
+    let mut nes = create_nes();
+    nes.run_frame().unwrap();
+    assert!(nes.coverage().is_none());
+}
+
+#[test]
+fn test_coverage_records_accesses() {
+    let mut nes = create_nes();
+    nes.enable_coverage();
+    for _ in 0..10 {
+        nes.run_frame().unwrap();
+    }
+    let coverage = nes.coverage().unwrap();
+
+    // LDA #$00 at the reset vector: opcode executed, operand only read
+    assert_eq!(coverage.get(0xC000), Access::EXECUTED | Access::READ);
+    assert_eq!(coverage.get(0xC001), Access::READ);
+
+    assert!(coverage.get(0x2000).contains(Access::WRITTEN));
+    assert!(coverage.get(0x2002).contains(Access::READ));
+    assert_eq!(coverage.get(0x8000), Access::empty());
+
+    // NMI vector, RTI handler and the interrupt frame on the stack
+    assert!(coverage.get(0xFFFA).contains(Access::READ));
+    assert!(coverage.get(0xC100).contains(Access::EXECUTED));
+    assert!(coverage.get(0x01FD).contains(Access::WRITTEN));
+
+    // 33 instructions in the main program plus the RTI
+    assert_eq!(coverage.count(Access::EXECUTED), 34);
+    assert_eq!(coverage.addresses(Access::EXECUTED).next(), Some(0xC000));
+    assert_eq!(coverage.as_bytes().len(), 0x10000);
+}
+
+#[test]
+fn test_coverage_with_idle_skip() {
+    let mut nes = create_nes();
+    for _ in 0..3 {
+        nes.run_frame().unwrap();
+    }
+
+    // By now the CPU parks in `JMP $C04C`, so it is only ever skipped
+    nes.set_idle_skip(true);
+    nes.enable_coverage();
+    nes.run_frame().unwrap();
+    assert!(nes.coverage().unwrap().get(0xC04C).contains(Access::EXECUTED));
+
+    nes.disable_coverage();
+    assert!(nes.coverage().is_none());
+}
\ No newline at end of file
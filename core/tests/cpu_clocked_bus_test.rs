@@ -0,0 +1,152 @@
+use ccnes_core::cpu::{ClockedBus, Cpu, CpuBus};
+use std::collections::VecDeque;
+
+// Stands in for the PPU: raises NMI (or IRQ) on scripted cycles and answers
+// $2002 reads from a queue
+struct ScriptedBus {
+    memory: [u8; 0x10000],
+    cycle: u64,
+    nmi_cycles: Vec<u64>,
+    irq_cycles: Vec<u64>,
+    status_reads: VecDeque<u8>,
+}
+
+impl CpuBus for ScriptedBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x2002 => self.status_reads.pop_front().unwrap_or(0),
+            _ => self.memory[addr as usize],
+        }
+    }
+    
+    fn write(&mut self, addr: u16, value: u8) {
+        self.memory[addr as usize] = value;
+    }
+}
+
+impl ClockedBus for ScriptedBus {
+    fn tick(&mut self, cpu: &mut Cpu) -> bool {
+        self.cycle += 1;
+        if self.nmi_cycles.contains(&self.cycle) {
+            cpu.trigger_nmi();
+            return true;
+        }
+        if self.irq_cycles.contains(&self.cycle) {
+            cpu.trigger_irq();
+        }
+        false
+    }
+}
+
+// `program` at $8000, a NOP as the NMI handler at $9000
+fn setup(program: &[u8], nmi_cycles: &[u64], status_reads: &[u8]) -> (Cpu, ScriptedBus) {
+    let mut bus = ScriptedBus {
+        memory: [0; 0x10000],
+        cycle: 0,
+        nmi_cycles: nmi_cycles.to_vec(),
+        irq_cycles: Vec::new(),
+        status_reads: status_reads.iter().copied().collect(),
+    };
+    bus.memory[0x8000..0x8000 + program.len()].copy_from_slice(program);
+    bus.memory[0x9000] = 0xEA;
+    bus.memory[0xFFFA..0xFFFE].copy_from_slice(&[0x00, 0x90, 0x00, 0x80]);
+    
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    (cpu, bus)
+}
+
+const NOPS: &[u8] = &[0xEA; 8];
+
+#[test]
+fn test_nmi_taken_after_current_instruction() {
+    // Raised on the first cycle of the second NOP
+    let (mut cpu, mut bus) = setup(NOPS, &[3], &[]);
+    assert_eq!(cpu.step_clocked(&mut bus), (2, false));
+    assert_eq!(cpu.step_clocked(&mut bus), (2, true));
+    
+    // Seven cycles of interrupt entry plus the handler's NOP
+    assert_eq!(cpu.step_clocked(&mut bus), (9, false));
+    assert_eq!(cpu.pc, 0x9001);
+}
+
+#[test]
+fn test_nmi_on_last_cycle_is_deferred() {
+    // Raised on the last cycle of the second NOP: the poll already
+    // happened, so the third NOP runs before the handler
+    let (mut cpu, mut bus) = setup(NOPS, &[4], &[]);
+    cpu.step_clocked(&mut bus);
+    assert_eq!(cpu.step_clocked(&mut bus), (2, true));
+    
+    assert_eq!(cpu.step_clocked(&mut bus), (2, false));
+    assert_eq!(cpu.pc, 0x8003);
+    assert_eq!(cpu.step_clocked(&mut bus), (9, false));
+    assert_eq!(cpu.pc, 0x9001);
+}
+
+// CLC, BCC +0, NOP: the branch is taken and stays on its page
+const BRANCH: &[u8] = &[0x18, 0x90, 0x00, 0xEA, 0xEA];
+
+#[test]
+fn test_nmi_before_branch_poll_taken_after_branch() {
+    // Raised on the first cycle of the branch, before its poll
+    let (mut cpu, mut bus) = setup(BRANCH, &[3], &[]);
+    cpu.step_clocked(&mut bus);
+    assert_eq!(cpu.step_clocked(&mut bus), (3, true));
+    
+    assert_eq!(cpu.step_clocked(&mut bus), (9, false));
+    assert_eq!(cpu.pc, 0x9001);
+}
+
+#[test]
+fn test_nmi_after_branch_poll_is_deferred() {
+    // Raised on the second cycle of the branch: a taken branch that stays
+    // on its page doesn't poll again, so the NOP after it runs first
+    let (mut cpu, mut bus) = setup(BRANCH, &[4], &[]);
+    cpu.step_clocked(&mut bus);
+    assert_eq!(cpu.step_clocked(&mut bus), (3, true));
+    
+    assert_eq!(cpu.step_clocked(&mut bus), (2, false));
+    assert_eq!(cpu.pc, 0x8004);
+    assert_eq!(cpu.step_clocked(&mut bus), (9, false));
+    assert_eq!(cpu.pc, 0x9001);
+}
+
+#[test]
+fn test_irq_after_branch_poll_is_deferred() {
+    // CLI first, then the same branch with the IRQ on its second cycle
+    let program = [0x58, 0x18, 0x90, 0x00, 0xEA, 0xEA];
+    let (mut cpu, mut bus) = setup(&program, &[], &[]);
+    bus.memory[0xFFFE..0x10000].copy_from_slice(&[0x00, 0x90]);
+    bus.irq_cycles = vec![6];
+    for _ in 0..3 {
+        cpu.step_clocked(&mut bus);
+    }
+    assert_eq!(cpu.pc, 0x8004);
+    
+    assert_eq!(cpu.step_clocked(&mut bus), (2, false));
+    assert_eq!(cpu.pc, 0x8005);
+    assert_eq!(cpu.step_clocked(&mut bus), (9, false));
+    assert_eq!(cpu.pc, 0x9001);
+}
+
+#[test]
+fn test_vblank_poll_loop() {
+    let program = [
+        0x2C, 0x02, 0x20, // BIT $2002
+        0x10, 0xFB,       // BPL -5
+        0x4C, 0x05, 0x80, // JMP $8005
+    ];
+    let (mut cpu, mut bus) = setup(&program, &[], &[0x00, 0x40, 0x80]);
+    
+    let mut steps = 0;
+    while cpu.pc != 0x8005 {
+        cpu.step_clocked(&mut bus);
+        steps += 1;
+    }
+    assert_eq!(steps, 6);
+    assert!(bus.status_reads.is_empty());
+    
+    // BIT 4 + taken BPL 3, twice, then BIT 4 + BPL 2
+    assert_eq!(bus.cycle, 20);
+}
\ No newline at end of file
@@ -0,0 +1,142 @@
+use ccnes_core::cpu::{Cpu, CpuBus, StatusFlags};
+
+// Mock bus for testing
+struct MockBus {
+    memory: [u8; 0x10000],
+}
+
+impl CpuBus for MockBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.memory[addr as usize]
+    }
+    
+    fn write(&mut self, addr: u16, value: u8) {
+        self.memory[addr as usize] = value;
+    }
+}
+
+struct CycleCase {
+    name: &'static str,
+    start: u16,
+    program: &'static [u8],
+    x: u8,
+    y: u8,
+    status: u8,
+    expected: u32,
+}
+
+// Pointer at $10/$11 -> $02FF for the (zp),Y cases
+fn run_case(case: &CycleCase) -> u32 {
+    let mut bus = MockBus { memory: [0; 0x10000] };
+    for (i, &byte) in case.program.iter().enumerate() {
+        bus.memory[case.start as usize + i] = byte;
+    }
+    bus.memory[0x10] = 0xFF;
+    bus.memory[0x11] = 0x02;
+    bus.memory[0xFFFC] = (case.start & 0xFF) as u8;
+    bus.memory[0xFFFD] = (case.start >> 8) as u8;
+    
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    cpu.x = case.x;
+    cpu.y = case.y;
+    cpu.status = StatusFlags::from_bits_truncate(case.status);
+    
+    cpu.step(&mut bus)
+}
+
+const Z: u8 = 0x26; // Zero flag set (plus I and unused)
+const NZ: u8 = 0x24; // Zero flag clear
+
+const CASES: &[CycleCase] = &[
+    // Reads add a cycle on page cross
+    CycleCase { name: "LDA abs,X", start: 0x8000, program: &[0xBD, 0x00, 0x02], x: 1, y: 0, status: NZ, expected: 4 },
+    CycleCase { name: "LDA abs,X cross", start: 0x8000, program: &[0xBD, 0xFF, 0x02], x: 1, y: 0, status: NZ, expected: 5 },
+    CycleCase { name: "LDA abs,Y cross", start: 0x8000, program: &[0xB9, 0xFF, 0x02], x: 0, y: 1, status: NZ, expected: 5 },
+    CycleCase { name: "LDX abs,Y cross", start: 0x8000, program: &[0xBE, 0xFF, 0x02], x: 0, y: 1, status: NZ, expected: 5 },
+    CycleCase { name: "LDA (zp),Y", start: 0x8000, program: &[0xB1, 0x10], x: 0, y: 0, status: NZ, expected: 5 },
+    CycleCase { name: "LDA (zp),Y cross", start: 0x8000, program: &[0xB1, 0x10], x: 0, y: 1, status: NZ, expected: 6 },
+    CycleCase { name: "ADC abs,X cross", start: 0x8000, program: &[0x7D, 0xFF, 0x02], x: 1, y: 0, status: NZ, expected: 5 },
+    CycleCase { name: "CMP abs,Y cross", start: 0x8000, program: &[0xD9, 0xFF, 0x02], x: 0, y: 1, status: NZ, expected: 5 },
+    // Stores and read-modify-write never add a cycle
+    CycleCase { name: "STA abs,X", start: 0x8000, program: &[0x9D, 0x00, 0x02], x: 1, y: 0, status: NZ, expected: 5 },
+    CycleCase { name: "STA abs,X cross", start: 0x8000, program: &[0x9D, 0xFF, 0x02], x: 1, y: 0, status: NZ, expected: 5 },
+    CycleCase { name: "STA abs,Y", start: 0x8000, program: &[0x99, 0x00, 0x02], x: 0, y: 1, status: NZ, expected: 5 },
+    CycleCase { name: "STA abs,Y cross", start: 0x8000, program: &[0x99, 0xFF, 0x02], x: 0, y: 1, status: NZ, expected: 5 },
+    CycleCase { name: "STA (zp),Y", start: 0x8000, program: &[0x91, 0x10], x: 0, y: 0, status: NZ, expected: 6 },
+    CycleCase { name: "STA (zp),Y cross", start: 0x8000, program: &[0x91, 0x10], x: 0, y: 1, status: NZ, expected: 6 },
+    CycleCase { name: "INC abs,X cross", start: 0x8000, program: &[0xFE, 0xFF, 0x02], x: 1, y: 0, status: NZ, expected: 7 },
+    // Branches: +1 when taken, +1 more when the target is on another page
+    CycleCase { name: "BNE not taken", start: 0x8000, program: &[0xD0, 0x10], x: 0, y: 0, status: Z, expected: 2 },
+    CycleCase { name: "BNE taken", start: 0x8000, program: &[0xD0, 0x10], x: 0, y: 0, status: NZ, expected: 3 },
+    CycleCase { name: "BNE taken forward cross", start: 0x80F0, program: &[0xD0, 0x20], x: 0, y: 0, status: NZ, expected: 4 },
+    CycleCase { name: "BNE taken backward cross", start: 0x8100, program: &[0xD0, 0xF0], x: 0, y: 0, status: NZ, expected: 4 },
+    CycleCase { name: "BEQ taken to next page start", start: 0x80FC, program: &[0xF0, 0x02], x: 0, y: 0, status: Z, expected: 4 },
+    CycleCase { name: "BEQ not taken at page end", start: 0x80FE, program: &[0xF0, 0x10], x: 0, y: 0, status: NZ, expected: 2 },
+];
+
+#[test]
+fn test_cpu_cycle_table() {
+    for case in CASES {
+        assert_eq!(run_case(case), case.expected, "{}", case.name);
+    }
+}
+
+#[test]
+fn test_interrupt_entry_cycles_reported() {
+    let mut bus = MockBus { memory: [0; 0x10000] };
+    bus.memory[0x8000] = 0xEA; // NOP
+    bus.memory[0x9000] = 0xEA; // NOP in NMI handler
+    bus.memory[0xFFFA] = 0x00;
+    bus.memory[0xFFFB] = 0x90;
+    bus.memory[0xFFFC] = 0x00;
+    bus.memory[0xFFFD] = 0x80;
+    
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    cpu.trigger_nmi();
+    
+    // 7 cycles of interrupt entry plus the 2 cycle NOP
+    assert_eq!(cpu.step(&mut bus), 9);
+    assert_eq!(cpu.pc, 0x9001);
+}
+
+struct RecordingBus {
+    memory: [u8; 0x10000],
+    reads: Vec<u16>,
+}
+
+impl CpuBus for RecordingBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.reads.push(addr);
+        self.memory[addr as usize]
+    }
+    
+    fn write(&mut self, addr: u16, value: u8) {
+        self.memory[addr as usize] = value;
+    }
+}
+
+#[test]
+fn test_indexed_store_reads_unfixed_address() {
+    let mut bus = RecordingBus { memory: [0; 0x10000], reads: Vec::new() };
+    bus.memory[0x8000..0x8006].copy_from_slice(&[0x9D, 0xFF, 0x02, 0x9D, 0x00, 0x02]); // STA $02FF,X; STA $0200,X
+    bus.memory[0xFFFD] = 0x80;
+    
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    cpu.x = 1;
+    cpu.a = 0x42;
+    
+    // Crossing: the dummy read hits $0200 before the write lands on $0300
+    bus.reads.clear();
+    assert_eq!(cpu.step(&mut bus), 5);
+    assert_eq!(bus.reads, [0x8000, 0x8001, 0x8002, 0x0200]);
+    assert_eq!(bus.memory[0x0300], 0x42);
+    
+    // Same page: the dummy read is of the target itself, and costs the same
+    bus.reads.clear();
+    assert_eq!(cpu.step(&mut bus), 5);
+    assert_eq!(bus.reads, [0x8003, 0x8004, 0x8005, 0x0201]);
+    assert_eq!(bus.memory[0x0201], 0x42);
+}
\ No newline at end of file
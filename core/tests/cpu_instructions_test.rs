@@ -1,3 +1,4 @@
+use ccnes_core::cpu::opcodes::{Instruction, OPCODE_TABLE};
 use ccnes_core::cpu::{Cpu, CpuBus, StatusFlags};
 
 // Mock bus for testing
@@ -277,6 +278,44 @@ fn test_branch_taken() {
     assert_eq!(cpu.a, 0x42);
 }
 
+// Opcode, flag it tests, and whether it branches when the flag is set
+const BRANCHES: [(u8, StatusFlags, bool); 8] = [
+    (0x10, StatusFlags::NEGATIVE, false), // BPL
+    (0x30, StatusFlags::NEGATIVE, true),  // BMI
+    (0x50, StatusFlags::OVERFLOW, false), // BVC
+    (0x70, StatusFlags::OVERFLOW, true),  // BVS
+    (0x90, StatusFlags::CARRY, false),    // BCC
+    (0xB0, StatusFlags::CARRY, true),     // BCS
+    (0xD0, StatusFlags::ZERO, false),     // BNE
+    (0xF0, StatusFlags::ZERO, true),      // BEQ
+];
+
+#[test]
+fn test_branch_not_taken_skips_operand() {
+    for (opcode, flag, when_set) in BRANCHES {
+        // The offset byte is a valid opcode, so running it by mistake would show
+        let (mut cpu, mut bus) = setup_cpu(&[opcode, 0xA9, 0xEA]);
+        cpu.status.set(flag, !when_set);
+        
+        let cycles = cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x8002, "Opcode {:02X}", opcode);
+        assert_eq!(cycles, 2, "Opcode {:02X}", opcode);
+    }
+}
+
+#[test]
+fn test_branch_taken_offsets_from_next_instruction() {
+    for (opcode, flag, when_set) in BRANCHES {
+        for (offset, target) in [(0x10, 0x8012), (0xFE, 0x8000), (0x80, 0x7F82)] {
+            let (mut cpu, mut bus) = setup_cpu(&[opcode, offset]);
+            cpu.status.set(flag, when_set);
+            
+            cpu.step(&mut bus);
+            assert_eq!(cpu.pc, target, "Opcode {:02X} offset {:02X}", opcode, offset);
+        }
+    }
+}
+
 #[test]
 fn test_jsr_rts() {
     let program = [
@@ -443,4 +482,170 @@ fn test_addressing_modes() {
     assert_eq!(cpu.a, 0x66);
 }
 
-// Unofficial opcodes test removed - not implemented yet
\ No newline at end of file
+#[test]
+fn test_every_opcode_executes() {
+    for opcode in 0..=0xFFu8 {
+        // Operand bytes point at $0080 so indirect modes stay in RAM
+        let program = [opcode, 0x80, 0x00];
+        let (mut cpu, mut bus) = setup_cpu(&program);
+        cpu.x = 0x01;
+        cpu.y = 0x01;
+        
+        let cycles = cpu.step(&mut bus);
+        assert!(cycles >= 1, "opcode {:02X}", opcode);
+    }
+    
+    let jams = OPCODE_TABLE
+        .iter()
+        .filter(|op| op.instruction == Instruction::KIL)
+        .count();
+    assert_eq!(jams, 12);
+}
+
+#[test]
+fn test_kil_jams_until_reset() {
+    let program = [0x02, 0xA9, 0x42]; // KIL, LDA #$42
+    let (mut cpu, mut bus) = setup_cpu(&program);
+    
+    cpu.step(&mut bus);
+    assert!(cpu.is_jammed());
+    
+    // Interrupts are ignored and nothing past the KIL runs
+    cpu.trigger_nmi();
+    for _ in 0..10 {
+        assert_eq!(cpu.step(&mut bus), 1);
+    }
+    assert_eq!(cpu.pc, 0x8000);
+    assert_eq!(cpu.a, 0x00);
+    
+    cpu.reset(&mut bus);
+    assert!(!cpu.is_jammed());
+    cpu.step(&mut bus);
+    assert!(cpu.is_jammed());
+}
+
+#[test]
+fn test_lax_sax() {
+    let program = [
+        0xA7, 0x10, // LAX $10
+        0xA9, 0x0F, // LDA #$0F
+        0x87, 0x11, // SAX $11
+    ];
+    let (mut cpu, mut bus) = setup_cpu(&program);
+    bus.memory[0x10] = 0x93;
+    
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a, 0x93);
+    assert_eq!(cpu.x, 0x93);
+    assert!(cpu.status.contains(StatusFlags::NEGATIVE));
+    
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(bus.memory[0x11], 0x03);
+}
+
+#[test]
+fn test_slo_dcp_isc() {
+    let program = [
+        0xA9, 0x01, // LDA #$01
+        0x07, 0x10, // SLO $10
+        0xC7, 0x11, // DCP $11
+        0x38,       // SEC
+        0xE7, 0x12, // ISC $12
+    ];
+    let (mut cpu, mut bus) = setup_cpu(&program);
+    bus.memory[0x10] = 0xC0;
+    bus.memory[0x11] = 0x82;
+    bus.memory[0x12] = 0x00;
+    
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(bus.memory[0x10], 0x80);
+    assert_eq!(cpu.a, 0x81);
+    assert!(cpu.status.contains(StatusFlags::CARRY));
+    
+    // $82 - 1 = $81, equal to A
+    cpu.step(&mut bus);
+    assert_eq!(bus.memory[0x11], 0x81);
+    assert!(cpu.status.contains(StatusFlags::ZERO));
+    
+    // $00 + 1 = $01, then A = $81 - $01
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(bus.memory[0x12], 0x01);
+    assert_eq!(cpu.a, 0x80);
+    assert!(cpu.status.contains(StatusFlags::CARRY));
+}
+
+#[test]
+fn test_immediate_unofficial_opcodes() {
+    let program = [
+        0xA9, 0xF0, // LDA #$F0
+        0x0B, 0x81, // ANC #$81
+        0xA9, 0xFF, // LDA #$FF
+        0x4B, 0x03, // ALR #$03
+        0xA9, 0x0F, // LDA #$0F
+        0xA2, 0x07, // LDX #$07
+        0xCB, 0x02, // AXS #$02
+    ];
+    let (mut cpu, mut bus) = setup_cpu(&program);
+    
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a, 0x80);
+    assert!(cpu.status.contains(StatusFlags::CARRY));
+    
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a, 0x01);
+    assert!(cpu.status.contains(StatusFlags::CARRY));
+    
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.x, 0x05);
+    assert!(cpu.status.contains(StatusFlags::CARRY));
+}
+
+#[test]
+fn test_unofficial_nop_reads_operand() {
+    let program = [0x1C, 0xFF, 0x00]; // NOP $00FF,X
+    let (mut cpu, mut bus) = setup_cpu(&program);
+    cpu.x = 0x01;
+    
+    // Crossing a page costs a cycle like the official absolute,X reads
+    assert_eq!(cpu.step(&mut bus), 5);
+    assert_eq!(cpu.pc, 0x8003);
+}
+
+#[test]
+fn test_instruction_length_per_addressing_mode() {
+    use ccnes_core::cpu::addressing::AddressingMode;
+    
+    let cases = [
+        (0xEA, AddressingMode::Implicit, 1),    // NOP
+        (0x0A, AddressingMode::Accumulator, 1), // ASL A
+        (0xA9, AddressingMode::Immediate, 2),   // LDA #
+        (0xA5, AddressingMode::ZeroPage, 2),    // LDA zp
+        (0xB5, AddressingMode::ZeroPageX, 2),   // LDA zp,X
+        (0xB6, AddressingMode::ZeroPageY, 2),   // LDX zp,Y
+        (0xAD, AddressingMode::Absolute, 3),    // LDA abs
+        (0xBD, AddressingMode::AbsoluteX, 3),   // LDA abs,X
+        (0xB9, AddressingMode::AbsoluteY, 3),   // LDA abs,Y
+        (0x6C, AddressingMode::Indirect, 3),    // JMP (abs)
+        (0xA1, AddressingMode::IndirectX, 2),   // LDA (zp,X)
+        (0xB1, AddressingMode::IndirectY, 2),   // LDA (zp),Y
+        (0xD0, AddressingMode::Relative, 2),    // BNE
+    ];
+    for (opcode, mode, length) in cases {
+        assert_eq!(Cpu::decode(opcode).unwrap().mode, mode, "opcode {:02X}", opcode);
+        assert_eq!(Cpu::instruction_length(opcode), length, "opcode {:02X}", opcode);
+    }
+    
+    assert_eq!(Cpu::decode(0x20).unwrap().instruction, Instruction::JSR);
+    assert_eq!(Cpu::instruction_length(0x20), 3);
+    // Unofficial opcodes decode too, the jams don't
+    assert_eq!(Cpu::decode(0xA7).unwrap().instruction, Instruction::LAX);
+    assert_eq!(Cpu::decode(0x02), None);
+    assert_eq!(Cpu::instruction_length(0x02), 1);
+}
@@ -0,0 +1,85 @@
+use ccnes_core::debugger::{disassemble, parse_nl_labels};
+use ccnes_core::cpu::CpuBus;
+use ccnes_core::{Apu, Bus, Disassembler, Ppu};
+use std::collections::HashMap;
+
+fn bus_with_program(addr: u16, program: &[u8]) -> Bus {
+    let mut bus = Bus::new(Ppu::new(), Apu::new());
+    for (i, &byte) in program.iter().enumerate() {
+        bus.write(addr + i as u16, byte);
+    }
+    bus
+}
+
+#[test]
+fn test_disassemble_without_labels() {
+    let bus = bus_with_program(0x0300, &[
+        0x20, 0x23, 0xC1, // JSR $C123
+        0xB5, 0x10,       // LDA $10,X
+        0x91, 0x20,       // STA ($20),Y
+        0x0A,             // ASL A
+        0xD0, 0xF6,       // BNE $0300
+        0xA7, 0x10,       // LAX $10
+    ]);
+    
+    assert_eq!(disassemble(&bus, 0x0300, 6), [
+        "0300: 20 23 C1  JSR $C123",
+        "0303: B5 10     LDA $10,X",
+        "0305: 91 20     STA ($20),Y",
+        "0307: 0A        ASL A",
+        "0308: D0 F6     BNE $0300",
+        "030A: A7 10     LAX $10",
+    ]);
+}
+
+#[test]
+fn test_disassemble_has_no_side_effects() {
+    let mut bus = bus_with_program(0x0300, &[0xEA, 0x11]); // NOP, then data
+    bus.write(0x4000, 0x5A); // Leaves $5A on the data bus
+    
+    // Only the one byte of the NOP is looked at, and without a bus read
+    assert_eq!(disassemble(&bus, 0x0300, 1), ["0300: EA        NOP"]);
+    assert_eq!(bus.read(0x4000), 0x5A);
+}
+
+#[test]
+fn test_disassemble_with_labels() {
+    let bus = bus_with_program(0x0300, &[
+        0x20, 0x23, 0xC1, // JSR $C123
+        0xB5, 0x10,       // LDA $10,X
+        0x6C, 0xFC, 0xFF, // JMP ($FFFC)
+        0xD0, 0xF6,       // BNE $0300
+        0x8D, 0x00, 0x20, // STA $2000
+    ]);
+    let labels = HashMap::from([
+        (0xC123, "ResetHandler".to_string()),
+        (0x0010, "Temp".to_string()),
+        (0xFFFC, "ResetVector".to_string()),
+        (0x0300, "Loop".to_string()),
+    ]);
+    
+    assert_eq!(Disassembler::with_labels(labels).disassemble(&bus, 0x0300, 5), [
+        "0300: 20 23 C1  JSR ResetHandler",
+        "0303: B5 10     LDA Temp,X",
+        "0305: 6C FC FF  JMP (ResetVector)",
+        "0308: D0 F6     BNE Loop",
+        "030A: 8D 00 20  STA $2000",
+    ]);
+}
+
+#[test]
+fn test_parse_nl_labels() {
+    let labels = parse_nl_labels(
+        "$C123#ResetHandler#Entry point\n\
+         $0300/10#Buffer#\n\
+         \n\
+         $8000##Unnamed\n\
+         garbage\n\
+         $00FF#ZpEnd\r\n",
+    );
+    
+    assert_eq!(labels.len(), 3);
+    assert_eq!(labels[&0xC123], "ResetHandler");
+    assert_eq!(labels[&0x0300], "Buffer");
+    assert_eq!(labels[&0x00FF], "ZpEnd");
+}
\ No newline at end of file
@@ -0,0 +1,120 @@
+use ccnes_core::{ControllerButton, Emulator, EmulatorConfig, NesError, Region};
+use ccnes_core::cartridge::CartridgeError;
+use ccnes_core::cpu::CpuBus;
+use tempfile::TempDir;
+
+fn create_emulator(save_dir: &TempDir) -> Emulator {
+    let config = EmulatorConfig {
+        save_dir: save_dir.path().to_path_buf(),
+        ..EmulatorConfig::default()
+    };
+
+    let mut emulator = Emulator::new(config);
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    emulator.load_rom_bytes(&rom_data, "test").expect("Failed to load ROM");
+    emulator
+}
+
+#[test]
+fn test_emulator_frame_output() {
+    let dir = TempDir::new().unwrap();
+    let mut emulator = create_emulator(&dir);
+
+    for _ in 0..5 {
+        emulator.run_frame().unwrap();
+    }
+
+    let screenshot = emulator.screenshot();
+    assert_eq!(screenshot.len(), 256 * 240 * 3);
+
+    // Screenshot matches the framebuffer
+    let color = emulator.framebuffer()[1000];
+    assert_eq!(&screenshot[3000..3003], &[(color >> 16) as u8, (color >> 8) as u8, color as u8]);
+
+    assert!(!emulator.audio_samples().is_empty());
+    assert!(emulator.audio_samples().is_empty());
+}
+
+#[test]
+fn test_emulator_key_map() {
+    let dir = TempDir::new().unwrap();
+    let mut emulator = create_emulator(&dir);
+
+    assert!(emulator.key_event("KeyZ", true));
+    assert!(!emulator.key_event("KeyQ", true));
+    assert!(emulator.controller(0).unwrap().is_pressed(ControllerButton::A));
+
+    emulator.set_button(1, ControllerButton::START, true);
+    assert!(emulator.controller(1).unwrap().is_pressed(ControllerButton::START));
+
+    emulator.key_event("KeyZ", false);
+    assert!(!emulator.controller(0).unwrap().is_pressed(ControllerButton::A));
+}
+
+#[test]
+fn test_emulator_save_slots() {
+    let dir = TempDir::new().unwrap();
+    let mut emulator = create_emulator(&dir);
+
+    emulator.run_frame().unwrap();
+    emulator.save_slot(2).expect("Failed to save");
+    assert!(emulator.slot_exists(2));
+    assert!(!emulator.slot_exists(3));
+
+    let saved_pc = emulator.nes().cpu.pc;
+    let saved_ram = emulator.nes_mut().bus.read(0x0200);
+    emulator.run_frame().unwrap();
+    emulator.nes_mut().bus.write(0x0200, saved_ram.wrapping_add(1));
+
+    emulator.load_slot(2).expect("Failed to load");
+    assert_eq!(emulator.nes().cpu.pc, saved_pc);
+    assert_eq!(emulator.nes_mut().bus.read(0x0200), saved_ram);
+}
+
+#[test]
+fn test_emulator_custom_palette() {
+    let dir = TempDir::new().unwrap();
+    let config = EmulatorConfig {
+        save_dir: dir.path().to_path_buf(),
+        palette: Some([0x123456; 64]),
+        ..EmulatorConfig::default()
+    };
+
+    let mut emulator = Emulator::new(config);
+    assert!(matches!(emulator.save_slot(0), Err(NesError::NoCartridge)), "Saving needs a ROM");
+    assert!(matches!(emulator.load_slot(0), Err(NesError::NoCartridge)));
+    assert!(matches!(emulator.run_frame(), Err(NesError::NoCartridge)), "Running needs a ROM");
+    assert!(matches!(
+        emulator.load_rom_bytes(b"not a rom", "test"),
+        Err(NesError::Cartridge(CartridgeError::UnknownFormat))
+    ));
+
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    emulator.load_rom_bytes(&rom_data, "test").unwrap();
+    emulator.run_frame().unwrap();
+    emulator.run_frame().unwrap();
+    assert!(emulator.framebuffer().iter().all(|&c| c == 0x123456));
+}
+
+#[test]
+fn test_emulator_region_detection() {
+    let dir = TempDir::new().unwrap();
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut emulator = Emulator::new(EmulatorConfig {
+        save_dir: dir.path().to_path_buf(),
+        ..EmulatorConfig::default()
+    });
+
+    emulator.load_rom_bytes(&rom_data, "test").unwrap();
+    assert_eq!(emulator.region(), Region::Ntsc);
+    emulator.load_rom_bytes(&rom_data, "Test Game (Europe)").unwrap();
+    assert_eq!(emulator.region(), Region::Pal);
+
+    let mut emulator = Emulator::new(EmulatorConfig {
+        save_dir: dir.path().to_path_buf(),
+        region: Some(Region::Ntsc),
+        ..EmulatorConfig::default()
+    });
+    emulator.load_rom_bytes(&rom_data, "Test Game (Europe)").unwrap();
+    assert_eq!(emulator.region(), Region::Ntsc);
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+use ccnes_core::{Cartridge, Nes, ExpansionDevice};
+use ccnes_core::cpu::CpuBus;
+use std::sync::{Arc, Mutex};
+
+// Records writes and exposes a DIP switch bank like a Vs. System board
+#[derive(Debug, Clone)]
+struct TestDevice {
+    writes: Arc<Mutex<Vec<(u16, u8)>>>,
+    dip_switches: u8,
+}
+
+impl ExpansionDevice for TestDevice {
+    fn read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x4016 => Some(self.dip_switches & 0x18),
+            0x4018 => Some(0x5A),
+            0x5000 => Some(0xA5),
+            _ => None,
+        }
+    }
+    
+    fn write(&mut self, addr: u16, value: u8) -> bool {
+        self.writes.lock().unwrap().push((addr, value));
+        addr == 0x5000
+    }
+    
+    fn clone_box(&self) -> Box<dyn ExpansionDevice> {
+        Box::new(self.clone())
+    }
+}
+
+fn create_nes() -> Nes {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines(std::io::Cursor::new(rom_data))
+        .expect("Failed to load ROM");
+    
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    nes
+}
+
+#[test]
+fn test_expansion_device_default_is_open() {
+    let mut nes = create_nes();
+    
+    assert_eq!(nes.bus.read(0x4018), 0);
+    assert_eq!(nes.bus.read(0x5000), 0);
+}
+
+#[test]
+fn test_expansion_device_reads_and_writes() {
+    let mut nes = create_nes();
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    nes.set_expansion_device(Some(Box::new(TestDevice {
+        writes: writes.clone(),
+        dip_switches: 0xFF,
+    })));
+    
+    // Controller data is combined with the DIP switch bits
+    nes.set_controller1(0x01);
+    nes.bus.write(0x4016, 0x01);
+    nes.bus.write(0x4016, 0x00);
+    assert_eq!(nes.bus.read(0x4016), 0x19);
+    
+    // Claimed reads outside the controller ports replace open bus/cartridge data
+    assert_eq!(nes.bus.read(0x4018), 0x5A);
+    assert_eq!(nes.bus.read(0x5000), 0xA5);
+    
+    nes.bus.write(0x4017, 0x40);
+    nes.bus.write(0x5000, 0x12);
+    
+    let writes = writes.lock().unwrap();
+    assert_eq!(*writes, vec![(0x4016, 0x01), (0x4016, 0x00), (0x4017, 0x40), (0x5000, 0x12)]);
+}
\ No newline at end of file
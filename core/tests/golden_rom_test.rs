@@ -0,0 +1,61 @@
+use ccnes_core::{Cartridge, Controller, ControllerButton, Nes};
+
+// FNV-1a, spelled out so the golden values don't depend on std's hasher
+fn hash_framebuffer(framebuffer: &[u32]) -> u64 {
+    framebuffer.iter().flat_map(|pixel| pixel.to_le_bytes()).fold(0xCBF29CE484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001B3)
+    })
+}
+
+// Boot `rom_data` on a fresh console, run `frames` frames and hash the picture
+fn run_and_hash(rom_data: &[u8], frames: usize, setup: impl FnOnce(&mut Nes)) -> (Nes, u64) {
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(rom_data).unwrap());
+    setup(&mut nes);
+    for _ in 0..frames {
+        nes.run_frame().unwrap();
+    }
+    let hash = hash_framebuffer(nes.get_framebuffer());
+    (nes, hash)
+}
+
+#[test]
+fn test_background_rom_golden_hash() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let (nes, hash) = run_and_hash(&rom_data, 10, |_| {});
+    
+    // Light blue backdrop behind the checkerboard tile
+    let colors: std::collections::HashSet<_> = nes.get_framebuffer().iter().collect();
+    assert!(colors.len() > 1);
+    assert_eq!(hash, 0x604C3413F95E1225);
+}
+
+#[test]
+fn test_sprite_rom_golden_hash() {
+    let rom_data = ccnes_core::test_rom::create_sprite_test_rom();
+    let (nes, hash) = run_and_hash(&rom_data, 10, |_| {});
+    
+    // The smiley at (128, 120) is drawn over the black backdrop
+    assert_ne!(nes.get_framebuffer()[124 * 256 + 130], 0);
+    assert_eq!(hash, 0xDF611E7A22C1E905);
+}
+
+#[test]
+fn test_controller_rom_golden_hash() {
+    let rom_data = ccnes_core::test_rom::create_controller_test_rom();
+    let buttons = ControllerButton::B | ControllerButton::START | ControllerButton::RIGHT;
+    let (nes, hash) = run_and_hash(&rom_data, 3, |nes| {
+        let mut controller = Controller::new();
+        controller.set_buttons(buttons);
+        nes.set_controller1_from_controller(&controller);
+    });
+    
+    assert_eq!(nes.bus.get_ram()[0x00], buttons.bits());
+    assert_eq!(hash, 0x5752AA3725E02325);
+}
+
+#[test]
+fn test_golden_runs_are_deterministic() {
+    let rom_data = ccnes_core::test_rom::create_sprite_test_rom();
+    assert_eq!(run_and_hash(&rom_data, 5, |_| {}).1, run_and_hash(&rom_data, 5, |_| {}).1);
+}
@@ -0,0 +1,128 @@
+use ccnes_core::cartridge::Cartridge;
+
+/// Every 8KB of PRG and 1KB of CHR starts with its own bank number
+fn create_rom(mapper: u8, prg_banks: u8, chr_banks: u8) -> Vec<u8> {
+    let prg_size = prg_banks as usize * 0x4000;
+    let chr_size = chr_banks as usize * 0x2000;
+    let mut rom_data = vec![0; 16 + prg_size + chr_size];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = prg_banks;
+    rom_data[5] = chr_banks;
+    rom_data[6] = mapper << 4;
+    rom_data[7] = mapper & 0xF0;
+    for offset in (0..prg_size).step_by(0x2000) {
+        rom_data[16 + offset] = (offset / 0x2000) as u8;
+    }
+    for offset in (0..chr_size).step_by(0x400) {
+        rom_data[16 + prg_size + offset] = (offset / 0x400) as u8;
+    }
+    rom_data
+}
+
+/// The maps must point at the bytes the mapper actually reads
+fn assert_maps_match_reads(cartridge: &Cartridge) {
+    for (i, offset) in cartridge.prg_bank_map().into_iter().enumerate() {
+        assert_eq!(offset % 0x2000, 0);
+        let addr = 0x8000 + i as u16 * 0x2000;
+        assert_eq!(cartridge.read_prg(addr) as usize, offset / 0x2000, "PRG window {}", i);
+    }
+    if cartridge.chr_rom_size() == 0 {
+        return;
+    }
+    for (i, offset) in cartridge.chr_bank_map().into_iter().enumerate() {
+        assert_eq!(offset % 0x400, 0);
+        let addr = i as u16 * 0x400;
+        assert_eq!(cartridge.read_chr(addr) as usize, offset / 0x400, "CHR window {}", i);
+    }
+}
+
+#[test]
+fn test_fixed_mapper_bank_map() {
+    // NROM-128 mirrors its single 16KB bank
+    let cartridge = Cartridge::from_ines_bytes(&create_rom(0, 1, 1)).unwrap();
+    assert_eq!(cartridge.prg_bank_map(), [0, 0x2000, 0, 0x2000]);
+    assert_eq!(cartridge.chr_bank_map(), [0, 0x400, 0x800, 0xC00, 0x1000, 0x1400, 0x1800, 0x1C00]);
+    assert_maps_match_reads(&cartridge);
+}
+
+#[test]
+fn test_nrom_128_vectors_from_mirror() {
+    let mut rom_data = create_rom(0, 1, 1);
+    // Vectors at the end of the only bank, $BFFA-$BFFF
+    rom_data[16 + 0x3FFA..16 + 0x4000].copy_from_slice(&[0x00, 0x81, 0x34, 0x82, 0x00, 0x80]);
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    
+    assert_eq!(cartridge.read_prg(0xFFFC), 0x34);
+    assert_eq!(cartridge.read_prg(0xFFFD), 0x82);
+    assert_eq!(cartridge.read_prg(0xFFFA), cartridge.read_prg(0xBFFA));
+    assert_eq!(cartridge.read_prg(0xC000), cartridge.read_prg(0x8000));
+    
+    let mut nes = ccnes_core::Nes::new();
+    nes.load_cartridge(cartridge);
+    assert_eq!(nes.cpu.pc, 0x8234);
+}
+
+#[test]
+fn test_nrom_256_maps_linearly() {
+    let cartridge = Cartridge::from_ines_bytes(&create_rom(0, 2, 1)).unwrap();
+    assert_eq!(cartridge.read_prg(0x8000), 0);
+    assert_eq!(cartridge.read_prg(0xC000), 2);
+    assert_eq!(cartridge.read_prg(0xE000), 3);
+}
+
+#[test]
+fn test_uxrom_bank_map() {
+    let mut cartridge = Cartridge::from_ines_bytes(&create_rom(2, 8, 1)).unwrap();
+    cartridge.write_prg(0x8000, 3);
+    assert_eq!(cartridge.prg_bank_map(), [0xC000, 0xE000, 0x1C000, 0x1E000]);
+    assert_maps_match_reads(&cartridge);
+}
+
+#[test]
+fn test_mmc1_bank_map() {
+    let mut cartridge = Cartridge::from_ines_bytes(&create_rom(1, 8, 4)).unwrap();
+    // Serial writes: control = 0x1C (4KB CHR, 16KB PRG with $C000 fixed)
+    let mut write_register = |addr: u16, value: u8| {
+        for bit in 0..5 {
+            cartridge.write_prg(addr, (value >> bit) & 1);
+        }
+    };
+    write_register(0x8000, 0x1C);
+    write_register(0xA000, 5);
+    write_register(0xC000, 2);
+    write_register(0xE000, 3);
+    
+    assert_eq!(cartridge.prg_bank_map(), [0xC000, 0xE000, 0x1C000, 0x1E000]);
+    assert_eq!(cartridge.chr_bank_map()[0], 5 * 0x1000);
+    assert_eq!(cartridge.chr_bank_map()[4], 2 * 0x1000);
+    assert_maps_match_reads(&cartridge);
+}
+
+#[test]
+fn test_mmc3_bank_map() {
+    let mut cartridge = Cartridge::from_ines_bytes(&create_rom(4, 8, 16)).unwrap();
+    for (register, bank) in [(0, 4), (1, 10), (2, 20), (3, 21), (4, 22), (5, 23), (6, 5), (7, 9)] {
+        cartridge.write_prg(0x8000, register);
+        cartridge.write_prg(0x8001, bank);
+    }
+    
+    assert_eq!(cartridge.prg_bank_map(), [5 * 0x2000, 9 * 0x2000, 14 * 0x2000, 15 * 0x2000]);
+    assert_eq!(cartridge.chr_bank_map(), [4, 5, 10, 11, 20, 21, 22, 23].map(|bank| bank * 0x400));
+    assert_maps_match_reads(&cartridge);
+    
+    // PRG/CHR mode bits swap the windows
+    cartridge.write_prg(0x8000, 0xC6);
+    cartridge.write_prg(0x8001, 5);
+    assert_eq!(cartridge.prg_bank_map(), [14 * 0x2000, 9 * 0x2000, 5 * 0x2000, 15 * 0x2000]);
+    assert_eq!(cartridge.chr_bank_map(), [20, 21, 22, 23, 4, 5, 10, 11].map(|bank| bank * 0x400));
+    assert_maps_match_reads(&cartridge);
+}
+
+#[test]
+fn test_switchable_mapper_bank_maps_match_reads() {
+    for (mapper, chr_banks, value) in [(3, 4, 0x02), (7, 0, 0x03), (11, 4, 0x21), (66, 4, 0x12)] {
+        let mut cartridge = Cartridge::from_ines_bytes(&create_rom(mapper, 8, chr_banks)).unwrap();
+        cartridge.write_prg(0x8000, value);
+        assert_maps_match_reads(&cartridge);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+use ccnes_core::cartridge::Mapper;
+use ccnes_core::cartridge::mappers::{Mapper4, Mapper5};
+
+// One scanline as seen by the MMC3: BG fetches from $0xxx, then a sprite fetch from $1xxx
+fn mmc3_scanline(mapper: &mut Mapper4) {
+    for _ in 0..20 {
+        mapper.clock_scanline(0x0000);
+    }
+    mapper.clock_scanline(0x1000);
+}
+
+fn mmc3_with_irq(latch: u8) -> Mapper4 {
+    let mut mapper = Mapper4::new(0x8000, 0x2000, 0x2000);
+    mapper.write_prg(0xC000, latch); // IRQ latch
+    mapper.write_prg(0xC001, 0);     // Reload
+    mapper.write_prg(0xE001, 0);     // Enable
+    mapper
+}
+
+// Scanlines until the IRQ line goes high
+fn scanlines_until_irq(mapper: &mut Mapper4) -> usize {
+    for line in 1..=256 {
+        mmc3_scanline(mapper);
+        if mapper.irq_pending() {
+            return line;
+        }
+    }
+    panic!("MMC3 IRQ never fired");
+}
+
+#[test]
+fn test_mmc3_irq_schedule_survives_save_state() {
+    let mut reference = mmc3_with_irq(5);
+    let expected = scanlines_until_irq(&mut reference);
+    assert_eq!(expected, 6);
+    
+    // Save two scanlines in, restore into a fresh mapper and keep counting
+    let mut mapper = mmc3_with_irq(5);
+    mmc3_scanline(&mut mapper);
+    mmc3_scanline(&mut mapper);
+    let state = mapper.get_state();
+    
+    let mut restored = Mapper4::new(0x8000, 0x2000, 0x2000);
+    restored.set_state(&state);
+    assert_eq!(scanlines_until_irq(&mut restored) + 2, expected);
+}
+
+#[test]
+fn test_mmc3_reload_flag_is_saved() {
+    // $C001 written but no scanline clocked yet: the reload must still happen
+    let mut mapper = mmc3_with_irq(3);
+    let state = mapper.get_state();
+    
+    let mut restored = Mapper4::new(0x8000, 0x2000, 0x2000);
+    restored.set_state(&state);
+    assert_eq!(scanlines_until_irq(&mut restored), scanlines_until_irq(&mut mapper));
+}
+
+#[test]
+fn test_mmc3_pending_irq_is_saved() {
+    let mut mapper = mmc3_with_irq(1);
+    scanlines_until_irq(&mut mapper);
+    
+    let mut restored = Mapper4::new(0x8000, 0x2000, 0x2000);
+    restored.set_state(&mapper.get_state());
+    assert!(restored.irq_pending());
+    
+    // Acknowledge through $E000 as a game would
+    restored.write_prg(0xE000, 0);
+    assert!(!restored.irq_pending());
+}
+
+#[test]
+fn test_mmc5_irq_state_is_saved() {
+    let mut mapper = Mapper5::new(0x8000, 0x2000, 0x8000);
+    mapper.write_prg(0x5203, 120);  // Compare scanline
+    mapper.write_prg(0x5204, 0x80); // Enable
+    mapper.write_prg(0x5114, 3);
+    
+    let mut restored = Mapper5::new(0x8000, 0x2000, 0x8000);
+    restored.set_state(&mapper.get_state());
+    
+    let prg_rom: Vec<u8> = (0..0x8000).map(|i| (i / 0x2000) as u8).collect();
+    assert_eq!(restored.read_prg(0x8000, &prg_rom), mapper.read_prg(0x8000, &prg_rom));
+    
+    // Disabling through the restored registers behaves like the original
+    restored.write_prg(0x5204, 0x00);
+    assert!(!restored.irq_pending());
+    assert!(matches!(restored.get_state(),
+        ccnes_core::cartridge::MapperState::Mapper5 { irq_compare: 120, irq_enabled: false, .. }));
+}
\ No newline at end of file
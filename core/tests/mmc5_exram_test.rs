@@ -0,0 +1,178 @@
+use ccnes_core::cartridge::Mirroring;
+use ccnes_core::cpu::CpuBus;
+use ccnes_core::{Cartridge, Nes};
+
+// MMC5 with 32KB CHR: 4KB bank 5 holds a solid tile 0 (color 3), the rest
+// is blank. The CPU spins in `JMP $8000`.
+fn create_nes() -> Nes {
+    let prg_size = 0x4000;
+    let chr_size = 0x8000;
+    let mut rom_data = vec![0; 16 + prg_size + chr_size];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 1;
+    rom_data[5] = 4;
+    rom_data[6] = 0x50;
+    
+    let prg = &mut rom_data[16..16 + prg_size];
+    prg[0..3].copy_from_slice(&[0x4C, 0x00, 0x80]);
+    prg[0x1FFC..0x1FFE].copy_from_slice(&[0x00, 0x80]);
+    
+    let chr = &mut rom_data[16 + prg_size..];
+    chr[5 * 0x1000..5 * 0x1000 + 16].fill(0xFF);
+    
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    nes.skip_ppu_warmup();
+    nes
+}
+
+fn write_ppu(nes: &mut Nes, addr: u16, values: &[u8]) {
+    nes.bus_mut().write(0x2006, (addr >> 8) as u8);
+    nes.bus_mut().write(0x2006, addr as u8);
+    for &value in values {
+        nes.bus_mut().write(0x2007, value);
+    }
+}
+
+// Render with tiles 0 and 1 of the top row pointing at bank 5 through
+// ExRAM, with palettes 2 and 1
+fn render(exram_mode: u8) -> Nes {
+    let mut nes = create_nes();
+    let bus = nes.bus_mut();
+    bus.write(0x5104, exram_mode);
+    bus.write(0x5C00, 0x80 | 5);
+    bus.write(0x5C01, 0x40 | 5);
+    
+    write_ppu(&mut nes, 0x3F00, &[0x0F, 0, 0, 0, 0, 0, 0, 0x16, 0, 0, 0, 0x2A]);
+    let bus = nes.bus_mut();
+    bus.write(0x2005, 0);
+    bus.write(0x2005, 0);
+    bus.write(0x2000, 0x00);
+    bus.write(0x2001, 0x0A);
+    
+    for _ in 0..2 {
+        nes.run_frame().unwrap();
+    }
+    nes
+}
+
+#[test]
+fn test_extended_attributes() {
+    let nes = render(1);
+    let frame = nes.get_framebuffer();
+    let pixel = |x: usize, y: usize| frame[y * 256 + x];
+    
+    // Sample the middle of each tile, clear of the first and last rows and
+    // columns
+    let backdrop = pixel(100, 100);
+    assert_ne!(pixel(4, 4), backdrop, "Tile 0 comes from CHR bank 5");
+    assert_ne!(pixel(12, 4), backdrop, "Tile 1 comes from CHR bank 5");
+    assert_ne!(pixel(4, 4), pixel(12, 4), "Each tile has its own palette");
+    assert_eq!(pixel(4, 4), pixel(5, 6));
+    assert_eq!(pixel(20, 4), backdrop);
+    assert_eq!(pixel(4, 12), backdrop);
+}
+
+#[test]
+fn test_extended_attributes_need_exram_mode_1() {
+    let nes = render(0);
+    let frame = nes.get_framebuffer();
+    assert!(frame.iter().all(|&color| color == frame[0]));
+}
+
+fn read_ppu(nes: &mut Nes, addr: u16) -> u8 {
+    nes.bus_mut().write(0x2006, (addr >> 8) as u8);
+    nes.bus_mut().write(0x2006, addr as u8);
+    nes.bus_mut().read(0x2007); // Fill the read buffer
+    nes.bus_mut().read(0x2007)
+}
+
+#[test]
+fn test_exram_nametable() {
+    let mut nes = create_nes();
+    let bus = nes.bus_mut();
+    bus.write(0x5104, 0x00);
+    bus.write(0x5105, 0b10 << 2); // $2400 from ExRAM, the rest CIRAM page 0
+    
+    write_ppu(&mut nes, 0x2000, &[0x11]);
+    write_ppu(&mut nes, 0x2400, &[0x42]);
+    assert_eq!(nes.bus_mut().read(0x5C00), 0x42);
+    assert_eq!(read_ppu(&mut nes, 0x2400), 0x42);
+    assert_eq!(read_ppu(&mut nes, 0x2000), 0x11);
+}
+
+#[test]
+fn test_ciram_page_per_nametable() {
+    let mut nes = create_nes();
+    // $2000 and $2C00 on page 0, $2400 and $2800 on page 1, which no
+    // mirroring mode can do
+    nes.bus_mut().write(0x5105, 0b00_01_01_00);
+    
+    write_ppu(&mut nes, 0x2000, &[0x11]);
+    write_ppu(&mut nes, 0x2400, &[0x22]);
+    assert_eq!(read_ppu(&mut nes, 0x2C00), 0x11);
+    assert_eq!(read_ppu(&mut nes, 0x2800), 0x22);
+    assert_eq!(nes.bus.ppu.dump_vram()[0x000], 0x11);
+    assert_eq!(nes.bus.ppu.dump_vram()[0x400], 0x22);
+    
+    // Swapping the pages swaps what each nametable shows
+    nes.bus_mut().write(0x5105, 0b01_00_00_01);
+    assert_eq!(read_ppu(&mut nes, 0x2000), 0x22);
+    assert_eq!(read_ppu(&mut nes, 0x2400), 0x11);
+    
+    // Layouts a mirroring mode can describe are reported as one
+    nes.bus_mut().write(0x5105, 0x44);
+    assert_eq!(nes.bus.cartridge.as_ref().unwrap().mirroring(), Mirroring::Vertical);
+}
+
+#[test]
+fn test_fill_mode_nametable() {
+    let mut nes = create_nes();
+    let bus = nes.bus_mut();
+    bus.write(0x5105, 0xFF);
+    bus.write(0x5106, 0x33);
+    bus.write(0x5107, 0x02);
+    
+    // Writes are dropped and every table reads back the fill tile and palette
+    write_ppu(&mut nes, 0x2C00, &[0x99]);
+    assert_eq!(read_ppu(&mut nes, 0x2C00), 0x33);
+    assert_eq!(read_ppu(&mut nes, 0x2000), 0x33);
+    assert_eq!(read_ppu(&mut nes, 0x27C0), 0xAA);
+}
+
+#[test]
+fn test_chr_upper_bits_are_saved() {
+    let mut nes = create_nes();
+    nes.bus_mut().write(0x5130, 0x02);
+    let state = nes.cartridge().unwrap().get_mapper_state();
+    assert!(matches!(state, ccnes_core::cartridge::MapperState::Mapper5 { chr_upper: 2, .. }));
+}
+#[test]
+fn test_exram_is_saved() {
+    // Two frames each time: quick saves leave out the tiles the PPU has
+    // already fetched for the top of the next frame
+    let run = |nes: &mut Nes| {
+        nes.run_frame().unwrap();
+        nes.run_frame().unwrap();
+        nes.get_framebuffer().to_vec()
+    };
+    let mut nes = render(1);
+    let quick = nes.quick_save();
+    let mut full = Vec::new();
+    nes.save_full_state(&mut full).unwrap();
+    let frame = run(&mut nes);
+    
+    let clobber = |nes: &mut Nes| {
+        nes.bus_mut().write(0x5C00, 0);
+        nes.bus_mut().write(0x5C01, 0);
+        assert_ne!(run(nes), frame);
+    };
+    
+    clobber(&mut nes);
+    nes.quick_load(&quick).unwrap();
+    assert_eq!(run(&mut nes), frame);
+    
+    clobber(&mut nes);
+    nes.load_full_state(&full[..]).unwrap();
+    assert_eq!(run(&mut nes), frame);
+}
@@ -0,0 +1,274 @@
+use ccnes_core::{Cartridge, Nes, Ppu};
+use ccnes_core::cpu::{Cpu, CpuBus};
+
+// NROM image with `main` at $C000 and the NMI handler at $C100
+fn nmi_rom(main: &[u8], handler: &[u8]) -> Vec<u8> {
+    let mut rom = vec![0; 16 + 0x4000 + 0x2000];
+    rom[0..4].copy_from_slice(b"NES\x1A");
+    rom[4] = 1;
+    rom[5] = 1;
+    
+    let prg = &mut rom[16..16 + 0x4000];
+    prg[..main.len()].copy_from_slice(main);
+    prg[0x100..0x100 + handler.len()].copy_from_slice(handler);
+    prg[0x200] = 0x40; // RTI for IRQ/BRK
+    prg[0x3FFA..].copy_from_slice(&[0x00, 0xC1, 0x00, 0xC0, 0x00, 0xC2]);
+    rom
+}
+
+// Enable NMI, then run `main_loop` forever
+fn main_program(main_loop: &[u8]) -> Vec<u8> {
+    let mut main = vec![
+        0xA9, 0x80,       // LDA #$80
+        0x8D, 0x00, 0x20, // STA $2000
+    ];
+    main.extend_from_slice(main_loop);
+    main.extend_from_slice(&[0x4C, 0x05, 0xC0]); // JMP $C005
+    main
+}
+
+// NMIs taken over `frames` frames, counted in $10 by the handler
+fn nmi_count(main_loop: &[u8], handler_body: &[u8], frames: usize) -> u8 {
+    let mut handler = vec![0xE6, 0x10]; // INC $10
+    handler.extend_from_slice(handler_body);
+    handler.push(0x40); // RTI
+    
+    let rom = nmi_rom(&main_program(main_loop), &handler);
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom).unwrap());
+    nes.reset();
+    nes.skip_ppu_warmup();
+    for _ in 0..frames {
+        nes.run_frame().unwrap();
+    }
+    nes.bus.read(0x0010)
+}
+
+// Disable and re-enable NMI
+const TOGGLE: &[u8] = &[
+    0xA9, 0x00,       // LDA #$00
+    0x8D, 0x00, 0x20, // STA $2000
+    0xA9, 0x80,       // LDA #$80
+    0x8D, 0x00, 0x20, // STA $2000
+];
+
+#[test]
+fn test_nmi_once_per_vblank() {
+    // The NMI line stays high for all of VBlank but only the edge counts
+    let count = nmi_count(&[], &[], 10);
+    assert!((9..=10).contains(&count), "Expected one NMI per frame, got {}", count);
+}
+
+#[test]
+fn test_ppuctrl_toggle_in_vblank() {
+    // Re-enabling NMI while the VBlank flag is still set raises a new edge
+    assert!(nmi_count(TOGGLE, &[], 10) > 10, "Toggling PPUCTRL bit 7 in VBlank should retrigger NMI");
+    
+    // Acknowledging the flag in the handler leaves nothing to retrigger on
+    let count = nmi_count(TOGGLE, &[0x2C, 0x02, 0x20], 10); // BIT $2002
+    assert!((9..=10).contains(&count), "Expected one NMI per frame, got {}", count);
+}
+
+struct MockBus {
+    memory: [u8; 0x10000],
+}
+
+impl CpuBus for MockBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.memory[addr as usize]
+    }
+    
+    fn write(&mut self, addr: u16, value: u8) {
+        self.memory[addr as usize] = value;
+    }
+}
+
+fn nop_cpu() -> (Cpu, MockBus) {
+    let mut bus = MockBus { memory: [0xEA; 0x10000] }; // NOPs everywhere
+    bus.memory[0xFFFA] = 0x00;
+    bus.memory[0xFFFB] = 0x90;
+    bus.memory[0xFFFC] = 0x00;
+    bus.memory[0xFFFD] = 0x80;
+    
+    let mut cpu = Cpu::new();
+    cpu.reset(&mut bus);
+    (cpu, bus)
+}
+
+#[test]
+fn test_nmi_in_last_cycle_runs_one_more_instruction() {
+    let (mut cpu, mut bus) = nop_cpu();
+    cpu.step(&mut bus);
+    
+    // Raised during the NOP's final cycle
+    cpu.trigger_nmi();
+    cpu.delay_nmi();
+    
+    assert_eq!(cpu.step(&mut bus), 2);
+    assert_eq!(cpu.pc, 0x8002, "One more instruction runs before the NMI");
+    
+    assert_eq!(cpu.step(&mut bus), 9);
+    assert_eq!(cpu.pc, 0x9001);
+}
+
+#[test]
+fn test_nmi_cancelled_before_boundary() {
+    let (mut cpu, mut bus) = nop_cpu();
+    cpu.trigger_nmi();
+    cpu.delay_nmi();
+    cpu.step(&mut bus);
+    cpu.cancel_nmi();
+    
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x8002);
+}
+
+#[test]
+fn test_status_read_racing_vblank() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    
+    for (dots_after_edge, cancelled) in [(0, true), (1, true), (2, false), (10, false)] {
+        let mut ppu = Ppu::new();
+        ppu.write_register(0, 0x80);
+        while !ppu.step(&cartridge).nmi {}
+        for _ in 0..dots_after_edge {
+            assert!(!ppu.step(&cartridge).nmi, "NMI edge must be reported once");
+        }
+        
+        let status = ppu.read_register(2);
+        assert_ne!(status & 0x80, 0, "VBlank flag is visible {} dots after it was set", dots_after_edge);
+        assert_eq!(ppu.take_nmi_cancelled(), cancelled, "{} dots after VBlank", dots_after_edge);
+        assert!(!ppu.take_nmi_cancelled());
+    }
+}
+
+#[test]
+fn test_status_read_per_dot_around_vblank() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    let new_ppu = || {
+        let mut ppu = Ppu::new();
+        ppu.write_register(0, 0x80);
+        ppu
+    };
+    
+    // Dots until the one that sets VBlank
+    let mut ppu = new_ppu();
+    let mut edge = 1;
+    while !ppu.step(&cartridge).nmi {
+        edge += 1;
+    }
+    
+    // Read after `dots` steps: (flag read, NMI still raised, NMI taken back)
+    let cases = [
+        (edge - 2, false, true, false),
+        (edge - 1, false, false, false),
+        (edge, true, true, true),
+        (edge + 1, true, true, true),
+        (edge + 2, true, true, false),
+    ];
+    for (dots, flag, nmi, cancelled) in cases {
+        let mut ppu = new_ppu();
+        let mut raised = false;
+        for _ in 0..dots {
+            raised |= ppu.step(&cartridge).nmi;
+        }
+        
+        let status = ppu.read_register(2);
+        for _ in 0..10 {
+            raised |= ppu.step(&cartridge).nmi;
+        }
+        
+        let offset = dots - edge;
+        assert_eq!(status & 0x80 != 0, flag, "Flag read {} dots from VBlank", offset);
+        assert_eq!(raised, nmi, "NMI edge {} dots from VBlank", offset);
+        assert_eq!(ppu.take_nmi_cancelled(), cancelled, "NMI taken back {} dots from VBlank", offset);
+        if !nmi {
+            assert_eq!(ppu.read_register(2) & 0x80, 0, "Suppressed flag stays clear");
+        }
+    }
+}
+
+#[test]
+fn test_frame_complete_once_per_frame() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    let mut ppu = Ppu::new();
+    ppu.write_register(0, 0x80);
+    
+    // Each frame raises NMI once and then completes once, a fixed number
+    // of dots apart
+    let mut events = Vec::new();
+    for dot in 0..4 * 341 * 263 {
+        let result = ppu.step(&cartridge);
+        if result.nmi {
+            events.push(("nmi", dot));
+        }
+        if result.frame_complete {
+            events.push(("frame", dot));
+        }
+    }
+    
+    let kinds: Vec<_> = events.iter().map(|&(kind, _)| kind).collect();
+    assert_eq!(&kinds[..6], &["nmi", "frame", "nmi", "frame", "nmi", "frame"]);
+    let frames: Vec<_> = events.iter().filter(|e| e.0 == "frame").map(|e| e.1).collect();
+    assert_eq!(frames[2] - frames[1], frames[1] - frames[0]);
+}
+
+// PPU steps left before the one that sets VBlank, on scanlines up to 241
+fn dots_to_vblank(ppu: &Ppu) -> i32 {
+    (241 - ppu.scanline() as i32) * 341 + 1 - ppu.dot() as i32
+}
+
+// Start `LDA $2002` with the PPU `dots` steps short of setting VBlank:
+// (flag read, NMIs taken, flag read again afterwards)
+fn nes_status_read(dots: i32) -> (bool, u8, bool) {
+    let rom = nmi_rom(&main_program(&[]), &[0xE6, 0x10, 0x40]); // INC $10, RTI
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom).unwrap());
+    nes.reset();
+    nes.skip_ppu_warmup();
+    nes.run_frame().unwrap();
+    while nes.ppu().scanline() >= 241 || dots_to_vblank(nes.ppu()) > 60 {
+        nes.step().unwrap();
+    }
+    
+    // Line the PPU up a whole number of CPU cycles away, then close in
+    while (dots_to_vblank(nes.ppu()) - dots) % 3 != 0 {
+        let cartridge = nes.bus.cartridge.as_ref().unwrap();
+        nes.bus.ppu.step(cartridge);
+    }
+    while dots_to_vblank(nes.ppu()) > dots {
+        nes.cpu.tick_bus(&mut nes.bus, 1);
+    }
+    
+    // LDA $2002, STA $11, then spin on JMP $0305
+    for (i, &byte) in [0xAD, 0x02, 0x20, 0x85, 0x11, 0x4C, 0x05, 0x03].iter().enumerate() {
+        nes.bus.write(0x0300 + i as u16, byte);
+    }
+    nes.bus.write(0x0010, 0);
+    nes.cpu.pc = 0x0300;
+    for _ in 0..20 {
+        nes.step().unwrap();
+    }
+    (nes.bus.read(0x0011) & 0x80 != 0, nes.bus.read(0x0010), nes.bus.read(0x2002) & 0x80 != 0)
+}
+
+#[test]
+fn test_nes_status_read_racing_vblank() {
+    // Through `Nes` the read races VBlank on the dot its instruction starts
+    // on, as `Ppu::read_register` does. A real LDA $2002 reads on its
+    // fourth cycle, so 10 dots out would be the suppressing read there.
+    let cases = [
+        (10, false, 1, true),
+        (1, false, 1, true),
+        (0, false, 0, false),
+        (-1, true, 0, false),
+        (-2, true, 0, false),
+        (-3, true, 1, false),
+    ];
+    for (dots, flag, nmis, flag_after) in cases {
+        assert_eq!(nes_status_read(dots), (flag, nmis, flag_after), "LDA $2002 {} dots before VBlank", dots);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+use ccnes_core::cartridge::{Cartridge, ChrLatch};
+use ccnes_core::cpu::CpuBus;
+use ccnes_core::Nes;
+
+fn create_rom(mapper: u8, chr_banks: u8) -> Vec<u8> {
+    let chr_size = chr_banks as usize * 0x2000;
+    let mut rom_data = vec![0; 16 + 0x8000 + chr_size];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 2;
+    rom_data[5] = chr_banks;
+    rom_data[6] = mapper << 4;
+    rom_data[7] = mapper & 0xF0;
+    // Each 4KB of CHR ROM starts with its own number
+    for offset in (0..chr_size).step_by(0x1000) {
+        rom_data[16 + 0x8000 + offset] = (offset / 0x1000) as u8;
+    }
+    rom_data
+}
+
+// Upload a tile the way games do, through PPUADDR/PPUDATA
+fn upload_tile(nes: &mut Nes, addr: u16, tile: &[u8; 16]) {
+    nes.bus.write(0x2006, (addr >> 8) as u8);
+    nes.bus.write(0x2006, addr as u8);
+    for &byte in tile {
+        nes.bus.write(0x2007, byte);
+    }
+}
+
+#[test]
+fn test_chr_ram_uploads_show_in_pattern_table() {
+    let tile: [u8; 16] = core::array::from_fn(|i| (i * 17) as u8 | 1);
+    
+    // NROM, UxROM, AxROM and MMC1 boards without CHR ROM
+    for mapper in [0, 2, 7, 1] {
+        let mut nes = Nes::new();
+        nes.load_cartridge(Cartridge::from_ines_bytes(&create_rom(mapper, 0)).unwrap());
+        nes.skip_ppu_warmup();
+        assert!(nes.bus.cartridge.as_ref().unwrap().has_chr_ram());
+        
+        upload_tile(&mut nes, 0x0010, &tile);
+        upload_tile(&mut nes, 0x1FF0, &tile);
+        
+        let cartridge = nes.bus.cartridge.as_ref().unwrap();
+        assert_eq!(cartridge.pattern_table(0, None)[0x10..0x20], tile, "mapper {}", mapper);
+        assert_eq!(cartridge.pattern_table(1, None)[0xFF0..], tile, "mapper {}", mapper);
+        assert_eq!(cartridge.read_chr(0x0010), tile[0], "mapper {}", mapper);
+        
+        // And reads back through PPUDATA after the buffered read
+        nes.bus.write(0x2006, 0x00);
+        nes.bus.write(0x2006, 0x11);
+        nes.bus.read(0x2007);
+        assert_eq!(nes.bus.read(0x2007), tile[1], "mapper {}", mapper);
+    }
+}
+
+#[test]
+fn test_chr_rom_ignores_writes() {
+    let mut cartridge = Cartridge::from_ines_bytes(&create_rom(0, 1)).unwrap();
+    assert!(!cartridge.has_chr_ram());
+    cartridge.write_chr(0x1000, 0xAA);
+    assert_eq!(cartridge.pattern_table(1, None)[0], 1);
+}
+
+#[test]
+fn test_mmc2_pattern_table_latch_choice() {
+    let mut cartridge = Cartridge::from_ines_bytes(&create_rom(9, 4)).unwrap();
+    cartridge.write_prg(0xB000, 2); // $0000 table, $FD latch
+    cartridge.write_prg(0xC000, 5); // $0000 table, $FE latch
+    cartridge.write_prg(0xD000, 3); // $1000 table, $FD latch
+    cartridge.write_prg(0xE000, 7); // $1000 table, $FE latch
+    
+    assert_eq!(cartridge.pattern_table(0, None)[0], 2);
+    assert_eq!(cartridge.pattern_table(0, Some([ChrLatch::Fe, ChrLatch::Fd]))[0], 5);
+    assert_eq!(cartridge.pattern_table(1, Some([ChrLatch::Fe, ChrLatch::Fd]))[0], 3);
+    assert_eq!(cartridge.pattern_table(1, Some([ChrLatch::Fd, ChrLatch::Fe]))[0], 7);
+}
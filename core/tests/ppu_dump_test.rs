@@ -0,0 +1,58 @@
+use ccnes_core::Ppu;
+
+fn write_vram(ppu: &mut Ppu, addr: u16, data: &[u8]) {
+    ppu.write_register(6, (addr >> 8) as u8);
+    ppu.write_register(6, addr as u8);
+    for &byte in data {
+        ppu.write_register(7, byte);
+    }
+}
+
+#[test]
+fn test_dumps_match_register_writes() {
+    let mut ppu = Ppu::new();
+    
+    write_vram(&mut ppu, 0x2000, &[0x11, 0x22]);
+    write_vram(&mut ppu, 0x3F00, &[0x0F, 0x30]);
+    ppu.write_register(3, 0x04);
+    ppu.write_register(4, 0x55);
+    
+    let vram = ppu.dump_vram();
+    assert_eq!(&vram[0..2], &[0x11, 0x22]);
+    
+    let palette = ppu.dump_palette();
+    assert_eq!(&palette[0..2], &[0x0F, 0x30]);
+    
+    assert_eq!(ppu.dump_oam()[4], 0x55);
+}
+
+#[test]
+fn test_load_round_trip() {
+    let mut oam = [0; 256];
+    let mut vram = [0; 0x800];
+    let mut palette = [0; 32];
+    for (i, byte) in oam.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    for (i, byte) in vram.iter_mut().enumerate() {
+        *byte = (i * 7) as u8;
+    }
+    for (i, byte) in palette.iter_mut().enumerate() {
+        *byte = (i as u8) & 0x3F;
+    }
+    
+    let mut ppu = Ppu::new();
+    ppu.load_oam(&oam);
+    ppu.load_vram(&vram);
+    ppu.load_palette(&palette);
+    
+    assert_eq!(ppu.dump_oam(), oam);
+    assert_eq!(ppu.dump_vram(), &vram);
+    assert_eq!(ppu.dump_palette(), palette);
+    
+    // Loaded data is what the game sees through $2007
+    ppu.write_register(6, 0x20);
+    ppu.write_register(6, 0x01);
+    ppu.read_register(7); // Prime the read buffer
+    assert_eq!(ppu.read_register(7), vram[1]);
+}
\ No newline at end of file
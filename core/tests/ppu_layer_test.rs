@@ -0,0 +1,82 @@
+use ccnes_core::{Cartridge, Nes};
+use ccnes_core::ppu::LayerMask;
+use std::collections::HashSet;
+
+fn run_test_rom(layers: Option<LayerMask>) -> Vec<u32> {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines(std::io::Cursor::new(rom_data))
+        .expect("Failed to load test ROM");
+    
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    nes.bus.ppu.set_layer_override(layers);
+    
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    
+    nes.get_framebuffer().to_vec()
+}
+
+#[test]
+fn test_layer_override_hides_background() {
+    let normal = run_test_rom(None);
+    let unique_colors: HashSet<_> = normal.iter().collect();
+    assert!(unique_colors.len() > 1, "Background tiles should produce multiple colors");
+    
+    // With the background hidden only the backdrop color remains
+    let hidden = run_test_rom(Some(LayerMask::HIDE_BACKGROUND));
+    let unique_colors: HashSet<_> = hidden.iter().collect();
+    assert_eq!(unique_colors.len(), 1, "Only the backdrop color should be visible");
+}
+
+#[test]
+fn test_layer_override_no_clipping() {
+    // The test ROM leaves the left 8 pixel background clip enabled
+    let clipped = run_test_rom(None);
+    let unclipped = run_test_rom(Some(LayerMask::NO_CLIPPING));
+    
+    let row = 100 * 256;
+    let left_clipped: HashSet<_> = clipped[row..row + 8].iter().collect();
+    let left_unclipped: HashSet<_> = unclipped[row..row + 8].iter().collect();
+    assert_eq!(left_clipped.len(), 1);
+    assert!(left_unclipped.len() > 1);
+}
+
+#[test]
+fn test_output_filter_runs_once_per_frame() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut plain = Nes::new();
+    plain.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    let mut filtered = Nes::new();
+    filtered.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    
+    let frames = Rc::new(Cell::new(0));
+    let counter = frames.clone();
+    filtered.bus.ppu.set_output_filter(Some(Box::new(move |frame: &mut [u32]| {
+        assert_eq!(frame.len(), 256 * 240);
+        counter.set(counter.get() + 1);
+        for pixel in frame {
+            *pixel ^= 0x00FFFFFF;
+        }
+    })));
+    
+    for _ in 0..5 {
+        plain.run_frame().unwrap();
+        filtered.run_frame().unwrap();
+    }
+    assert_eq!(frames.get(), 5);
+    let inverted: Vec<u32> = plain.get_framebuffer().iter().map(|&c| c ^ 0x00FFFFFF).collect();
+    assert_eq!(filtered.get_framebuffer(), &inverted[..]);
+    
+    // Copies of the machine don't run it, and it can be removed
+    let mut copy = filtered.clone();
+    copy.run_frame().unwrap();
+    assert_eq!(frames.get(), 5);
+    filtered.bus.ppu.set_output_filter(None);
+    filtered.run_frame().unwrap();
+    assert_eq!(frames.get(), 5);
+}
\ No newline at end of file
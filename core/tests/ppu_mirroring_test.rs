@@ -0,0 +1,166 @@
+use ccnes_core::cartridge::{Cartridge, Mirroring};
+use ccnes_core::cpu::CpuBus;
+use ccnes_core::{Nes, Ppu};
+
+const NAMETABLES: [u16; 4] = [0x2000, 0x2400, 0x2800, 0x2C00];
+
+fn write_vram(ppu: &mut Ppu, addr: u16, value: u8) {
+    ppu.write_register(6, (addr >> 8) as u8);
+    ppu.write_register(6, addr as u8);
+    ppu.write_register(7, value);
+}
+
+fn read_vram(ppu: &mut Ppu, addr: u16) -> u8 {
+    ppu.write_register(6, (addr >> 8) as u8);
+    ppu.write_register(6, addr as u8);
+    ppu.read_register(7); // Fill the read buffer
+    ppu.read_register(7)
+}
+
+/// Write 1-4 to the four nametables and read back what each one holds
+fn nametable_contents(mirroring: Mirroring) -> [u8; 4] {
+    let mut ppu = Ppu::new();
+    ppu.set_mirroring(mirroring);
+    for (i, &addr) in NAMETABLES.iter().enumerate() {
+        write_vram(&mut ppu, addr + 0x55, i as u8 + 1);
+    }
+    NAMETABLES.map(|addr| read_vram(&mut ppu, addr + 0x55))
+}
+
+#[test]
+fn test_four_screen_nametables_are_independent() {
+    let mut rom_data = ccnes_core::test_rom::create_test_rom();
+    rom_data[6] |= 0x08; // Four-screen VRAM
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    nes.skip_ppu_warmup();
+    for (i, &addr) in NAMETABLES.iter().enumerate() {
+        write_bus_vram(&mut nes, addr + 0x55, i as u8 + 1);
+    }
+    let contents = NAMETABLES.map(|addr| {
+        nes.bus.write(0x2006, ((addr + 0x55) >> 8) as u8);
+        nes.bus.write(0x2006, (addr + 0x55) as u8);
+        nes.bus.read(0x2007); // Fill the read buffer
+        nes.bus.read(0x2007)
+    });
+    assert_eq!(contents, [1, 2, 3, 4]);
+    
+    // $2800 and $2C00 live on the cartridge
+    assert_eq!(nes.bus.ppu.dump_vram()[0x455], 2);
+    assert_eq!(nes.bus.cartridge.as_ref().unwrap().nametable_ram()[0x455], 4);
+}
+
+#[test]
+fn test_two_screen_mirroring() {
+    assert_eq!(nametable_contents(Mirroring::Horizontal), [2, 2, 4, 4]);
+    assert_eq!(nametable_contents(Mirroring::Vertical), [3, 4, 3, 4]);
+    assert_eq!(nametable_contents(Mirroring::SingleScreenLow), [4, 4, 4, 4]);
+    assert_eq!(nametable_contents(Mirroring::SingleScreenHigh), [4, 4, 4, 4]);
+}
+
+#[test]
+fn test_single_screen_selects_a_half() {
+    let mut ppu = Ppu::new();
+    ppu.set_mirroring(Mirroring::SingleScreenHigh);
+    write_vram(&mut ppu, 0x2C10, 0x77);
+    assert_eq!(ppu.dump_vram()[0x410], 0x77);
+    
+    ppu.set_mirroring(Mirroring::SingleScreenLow);
+    write_vram(&mut ppu, 0x2410, 0x66);
+    assert_eq!(ppu.dump_vram()[0x010], 0x66);
+    assert_eq!(ppu.dump_vram()[0x410], 0x77);
+}
+
+#[test]
+fn test_mirroring_from_header() {
+    let mut rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert_eq!(nes.bus.ppu.mirroring(), Mirroring::Horizontal);
+    
+    rom_data[6] |= 0x08; // Four-screen VRAM
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert_eq!(nes.bus.ppu.mirroring(), Mirroring::FourScreen);
+}
+
+fn write_bus_vram(nes: &mut Nes, addr: u16, value: u8) {
+    nes.bus.write(0x2006, (addr >> 8) as u8);
+    nes.bus.write(0x2006, addr as u8);
+    nes.bus.write(0x2007, value);
+}
+
+#[test]
+fn test_axrom_switches_single_screen_page() {
+    // AxROM: 32KB PRG, CHR RAM, page select in bit 4 of $8000-$FFFF
+    let mut rom_data = vec![0; 16 + 0x8000];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 2;
+    rom_data[6] = 0x70;
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    nes.skip_ppu_warmup();
+    
+    nes.bus.write(0x8000, 0x10);
+    assert_eq!(nes.bus.cartridge.as_ref().unwrap().mirroring(), Mirroring::SingleScreenHigh);
+    write_bus_vram(&mut nes, 0x2010, 0x77);
+    assert_eq!(nes.bus.ppu.dump_vram()[0x410], 0x77);
+    
+    nes.bus.write(0x8000, 0x00);
+    assert_eq!(nes.bus.cartridge.as_ref().unwrap().mirroring(), Mirroring::SingleScreenLow);
+    write_bus_vram(&mut nes, 0x2C10, 0x66);
+    assert_eq!(nes.bus.ppu.dump_vram()[0x010], 0x66);
+    assert_eq!(nes.bus.ppu.dump_vram()[0x410], 0x77);
+}
+
+fn set_scroll(ppu: &mut Ppu, nametable: u8, x: u8, y: u8) {
+    ppu.write_register(0, nametable);
+    ppu.write_register(5, x);
+    ppu.write_register(5, y);
+}
+
+#[test]
+fn test_tile_at_screen() {
+    let mut ppu = Ppu::new();
+    ppu.set_mirroring(Mirroring::Vertical);
+    // $2000 holds (row * 32 + column), $2400 the same with bit 7 flipped
+    for (base, flip) in [(0x2000, 0x00), (0x2400, 0x80)] {
+        for i in 0..960u16 {
+            write_vram(&mut ppu, base + i, i as u8 ^ flip);
+        }
+    }
+    // Palettes 0, 1, 2, 3 for the four quadrants of the top-left block
+    write_vram(&mut ppu, 0x23C0, 0b11_10_01_00);
+    
+    set_scroll(&mut ppu, 0, 0, 0);
+    assert_eq!(ppu.tile_at_screen(0, 0, None), Some((0x2000, 0x00, 0)));
+    assert_eq!(ppu.tile_at_screen(17, 9, None), Some((0x2022, 0x22, 1)));
+    assert_eq!(ppu.tile_at_screen(3, 17, None), Some((0x2040, 0x40, 2)));
+    assert_eq!(ppu.tile_at_screen(31, 31, None), Some((0x2063, 0x63, 3)));
+    assert_eq!(ppu.tile_at_screen(256, 0, None), None);
+    assert_eq!(ppu.tile_at_screen(0, 240, None), None);
+    
+    // Fine X: 5 + 2 is still in column 0, 5 + 3 isn't
+    set_scroll(&mut ppu, 0, 5, 0);
+    assert_eq!(ppu.tile_at_screen(2, 0, None).unwrap().0, 0x2000);
+    assert_eq!(ppu.tile_at_screen(3, 0, None).unwrap().0, 0x2001);
+    
+    // Scrolling right across into the second nametable
+    set_scroll(&mut ppu, 0, 250, 0);
+    assert_eq!(ppu.tile_at_screen(5, 0, None), Some((0x201F, 0x1F, 0)));
+    assert_eq!(ppu.tile_at_screen(10, 0, None), Some((0x2400, 0x80, 0)));
+    
+    // Starting in $2400 and wrapping back to $2000
+    set_scroll(&mut ppu, 1, 8, 0);
+    assert_eq!(ppu.tile_at_screen(255, 0, None), Some((0x2000, 0x00, 0)));
+    
+    // Row 29 wraps down into $2800, which mirrors $2000 here
+    set_scroll(&mut ppu, 0, 0, 236);
+    assert_eq!(ppu.tile_at_screen(0, 3, None), Some((0x23A0, 0xA0, 0)));
+    assert_eq!(ppu.tile_at_screen(0, 4, None), Some((0x2800, 0x00, 0)));
+    assert_eq!(ppu.tile_at_screen(8, 12, None), Some((0x2821, 0x21, 0)));
+    
+    // Scrolled into the attribute rows, rows 30 and 31 wrap within the table
+    set_scroll(&mut ppu, 0, 0, 248);
+    assert_eq!(ppu.tile_at_screen(0, 0, None).unwrap().0, 0x23E0);
+    assert_eq!(ppu.tile_at_screen(0, 8, None).unwrap().0, 0x2000);
+}
@@ -0,0 +1,160 @@
+use ccnes_core::{Cartridge, Ppu};
+
+fn set_vram_addr(ppu: &mut Ppu, addr: u16) {
+    ppu.write_register(6, (addr >> 8) as u8);
+    ppu.write_register(6, addr as u8);
+}
+
+fn read_palette(ppu: &mut Ppu, addr: u16) -> u8 {
+    set_vram_addr(ppu, addr);
+    ppu.read_register(7)
+}
+
+fn render_backdrop(color: u8, mask: u8, greyscale_reads: bool) -> u32 {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines(std::io::Cursor::new(rom_data))
+        .expect("Failed to load test ROM");
+
+    let mut ppu = Ppu::new();
+    ppu.set_greyscale_palette_reads(greyscale_reads);
+    ppu.palette[0] = color;
+    ppu.write_register(1, mask);
+    for _ in 0..341 * 262 {
+        ppu.step(&cartridge);
+    }
+    ppu.framebuffer[0]
+}
+
+#[test]
+fn test_palette_read_greyscale() {
+    let mut ppu = Ppu::new();
+    ppu.palette[1] = 0x2A;
+
+    // Greyscale off: full 6-bit value
+    assert_eq!(read_palette(&mut ppu, 0x3F01), 0x2A);
+
+    // Greyscale on: low nibble cleared, as on a 2C02
+    ppu.write_register(1, 0x01);
+    assert_eq!(read_palette(&mut ppu, 0x3F01), 0x20);
+
+    // Palette RAM itself is untouched
+    assert_eq!(ppu.palette[1], 0x2A);
+
+    // Revisions without the read mask return the stored value
+    ppu.set_greyscale_palette_reads(false);
+    assert_eq!(read_palette(&mut ppu, 0x3F01), 0x2A);
+}
+
+#[test]
+fn test_palette_read_open_bus_bits() {
+    let mut ppu = Ppu::new();
+    ppu.palette[1] = 0x2A;
+
+    // Last write leaves $C1 on the PPU bus, bits 6-7 come from there
+    ppu.write_register(6, 0x3F);
+    ppu.write_register(6, 0xC1);
+    assert_eq!(ppu.read_register(7), 0xEA);
+}
+
+#[test]
+fn test_greyscale_applies_to_output() {
+    let grey = render_backdrop(0x20, 0x00, true);
+    assert_eq!(render_backdrop(0x2A, 0x01, true), grey);
+    assert_ne!(render_backdrop(0x2A, 0x00, true), grey);
+
+    // Disabling the read mask does not change what is displayed
+    assert_eq!(render_backdrop(0x2A, 0x01, false), grey);
+}
+
+#[test]
+fn test_palette_read_fills_buffer_from_nametable() {
+    let mut ppu = Ppu::new();
+    ppu.palette[1] = 0x2A;
+
+    // Nametable byte underneath $3F01 lives at $2F01
+    set_vram_addr(&mut ppu, 0x2F01);
+    ppu.write_register(7, 0x77);
+
+    // Palette reads are immediate...
+    assert_eq!(read_palette(&mut ppu, 0x3F01), 0x2A);
+
+    // ...but refill the read buffer with $2F01, which the next read returns
+    set_vram_addr(&mut ppu, 0x2000);
+    assert_eq!(ppu.read_register(7), 0x77);
+}
+
+#[test]
+fn test_current_colors() {
+    let mut ppu = Ppu::new();
+    set_vram_addr(&mut ppu, 0x3F01);
+    ppu.write_register(7, 0x30);
+    set_vram_addr(&mut ppu, 0x3F10);
+    for value in [0x16, 0x01] {
+        ppu.write_register(7, value);
+    }
+    set_vram_addr(&mut ppu, 0x3F14);
+    ppu.write_register(7, 0x2A);
+
+    let colors = ppu.current_colors();
+    assert_eq!(colors[0x00], 0x00B53120, "$3F10 wrote the backdrop");
+    assert_eq!(colors[0x10], 0x00B53120);
+    assert_eq!(colors[0x01], 0x00FFFEFF);
+    assert_eq!(colors[0x11], 0x00002A88);
+    assert_eq!(colors[0x04], 0x005CE430);
+    assert_eq!(colors[0x14], 0x005CE430);
+    assert_eq!(colors[0x02], 0x00666666, "Entry still at $00");
+
+    ppu.set_color_table(Some(core::array::from_fn(|i| i as u32 * 0x010101)));
+    let colors = ppu.current_colors();
+    assert_eq!(colors[0x01], 0x303030);
+    assert_eq!(colors[0x10], 0x161616);
+    assert_eq!(colors[0x14], 0x2A2A2A);
+}
+
+#[test]
+fn test_palette_mirrors_alias() {
+    let mut ppu = Ppu::new();
+
+    // $3F10/$14/$18/$1C and $3F00/$04/$08/$0C are the same bytes either way round
+    for (i, (mirror, entry)) in [(0x3F10, 0x3F00), (0x3F14, 0x3F04), (0x3F18, 0x3F08), (0x3F1C, 0x3F0C)].into_iter().enumerate() {
+        set_vram_addr(&mut ppu, mirror);
+        ppu.write_register(7, 0x20 + i as u8);
+        assert_eq!(read_palette(&mut ppu, entry), 0x20 + i as u8);
+
+        set_vram_addr(&mut ppu, entry);
+        ppu.write_register(7, 0x10 + i as u8);
+        assert_eq!(read_palette(&mut ppu, mirror), 0x10 + i as u8);
+
+        // As is every 32 byte mirror of palette RAM up to $3FFF. Bits 6-7
+        // are open bus, left high by the $2006 write.
+        assert_eq!(read_palette(&mut ppu, mirror + 0xE0) & 0x3F, 0x10 + i as u8);
+    }
+
+    // The other sprite palette entries are their own
+    set_vram_addr(&mut ppu, 0x3F11);
+    ppu.write_register(7, 0x2C);
+    assert_eq!(read_palette(&mut ppu, 0x3F01), 0x00);
+    assert_eq!(read_palette(&mut ppu, 0x3F11), 0x2C);
+}
+
+#[test]
+fn test_luminance_at() {
+    let mut ppu = Ppu::new();
+    ppu.framebuffer[0] = 0x00FFFFFF;
+    ppu.framebuffer[5 * 256 + 3] = 0x00FF0000;
+    assert_eq!(ppu.luminance_at(0, 0), 255);
+    assert_eq!(ppu.luminance_at(3, 5), 76);
+    assert_eq!(ppu.luminance_at(1, 0), 0);
+
+    // Off-screen reads as black
+    assert_eq!(ppu.luminance_at(256, 0), 0);
+    assert_eq!(ppu.luminance_at(0, 240), 0);
+
+    // A rendered white backdrop is brighter than a black one
+    let white = render_backdrop(0x30, 0x00, true);
+    let black = render_backdrop(0x0F, 0x00, true);
+    ppu.framebuffer[0] = white;
+    ppu.framebuffer[1] = black;
+    assert!(ppu.luminance_at(0, 0) > 200);
+    assert_eq!(ppu.luminance_at(1, 0), 0);
+}
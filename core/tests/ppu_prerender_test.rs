@@ -0,0 +1,108 @@
+use ccnes_core::{Cartridge, Ppu};
+
+fn run_frames(ppu: &mut Ppu, cartridge: &Cartridge, frames: usize) {
+    for _ in 0..frames * 341 * 262 {
+        ppu.step(cartridge);
+    }
+}
+
+#[test]
+fn test_no_stale_sprites_on_first_scanline() {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines(std::io::Cursor::new(rom_data))
+        .expect("Failed to load test ROM");
+    
+    let mut ppu = Ppu::new();
+    ppu.oam.fill(0xFF);
+    // Sprite 0 near the bottom of the screen using the checkerboard tile
+    ppu.oam[0] = 234;
+    ppu.oam[1] = 0;
+    ppu.oam[2] = 0;
+    ppu.oam[3] = 100;
+    ppu.palette[0] = 0x0F;
+    ppu.palette[0x11] = 0x30;
+    ppu.palette[0x12] = 0x30;
+    ppu.palette[0x13] = 0x30;
+    ppu.write_register(1, 0x14); // Sprites only, no left clipping
+    
+    run_frames(&mut ppu, &cartridge, 2);
+    
+    let backdrop = ppu.framebuffer[0];
+    let bottom = 239 * 256;
+    assert!(ppu.framebuffer[bottom + 100..bottom + 108].iter().any(|&c| c != backdrop),
+        "Sprite should be visible on the last scanline");
+    
+    // Line 0 never has sprites; the pre-render line must not carry them over
+    assert!(ppu.framebuffer[100..108].iter().all(|&c| c == backdrop),
+        "Sprites from the bottom of the previous frame leaked onto scanline 0");
+}
+
+#[test]
+fn test_first_tiles_of_scanline_zero_are_prefetched() {
+    // NROM with horizontal mirroring; tile 1 is solid color 1
+    let mut rom_data = vec![0; 16 + 0x4000 + 0x2000];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 1;
+    rom_data[5] = 1;
+    let chr = 16 + 0x4000;
+    rom_data[chr + 0x10..chr + 0x18].fill(0xFF);
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    
+    let mut ppu = Ppu::new();
+    ppu.set_mirroring(cartridge.mirroring());
+    ppu.palette[0] = 0x0F;
+    ppu.palette[1] = 0x30;
+    
+    // Tile 1 at the top-left of $2000 only. The tile row fetched at the
+    // end of scanline 239 comes from $2800, which is blank.
+    ppu.write_register(6, 0x20);
+    ppu.write_register(6, 0x00);
+    ppu.write_register(7, 0x01);
+    ppu.write_register(6, 0x00);
+    ppu.write_register(6, 0x00);
+    ppu.write_register(1, 0x0A); // Background only, no left clipping
+    
+    run_frames(&mut ppu, &cartridge, 2);
+    
+    let backdrop = ppu.framebuffer[8 * 256];
+    assert_ne!(ppu.framebuffer[2], backdrop, "Scanline 0 starts with stale tiles");
+    assert_eq!(ppu.framebuffer[7 * 256 + 4], ppu.framebuffer[2]);
+    assert_eq!(ppu.framebuffer[12], backdrop);
+}
+
+#[test]
+fn test_in_range_sprites_fetched_after_empty_prerender_slots() {
+    // NROM; tiles 1 and $FF are solid color 1
+    let mut rom_data = vec![0; 16 + 0x4000 + 0x2000];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 1;
+    rom_data[5] = 1;
+    let chr = 16 + 0x4000;
+    rom_data[chr + 0x10..chr + 0x18].fill(0xFF);
+    rom_data[chr + 0xFF0..chr + 0xFF8].fill(0xFF);
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).unwrap();
+    
+    let mut ppu = Ppu::new();
+    ppu.oam.fill(0xFF);
+    // Sprite 0 at the top of the screen. Line 0 has no sprites, so it
+    // first shows up on line 1.
+    ppu.oam[0] = 0;
+    ppu.oam[1] = 1;
+    ppu.oam[2] = 0;
+    ppu.oam[3] = 100;
+    ppu.palette[0] = 0x0F;
+    ppu.palette[0x11] = 0x30;
+    ppu.write_register(1, 0x14); // Sprites only, no left clipping
+    
+    run_frames(&mut ppu, &cartridge, 2);
+    
+    let backdrop = ppu.framebuffer[0];
+    for line in 1..8 {
+        let row = &ppu.framebuffer[line * 256..(line + 1) * 256];
+        assert!(row[100..108].iter().all(|&c| c != backdrop), "Sprite missing on scanline {}", line);
+        // The other seven slots fetched tile $FF but stay empty
+        assert!(row[..100].iter().chain(&row[108..]).all(|&c| c == backdrop),
+            "Dummy tile $FF fetch was drawn on scanline {}", line);
+    }
+    assert!(ppu.framebuffer[..256].iter().all(|&c| c == backdrop));
+}
@@ -0,0 +1,91 @@
+use ccnes_core::{Cartridge, Ppu};
+
+const DOTS_PER_LINE: usize = 341;
+
+// Sprite 0 over the checkerboard background tile at x = 100, OAM Y = 49
+fn sprite_zero_setup() -> (Ppu, Cartridge) {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let cartridge = Cartridge::from_ines_bytes(&rom_data).expect("Failed to load test ROM");
+    
+    let mut ppu = Ppu::new();
+    ppu.oam.fill(0xFF);
+    ppu.oam[0] = 49;
+    ppu.oam[1] = 0;
+    ppu.oam[2] = 0;
+    ppu.oam[3] = 100;
+    ppu.write_register(1, 0x1E); // Background and sprites, no left clipping
+    (ppu, cartridge)
+}
+
+fn run_dots(ppu: &mut Ppu, cartridge: &Cartridge, dots: usize) {
+    for _ in 0..dots {
+        ppu.step(cartridge);
+    }
+}
+
+fn sprite_zero_hit(ppu: &mut Ppu) -> bool {
+    ppu.read_register(2) & 0x40 != 0
+}
+
+#[test]
+fn test_sprite_zero_hit_with_rendering_enabled() {
+    let (mut ppu, cartridge) = sprite_zero_setup();
+    run_dots(&mut ppu, &cartridge, 52 * DOTS_PER_LINE);
+    assert!(sprite_zero_hit(&mut ppu));
+}
+
+#[test]
+fn test_no_sprite_zero_hit_after_mid_scanline_disable() {
+    for mask in [0x00, 0x0E, 0x16] {
+        let (mut ppu, cartridge) = sprite_zero_setup();
+        
+        // Dot 50 of scanline 49, before sprite 0 has been reached
+        run_dots(&mut ppu, &cartridge, 49 * DOTS_PER_LINE + 50);
+        assert!(!sprite_zero_hit(&mut ppu));
+        
+        ppu.write_register(1, mask);
+        run_dots(&mut ppu, &cartridge, 100);
+        assert!(!sprite_zero_hit(&mut ppu), "Hit registered with PPUMASK = {:#04x}", mask);
+    }
+}
+
+// First dot of the frame with the hit flag up, if any
+fn sprite_zero_hit_dot(ppu: &mut Ppu, cartridge: &Cartridge) -> Option<usize> {
+    (0..240 * DOTS_PER_LINE).find(|_| {
+        ppu.step(cartridge);
+        sprite_zero_hit(ppu)
+    })
+}
+
+#[test]
+fn test_sprite_zero_hit_without_frame_output() {
+    // Positions around the left clip and the right edge, flips, clipping on and off
+    for x in [0, 3, 7, 8, 100, 248, 250, 255] {
+        for attributes in [0x00, 0x40, 0x80, 0x20] {
+            for mask in [0x1E, 0x18, 0x1A] {
+                let setup = || {
+                    let (mut ppu, cartridge) = sprite_zero_setup();
+                    ppu.oam[2] = attributes;
+                    ppu.oam[3] = x;
+                    ppu.write_register(1, mask);
+                    (ppu, cartridge)
+                };
+                let (mut drawn, cartridge) = setup();
+                let (mut skipped, _) = setup();
+                skipped.set_frame_output(false);
+                
+                let expected = sprite_zero_hit_dot(&mut drawn, &cartridge);
+                assert_eq!(
+                    sprite_zero_hit_dot(&mut skipped, &cartridge),
+                    expected,
+                    "x {} attributes {:#04x} mask {:#04x}", x, attributes, mask
+                );
+                if x == 100 && attributes == 0x00 {
+                    assert!(expected.is_some(), "mask {:#04x}", mask);
+                }
+                // Nothing was drawn
+                assert!(skipped.framebuffer.iter().all(|&color| color == 0));
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "raw-output")]
+
+use ccnes_core::cpu::CpuBus;
+use ccnes_core::{Cartridge, Nes};
+
+fn create_nes() -> Nes {
+    let rom_data = ccnes_core::test_rom::create_test_rom();
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    nes
+}
+
+#[test]
+fn test_raw_output_palette_index() {
+    let nes = create_nes();
+    let raw = nes.ppu().raw_output();
+    assert_eq!(raw.len(), 256 * 240);
+    
+    // The test ROM fills the screen with background color $21
+    assert_eq!(raw[256 * 120 + 128], 0x21);
+}
+
+#[test]
+fn test_raw_output_emphasis_and_greyscale() {
+    let mut nes = create_nes();
+    // Background on, greyscale, all three emphasis bits
+    nes.bus_mut().write(0x2001, 0xE9);
+    nes.run_frame().unwrap();
+    
+    assert_eq!(nes.ppu().raw_output()[256 * 120 + 128], 0x20 | 0x1C0);
+}
\ No newline at end of file
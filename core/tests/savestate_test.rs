@@ -1,6 +1,9 @@
 use ccnes_core::nes::Nes;
 use ccnes_core::cartridge::Cartridge;
 use ccnes_core::savestate::{SaveStateManager, SaveStateError};
+use ccnes_core::cpu::CpuBus;
+use ccnes_core::apu::{MixingMode, ResamplerQuality};
+use ccnes_core::ppu::{AccuracyLevel, LayerMask};
 use std::io::Cursor;
 use tempfile::TempDir;
 
@@ -39,7 +42,7 @@ fn test_savestate_basic() {
     
     // Run a few steps
     for _ in 0..10 {
-        nes.step();
+        nes.step().unwrap();
     }
     
     // Create save state
@@ -47,7 +50,7 @@ fn test_savestate_basic() {
     
     // Modify NES state
     for _ in 0..10 {
-        nes.step();
+        nes.step().unwrap();
     }
     
     // Load save state
@@ -67,7 +70,7 @@ fn test_savestate_serialization() {
     
     // Run some steps to change state
     for _ in 0..50 {
-        nes.step();
+        nes.step().unwrap();
     }
     
     // Save to memory
@@ -95,7 +98,7 @@ fn test_savestate_manager() {
     
     // Run some steps
     for _ in 0..20 {
-        nes.step();
+        nes.step().unwrap();
     }
     
     // Save to slot 0
@@ -104,7 +107,7 @@ fn test_savestate_manager() {
     
     // Modify state
     for _ in 0..20 {
-        nes.step();
+        nes.step().unwrap();
     }
     
     // Save to slot 1
@@ -172,4 +175,393 @@ fn test_savestate_version_validation() {
     // Original data should still work
     let result = nes.load_state_from_slice(&save_data);
     assert!(result.is_ok());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_savestate_checksum_detects_corruption() {
+    let rom_data = create_test_rom();
+    let cartridge = Cartridge::from_ines(&rom_data[..]).expect("Failed to create cartridge");
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    nes.bus.write(0x0200, 0x42);
+    
+    let save_data = nes.save_state_to_vec().expect("Failed to save state");
+    
+    // A flipped bit in the RAM payload would otherwise deserialize fine
+    let mut corrupted = save_data.clone();
+    let middle = corrupted.len() / 2;
+    corrupted[middle] ^= 0x01;
+    assert!(matches!(nes.load_state_from_slice(&corrupted), Err(SaveStateError::Corrupted)));
+    
+    let truncated = &save_data[..save_data.len() - 10];
+    assert!(matches!(nes.load_state_from_slice(truncated), Err(SaveStateError::Corrupted)));
+    assert!(matches!(nes.load_state_from_slice(&[]), Err(SaveStateError::Corrupted)));
+    
+    // Full states are checked the same way
+    let mut full = Vec::new();
+    nes.save_full_state(&mut full).unwrap();
+    let last = full.len() - 1;
+    full[last] ^= 0x80;
+    assert!(matches!(nes.load_full_state(&full[..]), Err(SaveStateError::Corrupted)));
+    
+    nes.load_state_from_slice(&save_data).expect("Failed to load state");
+    assert_eq!(nes.bus.read(0x0200), 0x42);
+}
+
+fn create_nes() -> Nes {
+    let cartridge = Cartridge::from_ines(&create_test_rom()[..]).expect("Failed to create cartridge");
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    nes
+}
+
+#[test]
+fn test_savestate_mid_oam_dma() {
+    let mut nes = create_nes();
+    for i in 0..256 {
+        nes.bus.write(0x0300 + i, (i as u8).wrapping_mul(7));
+    }
+    
+    // Start a DMA from page $03 and save part way through
+    nes.bus.write(0x4014, 0x03);
+    for _ in 0..200 {
+        nes.step().unwrap();
+    }
+    let save_data = nes.save_state_to_vec().expect("Failed to save state");
+    
+    let mut restored = create_nes();
+    restored.load_state_from_slice(&save_data).expect("Failed to load state");
+    assert_eq!(restored.cpu.stall_cycles, nes.cpu.stall_cycles);
+    for _ in 0..400 {
+        restored.step().unwrap();
+    }
+    
+    for i in 0..256 {
+        assert_eq!(restored.bus.ppu.oam[i], (i as u8).wrapping_mul(7), "OAM byte {}", i);
+    }
+}
+
+#[test]
+fn test_savestate_mid_controller_read() {
+    let mut nes = create_nes();
+    nes.set_controller1(0b1010_0110);
+    nes.bus.write(0x4016, 1);
+    nes.bus.write(0x4016, 0);
+    let first: Vec<u8> = (0..3).map(|_| nes.bus.read(0x4016) & 1).collect();
+    assert_eq!(first, [0, 1, 1]);
+    
+    let save_data = nes.save_state_to_vec().expect("Failed to save state");
+    let mut restored = create_nes();
+    restored.load_state_from_slice(&save_data).expect("Failed to load state");
+    
+    // The shift register picks up where it was saved
+    let rest: Vec<u8> = (0..5).map(|_| restored.bus.read(0x4016) & 1).collect();
+    assert_eq!(rest, [0, 0, 1, 0, 1]);
+}
+
+#[test]
+fn test_full_state_round_trip() {
+    let cartridge = Cartridge::from_ines_bytes(&ccnes_core::test_rom::create_test_rom()).unwrap();
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    for _ in 0..10 {
+        nes.run_frame().unwrap();
+    }
+    
+    let mut save_data = Vec::new();
+    nes.save_full_state(&mut save_data).expect("Failed to save full state");
+    
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    let framebuffer = nes.get_framebuffer().to_vec();
+    let samples = nes.bus.apu.get_samples();
+    let (pc, clock) = (nes.cpu.pc, nes.clock());
+    
+    // Replaying from the snapshot reproduces the same frames and audio
+    nes.load_full_state(&save_data[..]).expect("Failed to load full state");
+    for _ in 0..5 {
+        nes.run_frame().unwrap();
+    }
+    assert_eq!(nes.get_framebuffer(), &framebuffer[..]);
+    assert_eq!(nes.bus.apu.get_samples(), samples);
+    assert_eq!((nes.cpu.pc, nes.clock()), (pc, clock));
+    
+    // Quick saves aren't full states
+    let quick = nes.save_state_to_vec().unwrap();
+    assert!(nes.load_full_state(&quick[..]).is_err());
+}
+
+#[test]
+fn test_full_state_keeps_frontend_settings() {
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&create_test_rom()).unwrap());
+    let mut save_data = Vec::new();
+    nes.save_full_state(&mut save_data).unwrap();
+    
+    nes.bus.ppu.set_accuracy(AccuracyLevel::Fast);
+    nes.bus.ppu.set_sprite_limit(None);
+    nes.bus.ppu.set_layer_override(Some(LayerMask::HIDE_SPRITES));
+    nes.bus.ppu.set_greyscale_palette_reads(false);
+    nes.bus.apu.set_sample_rate(48000);
+    nes.bus.apu.set_quality(ResamplerQuality::High);
+    nes.bus.apu.set_mixing(MixingMode::Linear);
+    nes.bus.apu.set_audio_enabled(false);
+    
+    nes.load_full_state(&save_data[..]).unwrap();
+    assert_eq!(nes.bus.ppu.accuracy(), AccuracyLevel::Fast);
+    assert_eq!(nes.bus.ppu.sprite_limit(), None);
+    assert_eq!(nes.bus.ppu.layer_override(), Some(LayerMask::HIDE_SPRITES));
+    assert!(!nes.bus.ppu.greyscale_palette_reads());
+    assert_eq!(nes.bus.apu.sample_rate(), 48000);
+    assert_eq!(nes.bus.apu.quality(), ResamplerQuality::High);
+    assert_eq!(nes.bus.apu.mixing(), MixingMode::Linear);
+    assert!(!nes.bus.apu.audio_enabled());
+}
+
+#[test]
+fn test_full_state_keeps_write_toggle_and_odd_frame() {
+    let cartridge = Cartridge::from_ines_bytes(&ccnes_core::test_rom::create_test_rom()).unwrap();
+    let mut nes = Nes::new();
+    nes.load_cartridge(cartridge);
+    nes.run_frame().unwrap();
+    
+    // Save halfway through setting PPUADDR to $2345
+    nes.bus.read(0x2002);
+    nes.bus.write(0x2006, 0x23);
+    assert!(nes.bus.ppu.write_toggle());
+    let odd_frame = nes.bus.ppu.odd_frame();
+    let mut save_data = Vec::new();
+    nes.save_full_state(&mut save_data).unwrap();
+    
+    // Clear the toggle and flip the frame parity before restoring
+    nes.bus.write(0x2006, 0x00);
+    nes.run_frame().unwrap();
+    assert!(!nes.bus.ppu.write_toggle());
+    assert_ne!(nes.bus.ppu.odd_frame(), odd_frame);
+    
+    nes.load_full_state(&save_data[..]).unwrap();
+    assert!(nes.bus.ppu.write_toggle());
+    assert_eq!(nes.bus.ppu.odd_frame(), odd_frame);
+    
+    // The second write completes the address the first one started
+    nes.bus.write(0x2006, 0x45);
+    nes.bus.write(0x2007, 0xAB);
+    assert_eq!(nes.bus.ppu.dump_vram()[0x345], 0xAB);
+}
+
+#[test]
+fn test_savestate_keeps_banked_prg_ram() {
+    // MMC5 banks its 32KB of PRG RAM into $6000-$7FFF 8KB at a time
+    let mut rom_data = vec![0; 16 + 0x4000];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = 1;
+    rom_data[6] = 0x52;
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    assert_eq!(nes.bus.cartridge.as_ref().unwrap().prg_ram_size(), 0x8000);
+    
+    // Fill the first 32KB, a different value per bank, and leave bank 1 mapped
+    let fill_banks = |nes: &mut Nes, base: u8| {
+        for bank in 0..4 {
+            nes.bus.write(0x5113, bank);
+            nes.bus.write(0x6000, base + bank);
+            nes.bus.write(0x7FFF, base + bank);
+        }
+        nes.bus.write(0x5113, 1);
+    };
+    fill_banks(&mut nes, 0x10);
+    let quick = nes.save_state_to_vec().unwrap();
+    let mut full = Vec::new();
+    nes.save_full_state(&mut full).unwrap();
+    
+    let check_banks = |nes: &mut Nes| {
+        assert_eq!(nes.bus.read(0x6000), 0x11);
+        for bank in 0..4 {
+            nes.bus.write(0x5113, bank);
+            assert_eq!(nes.bus.read(0x6000), 0x10 + bank);
+            assert_eq!(nes.bus.read(0x7FFF), 0x10 + bank);
+        }
+        nes.bus.write(0x5113, 1);
+    };
+    fill_banks(&mut nes, 0x80);
+    nes.load_state_from_slice(&quick).unwrap();
+    check_banks(&mut nes);
+    
+    fill_banks(&mut nes, 0x80);
+    nes.load_full_state(&full[..]).unwrap();
+    check_banks(&mut nes);
+    
+    // The battery save holds all of it too
+    let ram = nes.bus.cartridge.as_ref().unwrap().battery_ram().unwrap();
+    assert_eq!(ram.len(), 0x8000);
+    assert_eq!(ram[3 * 0x2000 + 0x1FFF], 0x13);
+    
+    // A state from a cartridge with a different amount of PRG RAM is refused
+    let mut other = Nes::new();
+    other.load_cartridge(Cartridge::from_ines_bytes(&create_test_rom()).unwrap());
+    assert!(matches!(other.load_state_from_slice(&quick), Err(SaveStateError::Corrupted)));
+}
+
+#[test]
+fn test_savestate_keeps_chr_ram() {
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&create_test_rom()).unwrap());
+    nes.skip_ppu_warmup();
+    assert!(nes.bus.cartridge.as_ref().unwrap().has_chr_ram());
+    
+    let upload = |nes: &mut Nes, value: u8| {
+        nes.bus.write(0x2006, 0x10);
+        nes.bus.write(0x2006, 0x00);
+        for _ in 0..16 {
+            nes.bus.write(0x2007, value);
+        }
+    };
+    upload(&mut nes, 0x5A);
+    let quick = nes.save_state_to_vec().unwrap();
+    let mut full = Vec::new();
+    nes.save_full_state(&mut full).unwrap();
+    
+    let tile = |nes: &Nes| nes.bus.cartridge.as_ref().unwrap().chr_ram().unwrap()[0x1000..0x1010].to_vec();
+    upload(&mut nes, 0xA5);
+    nes.load_state_from_slice(&quick).unwrap();
+    assert_eq!(tile(&nes), [0x5A; 16]);
+    
+    upload(&mut nes, 0xA5);
+    nes.load_full_state(&full[..]).unwrap();
+    assert_eq!(tile(&nes), [0x5A; 16]);
+    
+    // CHR ROM carts have nothing to restore it into
+    let mut other = Nes::new();
+    other.load_cartridge(Cartridge::from_ines_bytes(&ccnes_core::test_rom::create_test_rom()).unwrap());
+    assert!(matches!(other.load_state_from_slice(&quick), Err(SaveStateError::Corrupted)));
+}
+
+#[test]
+fn test_savestate_keeps_jammed_cpu() {
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&create_test_rom()).unwrap());
+    nes.bus.write(0x0300, 0x02); // KIL
+    nes.cpu.pc = 0x0300;
+    let before = nes.save_state_to_vec().unwrap();
+    
+    nes.step().unwrap();
+    assert!(nes.cpu.is_jammed());
+    let jammed = nes.save_state_to_vec().unwrap();
+    
+    // Going back to before the KIL runs the CPU again
+    nes.load_state_from_slice(&before).unwrap();
+    assert!(!nes.cpu.is_jammed());
+    nes.load_state_from_slice(&jammed).unwrap();
+    assert!(nes.cpu.is_jammed());
+}
+
+#[test]
+fn test_states_keep_four_screen_nametables() {
+    let mut rom_data = ccnes_core::test_rom::create_test_rom();
+    rom_data[6] |= 0x08; // Four-screen VRAM
+    let mut nes = Nes::new();
+    nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+    nes.skip_ppu_warmup();
+    let write_nametables = |nes: &mut Nes, value: u8| {
+        for addr in [0x2055u16, 0x2C55] {
+            nes.bus.write(0x2006, (addr >> 8) as u8);
+            nes.bus.write(0x2006, addr as u8);
+            nes.bus.write(0x2007, value);
+        }
+    };
+    write_nametables(&mut nes, 0x5A);
+    assert_eq!(nes.bus.dump_vram().len(), 0x1000);
+    
+    let quick = nes.quick_save();
+    let mut full = Vec::new();
+    nes.save_full_state(&mut full).unwrap();
+    
+    write_nametables(&mut nes, 0);
+    nes.quick_load(&quick).unwrap();
+    assert_eq!(nes.bus.dump_vram()[0x055], 0x5A);
+    assert_eq!(nes.bus.dump_vram()[0xC55], 0x5A);
+    
+    write_nametables(&mut nes, 0);
+    nes.load_full_state(Cursor::new(full)).unwrap();
+    assert_eq!(nes.bus.dump_vram()[0x055], 0x5A);
+    assert_eq!(nes.bus.dump_vram()[0xC55], 0x5A);
+    
+    // A dump without the board's 2KB is turned down untouched
+    let vram = nes.bus.dump_vram();
+    assert!(!nes.bus.load_vram(&[0; 0x800]));
+    assert!(!nes.bus.load_vram(&[]));
+    assert_eq!(nes.bus.dump_vram(), vram);
+    assert!(nes.bus.load_vram(&vram));
+}
+
+/// 128KB PRG and 32KB CHR where every 8KB of PRG and 1KB of CHR starts
+/// with its own bank number
+fn create_banked_rom(mapper: u8) -> Vec<u8> {
+    let (prg_size, chr_size) = (0x20000, 0x8000);
+    let mut rom_data = vec![0; 16 + prg_size + chr_size];
+    rom_data[0..4].copy_from_slice(b"NES\x1A");
+    rom_data[4] = (prg_size / 0x4000) as u8;
+    rom_data[5] = (chr_size / 0x2000) as u8;
+    rom_data[6] = mapper << 4;
+    rom_data[7] = mapper & 0xF0;
+    for offset in (0..prg_size).step_by(0x2000) {
+        rom_data[16 + offset] = (offset / 0x2000) as u8;
+    }
+    for offset in (0..chr_size).step_by(0x400) {
+        rom_data[16 + prg_size + offset] = (offset / 0x400) as u8;
+    }
+    rom_data
+}
+
+/// Bank numbers visible in each PRG and CHR window, plus the mirroring
+fn bank_layout(nes: &Nes) -> (Vec<u8>, Vec<u8>, u8) {
+    let cartridge = nes.bus.cartridge.as_ref().unwrap();
+    let prg = (0..4).map(|i| cartridge.read_prg(0x8000 + i * 0x2000)).collect();
+    let chr = (0..8).map(|i| cartridge.read_chr(i * 0x400)).collect();
+    (prg, chr, cartridge.mirroring() as u8)
+}
+
+/// Both kinds of state restore the bank registers the writes selected
+fn assert_mapper_state_round_trips(mapper: u8, writes: &[(u16, u8)]) {
+    let create = || {
+        let mut nes = Nes::new();
+        nes.load_cartridge(Cartridge::from_ines_bytes(&create_banked_rom(mapper)).unwrap());
+        nes
+    };
+    let mut nes = create();
+    let initial = bank_layout(&nes);
+    for &(addr, value) in writes {
+        nes.bus.write(addr, value);
+    }
+    let layout = bank_layout(&nes);
+    assert_ne!(layout, initial, "mapper {} writes should switch banks", mapper);
+    
+    let quick = nes.save_state_to_vec().unwrap();
+    let mut full = Vec::new();
+    nes.save_full_state(&mut full).unwrap();
+    
+    let mut restored = create();
+    restored.load_state_from_slice(&quick).unwrap();
+    assert_eq!(bank_layout(&restored), layout, "mapper {} quick save", mapper);
+    
+    let mut restored = create();
+    restored.load_full_state(&full[..]).unwrap();
+    assert_eq!(bank_layout(&restored), layout, "mapper {} full state", mapper);
+}
+
+#[test]
+fn test_savestate_keeps_mmc2_banks() {
+    let writes = [(0xA000, 0x05), (0xB000, 0x03), (0xC000, 0x04), (0xD000, 0x06), (0xE000, 0x07), (0xF000, 0x01)];
+    assert_mapper_state_round_trips(9, &writes);
+}
+
+#[test]
+fn test_savestate_keeps_color_dreams_banks() {
+    assert_mapper_state_round_trips(11, &[(0x8000, 0x21)]);
+}
+
+#[test]
+fn test_savestate_keeps_gxrom_banks() {
+    assert_mapper_state_round_trips(66, &[(0x8000, 0x12)]);
+}
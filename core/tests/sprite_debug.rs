@@ -20,7 +20,7 @@ fn debug_sprite_rendering() {
     // Run for a few frames
     for frame in 0..5 {
         println!("\nFrame {}:", frame);
-        nes.run_frame();
+        nes.run_frame().unwrap();
         
         // Check PPU state
         println!("PPU CTRL: {:02X}", nes.bus.ppu.get_ctrl());
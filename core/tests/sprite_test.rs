@@ -15,7 +15,7 @@ fn test_sprite_rendering() {
     
     // Run for several frames to ensure sprites are rendered
     for _ in 0..10 {
-        nes.run_frame();
+        nes.run_frame().unwrap();
     }
     
     // Check that we have non-zero pixels (sprite should be visible)
@@ -56,7 +56,7 @@ fn test_oam_dma() {
     
     // Run enough cycles for DMA to complete (513+ cycles)
     for _ in 0..600 {
-        nes.step();
+        nes.step().unwrap();
     }
     
     // Verify OAM was filled with data from page 2
@@ -64,4 +64,32 @@ fn test_oam_dma() {
         let oam_value = nes.bus.ppu.oam[i];
         assert_eq!(oam_value, i as u8, "OAM[{}] should equal {}", i, i);
     }
+}
+
+#[test]
+fn test_fast_path_matches_accurate_path() {
+    use ccnes_core::ppu::AccuracyLevel;
+    
+    for accuracy in [AccuracyLevel::Accurate, AccuracyLevel::Fast] {
+        let rom_data = ccnes_core::test_rom::create_sprite_test_rom();
+        let mut nes = Nes::new();
+        nes.load_cartridge(Cartridge::from_ines_bytes(&rom_data).unwrap());
+        nes.bus.ppu.set_accuracy(accuracy);
+        nes.bus.ppu.set_verify_fast_path(true);
+        for _ in 0..10 {
+            nes.run_frame().unwrap();
+        }
+        
+        // Crowded lines: overflow, the sprite limit and 8x16 sprites
+        nes.bus.ppu.write_register(1, 0x1E);
+        for (i, byte) in nes.bus.ppu.oam.iter_mut().enumerate() {
+            *byte = (i * 7 % 97) as u8;
+        }
+        for limit in [Some(8), None] {
+            nes.bus.ppu.set_sprite_limit(limit);
+            nes.run_frame().unwrap();
+        }
+        nes.bus.ppu.write_register(0, 0x20);
+        nes.run_frame().unwrap();
+    }
 }
\ No newline at end of file
@@ -21,7 +21,7 @@ fn debug_test_rom() {
         println!("Step {}: PC={:04X}, cycles={}", i, pc, cycles);
         
         // Step one CPU instruction
-        nes.step();
+        nes.step().unwrap();
         
         // Check if we hit an invalid opcode
         if i > 0 && nes.cpu.pc == pc {
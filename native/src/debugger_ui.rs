@@ -1,10 +1,11 @@
-use ccnes_core::{Debugger, DebuggerState, BreakpointType, DebugInfo};
+use ccnes_core::{Debugger, DebuggerState, BreakpointType, DebugInfo, Disassembler};
 use ccnes_core::{Nes, debugger};
 use sdl2::keyboard::Keycode;
 use std::io::{self, Write};
 
 pub struct DebuggerUI {
     debugger: Debugger,
+    disassembler: Disassembler,
     show_debugger: bool,
     command_buffer: String,
 }
@@ -13,6 +14,7 @@ impl DebuggerUI {
     pub fn new() -> Self {
         Self {
             debugger: Debugger::new(),
+            disassembler: Disassembler::new(),
             show_debugger: false,
             command_buffer: String::new(),
         }
@@ -72,7 +74,7 @@ impl DebuggerUI {
     }
     
     pub fn update(&mut self, nes: &mut Nes) {
-        self.debugger.update_after_step(&nes.cpu);
+        self.debugger.update_after_step(nes.cpu());
         
         if self.debugger.is_paused() && self.show_debugger {
             self.print_status(nes);
@@ -85,7 +87,7 @@ impl DebuggerUI {
     
     fn print_help(&self) {
         println!("\nDebugger Commands:");
-        println!("  F1  - Show this help");
+        println!("  F1  - Show this help (toggles the HUD while closed)");
         println!("  F2  - Show CPU state");
         println!("  F3  - Step one instruction");
         println!("  F4  - Resume execution");
@@ -96,8 +98,8 @@ impl DebuggerUI {
     
     fn print_cpu_state(&self, nes: &Nes) {
         let debug_info = DebugInfo {
-            cpu: &nes.cpu,
-            bus: &nes.bus,
+            cpu: nes.cpu(),
+            bus: nes.bus(),
         };
         println!("{}", debug_info);
     }
@@ -106,15 +108,15 @@ impl DebuggerUI {
         println!("\n--- Debugger Paused ---");
         
         // Show current instruction
-        let pc = nes.cpu.pc;
-        let disasm = debugger::disassemble(&mut nes.bus, pc, 1);
+        let pc = nes.cpu().pc;
+        let disasm = self.disassembler.disassemble(nes.bus(), pc, 1);
         if let Some(instruction) = disasm.first() {
             println!("Next: {}", instruction);
         }
         
         // Show nearby instructions
         let start = pc.saturating_sub(3);
-        let disasm = debugger::disassemble(&mut nes.bus, start, 7);
+        let disasm = self.disassembler.disassemble(nes.bus(), start, 7);
         println!("\nNearby instructions:");
         for line in disasm {
             let addr = u16::from_str_radix(&line[0..4], 16).unwrap_or(0);
@@ -163,12 +165,12 @@ impl DebuggerUI {
                 "disasm" | "d" => {
                     let addr = parts.get(1)
                         .and_then(|s| u16::from_str_radix(s.trim_start_matches("0x"), 16).ok())
-                        .unwrap_or(nes.cpu.pc);
+                        .unwrap_or(nes.cpu().pc);
                     let count = parts.get(2)
                         .and_then(|s| s.parse().ok())
                         .unwrap_or(10);
                     
-                    let disasm = debugger::disassemble(&mut nes.bus, addr, count);
+                    let disasm = self.disassembler.disassemble(nes.bus(), addr, count);
                     for line in disasm {
                         println!("{}", line);
                     }
@@ -180,13 +182,26 @@ impl DebuggerUI {
                                 .and_then(|s| s.parse().ok())
                                 .unwrap_or(128);
                             
-                            let dump = debugger::dump_memory(&mut nes.bus, addr, length);
+                            let dump = debugger::dump_memory(nes.bus_mut(), addr, length);
                             for line in dump {
                                 println!("{}", line);
                             }
                         }
                     }
                 }
+                "labels" => {
+                    // FCEUX .nl file, e.g. game.nes.3.nl for the fixed bank
+                    if let Some(path) = parts.get(1) {
+                        match std::fs::read_to_string(path) {
+                            Ok(text) => {
+                                let labels = debugger::parse_nl_labels(&text);
+                                println!("Loaded {} labels", labels.len());
+                                self.disassembler = Disassembler::with_labels(labels);
+                            }
+                            Err(e) => println!("Failed to read {}: {}", path, e),
+                        }
+                    }
+                }
                 "trace" => {
                     if parts.get(1).map(|&s| s == "on").unwrap_or(false) {
                         self.debugger.enable_trace();
@@ -1,23 +1,30 @@
-use ccnes_core::{Cartridge, Nes, Controller, ControllerButton, SaveStateError};
-use clap::Parser;
+use ccnes_core::{BatterySave, Cartridge, Nes, Controller, ControllerButton, Region, SaveStateError};
+use clap::{Parser, ValueEnum};
 use log::info;
 use sdl2::audio::{AudioCallback, AudioSpecDesired};
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::PixelFormatEnum;
-use std::fs::File;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 mod debugger_ui;
+mod overlay;
+mod rom_loader;
 use debugger_ui::DebuggerUI;
+use overlay::{Hud, Overlay};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// ROM file to load
+    /// ROM file to load, or a .zip containing one
     rom_path: String,
     
+    /// ROM to use when the zip archive holds several
+    #[arg(long)]
+    entry: Option<String>,
+    
     /// Scale factor for display
     #[arg(short, long, default_value_t = 3)]
     scale: u32,
@@ -25,12 +32,73 @@ struct Args {
     /// Start in fullscreen mode
     #[arg(short, long)]
     fullscreen: bool,
+    
+    /// 192-byte .pal file (64 RGB triplets) replacing the built-in colors
+    #[arg(long)]
+    palette: Option<String>,
+    
+    /// Console region, sets the frame rate [default: from the ROM header or
+    /// file name, else ntsc]
+    #[arg(long, value_enum)]
+    region: Option<RegionArg>,
+    
+    /// Start with audio muted
+    #[arg(long)]
+    mute: bool,
+    
+    /// Audio volume in percent
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+    volume: u8,
+    
+    /// Draw only one frame in every N+1 on slow machines; the game still
+    /// runs at full speed
+    #[arg(long, default_value_t = 0)]
+    frame_skip: u8,
+    
+    /// Holding both Left and Right (or Up and Down) counts only the later
+    /// press, as some games glitch on input a real pad can't make
+    #[arg(long)]
+    no_opposites: bool,
+    
+    /// Run headless past the title screen, save a PPM screenshot here and exit
+    #[arg(long)]
+    thumbnail: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum RegionArg {
+    Ntsc,
+    Pal,
+}
+
+impl From<RegionArg> for Region {
+    fn from(region: RegionArg) -> Self {
+        match region {
+            RegionArg::Ntsc => Region::Ntsc,
+            RegionArg::Pal => Region::Pal,
+        }
+    }
 }
 
 const NES_WIDTH: u32 = 256;
 const NES_HEIGHT: u32 = 240;
-const TARGET_FPS: u32 = 60;
-const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / TARGET_FPS as u64);
+const PALETTE_FILE_SIZE: usize = 64 * 3;
+
+fn load_palette(path: &str) -> Result<[u32; 64], Box<dyn std::error::Error>> {
+    let data = std::fs::read(path)?;
+    if data.len() != PALETTE_FILE_SIZE {
+        return Err(format!(
+            "Palette file {} is {} bytes, expected {} (64 RGB triplets)",
+            path, data.len(), PALETTE_FILE_SIZE
+        ).into());
+    }
+    
+    let mut colors = [0u32; 64];
+    for (color, rgb) in colors.iter_mut().zip(data.chunks_exact(3)) {
+        *color = ((rgb[0] as u32) << 16) | ((rgb[1] as u32) << 8) | rgb[2] as u32;
+    }
+    Ok(colors)
+}
 
 struct AudioOutput {
     samples: Arc<Mutex<Vec<f32>>>,
@@ -57,16 +125,68 @@ impl AudioCallback for AudioOutput {
     }
 }
 
+/// Binary PPM: no image crate needed and most viewers open it
+fn write_ppm(path: &str, framebuffer: &[u32]) -> std::io::Result<()> {
+    let mut data = format!("P6\n{} {}\n255\n", NES_WIDTH, NES_HEIGHT).into_bytes();
+    for &color in framebuffer {
+        data.extend_from_slice(&[(color >> 16) as u8, (color >> 8) as u8, color as u8]);
+    }
+    std::fs::write(path, data)
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
     let args = Args::parse();
     
     info!("Loading ROM: {}", args.rom_path);
-    let rom_file = File::open(&args.rom_path)?;
-    let cartridge = Cartridge::from_ines(rom_file)?;
+    let rom_data = rom_loader::load_rom(&args.rom_path, args.entry.as_deref())?;
+    let mut cartridge = Cartridge::from_bytes(&rom_data)?;
+    let header = cartridge.header_info();
+    info!(
+        "Loaded: {} ({}, {}KB PRG / {}KB CHR)",
+        args.rom_path,
+        header.mapper_name,
+        header.prg_rom_size / 1024,
+        header.chr_rom_size / 1024,
+    );
+    
+    let file_name = Path::new(&args.rom_path).file_name().and_then(|name| name.to_str()).unwrap_or("");
+    let region = Region::detect(args.region.map(Region::from), &cartridge, file_name);
+    
+    let mut battery = BatterySave::new(
+        Path::new(&args.rom_path).with_extension("sav"),
+        BatterySave::DEFAULT_FLUSH_DELAY,
+    );
+    // A damaged or unreadable save shouldn't keep the game from starting
+    match battery.load(&mut cartridge) {
+        Ok(true) => info!("Loaded battery save: {}", battery.path().display()),
+        Ok(false) => {}
+        Err(e) => log::warn!("Failed to read {}: {}", battery.path().display(), e),
+    }
     
     let mut nes = Nes::new();
     nes.load_cartridge(cartridge);
+    nes.set_region(region);
+    
+    if let Some(path) = &args.palette {
+        nes.ppu_mut().set_color_table(Some(load_palette(path)?));
+        info!("Loaded palette: {}", path);
+    }
+    
+    if let Some(path) = &args.thumbnail {
+        // Two seconds for the title screen, tap Start, three seconds of play
+        let start = ControllerButton::START.bits();
+        let framebuffer = nes.run_script(&[(120, [0, 0]), (5, [start, 0]), (180, [0, 0])])?;
+        write_ppm(path, framebuffer)?;
+        info!("Saved thumbnail: {}", path);
+        return Ok(());
+    }
+    
+    // Only NTSC timing is emulated; the region sets frame pacing
+    info!("Region: {:?}", region);
+    nes.set_frame_skip(args.frame_skip);
+    let frame_duration = Duration::from_secs_f64(1.0 / region.frame_rate());
+    let volume = if args.mute { 0.0 } else { args.volume as f32 / 100.0 };
     
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
@@ -115,10 +235,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut event_pump = sdl_context.event_pump()?;
     let mut framebuffer = vec![0u8; (NES_WIDTH * NES_HEIGHT * 3) as usize];
     let mut controller = Controller::new();
+    controller.set_disallow_opposites(args.no_opposites);
     
     // Save state slots (10 slots)
     let mut save_states: Vec<Option<Vec<u8>>> = vec![None; 10];
     let mut current_save_slot = 0;
+    let mut overlay = Overlay::new(Duration::from_secs(2));
+    let mut hud = Hud::new();
     
     // Debugger
     let mut debugger_ui = DebuggerUI::new();
@@ -133,6 +256,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     match keycode {
                         Keycode::Escape => break 'running,
                         Keycode::R => nes.reset(),
+                        Keycode::P => nes.power_cycle(),
                         Keycode::F11 => {
                             use sdl2::video::FullscreenType;
                             let window = canvas.window_mut();
@@ -151,9 +275,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 Ok(data) => {
                                     save_states[current_save_slot] = Some(data);
                                     info!("Saved state to slot {}", current_save_slot);
+                                    overlay.show(format!("SLOT {} SAVED", current_save_slot));
                                 }
                                 Err(e) => {
                                     info!("Failed to save state: {}", e);
+                                    overlay.show(format!("SLOT {} SAVE FAILED", current_save_slot));
                                 }
                             }
                         }
@@ -163,26 +289,33 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 match nes.load_state_from_slice(data) {
                                     Ok(()) => {
                                         info!("Loaded state from slot {}", current_save_slot);
+                                        overlay.show(format!("SLOT {} LOADED", current_save_slot));
                                     }
                                     Err(e) => {
                                         info!("Failed to load state: {}", e);
+                                        overlay.show(format!("SLOT {} LOAD FAILED", current_save_slot));
                                     }
                                 }
                             } else {
                                 info!("No save state in slot {}", current_save_slot);
+                                overlay.show(format!("SLOT {} EMPTY", current_save_slot));
                             }
                         }
-                        // Select save slot 0-9
-                        Keycode::Num0 => current_save_slot = 0,
-                        Keycode::Num1 => current_save_slot = 1,
-                        Keycode::Num2 => current_save_slot = 2,
-                        Keycode::Num3 => current_save_slot = 3,
-                        Keycode::Num4 => current_save_slot = 4,
-                        Keycode::Num5 => current_save_slot = 5,
-                        Keycode::Num6 => current_save_slot = 6,
-                        Keycode::Num7 => current_save_slot = 7,
-                        Keycode::Num8 => current_save_slot = 8,
-                        Keycode::Num9 => current_save_slot = 9,
+                        // Select save slot 0-9, or cycle with [ and ]
+                        Keycode::Num0 | Keycode::Num1 | Keycode::Num2 | Keycode::Num3 | Keycode::Num4
+                        | Keycode::Num5 | Keycode::Num6 | Keycode::Num7 | Keycode::Num8 | Keycode::Num9
+                        | Keycode::LeftBracket | Keycode::RightBracket => {
+                            current_save_slot = match keycode {
+                                Keycode::LeftBracket => (current_save_slot + save_states.len() - 1) % save_states.len(),
+                                Keycode::RightBracket => (current_save_slot + 1) % save_states.len(),
+                                // SDL keycodes for 0-9 are their ASCII values
+                                _ => (keycode as i32 - Keycode::Num0 as i32) as usize,
+                            };
+                            let status = if save_states[current_save_slot].is_some() { "FILLED" } else { "EMPTY" };
+                            overlay.show(format!("SLOT {} {}", current_save_slot, status));
+                        }
+                        // HUD toggle; the open debugger uses F1 for its help
+                        Keycode::F1 if !debugger_ui.is_active() => hud.toggle(),
                         // Debugger toggle
                         Keycode::F10 => {
                             debugger_ui.toggle();
@@ -224,23 +357,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         if debugger_ui.is_active() && debugger_ui.get_debugger().is_paused() {
             // In debug mode, step through instructions
             for _ in 0..29780 { // Approximate cycles per frame
-                nes.step();
+                nes.step()?;
                 debugger_ui.update(&mut nes);
                 if debugger_ui.get_debugger().is_paused() {
                     break;
                 }
             }
         } else {
-            nes.run_frame();
+            nes.run_frame()?;
+        }
+        
+        if let Some(cartridge) = nes.cartridge_mut() {
+            if let Err(e) = battery.end_frame(cartridge) {
+                log::warn!("Failed to write {}: {}", battery.path().display(), e);
+            }
         }
         
         debugger_ui.update_frame();
         
         // Get audio samples and push to audio buffer
-        let samples = nes.bus.apu.get_samples();
+        let samples = nes.apu_mut().get_samples();
         if !samples.is_empty() {
             let mut audio_buffer = audio_samples.lock().unwrap();
-            audio_buffer.extend_from_slice(&samples);
+            audio_buffer.extend(samples.iter().map(|sample| sample * volume));
             
             // Prevent buffer overflow - keep only last ~0.5 seconds
             if audio_buffer.len() > 22050 {
@@ -268,6 +407,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         
+        overlay.draw(&mut framebuffer, NES_WIDTH as usize);
+        hud.end_frame();
+        if hud.is_visible() {
+            let queued_samples = audio_samples.lock().unwrap().len();
+            let queued_ms = queued_samples * 1000 / audio_device.spec().freq as usize;
+            let lines = [
+                format!("REGION {:?}", region),
+                format!("MAPPER {}", nes.cartridge().map_or("NONE", |cartridge| cartridge.mapper_name())),
+                format!("SLOT {}", current_save_slot),
+                format!("AUDIO {}MS", queued_ms),
+                format!("CPU {} CYC", nes.last_frame_stats().cpu_cycles),
+            ];
+            hud.draw(&mut framebuffer, NES_WIDTH as usize, &lines);
+        }
+        
         // Update texture and render
         texture.update(None, &framebuffer, (NES_WIDTH * 3) as usize)?;
         canvas.clear();
@@ -276,11 +430,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         
         // Frame rate limiting
         let frame_elapsed = frame_start.elapsed();
-        if frame_elapsed < FRAME_DURATION {
-            std::thread::sleep(FRAME_DURATION - frame_elapsed);
+        if frame_elapsed < frame_duration {
+            std::thread::sleep(frame_duration - frame_elapsed);
         }
         frame_start = Instant::now();
     }
     
+    if let Some(cartridge) = nes.cartridge_mut() {
+        if battery.flush(cartridge)? {
+            info!("Saved battery save: {}", battery.path().display());
+        }
+    }
+    
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,188 @@
+use std::time::{Duration, Instant};
+
+const GLYPH_SIZE: usize = 8;
+const MARGIN: usize = 8;
+const PADDING: usize = 2;
+const TEXT_COLOR: [u8; 3] = [0xFF, 0xFF, 0xFF];
+const BOX_COLOR: [u8; 3] = [0x00, 0x00, 0x00];
+
+/// Short status line drawn into the top-left corner of the RGB24
+/// framebuffer, e.g. after switching save slots
+pub struct Overlay {
+    text: String,
+    shown_at: Option<Instant>,
+    duration: Duration,
+}
+
+impl Overlay {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            text: String::new(),
+            shown_at: None,
+            duration,
+        }
+    }
+    
+    pub fn show(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.shown_at = Some(Instant::now());
+    }
+    
+    pub fn is_visible(&self) -> bool {
+        self.shown_at.is_some_and(|shown_at| shown_at.elapsed() < self.duration)
+    }
+    
+    pub fn draw(&self, framebuffer: &mut [u8], width: usize) {
+        if !self.is_visible() {
+            return;
+        }
+        draw_text_box(framebuffer, width, MARGIN, MARGIN, &[self.text.as_str()]);
+    }
+}
+
+/// Toggleable heads-up display in the bottom-left corner: measured FPS plus
+/// whatever status lines the frontend passes in
+pub struct Hud {
+    visible: bool,
+    frames: u32,
+    window_start: Instant,
+    fps: f64,
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            frames: 0,
+            window_start: Instant::now(),
+            fps: 0.0,
+        }
+    }
+    
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+    
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    
+    /// Call once per presented frame; FPS is averaged over about a second
+    pub fn end_frame(&mut self) {
+        self.frames += 1;
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames as f64 / elapsed.as_secs_f64();
+            self.frames = 0;
+            self.window_start = Instant::now();
+        }
+    }
+    
+    pub fn draw(&self, framebuffer: &mut [u8], width: usize, lines: &[String]) {
+        if !self.visible {
+            return;
+        }
+        
+        let mut all_lines = vec![format!("FPS {:.1}", self.fps)];
+        all_lines.extend(lines.iter().cloned());
+        let lines: Vec<&str> = all_lines.iter().map(String::as_str).collect();
+        
+        let height = framebuffer.len() / 3 / width;
+        let box_height = lines.len() * GLYPH_SIZE + PADDING * 2;
+        let top = height.saturating_sub(MARGIN + box_height);
+        draw_text_box(framebuffer, width, MARGIN, top, &lines);
+    }
+}
+
+/// Lines of text on a black box whose top-left corner is at (left, top)
+fn draw_text_box(framebuffer: &mut [u8], width: usize, left: usize, top: usize, lines: &[&str]) {
+    let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let box_width = columns * GLYPH_SIZE + PADDING * 2;
+    let box_height = lines.len() * GLYPH_SIZE + PADDING * 2;
+    for y in top..top + box_height {
+        for x in left..left + box_width {
+            put_pixel(framebuffer, width, x, y, BOX_COLOR);
+        }
+    }
+    
+    for (row_index, line) in lines.iter().enumerate() {
+        let glyph_top = top + PADDING + row_index * GLYPH_SIZE;
+        for (i, c) in line.chars().enumerate() {
+            let glyph_left = left + PADDING + i * GLYPH_SIZE;
+            for (dy, row) in glyph(c).iter().enumerate() {
+                for dx in 0..GLYPH_SIZE {
+                    // Bit 0 is the leftmost pixel
+                    if row & (1 << dx) != 0 {
+                        put_pixel(framebuffer, width, glyph_left + dx, glyph_top + dy, TEXT_COLOR);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Text running off the right or bottom edge is clipped
+fn put_pixel(framebuffer: &mut [u8], width: usize, x: usize, y: usize, color: [u8; 3]) {
+    if x >= width {
+        return;
+    }
+    let offset = (y * width + x) * 3;
+    if let Some(pixel) = framebuffer.get_mut(offset..offset + 3) {
+        pixel.copy_from_slice(&color);
+    }
+}
+
+/// 8x8 bitmap for the characters the overlay needs (font8x8, public
+/// domain). Lowercase is drawn as uppercase; anything else is blank.
+fn glyph(c: char) -> [u8; 8] {
+    match c.to_ascii_uppercase() {
+        '!' => [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00],
+        '/' => [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00],
+        '0' => [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00],
+        '1' => [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00],
+        '2' => [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00],
+        '3' => [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00],
+        '4' => [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00],
+        '5' => [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00],
+        '6' => [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00],
+        '7' => [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00],
+        '8' => [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00],
+        '9' => [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00],
+        'A' => [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00],
+        'B' => [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00],
+        'C' => [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00],
+        'D' => [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00],
+        'E' => [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00],
+        'F' => [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00],
+        'G' => [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00],
+        'H' => [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00],
+        'I' => [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00],
+        'J' => [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00],
+        'K' => [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00],
+        'L' => [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00],
+        'M' => [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00],
+        'N' => [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00],
+        'O' => [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00],
+        'P' => [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00],
+        'Q' => [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00],
+        'R' => [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00],
+        'S' => [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00],
+        'T' => [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00],
+        'U' => [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00],
+        'V' => [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00],
+        'W' => [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00],
+        'X' => [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00],
+        'Y' => [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00],
+        'Z' => [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00],
+        _ => [0x00; 8],
+    }
+}
\ No newline at end of file
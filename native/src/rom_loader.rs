@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::path::Path;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Read an iNES image from `path`. Zip archives are unpacked: the single
+/// `.nes` entry is used, or `entry` picks one when there are several.
+/// Anything that isn't a zip is returned as-is.
+pub fn load_rom(path: &str, entry: Option<&str>) -> Result<Vec<u8>, Box<dyn Error>> {
+    let data = std::fs::read(path)?;
+    
+    let is_zip = data.starts_with(ZIP_MAGIC)
+        || Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        return Ok(data);
+    }
+    
+    extract_rom(path, data, entry)
+}
+
+#[cfg(feature = "zip")]
+fn extract_rom(path: &str, data: Vec<u8>, entry: Option<&str>) -> Result<Vec<u8>, Box<dyn Error>> {
+    use std::io::{Cursor, Read};
+    
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))?;
+    let roms: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.to_ascii_lowercase().ends_with(".nes"))
+        .map(String::from)
+        .collect();
+    
+    let name = match (entry, roms.as_slice()) {
+        (Some(entry), _) => roms
+            .iter()
+            .find(|name| *name == entry || Path::new(name).file_name().is_some_and(|f| f == entry))
+            .ok_or_else(|| format!("{} has no entry named {} (ROMs: {})", path, entry, roms.join(", ")))?,
+        (None, [single]) => single,
+        (None, []) => return Err(format!("{} contains no .nes files", path).into()),
+        (None, _) => {
+            return Err(format!(
+                "{} contains several ROMs, pick one with --entry: {}",
+                path,
+                roms.join(", ")
+            ).into())
+        }
+    };
+    
+    let mut file = archive.by_name(name)?;
+    let mut rom = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut rom)?;
+    Ok(rom)
+}
+
+#[cfg(not(feature = "zip"))]
+fn extract_rom(path: &str, _data: Vec<u8>, _entry: Option<&str>) -> Result<Vec<u8>, Box<dyn Error>> {
+    Err(format!("{} is a zip archive but ccnes was built without the zip feature", path).into())
+}
\ No newline at end of file
@@ -1,4 +1,4 @@
-use ccnes_core::{Cartridge, Nes, Controller, ControllerButton};
+use ccnes_core::{Cartridge, Nes, NesError, Controller, ControllerButton, Region};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::Clamped;
 use web_sys::{CanvasRenderingContext2d, ImageData};
@@ -13,6 +13,11 @@ macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
+/// `run_realtime` never runs more than this many frames per call; a
+/// longer gap (a background tab, a debugger pause) is dropped rather
+/// than fast-forwarded through
+const MAX_REALTIME_FRAMES: u32 = 2;
+
 #[wasm_bindgen]
 pub struct WasmNes {
     nes: Nes,
@@ -20,6 +25,8 @@ pub struct WasmNes {
     controller1: Controller,
     #[allow(dead_code)]
     controller2: Controller,
+    // Real time `run_realtime` has been given but not yet emulated
+    pending_ms: f64,
 }
 
 #[wasm_bindgen]
@@ -36,16 +43,18 @@ impl WasmNes {
             framebuffer: vec![0; 256 * 240 * 4], // RGBA format
             controller1: Controller::new(),
             controller2: Controller::new(),
+            pending_ms: 0.0,
         }
     }
     
     pub fn load_rom(&mut self, rom_data: &[u8]) -> Result<(), JsValue> {
         console_log!("Loading ROM, size: {} bytes", rom_data.len());
         
-        let cartridge = Cartridge::from_ines(std::io::Cursor::new(rom_data))
-            .map_err(|e| JsValue::from_str(&format!("Failed to load ROM: {}", e)))?;
+        let cartridge = Cartridge::from_bytes(rom_data)
+            .map_err(|e| js_error("Failed to load ROM", NesError::from(e)))?;
         
         self.nes.load_cartridge(cartridge);
+        self.pending_ms = 0.0;
         console_log!("ROM loaded successfully");
         Ok(())
     }
@@ -55,14 +64,70 @@ impl WasmNes {
         self.nes.reset();
     }
     
+    pub fn power_cycle(&mut self) {
+        console_log!("Power cycling NES");
+        self.nes.power_cycle();
+    }
+    
     pub fn get_sample_rate(&self) -> u32 {
-        44100
+        self.nes.apu().sample_rate()
+    }
+    
+    /// Match the AudioContext's `sampleRate` (often 48000) so audio plays
+    /// at the right pitch
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.nes.apu_mut().set_sample_rate(sample_rate);
+    }
+    
+    /// Samples `run_frame` returns per frame, rounded up, for sizing buffers
+    pub fn samples_per_frame(&self) -> u32 {
+        (self.nes.apu().sample_rate() as f64 / Region::Ntsc.frame_rate()).ceil() as u32
     }
     
+    /// Run exactly one frame, for callers that pace themselves
     pub fn run_frame(&mut self) -> js_sys::Float32Array {
-        self.nes.run_frame();
+        // Nothing to run until a ROM is loaded
+        if self.nes.run_frame().is_err() {
+            return js_sys::Float32Array::new_with_length(0);
+        }
+        
+        if self.nes.last_frame_stats().drawn {
+            self.update_framebuffer();
+        }
+        self.take_samples()
+    }
+    
+    /// Call from every requestAnimationFrame with the milliseconds since
+    /// the previous call. Runs as many frames as real time calls for at
+    /// the region's frame rate: none on some 120/144Hz refreshes, now and
+    /// then two on a display slower than the game. Returns the audio of
+    /// every frame run; `render` shows the latest one.
+    pub fn run_realtime(&mut self, elapsed_ms: f64) -> js_sys::Float32Array {
+        let frame_ms = 1000.0 / self.nes.region().frame_rate();
+        self.pending_ms += elapsed_ms.max(0.0);
+        
+        let mut frames = 0;
+        let mut drawn = false;
+        while self.pending_ms >= frame_ms && frames < MAX_REALTIME_FRAMES {
+            if self.nes.run_frame().is_err() {
+                self.pending_ms = 0.0;
+                break;
+            }
+            self.pending_ms -= frame_ms;
+            frames += 1;
+            drawn |= self.nes.last_frame_stats().drawn;
+        }
+        // Whatever is still owed after the cap is lost time, not a backlog
+        self.pending_ms = self.pending_ms.min(frame_ms);
         
-        // Get framebuffer from PPU and convert to RGBA
+        if drawn {
+            self.update_framebuffer();
+        }
+        self.take_samples()
+    }
+    
+    /// Convert the PPU's framebuffer to RGBA for `render`
+    fn update_framebuffer(&mut self) {
         let nes_framebuffer = self.nes.get_framebuffer();
         for y in 0..240 {
             for x in 0..256 {
@@ -81,14 +146,22 @@ impl WasmNes {
                 self.framebuffer[idx + 3] = 255;  // Alpha
             }
         }
-        
-        // Get audio samples
-        let samples = self.nes.bus.apu.get_samples();
+    }
+    
+    fn take_samples(&mut self) -> js_sys::Float32Array {
+        let samples = self.nes.apu_mut().get_samples();
         let audio_array = js_sys::Float32Array::new_with_length(samples.len() as u32);
         audio_array.copy_from(&samples);
         audio_array
     }
     
+    /// Draw only one frame in every `frames + 1` on slow devices. The game,
+    /// its timing and its audio run as normal; `render` shows the last
+    /// drawn frame in between.
+    pub fn set_frame_skip(&mut self, frames: u8) {
+        self.nes.set_frame_skip(frames);
+    }
+    
     pub fn render(&self, ctx: &CanvasRenderingContext2d) -> Result<(), JsValue> {
         let image_data = ImageData::new_with_u8_clamped_array_and_sh(
             Clamped(&self.framebuffer),
@@ -108,6 +181,21 @@ impl WasmNes {
         }
     }
     
+    /// Push both pads in one call, once per frame from the Gamepad API.
+    /// Bits: 0 A, 1 B, 2 Select, 3 Start, 4 Up, 5 Down, 6 Left, 7 Right.
+    /// Keyboard state from `key_down`/`key_up` is overwritten until the next key event.
+    pub fn set_controllers(&mut self, state1: u8, state2: u8) {
+        self.nes.set_controllers(state1, state2);
+    }
+    
+    /// Let only the later of Left/Right or Up/Down count when keys for both
+    /// are held. Applies to `key_down`/`key_up`; `set_controllers` is raw.
+    pub fn set_disallow_opposites(&mut self, disallow: bool) {
+        self.controller1.set_disallow_opposites(disallow);
+        self.controller2.set_disallow_opposites(disallow);
+        self.nes.set_controller1_from_controller(&self.controller1);
+    }
+    
     pub fn key_down(&mut self, key_code: &str) {
         self.update_controller(key_code, true);
     }
@@ -118,12 +206,12 @@ impl WasmNes {
     
     pub fn save_state(&self) -> Result<Vec<u8>, JsValue> {
         self.nes.save_state_to_vec()
-            .map_err(|e| JsValue::from_str(&format!("Failed to save state: {}", e)))
+            .map_err(|e| js_error("Failed to save state", NesError::from(e)))
     }
     
     pub fn load_state(&mut self, data: &[u8]) -> Result<(), JsValue> {
         self.nes.load_state_from_slice(data)
-            .map_err(|e| JsValue::from_str(&format!("Failed to load state: {}", e)))
+            .map_err(|e| js_error("Failed to load state", NesError::from(e)))
     }
     
     fn update_controller(&mut self, key_code: &str, pressed: bool) {
@@ -147,6 +235,11 @@ impl WasmNes {
     }
 }
 
+/// JS exceptions carry the `NesError` message after a short context
+fn js_error(context: &str, error: NesError) -> JsValue {
+    JsValue::from_str(&format!("{}: {}", context, error))
+}
+
 #[wasm_bindgen(start)]
 pub fn main() {
     console_log!("CCNES WASM module loaded");
@@ -2,6 +2,7 @@ import init, { WasmNes } from '../pkg/ccnes_wasm.js';
 
 let nes = null;
 let animationId = null;
+let lastFrameTime = null;
 let isPaused = false;
 let controllerState = 0;
 
@@ -29,9 +30,12 @@ async function main() {
     console.log('WASM module initialized');
 }
 
-function gameLoop() {
+function gameLoop(currentTime) {
+    // Time spent paused isn't owed to the emulator
+    const elapsed = lastFrameTime === null || currentTime === undefined ? 0 : currentTime - lastFrameTime;
+    lastFrameTime = currentTime ?? null;
     if (!isPaused && nes) {
-        nes.run_frame();
+        nes.run_realtime(elapsed);
         nes.render(ctx);
     }
     animationId = requestAnimationFrame(gameLoop);